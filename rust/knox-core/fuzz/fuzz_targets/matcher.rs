@@ -0,0 +1,8 @@
+#![no_main]
+
+use knox_core::selftest;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    selftest::exercise(data);
+});
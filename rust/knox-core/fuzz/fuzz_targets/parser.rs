@@ -0,0 +1,13 @@
+#![no_main]
+
+use knox_core::parser::CodeParser;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+    for language in ["python", "javascript", "typescript", "rust"] {
+        let parser = CodeParser::new(Some(language.to_string()));
+        let _ = parser.parse(&text);
+        let _ = parser.analyze_complexity(&text);
+    }
+});
@@ -0,0 +1,223 @@
+//! Variable assignments for the regex backend of `CodeParser`
+//!
+//! An assignment starts a statement: a name, dotted attribute or list of
+//! names, optionally after a declaration keyword (`let`, `const`, `var`,
+//! `static`) and followed by a type annotation, then `=` or Go's `:=`. Its
+//! value runs to the end of the statement, a `;` or line break outside
+//! brackets and literals, so a dict or call spanning lines is read whole.
+//! Comparisons, augmented assignments (`+=`) and keyword arguments aren't
+//! assignments, and neither is anything inside a comment or a literal.
+
+use crate::calls::literal_at;
+use crate::context::LineIndex;
+use crate::parser::{ParsedAssignment, ParsedComment, StringLiteral};
+use regex::Regex;
+use std::sync::OnceLock;
+
+fn assignment_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(concat!(
+            r"(?m)^[ \t]*(?:(?:export|pub(?:\([^)\n]*\))?)\s+)?",
+            r"(?:(?:let|const|var|static)\s+(?:mut\s+)?)?",
+            r"(?P<name>[A-Za-z_$][\w$]*(?:\s*\.\s*[A-Za-z_$][\w$]*)*",
+            r"(?:[ \t]*,[ \t]*[A-Za-z_$][\w$]*)*)",
+            r"(?:[ \t]*:[ \t]*[^=:\n;][^=\n;]*?)?[ \t]*:?=",
+        ))
+        .unwrap()
+    })
+}
+
+/// Names ending a statement header rather than starting an assignment:
+/// `else: x = 1`, `default: x = 1`
+const KEYWORDS: &[&str] = &["else", "try", "finally", "case", "default", "lambda", "do"];
+
+/// The comment containing `offset`, `comments` being in document order
+fn comment_at(comments: &[ParsedComment], offset: usize) -> Option<&ParsedComment> {
+    let after = comments.partition_point(|c| c.start_byte <= offset);
+    comments[..after].last().filter(|c| offset < c.end_byte)
+}
+
+/// Offset just past the value starting at `start`: the first `;` or line
+/// break outside brackets, literals and line continuations, or the first
+/// comment outside brackets
+fn value_end(
+    content: &str,
+    start: usize,
+    literals: &[StringLiteral],
+    comments: &[ParsedComment],
+) -> usize {
+    let bytes = content.as_bytes();
+    let mut depth = 0usize;
+    let mut offset = start;
+    while offset < bytes.len() {
+        if let Some(literal) = literal_at(literals, offset) {
+            offset = literal.end_byte.max(offset + 1);
+            continue;
+        }
+        if let Some(comment) = comment_at(comments, offset) {
+            if depth == 0 {
+                return offset;
+            }
+            offset = comment.end_byte.max(offset + 1);
+            continue;
+        }
+        match bytes[offset] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' if depth == 0 => return offset,
+            b')' | b']' | b'}' => depth -= 1,
+            b'\\' if bytes.get(offset + 1) == Some(&b'\n') => offset += 1,
+            b';' | b'\n' if depth == 0 => return offset,
+            _ => {}
+        }
+        offset += 1;
+    }
+    bytes.len()
+}
+
+/// Every assignment in `content`, given its string literals and comments
+/// (with byte spans)
+pub fn scan_assignments(
+    content: &str,
+    literals: &[StringLiteral],
+    comments: &[ParsedComment],
+) -> Vec<ParsedAssignment> {
+    let index = LineIndex::new(content);
+    let bytes = content.as_bytes();
+    let mut assignments = Vec::new();
+    // Parentheses and brackets open before the current match, so lines of
+    // a multi-line call or signature (`timeout: int = 30,`) are skipped
+    let mut depth = 0usize;
+    let mut scanned = 0;
+    for captures in assignment_regex().captures_iter(content) {
+        let (Some(found), Some(name)) = (captures.get(0), captures.name("name")) else {
+            continue;
+        };
+        let start = name.start();
+        while scanned < start {
+            if let Some(literal) = literal_at(literals, scanned) {
+                scanned = literal.end_byte.max(scanned + 1);
+                continue;
+            }
+            if let Some(comment) = comment_at(comments, scanned) {
+                scanned = comment.end_byte.max(scanned + 1);
+                continue;
+            }
+            match bytes[scanned] {
+                b'(' | b'[' => depth += 1,
+                b')' | b']' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            scanned += 1;
+        }
+        if depth > 0
+            || literal_at(literals, start).is_some()
+            || comment_at(comments, start).is_some()
+        {
+            continue;
+        }
+        // `==`, `=>`
+        if matches!(bytes.get(found.end()), Some(b'=' | b'>')) {
+            continue;
+        }
+        if KEYWORDS.contains(&name.as_str()) {
+            continue;
+        }
+        // JavaScript and Rust values may start on the next line
+        let rest = &content[found.end()..];
+        let value_start = found.end() + rest.len() - rest.trim_start().len();
+        let end = value_end(content, value_start, literals, comments);
+        let value = content[value_start..end].trim();
+        if value.is_empty() {
+            continue;
+        }
+        assignments.push(ParsedAssignment {
+            name: name.as_str().to_string(),
+            value: value.to_string(),
+            line_number: index.line_col(start).0,
+        });
+    }
+    assignments
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constructs::ConstructRule;
+    use crate::matcher::PatternMatcher;
+    use crate::parser::CodeParser;
+    use crate::severity::Severity;
+
+    fn assignments(parser: &CodeParser, code: &str) -> Vec<(String, String, usize)> {
+        parser
+            .extract_assignments(code)
+            .into_iter()
+            .map(|a| (a.name, a.value, a.line_number))
+            .collect()
+    }
+
+    fn assignment(name: &str, value: &str, line_number: usize) -> (String, String, usize) {
+        (name.to_string(), value.to_string(), line_number)
+    }
+
+    #[test]
+    fn test_assignments_span_statements_not_comparisons() {
+        let code = concat!(
+            "API_SECRET = \"hunter2\"  # rotate me\n",
+            "# token = \"old\"\n",
+            "def connect(host, timeout: int = 30,\n",
+            "            retries=3):\n",
+            "    self.conn: Conn = open_conn(\n",
+            "        host, timeout=timeout,\n",
+            "    )\n",
+            "    if self.conn == None: return\n",
+            "    count += 1\n",
+            "    user, err = \"\"\"\n",
+            "x = 1\"\"\", None\n",
+        );
+        let parser = CodeParser::new(None);
+        assert_eq!(
+            assignments(&parser, code),
+            vec![
+                assignment("API_SECRET", "\"hunter2\"", 1),
+                assignment(
+                    "self.conn",
+                    "open_conn(\n        host, timeout=timeout,\n    )",
+                    5
+                ),
+                assignment("user, err", "\"\"\"\nx = 1\"\"\", None", 10),
+            ]
+        );
+
+        let mut parser = CodeParser::new(Some("typescript".to_string()));
+        parser.set_backend("regex".to_string()).unwrap();
+        let code = concat!(
+            "export const dbPassword: string = `p4ss`; let n = 0\n",
+            "const onClick = () => n++;\n",
+            "/* apiKey = \"x\" */ if (a === b) { total = a; }\n",
+        );
+        assert_eq!(
+            assignments(&parser, code),
+            vec![
+                assignment("dbPassword", "`p4ss`", 1),
+                assignment("onClick", "() => n++", 2),
+            ]
+        );
+
+        let mut matcher = PatternMatcher::new();
+        matcher.add_construct_rule(
+            ConstructRule::new(
+                "hardcoded_secret".to_string(),
+                Severity::High,
+                "secrets".to_string(),
+                "Secret assigned a literal".to_string(),
+                "assignment".to_string(),
+                r#"(?i)(secret|password)\w* = ["'`]"#.to_string(),
+                None,
+            )
+            .unwrap(),
+        );
+        let matches = matcher.match_constructs(&parser.parse(code));
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].matched_text, "dbPassword = `p4ss`");
+    }
+}
@@ -0,0 +1,333 @@
+//! Finding baselines with rename detection
+//!
+//! A baseline records the findings accepted at some point, keyed by file
+//! path, so later scans only report what is new. Each file's content hash
+//! is recorded too: when a baselined path disappears and a file with the
+//! same content shows up elsewhere, the file is treated as moved and keeps
+//! its baselined findings instead of reporting them all as new.
+//!
+//! Hashes come from the scan (`FastScanner.set_hash_contents`, or
+//! `ScanOptions.hash_contents` for directory scans) when it recorded them; otherwise each file is read and hashed once.
+
+use crate::inventory;
+use crate::matcher::Match;
+use crate::scanner::ScanResult;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Bumped whenever the baseline layout changes incompatibly
+pub const BASELINE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct BaselineEntry {
+    rule: String,
+    /// Hash of the rule and matched text, independent of line numbers
+    fingerprint: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct BaselineFile {
+    content_hash: Option<String>,
+    entries: Vec<BaselineEntry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct Baseline {
+    format_version: u32,
+    files: BTreeMap<String, BaselineFile>,
+}
+
+#[derive(Debug, Clone, Default)]
+#[pyclass]
+pub struct BaselineDiff {
+    /// Findings not in the baseline
+    #[pyo3(get)]
+    pub new: Vec<Match>,
+    /// Findings already in the baseline, under their file's current path
+    #[pyo3(get)]
+    pub existing: Vec<Match>,
+    /// Baselined findings no longer present, as `path: rule`
+    #[pyo3(get)]
+    pub fixed: Vec<String>,
+    /// Current path of each moved file, mapped to its baselined path
+    #[pyo3(get)]
+    pub moved: BTreeMap<String, String>,
+}
+
+#[pymethods]
+impl BaselineDiff {
+    fn __repr__(&self) -> String {
+        format!(
+            "BaselineDiff(new={}, existing={}, fixed={}, moved={})",
+            self.new.len(),
+            self.existing.len(),
+            self.fixed.len(),
+            self.moved.len()
+        )
+    }
+}
+
+fn fingerprint(m: &Match) -> String {
+    inventory::content_hash(format!("{}\0{}", m.pattern_name, m.matched_text.trim()).as_bytes())
+}
+
+/// Hash recorded by the scan, or of the file as it is now on disk
+fn file_hash(result: &ScanResult) -> Option<String> {
+    result.content_hash.clone().or_else(|| {
+        fs::read(&result.file_path)
+            .ok()
+            .map(|content| inventory::content_hash(&content))
+    })
+}
+
+#[pymethods]
+impl Baseline {
+    /// Baseline every finding in `results`
+    #[staticmethod]
+    pub fn from_results(results: Vec<ScanResult>) -> Self {
+        Self::build(&results)
+    }
+
+    /// Read a baseline written by `save`
+    #[staticmethod]
+    pub fn load(path: String) -> PyResult<Self> {
+        Self::read(Path::new(&path))
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))
+    }
+
+    pub fn save(&self, path: String) -> PyResult<()> {
+        self.write(Path::new(&path))
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))
+    }
+
+    #[getter]
+    pub fn finding_count(&self) -> usize {
+        self.files.values().map(|f| f.entries.len()).sum()
+    }
+
+    /// Split the findings in `results` into new and baselined ones,
+    /// following files that moved since the baseline was taken
+    pub fn diff(&self, results: Vec<ScanResult>) -> BaselineDiff {
+        self.compare(&results)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Baseline(files={}, findings={})",
+            self.files.len(),
+            self.finding_count()
+        )
+    }
+}
+
+impl Baseline {
+    pub fn build(results: &[ScanResult]) -> Self {
+        let files = results
+            .iter()
+            .filter(|r| r.error.is_none())
+            .map(|result| {
+                let entries = result
+                    .all_matches()
+                    .iter()
+                    .map(|m| BaselineEntry {
+                        rule: m.pattern_name.clone(),
+                        fingerprint: fingerprint(m),
+                    })
+                    .collect();
+                let file = BaselineFile {
+                    content_hash: file_hash(result),
+                    entries,
+                };
+                (result.file_path.clone(), file)
+            })
+            .collect();
+        Baseline {
+            format_version: BASELINE_FORMAT_VERSION,
+            files,
+        }
+    }
+
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        let baseline: Baseline = serde_json::from_str(&json)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if baseline.format_version != BASELINE_FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "unsupported baseline format {} (expected {})",
+                    baseline.format_version, BASELINE_FORMAT_VERSION
+                ),
+            ));
+        }
+        Ok(baseline)
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+
+    /// Baselined paths of files that moved, keyed by their current path
+    ///
+    /// Only exact content matches count: a file that was both moved and
+    /// edited is reported as deleted plus new.
+    fn detect_moves(&self, results: &[ScanResult]) -> BTreeMap<String, String> {
+        let current: HashMap<&str, &ScanResult> =
+            results.iter().map(|r| (r.file_path.as_str(), r)).collect();
+        let mut gone_by_hash: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (path, file) in &self.files {
+            if let (false, Some(hash)) = (current.contains_key(path.as_str()), &file.content_hash) {
+                gone_by_hash.entry(hash).or_default().push(path);
+            }
+        }
+
+        let mut moved = BTreeMap::new();
+        for result in results {
+            if gone_by_hash.is_empty() {
+                break;
+            }
+            if self.files.contains_key(&result.file_path) || result.error.is_some() {
+                continue;
+            }
+            let Some(hash) = file_hash(result) else {
+                continue;
+            };
+            if let Some(old_paths) = gone_by_hash.get_mut(hash.as_str()) {
+                let old = old_paths.remove(0);
+                if old_paths.is_empty() {
+                    gone_by_hash.remove(hash.as_str());
+                }
+                moved.insert(result.file_path.clone(), old.to_string());
+            }
+        }
+        moved
+    }
+
+    pub fn compare(&self, results: &[ScanResult]) -> BaselineDiff {
+        let moved = self.detect_moves(results);
+        let mut diff = BaselineDiff::default();
+        let mut seen: HashSet<&str> = HashSet::new();
+
+        for result in results {
+            let baselined = moved
+                .get(&result.file_path)
+                .unwrap_or(&result.file_path)
+                .as_str();
+            let Some(file) = self.files.get(baselined) else {
+                diff.new.extend(result.all_matches().iter().cloned());
+                continue;
+            };
+            seen.insert(baselined);
+
+            // Each baselined fingerprint absorbs one finding
+            let mut remaining: HashMap<&str, usize> = HashMap::new();
+            for entry in &file.entries {
+                *remaining.entry(&entry.fingerprint).or_default() += 1;
+            }
+            for m in result.all_matches() {
+                let print = fingerprint(m);
+                match remaining.get_mut(print.as_str()) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        diff.existing.push(m.clone());
+                    }
+                    _ => diff.new.push(m.clone()),
+                }
+            }
+            for entry in &file.entries {
+                if let Some(count) = remaining.get_mut(entry.fingerprint.as_str()) {
+                    if *count > 0 {
+                        *count -= 1;
+                        diff.fixed.push(format!("{}: {}", baselined, entry.rule));
+                    }
+                }
+            }
+        }
+
+        for (path, file) in &self.files {
+            if !seen.contains(path.as_str()) {
+                diff.fixed.extend(
+                    file.entries
+                        .iter()
+                        .map(|entry| format!("{}: {}", path, entry.rule)),
+                );
+            }
+        }
+        diff.moved = moved;
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::FastScanner;
+    use std::fs::write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_moved_file_keeps_baselined_findings() {
+        let temp_dir = TempDir::new().unwrap();
+        let old_path = temp_dir.path().join("settings.py");
+        let content = "DEBUG = True\npassword = \"hunter2hunter2\"\n";
+        write(&old_path, content).unwrap();
+        let mut scanner = FastScanner::new(None);
+        let scan = |scanner: &mut FastScanner, path: &Path| {
+            scanner
+                .scan_file_sync(path.to_string_lossy().to_string())
+                .unwrap()
+        };
+
+        let baseline = Baseline::build(&[scan(&mut scanner, &old_path)]);
+        assert_eq!(baseline.finding_count(), 2);
+        let baseline_path = temp_dir.path().join("baseline.json");
+        baseline.write(&baseline_path).unwrap();
+        let baseline = Baseline::read(&baseline_path).unwrap();
+
+        let new_path = temp_dir.path().join("config").join("settings.py");
+        fs::create_dir(new_path.parent().unwrap()).unwrap();
+        fs::rename(&old_path, &new_path).unwrap();
+        let diff = baseline.compare(&[scan(&mut scanner, &new_path)]);
+        assert!(diff.new.is_empty());
+        assert_eq!(diff.existing.len(), 2);
+        assert!(diff.fixed.is_empty());
+        assert_eq!(
+            diff.moved[&new_path.to_string_lossy().to_string()],
+            old_path.to_string_lossy()
+        );
+
+        // Once edited, the file is treated as new and the old path as fixed
+        write(&new_path, format!("{}verify=False\n", content)).unwrap();
+        let diff = baseline.compare(&[scan(&mut scanner, &new_path)]);
+        assert!(diff.moved.is_empty());
+        assert_eq!(diff.new.len(), 3);
+        assert_eq!(diff.fixed.len(), 2);
+    }
+
+    #[test]
+    fn test_hash_recorded_by_scan_is_used() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.py");
+        write(&path, "DEBUG = True\n").unwrap();
+        let mut scanner = FastScanner::new(None);
+        scanner.set_hash_contents(true);
+        let result = scanner
+            .scan_file_sync(path.to_string_lossy().to_string())
+            .unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let expected = inventory::content_hash(b"DEBUG = True\n");
+        assert_eq!(file_hash(&result), Some(expected.clone()));
+        let baseline = Baseline::build(&[result]);
+        assert_eq!(
+            baseline.files.values().next().unwrap().content_hash,
+            Some(expected)
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! Batched transfer of scan results to Python
+//!
+//! Converting every `ScanResult` and `Match` into its own Python object
+//! dominates wall time on match-heavy scans. A `ResultBuffer` instead holds
+//! all results as one JSON Lines buffer that Python can hand to a decoder
+//! in one go, or index into, decoding a single result on demand.
+
+use crate::scanner::{collect_tree, ScanResult};
+use crate::scanoptions::ScanOptions;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+/// Encoding of the buffer, one JSON document per line
+pub const BUFFER_FORMAT: &str = "jsonl";
+
+#[pyclass]
+pub struct ResultBuffer {
+    data: Vec<u8>,
+    /// Byte range of each encoded result within `data`
+    offsets: Vec<(usize, usize)>,
+    match_count: usize,
+}
+
+#[pymethods]
+impl ResultBuffer {
+    /// Encoding used by `to_bytes`
+    #[getter]
+    pub fn format(&self) -> &'static str {
+        BUFFER_FORMAT
+    }
+
+    /// Total number of matches across all results
+    #[getter]
+    pub fn match_count(&self) -> usize {
+        self.match_count
+    }
+
+    fn __len__(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Decode a single result
+    pub fn get(&self, index: usize) -> PyResult<ScanResult> {
+        let (start, end) = *self.offsets.get(index).ok_or_else(|| {
+            pyo3::exceptions::PyIndexError::new_err(format!(
+                "Result index {} out of range ({} results)",
+                index,
+                self.offsets.len()
+            ))
+        })?;
+        serde_json::from_slice(&self.data[start..end])
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+    }
+
+    /// The whole buffer as bytes, without decoding any result
+    pub fn to_bytes<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, &self.data)
+    }
+}
+
+impl ResultBuffer {
+    pub fn from_results(results: &[ScanResult]) -> Self {
+        let mut data = Vec::new();
+        let mut offsets = Vec::with_capacity(results.len());
+        let mut match_count = 0;
+
+        for result in results {
+            let start = data.len();
+            // ScanResult only holds strings and numbers, so this can't fail
+            serde_json::to_writer(&mut data, result).expect("ScanResult serializes");
+            offsets.push((start, data.len()));
+            data.push(b'\n');
+            match_count += result.matches.len();
+        }
+
+        ResultBuffer {
+            data,
+            offsets,
+            match_count,
+        }
+    }
+}
+
+/// Scan a directory like `scan_directory`, returning results in one buffer
+///
+/// The buffer already defers decoding, so `lazy` doesn't apply.
+#[pyfunction]
+#[pyo3(signature = (path, options=None))]
+pub fn scan_directory_buffer(path: String, options: Option<ScanOptions>) -> PyResult<ResultBuffer> {
+    let results = collect_tree(&path, &options.unwrap_or_default())?;
+    Ok(ResultBuffer::from_results(&results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_buffer_decodes_results_on_demand() {
+        let temp_dir = TempDir::new().unwrap();
+        write(temp_dir.path().join("a.py"), "DEBUG = True\n").unwrap();
+        write(temp_dir.path().join("b.py"), "x = 1\n").unwrap();
+
+        let buffer = scan_directory_buffer(
+            temp_dir.path().to_string_lossy().to_string(),
+            Some(ScanOptions {
+                parallel: false,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+        assert_eq!(buffer.__len__(), 2);
+        assert_eq!(buffer.match_count(), 1);
+        assert_eq!(buffer.data.iter().filter(|&&b| b == b'\n').count(), 2);
+
+        let decoded: Vec<ScanResult> = (0..2).map(|i| buffer.get(i).unwrap()).collect();
+        assert_eq!(decoded.iter().map(|r| r.matches.len()).sum::<usize>(), 1);
+        assert!(buffer.get(2).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! Call sites for the regex backend of `CodeParser`
+//!
+//! A call is a (dotted) name followed by `(`; its arguments run to the
+//! matching `)`, however many lines that takes. Parentheses inside string
+//! literals don't count, calls written inside a literal or after a line
+//! comment marker are skipped, and so are control-flow keywords and the
+//! names in function definitions, which look like calls to a regex.
+
+use crate::context::LineIndex;
+use crate::parser::{ParsedCall, StringLiteral};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+fn call_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"[A-Za-z_$][\w$]*(?:\s*(?:\.|::)\s*[A-Za-z_$][\w$]*)*!?\s*\(").unwrap()
+    })
+}
+
+/// Names followed by `(` that aren't calls
+const KEYWORDS: &[&str] = &[
+    "if", "elif", "else", "for", "foreach", "while", "switch", "match", "case", "return", "catch",
+    "with", "and", "or", "not", "in", "is", "assert", "yield", "await", "del", "except", "sizeof",
+    "typeof",
+    // Anonymous functions: `function (a) {`, Go's `func(w http.ResponseWriter)`
+    "function", "func",
+];
+
+/// Words introducing a definition whose name is followed by `(`
+const DEFINITIONS: &[&str] = &["def", "function", "fn", "func", "class", "struct", "impl"];
+
+/// The literal containing `offset`, `literals` being in document order
+pub fn literal_at(literals: &[StringLiteral], offset: usize) -> Option<&StringLiteral> {
+    let after = literals.partition_point(|l| l.start_byte <= offset);
+    literals[..after].last().filter(|l| offset < l.end_byte)
+}
+
+/// Byte offsets of each `(` and its matching `)`, skipping literals, in
+/// one pass so unbalanced input doesn't rescan to the end for every call
+fn paren_pairs(content: &str, literals: &[StringLiteral]) -> HashMap<usize, usize> {
+    let mut pairs = HashMap::new();
+    let mut open = Vec::new();
+    let bytes = content.as_bytes();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        if let Some(literal) = literal_at(literals, offset) {
+            offset = literal.end_byte.max(offset + 1);
+            continue;
+        }
+        match bytes[offset] {
+            b'(' => open.push(offset),
+            b')' => {
+                if let Some(start) = open.pop() {
+                    pairs.insert(start, offset);
+                }
+            }
+            _ => {}
+        }
+        offset += 1;
+    }
+    pairs
+}
+
+/// Byte offset of the first line comment marker on `line`, outside
+/// literals, given the offset the line starts at
+fn comment_start(
+    line: &str,
+    line_start: usize,
+    literals: &[StringLiteral],
+    markers: &[String],
+) -> Option<usize> {
+    markers
+        .iter()
+        .filter_map(|marker| {
+            line.match_indices(marker.as_str())
+                .map(|(i, _)| line_start + i)
+                .find(|&offset| literal_at(literals, offset).is_none())
+        })
+        .min()
+}
+
+/// Every call in `content`, given its string literals (with byte spans)
+/// and the language's line comment markers
+pub fn scan_calls(
+    content: &str,
+    literals: &[StringLiteral],
+    markers: &[String],
+) -> Vec<ParsedCall> {
+    let index = LineIndex::new(content);
+    let pairs = paren_pairs(content, literals);
+    let mut comments: HashMap<usize, Option<usize>> = HashMap::new();
+    let mut calls = Vec::new();
+    for found in call_regex().find_iter(content) {
+        let start = found.start();
+        let callee: String = found.as_str()[..found.len() - 1]
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        if KEYWORDS.contains(&callee.as_str()) || literal_at(literals, start).is_some() {
+            continue;
+        }
+        let (line_number, column) = index.line_col(start);
+        let comment = *comments.entry(line_number).or_insert_with(|| {
+            let (line_start, _) = index.lines_span(line_number, line_number);
+            let line = index.line(line_number).unwrap_or_default();
+            comment_start(line, line_start, literals, markers)
+        });
+        if comment.is_some_and(|comment| comment < start) {
+            continue;
+        }
+        let before = content[..start].trim_end();
+        let previous = before
+            .rsplit(|c: char| !c.is_alphanumeric() && c != '_')
+            .next()
+            .unwrap_or_default();
+        if DEFINITIONS.contains(&previous) {
+            continue;
+        }
+        let open = found.end() - 1;
+        let Some(&close) = pairs.get(&open) else {
+            continue;
+        };
+        calls.push(ParsedCall {
+            callee,
+            arguments: content[open + 1..close].trim().to_string(),
+            line_number,
+            column,
+        });
+    }
+    calls
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constructs::ConstructRule;
+    use crate::matcher::PatternMatcher;
+    use crate::parser::CodeParser;
+    use crate::severity::Severity;
+
+    #[test]
+    fn test_calls_span_lines_and_skip_comments_and_literals() {
+        let code = concat!(
+            "import subprocess\n",
+            "def run(cmd):\n",
+            "    # subprocess.call(cmd)\n",
+            "    subprocess.run(\n",
+            "        [\"sh\", \"-c\", cmd],\n",
+            "        shell=True,\n",
+            "    )\n",
+            "    log(\"eval(x) is bad :)\")\n",
+            "    if ok(cmd): return os.path.join(base, str(cmd))\n",
+        );
+        let parser = CodeParser::new(None);
+        let calls = parser.extract_calls(code);
+        let sites: Vec<(&str, usize, usize)> = calls
+            .iter()
+            .map(|c| (c.callee.as_str(), c.line_number, c.column))
+            .collect();
+        assert_eq!(
+            sites,
+            vec![
+                ("subprocess.run", 4, 4),
+                ("log", 8, 4),
+                ("ok", 9, 7),
+                ("os.path.join", 9, 23),
+                ("str", 9, 42),
+            ]
+        );
+        assert_eq!(
+            calls[0].arguments,
+            "[\"sh\", \"-c\", cmd],\n        shell=True,"
+        );
+        assert_eq!(calls[1].arguments, "\"eval(x) is bad :)\"");
+
+        let mut matcher = PatternMatcher::new();
+        matcher.add_construct_rule(
+            ConstructRule::new(
+                "shell_true".to_string(),
+                Severity::High,
+                "injection".to_string(),
+                "Command run through a shell".to_string(),
+                "call_arguments".to_string(),
+                r"shell\s*=\s*True".to_string(),
+                None,
+            )
+            .unwrap(),
+        );
+        let matches = matcher.match_constructs(&parser.parse(code));
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 4);
+    }
+}
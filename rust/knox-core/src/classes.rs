@@ -0,0 +1,198 @@
+//! Class definitions for the regex backend of `CodeParser`
+//!
+//! The class line itself is matched by a per-language regex naming the
+//! class and its bases; decorators are the `@` lines right above it. Where
+//! the class ends depends on the language: a Python class runs while lines
+//! are indented deeper than its `class` keyword, a brace-delimited one to
+//! the `}` matching the `{` its header ends with.
+
+use crate::context::LineIndex;
+use crate::parser::{ParsedClass, StringLiteral};
+use regex::Regex;
+
+/// How the body of a class is delimited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Block {
+    Indent,
+    Braces,
+}
+
+/// Bases listed in a class header, without keyword arguments such as
+/// Python's `metaclass=`
+fn split_bases(list: &str) -> Vec<String> {
+    let mut bases = Vec::new();
+    let mut depth = 0usize;
+    let mut current = String::new();
+    for c in list.chars() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                bases.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    bases.push(current);
+    bases
+        .into_iter()
+        .map(|base| base.trim().to_string())
+        .filter(|base| !base.is_empty() && !base.contains('='))
+        .collect()
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Decorators on the lines right above 1-based line `line_number`, top
+/// first, without their `@`; also used for functions
+pub fn decorators_above(index: &LineIndex, line_number: usize) -> Vec<String> {
+    let mut decorators: Vec<String> = (1..line_number)
+        .rev()
+        .map_while(|n| {
+            let line = index.line(n)?.trim();
+            line.strip_prefix('@').map(|d| d.trim().to_string())
+        })
+        .collect();
+    decorators.reverse();
+    decorators
+}
+
+/// Last line of a Python block opened on `line_number`: the last line
+/// indented deeper than it before one that isn't (comments don't count)
+fn indented_block_end(index: &LineIndex, line_number: usize, indent: usize) -> usize {
+    let mut end = line_number;
+    for n in line_number + 1..=index.line_count() {
+        let line = index.line(n).unwrap_or_default();
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if indent_of(line) <= indent {
+            break;
+        }
+        end = n;
+    }
+    end
+}
+
+/// Byte offset of the `}` closing the `{` at `open`, skipping literals
+fn closing_brace(content: &str, open: usize, literals: &[StringLiteral]) -> Option<usize> {
+    let bytes = content.as_bytes();
+    let mut depth = 0usize;
+    let mut offset = open;
+    let mut literals = literals
+        .iter()
+        .skip_while(|l| l.end_byte <= open)
+        .peekable();
+    while offset < bytes.len() {
+        if let Some(literal) = literals.next_if(|l| l.start_byte <= offset) {
+            offset = offset.max(literal.end_byte);
+            continue;
+        }
+        match bytes[offset] {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(offset);
+                }
+            }
+            _ => {}
+        }
+        offset += 1;
+    }
+    None
+}
+
+/// Every class `regex` finds in `content`, one line at a time; the regex
+/// names the class in a `name` group and its bases in `bases` (and, for
+/// interfaces listed separately, `implements`)
+pub fn scan_classes(
+    content: &str,
+    regex: &Regex,
+    block: Block,
+    literals: &[StringLiteral],
+) -> Vec<ParsedClass> {
+    let index = LineIndex::new(content);
+    let mut classes = Vec::new();
+    for line_number in 1..=index.line_count() {
+        let line = index.line(line_number).unwrap_or_default();
+        let Some(captures) = regex.captures(line) else {
+            continue;
+        };
+        let Some(name) = captures.name("name") else {
+            continue;
+        };
+        let mut bases = Vec::new();
+        for group in ["bases", "implements"] {
+            if let Some(list) = captures.name(group) {
+                bases.extend(split_bases(list.as_str()));
+            }
+        }
+        let end_line = match block {
+            Block::Indent => indented_block_end(&index, line_number, indent_of(line)),
+            Block::Braces => {
+                let (line_start, _) = index.lines_span(line_number, line_number);
+                let header_end = line_start + captures.get(0).map_or(0, |m| m.end());
+                // The header regex ends at its `{`
+                let open = content[..header_end].rfind('{').unwrap_or(header_end);
+                closing_brace(content, open, literals)
+                    .map_or(index.line_count(), |close| index.line_col(close).0)
+            }
+        };
+        classes.push(ParsedClass {
+            name: name.as_str().to_string(),
+            bases,
+            decorators: decorators_above(&index, line_number),
+            line_number,
+            end_line,
+        });
+    }
+    classes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::CodeParser;
+
+    #[test]
+    fn test_class_spans_bases_and_decorators() {
+        let code = concat!(
+            "@dataclass(frozen=True)\n",
+            "class Loader(pickle.Unpickler, Generic[T], metaclass=ABCMeta):\n",
+            "    def find_class(self, module, name):\n",
+            "\n",
+            "# helpers\n",
+            "        return super().find_class(module, name)\n",
+            "x = Loader\n",
+            "class Empty: pass\n",
+        );
+        let classes = CodeParser::new(None).extract_classes(code);
+        let spans: Vec<(&str, usize, usize)> = classes
+            .iter()
+            .map(|c| (c.name.as_str(), c.line_number, c.end_line))
+            .collect();
+        assert_eq!(spans, vec![("Loader", 2, 6), ("Empty", 8, 8)]);
+        assert_eq!(classes[0].bases, vec!["pickle.Unpickler", "Generic[T]"]);
+        assert_eq!(classes[0].decorators, vec!["dataclass(frozen=True)"]);
+
+        let code = concat!(
+            "@Injectable()\n",
+            "export default class Store<T> extends Base<T> implements Repo, Cache {\n",
+            "  label = \"{\";\n",
+            "  get(id: string) { return this.items[id]; }\n",
+            "}\n",
+        );
+        let mut parser = CodeParser::new(Some("typescript".to_string()));
+        parser.set_backend("regex".to_string()).unwrap();
+        let classes = parser.extract_classes(code);
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[0].bases, vec!["Base<T>", "Repo", "Cache"]);
+        assert_eq!(classes[0].decorators, vec!["Injectable()"]);
+        assert_eq!((classes[0].line_number, classes[0].end_line), (2, 5));
+    }
+}
@@ -0,0 +1,313 @@
+//! Comments and docstrings for the regex backend of `CodeParser`
+//!
+//! Content is read once, left to right, so a comment marker inside a
+//! string literal (`"http://..."`, `"# not a comment"`) doesn't start a
+//! comment and a quote inside a comment doesn't start a string. Line
+//! comment markers come from `language`; block comments are known for the
+//! C family of languages, HTML and Ruby (`=begin`/`=end`), whose heredoc
+//! bodies are skipped like strings. A Python triple-quoted string standing
+//! alone as the first statement of the file or of a block (the line before
+//! it ends with `:`) is reported as a docstring.
+
+use crate::context::LineIndex;
+use crate::language;
+use crate::literals;
+use crate::parser::{CodeParser, ParsedComment};
+
+/// Block comment delimiters by language
+const BLOCK_COMMENTS: &[(&str, &[(&str, &str)])] = &[
+    ("javascript", &[("/*", "*/")]),
+    ("typescript", &[("/*", "*/")]),
+    ("rust", &[("/*", "*/")]),
+    ("go", &[("/*", "*/")]),
+    ("java", &[("/*", "*/")]),
+    ("kotlin", &[("/*", "*/")]),
+    ("swift", &[("/*", "*/")]),
+    ("c", &[("/*", "*/")]),
+    ("cpp", &[("/*", "*/")]),
+    ("csharp", &[("/*", "*/")]),
+    ("php", &[("/*", "*/")]),
+    ("css", &[("/*", "*/")]),
+    ("html", &[("<!--", "-->")]),
+    ("ruby", &[("=begin", "=end")]),
+];
+
+/// String delimiters by language, longest first; `"` and `'` otherwise
+const QUOTES: &[(&str, &[&str])] = &[
+    ("python", &["\"\"\"", "'''", "\"", "'"]),
+    ("javascript", &["`", "\"", "'"]),
+    ("typescript", &["`", "\"", "'"]),
+    ("go", &["`", "\"", "'"]),
+    ("java", &["\"\"\"", "\"", "'"]),
+    ("ruby", &["\"", "'", "`"]),
+    // `'` starts lifetimes and char literals, whose quotes are skipped
+    ("rust", &["\""]),
+];
+
+/// Opening and closing markers stripped from a comment's text
+const MARKERS: &[&str] = &[
+    "///", "//!", "//", "/**", "/*!", "/*", "<!--", "=begin", "#", "--",
+];
+const CLOSING_MARKERS: &[&str] = &["*/", "-->", "=end"];
+
+/// Text of a comment without its markers and surrounding whitespace
+pub fn comment_text(raw: &str) -> &str {
+    let mut text = raw.trim();
+    if let Some(marker) = MARKERS.iter().find(|m| text.starts_with(*m)) {
+        text = &text[marker.len()..];
+    }
+    if let Some(marker) = CLOSING_MARKERS.iter().find(|m| text.ends_with(*m)) {
+        text = &text[..text.len() - marker.len()];
+    }
+    text.trim()
+}
+
+/// Value of a string literal without its prefix and quotes; an
+/// unterminated literal is kept as written
+pub fn docstring_text(raw: &str) -> &str {
+    let raw = raw.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+    ["\"\"\"", "'''", "\"", "'"]
+        .into_iter()
+        .find_map(|q| raw.strip_prefix(q)?.strip_suffix(q))
+        .unwrap_or(raw)
+        .trim()
+}
+
+/// Block comment delimiters of `language`, none when it has no block
+/// comments or isn't known
+pub fn block_comments(language: &str) -> &'static [(&'static str, &'static str)] {
+    lookup(BLOCK_COMMENTS, language)
+        .copied()
+        .unwrap_or_default()
+}
+
+fn lookup<'a, T>(table: &'a [(&str, T)], language: &str) -> Option<&'a T> {
+    table
+        .iter()
+        .find(|(name, _)| *name == language)
+        .map(|(_, value)| value)
+}
+
+/// Byte offset just past the string opened by `quote` at `start`
+///
+/// Backslash escapes are honored; a one-character quote other than a
+/// backtick (or in Rust and Ruby, whose strings span lines) also ends at
+/// the end of the line, so an unterminated string doesn't swallow the rest
+/// of the file.
+fn string_end(bytes: &[u8], start: usize, quote: &str, language: &str) -> usize {
+    let stops_at_newline = quote.len() == 1 && quote != "`" && !matches!(language, "rust" | "ruby");
+    let mut offset = start + quote.len();
+    while offset < bytes.len() {
+        match bytes[offset] {
+            b'\\' => offset += 2,
+            b'\n' if stops_at_newline => return offset,
+            _ if bytes[offset..].starts_with(quote.as_bytes()) => return offset + quote.len(),
+            _ => offset += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Every comment and docstring in `content`
+pub fn scan_comments(content: &str, language: &str) -> Vec<ParsedComment> {
+    let markers = language::line_comment_markers(language);
+    let blocks = lookup(BLOCK_COMMENTS, language)
+        .copied()
+        .unwrap_or_default();
+    let quotes: Vec<String> = match (
+        lookup(QUOTES, language),
+        language::registered_spec(language),
+    ) {
+        (Some(quotes), _) => quotes.iter().map(|q| q.to_string()).collect(),
+        (None, Some(spec)) => spec.string_delimiters,
+        (None, None) => vec!["\"".to_string(), "'".to_string()],
+    };
+    let docstrings = language == "python";
+    // Ruby heredoc bodies, which no quote delimits
+    let heredocs: Vec<(usize, usize)> = match language {
+        "ruby" => literals::scan_strings(content, language)
+            .into_iter()
+            .filter(|s| s.quote_type.starts_with("<<"))
+            .map(|s| (s.start_byte, s.end_byte))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let mut heredocs = heredocs.into_iter().peekable();
+
+    let index = LineIndex::new(content);
+    let bytes = content.as_bytes();
+    let mut comments = Vec::new();
+    let mut push = |start: usize, end: usize, text: &str, is_docstring: bool| {
+        let end = start + content[start..end].trim_end().len();
+        comments.push(ParsedComment {
+            text: text.to_string(),
+            line_number: index.line_col(start).0,
+            end_line: index.line_col(end.saturating_sub(1).max(start)).0,
+            start_byte: start,
+            end_byte: end,
+            is_docstring,
+        });
+    };
+    // Last character of code before the current position, for docstrings
+    let mut last_code: Option<u8> = None;
+    let mut offset = 0;
+    while offset < bytes.len() {
+        while heredocs.next_if(|&(_, end)| end <= offset).is_some() {}
+        if let Some(&(start, end)) = heredocs.peek() {
+            if start <= offset {
+                offset = end;
+                continue;
+            }
+        }
+        let byte = bytes[offset];
+        // Markers and quotes are ASCII, so only ASCII bytes can start them
+        if !byte.is_ascii() {
+            last_code = Some(byte);
+            offset += 1;
+            continue;
+        }
+        let rest = &content[offset..];
+        if markers.iter().any(|m| rest.starts_with(m.as_str())) {
+            let end = rest.find('\n').map_or(bytes.len(), |i| offset + i);
+            push(offset, end, comment_text(&content[offset..end]), false);
+            offset = end;
+            continue;
+        }
+        if let Some((open, close)) = blocks.iter().find(|(open, _)| rest.starts_with(open)) {
+            let end = rest[open.len()..]
+                .find(close)
+                .map_or(bytes.len(), |i| offset + open.len() + i + close.len());
+            push(offset, end, comment_text(&content[offset..end]), false);
+            offset = end;
+            continue;
+        }
+        if language == "rust" && byte == b'\'' {
+            offset += literals::char_literal_len(rest);
+            last_code = Some(b'\'');
+            continue;
+        }
+        if let Some(quote) = quotes
+            .iter()
+            .find(|q| !q.is_empty() && rest.starts_with(q.as_str()))
+        {
+            let end = string_end(bytes, offset, quote, language);
+            if docstrings && quote.len() == 3 && matches!(last_code, None | Some(b':')) {
+                let line_start = content[..offset].rfind('\n').map_or(0, |i| i + 1);
+                if content[line_start..offset].trim().is_empty() {
+                    push(offset, end, docstring_text(&content[offset..end]), true);
+                }
+            }
+            last_code = Some(quote.as_bytes()[0]);
+            offset = end;
+            continue;
+        }
+        if !byte.is_ascii_whitespace() {
+            last_code = Some(byte);
+        }
+        offset += 1;
+    }
+    comments
+}
+
+/// Drop line findings that start inside a comment or docstring of
+/// `content`, for `PatternMatcher.set_skip_comments`
+///
+/// Byte offsets of `matches` are relative to `content`.
+pub fn drop_in_comments(
+    matches: &mut Vec<crate::matcher::Match>,
+    content: &str,
+    language: Option<String>,
+) {
+    if matches.is_empty() {
+        return;
+    }
+    let comments = CodeParser::new(language).extract_comments(content);
+    matches.retain(|m| {
+        m.start_byte.is_none_or(|start| {
+            !comments
+                .iter()
+                .any(|c| c.start_byte <= start && start < c.end_byte)
+        })
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::matcher::PatternMatcher;
+    use crate::parser::CodeParser;
+
+    #[test]
+    fn test_comments_and_docstrings() {
+        let code = concat!(
+            "\"\"\"Module docs.\"\"\"\n",
+            "URL = \"http://host/#anchor\"  # password: hunter2\n",
+            "def load(path):\n",
+            "    '''Load a file.\n",
+            "\n",
+            "    eval(path) is never called'''\n",
+            "    query = \"\"\"SELECT 1\"\"\"\n",
+            "    return path\n",
+        );
+        let comments: Vec<(String, usize, usize, bool)> = CodeParser::new(None)
+            .extract_comments(code)
+            .into_iter()
+            .map(|c| (c.text, c.line_number, c.end_line, c.is_docstring))
+            .collect();
+        assert_eq!(
+            comments,
+            vec![
+                ("Module docs.".to_string(), 1, 1, true),
+                ("password: hunter2".to_string(), 2, 2, false),
+                (
+                    "Load a file.\n\n    eval(path) is never called".to_string(),
+                    4,
+                    6,
+                    true
+                ),
+            ]
+        );
+
+        let js = CodeParser::new(Some("javascript".to_string()));
+        let code = "const u = 'a//b'; /* key:\n  abc */ f(`/*`); // done\n";
+        let comments = js.extract_comments(code);
+        assert_eq!(comments.len(), 2);
+        assert_eq!(
+            (comments[0].text.as_str(), comments[0].end_line),
+            ("key:\n  abc", 2)
+        );
+        assert_eq!(
+            &code[comments[1].start_byte..comments[1].end_byte],
+            "// done"
+        );
+    }
+
+    #[test]
+    fn test_unterminated_docstring() {
+        for code in ["\"\"\"ü", "'''ü"] {
+            let comments = CodeParser::new(None).extract_comments(code);
+            assert_eq!(comments[0].text, code);
+            CodeParser::new(None).analyze_complexity(code).unwrap();
+        }
+        assert_eq!(super::docstring_text("b'''x'''"), "x");
+        assert_eq!(super::docstring_text("\"\""), "");
+    }
+
+    #[test]
+    fn test_skip_comments_drops_commented_out_code() {
+        let code = "# os.system(cmd)\nos.system(cmd)\n";
+        let mut matcher = PatternMatcher::new();
+        matcher.set_language(Some("python".to_string()));
+        let lines = |matcher: &mut PatternMatcher| {
+            let mut lines: Vec<usize> = matcher
+                .match_content(code)
+                .iter()
+                .map(|m| m.line_number)
+                .collect();
+            lines.dedup();
+            lines
+        };
+        assert_eq!(lines(&mut matcher), vec![1, 2]);
+        matcher.set_skip_comments(true);
+        assert_eq!(lines(&mut matcher), vec![2]);
+    }
+}
@@ -0,0 +1,629 @@
+//! Per-function complexity for `CodeParser`
+//!
+//! Cyclomatic complexity is one plus the number of decision points in a
+//! function: branches, loops, caught exceptions, match arms and
+//! short-circuit boolean operators. Decision points are counted by
+//! language from the function's source with string literals and comments
+//! blanked out, and each one is credited to the innermost function around
+//! it, so a nested function or closure doesn't inflate its parent.
+//!
+//! Nesting depth is how many blocks deep a function's code goes below its
+//! body: by indentation for Python and Ruby, by braces elsewhere.
+
+use crate::classes::Block;
+use crate::context::LineIndex;
+use crate::parser::{ParsedComment, ParsedFunction, StringLiteral};
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct FunctionComplexity {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub line_number: usize,
+    /// Last line of the function body
+    #[pyo3(get)]
+    pub end_line: usize,
+    #[pyo3(get)]
+    pub cyclomatic: usize,
+    /// Blocks nested inside the body; 0 for straight-line code
+    #[pyo3(get)]
+    #[serde(default)]
+    pub nesting_depth: usize,
+    /// First line at `nesting_depth`
+    #[pyo3(get)]
+    #[serde(default)]
+    pub deepest_line: usize,
+}
+
+#[pymethods]
+impl FunctionComplexity {
+    fn __repr__(&self) -> String {
+        format!(
+            "FunctionComplexity(name={}, lines={}-{}, cyclomatic={}, nesting_depth={})",
+            self.name, self.line_number, self.end_line, self.cyclomatic, self.nesting_depth
+        )
+    }
+}
+
+/// Keywords and operators that each add a path through a function
+struct Decisions {
+    keywords: &'static [&'static str],
+    operators: &'static [&'static str],
+    /// `cond ? a : b`, written with spaces around the `?` so nullable types
+    /// (`String?`) and optional parameters (`x?: T`) aren't counted
+    ternary: bool,
+    /// Block keyword and the arrow ending each of its arms, for branch
+    /// constructs counted per arm (Kotlin's `when`); an `else` arm isn't
+    /// counted
+    arms: Option<(&'static str, &'static str)>,
+}
+
+const C_LIKE: Decisions = Decisions {
+    keywords: &["if", "for", "while", "case", "catch"],
+    operators: &["&&", "||"],
+    ternary: true,
+    arms: None,
+};
+
+const DECISIONS: &[(&str, Decisions)] = &[
+    (
+        "python",
+        Decisions {
+            keywords: &["if", "elif", "for", "while", "except", "case", "and", "or"],
+            operators: &[],
+            ternary: false,
+            arms: None,
+        },
+    ),
+    (
+        "javascript",
+        Decisions {
+            keywords: &["if", "for", "while", "case", "catch"],
+            operators: &["&&", "||", "??"],
+            ternary: true,
+            arms: None,
+        },
+    ),
+    (
+        "typescript",
+        Decisions {
+            keywords: &["if", "for", "while", "case", "catch"],
+            operators: &["&&", "||", "??"],
+            ternary: true,
+            arms: None,
+        },
+    ),
+    (
+        "rust",
+        Decisions {
+            keywords: &["if", "for", "while"],
+            // One per match arm
+            operators: &["&&", "||", "=>"],
+            ternary: false,
+            arms: None,
+        },
+    ),
+    (
+        "php",
+        Decisions {
+            keywords: &[
+                "if", "elseif", "for", "foreach", "while", "case", "catch", "and", "or",
+            ],
+            operators: &["&&", "||", "??"],
+            ternary: true,
+            arms: None,
+        },
+    ),
+    (
+        "ruby",
+        Decisions {
+            keywords: &[
+                "if", "elsif", "unless", "for", "while", "until", "when", "rescue", "and", "or",
+            ],
+            operators: &["&&", "||"],
+            ternary: true,
+            arms: None,
+        },
+    ),
+    (
+        "kotlin",
+        Decisions {
+            keywords: &["if", "for", "while", "catch"],
+            operators: &["&&", "||", "?:"],
+            ternary: false,
+            arms: Some(("when", "->")),
+        },
+    ),
+    (
+        "swift",
+        Decisions {
+            keywords: &["if", "guard", "for", "while", "case", "catch"],
+            operators: &["&&", "||", "??"],
+            ternary: true,
+            arms: None,
+        },
+    ),
+];
+
+fn decisions(language: &str) -> &'static Decisions {
+    DECISIONS
+        .iter()
+        .find(|(name, _)| *name == language)
+        .map_or(&C_LIKE, |(_, decisions)| decisions)
+}
+
+fn is_word(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+/// `content` with the byte `spans` of string literal values and comments
+/// replaced by spaces, newlines kept so offsets and line numbers still
+/// line up
+fn blank(content: &str, spans: &[(usize, usize)]) -> Vec<u8> {
+    let mut bytes = content.as_bytes().to_vec();
+    for &(start, end) in spans {
+        let end = end.min(content.len());
+        for byte in bytes[start.min(end)..end].iter_mut() {
+            if *byte != b'\n' {
+                *byte = b' ';
+            }
+        }
+    }
+    bytes
+}
+
+/// The word ending at `end` in `code`, whitespace before `end` skipped
+fn word_before(code: &[u8], end: usize) -> &[u8] {
+    let end = code[..end]
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(0, |i| i + 1);
+    let start = code[..end]
+        .iter()
+        .rposition(|&b| !is_word(b))
+        .map_or(0, |i| i + 1);
+    &code[start..end]
+}
+
+/// 0-based line of each decision point in `code`
+fn decision_lines(code: &[u8], decisions: &Decisions) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let mut line = 0;
+    let mut i = 0;
+    // Brace depth, and the depths of the open blocks counted per arm
+    let mut depth = 0;
+    let mut arm_blocks: Vec<usize> = Vec::new();
+    let mut arm_block_next = false;
+    while i < code.len() {
+        let byte = code[i];
+        if byte == b'\n' {
+            line += 1;
+            i += 1;
+        } else if is_word(byte) {
+            let start = i;
+            while i < code.len() && is_word(code[i]) {
+                i += 1;
+            }
+            let word = &code[start..i];
+            let after_dot = start > 0 && code[start - 1] == b'.';
+            if !after_dot && decisions.keywords.iter().any(|k| k.as_bytes() == word) {
+                lines.push(line);
+            }
+            if !after_dot && decisions.arms.is_some_and(|(k, _)| k.as_bytes() == word) {
+                arm_block_next = true;
+            }
+        } else if byte == b'{' || byte == b'}' {
+            if byte == b'{' {
+                depth += 1;
+                if std::mem::take(&mut arm_block_next) {
+                    arm_blocks.push(depth);
+                }
+            } else {
+                if arm_blocks.last() == Some(&depth) {
+                    arm_blocks.pop();
+                }
+                depth -= usize::from(depth > 0);
+            }
+            i += 1;
+        } else if let Some((_, arrow)) = decisions
+            .arms
+            .filter(|(_, arrow)| code[i..].starts_with(arrow.as_bytes()))
+        {
+            if arm_blocks.last() == Some(&depth) && word_before(code, i) != b"else" {
+                lines.push(line);
+            }
+            i += arrow.len();
+        } else if let Some(operator) = decisions
+            .operators
+            .iter()
+            .find(|op| code[i..].starts_with(op.as_bytes()))
+        {
+            lines.push(line);
+            i += operator.len();
+        } else {
+            if byte == b'?'
+                && decisions.ternary
+                && i > 0
+                && code[i - 1].is_ascii_whitespace()
+                && code.get(i + 1).is_some_and(|b| b.is_ascii_whitespace())
+            {
+                lines.push(line);
+            }
+            i += 1;
+        }
+    }
+    lines
+}
+
+/// Block level of one line of code
+#[derive(Clone, Copy)]
+struct Level {
+    /// Brace depth where the line starts
+    start: usize,
+    /// Deepest brace depth reached on the line
+    max: usize,
+    /// Brace depth where the line ends
+    end: usize,
+    /// Indentation; `None` for blank lines and lines continuing a
+    /// bracketed expression, string literal or comment
+    indent: Option<usize>,
+}
+
+/// 0-based lines of `content` that start inside one of `spans`
+fn continued_lines(content: &str, spans: &[(usize, usize)]) -> Vec<bool> {
+    let index = LineIndex::new(content);
+    let mut continued = vec![false; content.lines().count() + 1];
+    for &(start, end) in spans {
+        let end = end.min(content.len());
+        for (i, byte) in content.as_bytes()[start.min(end)..end].iter().enumerate() {
+            if *byte == b'\n' {
+                let (line, _) = index.line_col(start + i);
+                if let Some(flag) = continued.get_mut(line) {
+                    *flag = true;
+                }
+            }
+        }
+    }
+    continued
+}
+
+/// Level of each line of `code`, given the lines starting inside a
+/// literal or comment
+fn line_levels(code: &[u8], continued: &[bool]) -> Vec<Level> {
+    let mut levels = Vec::new();
+    let (mut braces, mut brackets) = (0usize, 0usize);
+    for (n, line) in code.split(|&b| b == b'\n').enumerate() {
+        let text = line.trim_ascii();
+        let skipped = text.is_empty() || brackets > 0 || continued.get(n) == Some(&true);
+        let start = braces;
+        let mut max = braces;
+        for &byte in line {
+            match byte {
+                b'{' => {
+                    braces += 1;
+                    max = max.max(braces);
+                }
+                b'}' => braces = braces.saturating_sub(1),
+                b'(' | b'[' => brackets += 1,
+                b')' | b']' => brackets = brackets.saturating_sub(1),
+                _ => {}
+            }
+        }
+        levels.push(Level {
+            start,
+            max,
+            end: braces,
+            indent: (!skipped).then(|| line.len() - line.trim_ascii_start().len()),
+        });
+    }
+    levels
+}
+
+/// Last line (1-based) of the function defined on line `header`: the last
+/// line indented deeper than the header, or the line with the brace
+/// closing the body opened on the header or the line after it
+fn body_end(levels: &[Level], header: usize, block: Block) -> usize {
+    let rest = &levels[header - 1..];
+    match block {
+        Block::Indent => {
+            let Some(header_indent) = rest[0].indent else {
+                return header;
+            };
+            let mut end = header;
+            for (i, level) in rest.iter().enumerate().skip(1) {
+                match level.indent {
+                    Some(indent) if indent <= header_indent => break,
+                    Some(_) => end = header + i,
+                    None => {}
+                }
+            }
+            end
+        }
+        Block::Braces => {
+            let outer = rest[0].start;
+            let Some(open) = rest.iter().take(2).position(|l| l.max > outer) else {
+                return header;
+            };
+            rest.iter()
+                .enumerate()
+                .skip(open)
+                .find(|(_, l)| l.end <= outer)
+                .map_or(header - 1 + rest.len(), |(i, _)| header + i)
+        }
+    }
+}
+
+/// Index of the innermost of `functions` whose span holds `line` (1-based)
+fn innermost(functions: &[FunctionComplexity], line: usize) -> Option<usize> {
+    functions
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| f.line_number <= line && line <= f.end_line)
+        .max_by_key(|(_, f)| f.line_number)
+        .map(|(i, _)| i)
+}
+
+/// Nesting depth of the function whose lines (1-based, own lines only,
+/// header first) are `lines`, and the first line reaching it
+fn nesting(levels: &[Level], lines: &[usize], block: Block) -> (usize, usize) {
+    let Some(&header) = lines.first() else {
+        return (0, 0);
+    };
+    let mut deepest = (0, header);
+    match block {
+        Block::Indent => {
+            let Some(header_indent) = levels[header - 1].indent else {
+                return deepest;
+            };
+            // Indentations of the open blocks, the header's first
+            let mut open = vec![header_indent];
+            for &line in &lines[1..] {
+                let Some(indent) = levels[line - 1].indent else {
+                    continue;
+                };
+                while open.last().is_some_and(|&open| indent < open) {
+                    open.pop();
+                }
+                if open.last().is_none_or(|&open| indent > open) {
+                    open.push(indent);
+                }
+                // Header and body are the first two levels
+                let depth = open.len().saturating_sub(2);
+                if depth > deepest.0 {
+                    deepest = (depth, line);
+                }
+            }
+        }
+        Block::Braces => {
+            let body = levels[header - 1].start + 1;
+            for &line in lines {
+                let depth = levels[line - 1].max.saturating_sub(body);
+                if depth > deepest.0 {
+                    deepest = (depth, line);
+                }
+            }
+        }
+    }
+    deepest
+}
+
+/// Cyclomatic complexity and nesting depth of each of `functions` (as
+/// extracted from `content`), given the file's string literals and
+/// comments
+pub fn analyze_functions(
+    content: &str,
+    language: &str,
+    functions: &[ParsedFunction],
+    literals: &[StringLiteral],
+    comments: &[ParsedComment],
+) -> Vec<FunctionComplexity> {
+    let block = match language {
+        "python" | "ruby" => Block::Indent,
+        _ => Block::Braces,
+    };
+    let spans: Vec<(usize, usize)> = literals
+        .iter()
+        .map(|l| (l.start_byte, l.end_byte))
+        .chain(comments.iter().map(|c| (c.start_byte, c.end_byte)))
+        .collect();
+    let code = blank(content, &spans);
+    let levels = line_levels(&code, &continued_lines(content, &spans));
+    let line_count = content.lines().count();
+
+    let mut analyzed: Vec<FunctionComplexity> = functions
+        .iter()
+        .filter(|f| f.line_number >= 1 && f.line_number <= line_count)
+        .map(|f| FunctionComplexity {
+            name: f.name.clone(),
+            line_number: f.line_number,
+            end_line: body_end(&levels, f.line_number, block).min(line_count),
+            cyclomatic: 1,
+            nesting_depth: 0,
+            deepest_line: f.line_number,
+        })
+        .collect();
+    let owners: Vec<Option<usize>> = (1..=line_count)
+        .map(|line| innermost(&analyzed, line))
+        .collect();
+
+    for line in decision_lines(&code, decisions(language)) {
+        if let Some(&Some(i)) = owners.get(line) {
+            analyzed[i].cyclomatic += 1;
+        }
+    }
+
+    for (i, function) in analyzed.iter_mut().enumerate() {
+        let own: Vec<usize> = (function.line_number..=function.end_line)
+            .filter(|&line| owners[line - 1] == Some(i))
+            .collect();
+        (function.nesting_depth, function.deepest_line) = nesting(&levels, &own, block);
+    }
+    analyzed
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::CodeParser;
+
+    fn cyclomatic(language: &str, code: &str) -> Vec<(String, usize)> {
+        CodeParser::new(Some(language.to_string()))
+            .analyze_functions(code)
+            .into_iter()
+            .map(|f| (f.name, f.cyclomatic))
+            .collect()
+    }
+
+    #[test]
+    fn test_cyclomatic_complexity_by_language() {
+        let python = concat!(
+            "def simple():\n",
+            "    return 1\n",
+            "\n",
+            "def check(user, items):\n",
+            "    # if this or that\n",
+            "    if user and user.active:\n",
+            "        return \"if or while\"\n",
+            "    elif user is None:\n",
+            "        pass\n",
+            "    for item in items:\n",
+            "        def inner(x):\n",
+            "            return x if x else 0\n",
+            "    try:\n",
+            "        pass\n",
+            "    except ValueError:\n",
+            "        pass\n",
+        );
+        assert_eq!(
+            cyclomatic("python", python),
+            vec![
+                ("simple".to_string(), 1),
+                ("check".to_string(), 6),
+                ("inner".to_string(), 2),
+            ]
+        );
+
+        let javascript = concat!(
+            "function route(req) {\n",
+            "  // if (legacy) { ... }\n",
+            "  const name = req.user?.name ?? \"anon\";\n",
+            "  if (req.admin && !req.locked) {\n",
+            "    return \"admin || root\";\n",
+            "  } else if (req.guest) {\n",
+            "    return name;\n",
+            "  }\n",
+            "  return req.fast ? run() : wait();\n",
+            "}\n",
+        );
+        assert_eq!(
+            cyclomatic("javascript", javascript),
+            vec![("route".to_string(), 6)]
+        );
+
+        let rust = concat!(
+            "fn kind(n: i32) -> &'static str {\n",
+            "    match n {\n",
+            "        0 => \"zero\",\n",
+            "        _ if n < 0 => \"negative\",\n",
+            "        _ => \"positive\",\n",
+            "    }\n",
+            "}\n",
+        );
+        assert_eq!(cyclomatic("rust", rust), vec![("kind".to_string(), 5)]);
+
+        let kotlin = concat!(
+            "fun describe(x: Any?): String {\n",
+            "    val name = x?.toString() ?: \"none\"\n",
+            "    return when (x) {\n",
+            "        is Int -> if (x > 0) \"positive\" else \"other\"\n",
+            "        \"a\", \"b\" -> items.map { it -> it.name }.joinToString()\n",
+            "        null -> name\n",
+            "        else -> \"unknown\"\n",
+            "    }\n",
+            "}\n",
+        );
+        assert_eq!(
+            cyclomatic("kotlin", kotlin),
+            vec![("describe".to_string(), 6)]
+        );
+    }
+
+    #[test]
+    fn test_nesting_depth_by_indentation_and_braces() {
+        let parser = CodeParser::new(Some("python".to_string()));
+        let python = concat!(
+            "def flat(a, b):\n",
+            "    total = max(a,\n",
+            "                b)\n",
+            "    query = \"\"\"\n",
+            "SELECT 1\n",
+            "\"\"\"\n",
+            "    return query\n",
+            "\n",
+            "def deep(rows):\n",
+            "    for row in rows:\n",
+            "        if row:\n",
+            "            # comment at any depth\n",
+            "            send(row,\n",
+            "                 retry=True)\n",
+            "    def helper():\n",
+            "        pass\n",
+        );
+        let depths: Vec<(String, usize, usize)> = parser
+            .analyze_functions(python)
+            .into_iter()
+            .map(|f| (f.name, f.nesting_depth, f.deepest_line))
+            .collect();
+        assert_eq!(
+            depths,
+            vec![
+                ("flat".to_string(), 0, 1),
+                ("deep".to_string(), 2, 13),
+                ("helper".to_string(), 0, 15),
+            ]
+        );
+        assert_eq!(
+            parser.analyze_complexity(python).unwrap()["max_nesting_depth"],
+            2
+        );
+
+        // A dict or set literal opening the body doesn't make it brace-delimited
+        let literal_first = concat!(
+            "def f(x):\n",
+            "    d = {\"a\": 1}\n",
+            "    if x:\n",
+            "        for i in x:\n",
+            "            if i:\n",
+            "                print({i})\n",
+            "    return d\n",
+        );
+        let functions = parser.analyze_functions(literal_first);
+        assert_eq!(
+            (
+                functions[0].end_line,
+                functions[0].cyclomatic,
+                functions[0].nesting_depth,
+                functions[0].deepest_line
+            ),
+            (7, 4, 3, 6)
+        );
+
+        let javascript = concat!(
+            "function handle(items) {\n",
+            "  const opts = { retry: true };\n",
+            "  for (const item of items) {\n",
+            "    if (item.ok) { send(item); }\n",
+            "  }\n",
+            "  return \"{{{\";\n",
+            "}\n",
+        );
+        let functions =
+            CodeParser::new(Some("javascript".to_string())).analyze_functions(javascript);
+        assert_eq!(
+            (functions[0].nesting_depth, functions[0].deepest_line),
+            (2, 4)
+        );
+    }
+}
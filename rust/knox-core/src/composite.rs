@@ -0,0 +1,164 @@
+//! Composite rules
+//!
+//! A line pattern alone can't express "`yaml.load(` unless the file uses
+//! `SafeLoader` somewhere". A composite rule pairs a trigger regex, reported
+//! at each line it matches like a line pattern, with file-level conditions:
+//! regexes that must all appear somewhere in the file, and regexes none of
+//! which may appear, for the trigger to count.
+
+use crate::severity::{self, Severity};
+use pyo3::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Kind reported on matches produced by composite rules
+pub const KIND_COMPOSITE: &str = "composite";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct CompositeRule {
+    #[pyo3(get, set)]
+    pub name: String,
+    #[pyo3(get)]
+    pub severity: Severity,
+    #[pyo3(get, set)]
+    pub category: String,
+    #[pyo3(get, set)]
+    pub description: String,
+    /// Regex reported at each line it matches
+    #[pyo3(get, set)]
+    pub trigger: String,
+    /// Regexes that must each appear somewhere in the file
+    #[pyo3(get, set)]
+    pub requires: Vec<String>,
+    /// Regexes whose presence anywhere in the file silences the rule
+    #[pyo3(get, set)]
+    pub forbids: Vec<String>,
+}
+
+#[pymethods]
+impl CompositeRule {
+    #[new]
+    pub fn new(
+        name: String,
+        #[pyo3(from_py_with = "severity::from_py")] severity: Severity,
+        category: String,
+        description: String,
+        trigger: String,
+        requires: Option<Vec<String>>,
+        forbids: Option<Vec<String>>,
+    ) -> Self {
+        CompositeRule {
+            name,
+            severity,
+            category,
+            description,
+            trigger,
+            requires: requires.unwrap_or_default(),
+            forbids: forbids.unwrap_or_default(),
+        }
+    }
+
+    /// Accepts a `Severity` or its name
+    #[setter(severity)]
+    fn set_py_severity(&mut self, value: &PyAny) -> PyResult<()> {
+        self.severity = severity::from_py(value)?;
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "CompositeRule(name={}, severity={})",
+            self.name, self.severity
+        )
+    }
+}
+
+impl CompositeRule {
+    /// Summary of the conditions, for finding explanations
+    pub fn clause(&self) -> String {
+        let mut clauses = vec![format!("`{}`", self.trigger)];
+        clauses.extend(
+            self.requires
+                .iter()
+                .map(|p| format!("file contains `{}`", p)),
+        );
+        clauses.extend(self.forbids.iter().map(|p| format!("file lacks `{}`", p)));
+        clauses.join("; ")
+    }
+
+    /// Every regex source the rule uses, trigger first
+    pub fn sources(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.trigger)
+            .chain(&self.requires)
+            .chain(&self.forbids)
+    }
+
+    /// Trigger hits as (1-based line, byte offset in the line, text) when
+    /// the file-level conditions hold
+    ///
+    /// `compile` resolves a regex source to a compiled regex; rules whose
+    /// regexes fail to compile never fire.
+    pub fn evaluate<'a, F>(&self, content: &'a str, mut compile: F) -> Vec<(usize, usize, &'a str)>
+    where
+        F: FnMut(&str) -> Option<Regex>,
+    {
+        let Some(trigger) = compile(&self.trigger) else {
+            return Vec::new();
+        };
+        if !trigger.is_match(content) {
+            return Vec::new();
+        }
+        for pattern in &self.requires {
+            match compile(pattern) {
+                Some(regex) if regex.is_match(content) => {}
+                _ => return Vec::new(),
+            }
+        }
+        for pattern in &self.forbids {
+            match compile(pattern) {
+                Some(regex) if !regex.is_match(content) => {}
+                _ => return Vec::new(),
+            }
+        }
+
+        content
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                trigger
+                    .find(line)
+                    .map(|found| (i + 1, found.start(), found.as_str()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigger_without_forbidden_pattern() {
+        let rule = CompositeRule::new(
+            "unsafe_yaml_load".to_string(),
+            Severity::High,
+            "deserialization".to_string(),
+            "yaml.load without SafeLoader".to_string(),
+            r"yaml\.load\(".to_string(),
+            Some(vec![r"import yaml".to_string()]),
+            Some(vec![r"Loader\s*=\s*(yaml\.)?SafeLoader".to_string()]),
+        );
+        let compile = |p: &str| Regex::new(p).ok();
+
+        let unsafe_load = "import yaml\n\nconfig = yaml.load(f)\n";
+        assert_eq!(
+            rule.evaluate(unsafe_load, compile),
+            vec![(3, 9, "yaml.load(")]
+        );
+        let safe_load = "import yaml\nconfig = yaml.load(f, Loader=yaml.SafeLoader)\n";
+        assert!(rule.evaluate(safe_load, compile).is_empty());
+        // Missing a required pattern
+        assert!(rule.evaluate("config = yaml.load(f)\n", compile).is_empty());
+    }
+}
@@ -0,0 +1,652 @@
+//! Rules over key paths in YAML and JSON configuration
+//!
+//! A regex over raw text can't tell `aws.secret_access_key` from a comment
+//! mentioning it, and a nested key has no single line spelling out its full
+//! path. Config rules instead run over the scalar leaves of the document,
+//! each reported with its dotted key path (`aws.secret_access_key`,
+//! `servers.0.password`) and the line it sits on.
+//!
+//! The YAML reader covers block mappings and sequences, which is what
+//! configuration files are written in; flow collections are seen as plain
+//! values and multi-line scalars are skipped.
+
+use crate::matcher;
+use crate::severity::{self, Severity};
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Kind reported on matches produced by config rules
+pub const KIND_CONFIG: &str = "config";
+
+/// Languages whose documents config rules run over
+pub const LANGUAGES: &[&str] = &["yaml", "json"];
+
+/// Nesting beyond this depth is not read, so hostile JSON can't exhaust the
+/// stack
+const MAX_JSON_DEPTH: usize = 128;
+
+/// A scalar in a config document
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigEntry {
+    /// Keys from the root, with sequence items as their index
+    pub path: Vec<String>,
+    pub value: String,
+    pub line_number: usize,
+}
+
+impl ConfigEntry {
+    pub fn dotted_path(&self) -> String {
+        self.path.join(".")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ConfigRule {
+    #[pyo3(get, set)]
+    pub name: String,
+    #[pyo3(get)]
+    pub severity: Severity,
+    #[pyo3(get, set)]
+    pub category: String,
+    #[pyo3(get, set)]
+    pub description: String,
+    /// Dotted key path, where `*` stands for one key and `**` for any number
+    /// of keys; compared case-insensitively
+    #[pyo3(get)]
+    pub key_path: String,
+    /// Regex the value must match; without one, any non-empty literal
+    /// value (not a `${VAR}` or `{{ template }}` reference) is reported
+    #[pyo3(get, set)]
+    pub value_pattern: Option<String>,
+    /// CWE identifier, e.g. `CWE-798`
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub cwe: Option<String>,
+    /// OWASP Top 10 category, e.g. `A05:2021`
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub owasp: Option<String>,
+    /// Links to documentation for the weakness
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub references: Vec<String>,
+}
+
+#[pymethods]
+impl ConfigRule {
+    #[new]
+    pub fn new(
+        name: String,
+        #[pyo3(from_py_with = "severity::from_py")] severity: Severity,
+        category: String,
+        description: String,
+        key_path: String,
+        value_pattern: Option<String>,
+    ) -> PyResult<Self> {
+        if key_path.split('.').any(str::is_empty) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid key path for {}: {:?}",
+                name, key_path
+            )));
+        }
+        Ok(ConfigRule {
+            name,
+            severity,
+            category,
+            description,
+            key_path,
+            value_pattern,
+            cwe: None,
+            owasp: None,
+            references: Vec::new(),
+        })
+    }
+
+    /// Accepts a `Severity` or its name
+    #[setter(severity)]
+    fn set_py_severity(&mut self, value: &PyAny) -> PyResult<()> {
+        self.severity = severity::from_py(value)?;
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ConfigRule(name={}, key_path={}, severity={})",
+            self.name, self.key_path, self.severity
+        )
+    }
+}
+
+fn path_matches(pattern: &[&str], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| path_matches(rest, &path[skip..])),
+        Some((segment, rest)) => path.split_first().is_some_and(|(key, path)| {
+            (*segment == "*" || segment.eq_ignore_ascii_case(key)) && path_matches(rest, path)
+        }),
+    }
+}
+
+/// Whether `value` is written out rather than taken from the environment or
+/// a template
+pub fn is_literal(value: &str) -> bool {
+    let value = value.trim();
+    !(value.is_empty()
+        || value == "~"
+        || value.eq_ignore_ascii_case("null")
+        || value.starts_with("${")
+        || value.starts_with("{{")
+        || (value.starts_with('<') && value.ends_with('>')))
+}
+
+impl ConfigRule {
+    /// Set the CWE and OWASP Top 10 ids, like `SecurityPattern::with_cwe`
+    pub fn with_cwe(mut self, cwe: &str, owasp: &str) -> Self {
+        self.cwe = Some(cwe.to_string());
+        self.owasp = Some(owasp.to_string());
+        self.references = matcher::weakness_references(cwe, owasp);
+        self
+    }
+
+    /// Summary of the condition, for finding explanations
+    pub fn clause(&self) -> String {
+        match &self.value_pattern {
+            Some(pattern) => format!("key path `{}` with value `{}`", self.key_path, pattern),
+            None => format!("key path `{}` with a literal value", self.key_path),
+        }
+    }
+
+    /// Entries whose key path this rule covers
+    pub fn candidates<'a>(&self, entries: &'a [ConfigEntry]) -> Vec<&'a ConfigEntry> {
+        let pattern: Vec<&str> = self.key_path.split('.').collect();
+        entries
+            .iter()
+            .filter(|e| path_matches(&pattern, &e.path))
+            .filter(|e| self.value_pattern.is_some() || is_literal(&e.value))
+            .collect()
+    }
+}
+
+fn config_rule(
+    name: &str,
+    severity: Severity,
+    category: &str,
+    description: &str,
+    key_path: &str,
+    value_pattern: Option<&str>,
+) -> ConfigRule {
+    ConfigRule {
+        name: name.to_string(),
+        severity,
+        category: category.to_string(),
+        description: description.to_string(),
+        key_path: key_path.to_string(),
+        value_pattern: value_pattern.map(str::to_string),
+        cwe: None,
+        owasp: None,
+        references: Vec::new(),
+    }
+}
+
+/// Config rules applied to YAML and JSON files by default
+pub fn default_config_rules() -> Vec<ConfigRule> {
+    vec![
+        config_rule(
+            "config_aws_secret_key",
+            Severity::Critical,
+            "secrets",
+            "AWS secret access key written into configuration",
+            "**.secret_access_key",
+            None,
+        )
+        .with_cwe("CWE-798", "A07:2021"),
+        config_rule(
+            "config_aws_secret_key_variable",
+            Severity::Critical,
+            "secrets",
+            "AWS_SECRET_ACCESS_KEY set in configuration",
+            "**.aws_secret_access_key",
+            None,
+        )
+        .with_cwe("CWE-798", "A07:2021"),
+        config_rule(
+            "config_password",
+            Severity::High,
+            "secrets",
+            "Password written into configuration",
+            "**.password",
+            None,
+        )
+        .with_cwe("CWE-798", "A07:2021"),
+        config_rule(
+            "config_security_disabled",
+            Severity::High,
+            "config",
+            "Security feature switched off in configuration",
+            "**.security.enabled",
+            Some(r"(?i)^(false|no|off)$"),
+        )
+        .with_cwe("CWE-693", "A05:2021"),
+        config_rule(
+            "config_tls_verify_disabled",
+            Severity::High,
+            "config",
+            "TLS certificate verification disabled in configuration",
+            "**.insecure_skip_verify",
+            Some(r"(?i)^(true|yes|on)$"),
+        )
+        .with_cwe("CWE-295", "A07:2021"),
+        config_rule(
+            "config_debug_enabled",
+            Severity::Medium,
+            "config",
+            "Debug mode enabled in configuration",
+            "**.debug",
+            Some(r"(?i)^(true|yes|on)$"),
+        )
+        .with_cwe("CWE-489", "A05:2021"),
+    ]
+}
+
+/// Scalar entries of a `language` document, empty for other languages
+pub fn extract_entries(language: &str, content: &str) -> Vec<ConfigEntry> {
+    match language {
+        "yaml" => yaml_entries(content),
+        "json" => {
+            let mut reader = JsonReader {
+                bytes: content.as_bytes(),
+                pos: 0,
+                line: 1,
+                entries: Vec::new(),
+            };
+            // A malformed document keeps the entries read before the error
+            let _ = reader.value(&mut Vec::new(), 0);
+            reader.entries
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn unquote(text: &str) -> String {
+    let text = text.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = text.strip_prefix(quote).and_then(|t| t.strip_suffix(quote)) {
+            return inner.to_string();
+        }
+    }
+    text.to_string()
+}
+
+/// `text` up to a ` #` comment outside quotes
+fn strip_yaml_comment(text: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '#') if previous.is_whitespace() => return &text[..i],
+            _ => {}
+        }
+        previous = c;
+    }
+    text
+}
+
+/// Split `key: value` at the first `: ` (or trailing `:`) outside quotes
+fn split_yaml_key(text: &str) -> Option<(String, &str)> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, ':') => {
+                let rest = &text[i + 1..];
+                if rest.is_empty() || rest.starts_with([' ', '\t']) {
+                    return Some((unquote(&text[..i]), rest.trim()));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+struct YamlFrame {
+    indent: usize,
+    key: String,
+    is_item: bool,
+    items: usize,
+}
+
+fn yaml_entries(content: &str) -> Vec<ConfigEntry> {
+    let mut entries = Vec::new();
+    let mut frames: Vec<YamlFrame> = Vec::new();
+    let mut root_items = 0;
+    // Lines of a `|` or `>` block scalar are indented past its key
+    let mut block_scalar: Option<usize> = None;
+
+    for (i, raw) in content.lines().enumerate() {
+        let line_number = i + 1;
+        let line = strip_yaml_comment(raw).trim_end();
+        let body = line.trim_start();
+        if body.is_empty() {
+            continue;
+        }
+        let mut indent = line.len() - body.len();
+        if let Some(scalar_indent) = block_scalar {
+            if indent > scalar_indent {
+                continue;
+            }
+            block_scalar = None;
+        }
+        if indent == 0 && (body.starts_with("---") || body.starts_with("...")) {
+            frames.clear();
+            root_items = 0;
+            continue;
+        }
+
+        let is_item = body == "-" || body.starts_with("- ");
+        while let Some(top) = frames.last() {
+            // `key:` followed by items at its own indent still owns them
+            let owns_items = is_item && !top.is_item && top.indent == indent;
+            if top.indent < indent || owns_items {
+                break;
+            }
+            frames.pop();
+        }
+
+        let mut body = body;
+        if is_item {
+            let counter = match frames.last_mut() {
+                Some(parent) => &mut parent.items,
+                None => &mut root_items,
+            };
+            let index = *counter;
+            *counter += 1;
+            frames.push(YamlFrame {
+                indent,
+                key: index.to_string(),
+                is_item: true,
+                items: 0,
+            });
+            let rest = body[1..].trim_start();
+            indent += body.len() - rest.len();
+            body = rest;
+            if body.is_empty() {
+                continue;
+            }
+        }
+
+        let path = |frames: &[YamlFrame]| -> Vec<String> {
+            frames.iter().map(|f| f.key.clone()).collect()
+        };
+        match split_yaml_key(body) {
+            Some((key, "")) => frames.push(YamlFrame {
+                indent,
+                key,
+                is_item: false,
+                items: 0,
+            }),
+            Some((_, value)) if value.starts_with(['|', '>']) => block_scalar = Some(indent),
+            Some((key, value)) => {
+                let mut path = path(&frames);
+                path.push(key);
+                entries.push(ConfigEntry {
+                    path,
+                    value: unquote(value),
+                    line_number,
+                });
+            }
+            None if is_item => entries.push(ConfigEntry {
+                path: path(&frames),
+                value: unquote(body),
+                line_number,
+            }),
+            None => {}
+        }
+    }
+    entries
+}
+
+struct JsonReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    line: usize,
+    entries: Vec<ConfigEntry>,
+}
+
+impl JsonReader<'_> {
+    fn skip_whitespace(&mut self) {
+        while let Some(&b) = self.bytes.get(self.pos) {
+            if !b.is_ascii_whitespace() {
+                break;
+            }
+            self.line += usize::from(b == b'\n');
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        self.skip_whitespace();
+        (self.bytes.get(self.pos) == Some(&byte)).then(|| self.pos += 1)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(&b) = self.bytes.get(self.pos) {
+            self.pos += 1;
+            match b {
+                b'\\' => self.pos += 1,
+                b'"' => {
+                    let raw = std::str::from_utf8(&self.bytes[start..self.pos]).ok()?;
+                    return serde_json::from_str(raw).ok();
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn value(&mut self, path: &mut Vec<String>, depth: usize) -> Option<()> {
+        if depth > MAX_JSON_DEPTH {
+            return None;
+        }
+        self.skip_whitespace();
+        let line_number = self.line;
+        match *self.bytes.get(self.pos)? {
+            b'{' => {
+                self.pos += 1;
+                if self.expect(b'}').is_some() {
+                    return Some(());
+                }
+                loop {
+                    self.skip_whitespace();
+                    if self.bytes.get(self.pos) != Some(&b'"') {
+                        return None;
+                    }
+                    path.push(self.string()?);
+                    self.expect(b':')?;
+                    let read = self.value(path, depth + 1);
+                    path.pop();
+                    read?;
+                    if self.expect(b',').is_none() {
+                        return self.expect(b'}');
+                    }
+                }
+            }
+            b'[' => {
+                self.pos += 1;
+                if self.expect(b']').is_some() {
+                    return Some(());
+                }
+                for index in 0.. {
+                    path.push(index.to_string());
+                    let read = self.value(path, depth + 1);
+                    path.pop();
+                    read?;
+                    if self.expect(b',').is_none() {
+                        break;
+                    }
+                }
+                self.expect(b']')
+            }
+            b'"' => {
+                let value = self.string()?;
+                self.push(path, value, line_number);
+                Some(())
+            }
+            _ => {
+                let start = self.pos;
+                while self
+                    .bytes
+                    .get(self.pos)
+                    .is_some_and(|b| !b",}] \t\r\n".contains(b))
+                {
+                    self.pos += 1;
+                }
+                if self.pos == start {
+                    return None;
+                }
+                let value = String::from_utf8_lossy(&self.bytes[start..self.pos]).to_string();
+                self.push(path, value, line_number);
+                Some(())
+            }
+        }
+    }
+
+    fn push(&mut self, path: &[String], value: String, line_number: usize) {
+        self.entries.push(ConfigEntry {
+            path: path.to_vec(),
+            value,
+            line_number,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::PatternMatcher;
+
+    fn flatten(entries: &[ConfigEntry]) -> Vec<(String, String, usize)> {
+        entries
+            .iter()
+            .map(|e| (e.dotted_path(), e.value.clone(), e.line_number))
+            .collect()
+    }
+
+    #[test]
+    fn test_yaml_and_json_key_paths() {
+        let yaml = "# deploy\naws:\n  region: eu-west-1\n  secret_access_key: \"abc#123\" # inline\nservers:\n- host: a\n  password: ${PASS}\n- b\nscript: |\n  key: not a key\nsecurity:\n    enabled: false\n";
+        let entries = flatten(&yaml_entries(yaml));
+        assert_eq!(
+            entries,
+            vec![
+                ("aws.region".to_string(), "eu-west-1".to_string(), 3),
+                (
+                    "aws.secret_access_key".to_string(),
+                    "abc#123".to_string(),
+                    4
+                ),
+                ("servers.0.host".to_string(), "a".to_string(), 6),
+                ("servers.0.password".to_string(), "${PASS}".to_string(), 7),
+                ("servers.1".to_string(), "b".to_string(), 8),
+                ("security.enabled".to_string(), "false".to_string(), 12),
+            ]
+        );
+
+        let json = "{\n  \"db\": {\"password\": \"hunter2\",\n    \"ports\": [5432,\n 5433]},\n  \"debug\": true\n}";
+        let entries = flatten(&extract_entries("json", json));
+        assert_eq!(
+            entries,
+            vec![
+                ("db.password".to_string(), "hunter2".to_string(), 2),
+                ("db.ports.0".to_string(), "5432".to_string(), 3),
+                ("db.ports.1".to_string(), "5433".to_string(), 4),
+                ("debug".to_string(), "true".to_string(), 5),
+            ]
+        );
+        assert_eq!(extract_entries("json", "{\"a\": [1, ").len(), 1);
+        assert!(extract_entries("json", &"[".repeat(10_000)).is_empty());
+    }
+
+    #[test]
+    fn test_default_config_rules() {
+        let yaml = "aws:\n  secret_access_key: wJalrXUtnFEMIK7MDENGbPxRfiCYEXAMPLEKEY\ndb:\n  password: ${DB_PASSWORD}\ncache:\n  password: hunter2 # knox:ignore\nsecurity:\n  enabled: 'false'\n";
+        let mut matcher = PatternMatcher::new();
+        let matches = matcher.match_config("yaml", yaml);
+        let found: Vec<(&str, usize, &str)> = matches
+            .iter()
+            .map(|m| {
+                (
+                    m.pattern_name.as_str(),
+                    m.line_number,
+                    m.matched_text.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "config_aws_secret_key",
+                    2,
+                    "aws.secret_access_key: wJalrXUtnFEMIK7MDENGbPxRfiCYEXAMPLEKEY"
+                ),
+                ("config_security_disabled", 8, "security.enabled: false"),
+            ]
+        );
+        assert_eq!(matches[0].kind, KIND_CONFIG);
+        assert_eq!(matches[0].column, 21);
+        assert_eq!(matches[0].cwe.as_deref(), Some("CWE-798"));
+        assert_eq!(matches[1].owasp.as_deref(), Some("A05:2021"));
+        assert!(matches[0].references[0].ends_with("/798.html"));
+        assert!(matcher.match_config("python", yaml).is_empty());
+
+        matcher.add_config_rule(
+            ConfigRule::new(
+                "public_bucket".to_string(),
+                Severity::High,
+                "config".to_string(),
+                "Bucket readable by anyone".to_string(),
+                "buckets.*.acl".to_string(),
+                Some("^public".to_string()),
+            )
+            .unwrap(),
+        );
+        let json = "{\"buckets\": [{\"acl\": \"private\"}, {\"acl\": \"public-read\"}]}";
+        let names: Vec<String> = matcher
+            .match_config("json", json)
+            .into_iter()
+            .map(|m| m.matched_text)
+            .collect();
+        assert_eq!(names, vec!["buckets.1.acl: public-read"]);
+    }
+
+    #[test]
+    fn test_key_path_patterns() {
+        let path: Vec<String> = ["prod", "aws", "secret_access_key"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(path_matches(&["**", "aws", "secret_access_key"], &path));
+        assert!(path_matches(&["*", "AWS", "*"], &path));
+        assert!(!path_matches(&["aws", "secret_access_key"], &path));
+        assert!(path_matches(&["**"], &path));
+        assert!(!is_literal("${AWS_SECRET}"));
+        assert!(!is_literal("{{ vault.secret }}"));
+        assert!(is_literal("wJalrXUtnFEMI"));
+        assert!(ConfigRule::new(
+            "r".to_string(),
+            Severity::Low,
+            "config".to_string(),
+            "d".to_string(),
+            "a..b".to_string(),
+            None,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,371 @@
+//! Rules over parsed code constructs
+//!
+//! Construct rules match a regex against one kind of structural element
+//! extracted by `CodeParser` (function names, parameters, import modules and
+//! items, string literals, call sites, classes, comments, assignments,
+//! taint sources) instead of raw lines, so a rule such as "any public
+//! function named `*_unsafe`" doesn't also fire on comments or calls, and
+//! one about `subprocess.run` doesn't fire on its import
+
+use crate::parser::{
+    ParsedAssignment, ParsedCall, ParsedClass, ParsedComment, ParsedFunction, ParsedImport,
+    ParsedSource, StringLiteral,
+};
+use crate::severity::{self, Severity};
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// Kind reported on matches produced by construct rules
+pub const KIND_CONSTRUCT: &str = "construct";
+
+/// Structural elements a construct rule can target
+pub const TARGETS: &[&str] = &[
+    "function_name",
+    "function_parameter",
+    "function_decorator",
+    "import_module",
+    "import_item",
+    "string_literal",
+    "call_name",
+    "call_arguments",
+    "class_name",
+    "class_base",
+    "class_decorator",
+    "comment",
+    "assignment_name",
+    "assignment_value",
+    // `name = value`, for rules on both ("`*_secret` assigned a literal")
+    "assignment",
+    "taint_source",
+];
+
+/// Constructs extracted from one file by `CodeParser::parse`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[pyclass]
+pub struct ParseArtifacts {
+    #[pyo3(get)]
+    pub functions: Vec<ParsedFunction>,
+    #[pyo3(get)]
+    pub imports: Vec<ParsedImport>,
+    #[pyo3(get)]
+    pub strings: Vec<StringLiteral>,
+    #[pyo3(get)]
+    #[serde(default)]
+    pub calls: Vec<ParsedCall>,
+    #[pyo3(get)]
+    #[serde(default)]
+    pub classes: Vec<ParsedClass>,
+    #[pyo3(get)]
+    #[serde(default)]
+    pub comments: Vec<ParsedComment>,
+    #[pyo3(get)]
+    #[serde(default)]
+    pub assignments: Vec<ParsedAssignment>,
+    #[pyo3(get)]
+    #[serde(default)]
+    pub sources: Vec<ParsedSource>,
+}
+
+#[pymethods]
+impl ParseArtifacts {
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        functions: Option<Vec<ParsedFunction>>,
+        imports: Option<Vec<ParsedImport>>,
+        strings: Option<Vec<StringLiteral>>,
+        calls: Option<Vec<ParsedCall>>,
+        classes: Option<Vec<ParsedClass>>,
+        comments: Option<Vec<ParsedComment>>,
+        assignments: Option<Vec<ParsedAssignment>>,
+        sources: Option<Vec<ParsedSource>>,
+    ) -> Self {
+        ParseArtifacts {
+            functions: functions.unwrap_or_default(),
+            imports: imports.unwrap_or_default(),
+            strings: strings.unwrap_or_default(),
+            calls: calls.unwrap_or_default(),
+            classes: classes.unwrap_or_default(),
+            comments: comments.unwrap_or_default(),
+            assignments: assignments.unwrap_or_default(),
+            sources: sources.unwrap_or_default(),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ParseArtifacts(functions={}, imports={}, strings={}, calls={}, classes={}, comments={}, assignments={}, sources={})",
+            self.functions.len(),
+            self.imports.len(),
+            self.strings.len(),
+            self.calls.len(),
+            self.classes.len(),
+            self.comments.len(),
+            self.assignments.len(),
+            self.sources.len()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ConstructRule {
+    #[pyo3(get, set)]
+    pub name: String,
+    #[pyo3(get)]
+    pub severity: Severity,
+    #[pyo3(get, set)]
+    pub category: String,
+    #[pyo3(get, set)]
+    pub description: String,
+    /// One of `TARGETS`
+    #[pyo3(get)]
+    pub target: String,
+    /// Regex matched against the text of each targeted construct
+    #[pyo3(get, set)]
+    pub pattern: String,
+    /// Skip private functions (names starting with `_`, or declared
+    /// `private`)
+    #[pyo3(get, set)]
+    pub public_only: bool,
+}
+
+#[pymethods]
+impl ConstructRule {
+    #[new]
+    pub fn new(
+        name: String,
+        #[pyo3(from_py_with = "severity::from_py")] severity: Severity,
+        category: String,
+        description: String,
+        target: String,
+        pattern: String,
+        public_only: Option<bool>,
+    ) -> PyResult<Self> {
+        if !TARGETS.contains(&target.as_str()) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown construct target: {} (expected one of {:?})",
+                target, TARGETS
+            )));
+        }
+
+        Ok(ConstructRule {
+            name,
+            severity,
+            category,
+            description,
+            target,
+            pattern,
+            public_only: public_only.unwrap_or(false),
+        })
+    }
+
+    /// Accepts a `Severity` or its name
+    #[setter(severity)]
+    fn set_py_severity(&mut self, value: &PyAny) -> PyResult<()> {
+        self.severity = severity::from_py(value)?;
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ConstructRule(name={}, target={}, severity={})",
+            self.name, self.target, self.severity
+        )
+    }
+}
+
+impl ConstructRule {
+    /// Summary of the condition, for finding explanations
+    pub fn clause(&self) -> String {
+        format!("{} matching `{}`", self.target, self.pattern)
+    }
+
+    /// (line number, text) of every construct this rule looks at
+    pub fn candidates<'a>(&self, artifacts: &'a ParseArtifacts) -> Vec<(usize, Cow<'a, str>)> {
+        let functions = artifacts
+            .functions
+            .iter()
+            .filter(|f| {
+                !self.public_only
+                    || !(f.name.starts_with('_') || f.modifiers.iter().any(|m| m == "private"))
+            });
+
+        let texts: Vec<(usize, &'a str)> = match self.target.as_str() {
+            "function_name" => functions
+                .map(|f| (f.line_number, f.name.as_str()))
+                .collect(),
+            "function_parameter" => functions
+                .flat_map(|f| f.parameters.iter().map(|p| (f.line_number, p.as_str())))
+                .collect(),
+            "function_decorator" => functions
+                .flat_map(|f| f.decorators.iter().map(|d| (f.line_number, d.as_str())))
+                .collect(),
+            "import_module" => artifacts
+                .imports
+                .iter()
+                .filter(|i| !i.module.is_empty())
+                .map(|i| (i.line_number, i.module.as_str()))
+                .collect(),
+            "import_item" => artifacts
+                .imports
+                .iter()
+                .flat_map(|i| i.items.iter().map(|item| (i.line_number, item.as_str())))
+                .collect(),
+            "string_literal" => artifacts
+                .strings
+                .iter()
+                .map(|s| (s.line_number, s.value.as_str()))
+                .collect(),
+            "call_name" => artifacts
+                .calls
+                .iter()
+                .map(|c| (c.line_number, c.callee.as_str()))
+                .collect(),
+            "call_arguments" => artifacts
+                .calls
+                .iter()
+                .map(|c| (c.line_number, c.arguments.as_str()))
+                .collect(),
+            "class_name" => artifacts
+                .classes
+                .iter()
+                .map(|c| (c.line_number, c.name.as_str()))
+                .collect(),
+            "class_base" => artifacts
+                .classes
+                .iter()
+                .flat_map(|c| c.bases.iter().map(|b| (c.line_number, b.as_str())))
+                .collect(),
+            "class_decorator" => artifacts
+                .classes
+                .iter()
+                .flat_map(|c| c.decorators.iter().map(|d| (c.line_number, d.as_str())))
+                .collect(),
+            "comment" => artifacts
+                .comments
+                .iter()
+                .map(|c| (c.line_number, c.text.as_str()))
+                .collect(),
+            "assignment_name" => artifacts
+                .assignments
+                .iter()
+                .map(|a| (a.line_number, a.name.as_str()))
+                .collect(),
+            "assignment_value" => artifacts
+                .assignments
+                .iter()
+                .map(|a| (a.line_number, a.value.as_str()))
+                .collect(),
+            "taint_source" => artifacts
+                .sources
+                .iter()
+                .map(|s| (s.line_number, s.expression.as_str()))
+                .collect(),
+            "assignment" => {
+                return artifacts
+                    .assignments
+                    .iter()
+                    .map(|a| {
+                        (
+                            a.line_number,
+                            Cow::Owned(format!("{} = {}", a.name, a.value)),
+                        )
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        texts
+            .into_iter()
+            .map(|(line_number, text)| (line_number, Cow::Borrowed(text)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::PatternMatcher;
+    use crate::parser::CodeParser;
+
+    #[test]
+    fn test_public_unsafe_functions_flagged() {
+        let code = "def load_unsafe(data):\n    pass\n\ndef _parse_unsafe(data):\n    pass\n# call load_unsafe() here\n";
+        let artifacts = CodeParser::new(None).parse(code);
+
+        let mut matcher = PatternMatcher::new();
+        matcher.add_construct_rule(
+            ConstructRule::new(
+                "public_unsafe_function".to_string(),
+                Severity::Medium,
+                "api".to_string(),
+                "Public function marked unsafe".to_string(),
+                "function_name".to_string(),
+                r"_unsafe$".to_string(),
+                Some(true),
+            )
+            .unwrap(),
+        );
+
+        let matches = matcher.match_constructs(&artifacts);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 1);
+        assert_eq!(matches[0].matched_text, "load_unsafe");
+        assert_eq!(matches[0].kind, KIND_CONSTRUCT);
+    }
+
+    #[test]
+    fn test_function_decorators_targeted() {
+        let code = concat!(
+            "@app.route(\"/transfer\", methods=[\"POST\"])\n",
+            "@csrf_exempt\n",
+            "def transfer(request):\n",
+            "    pass\n",
+            "\n",
+            "@login_required\n",
+            "def profile(request):\n",
+            "    pass\n",
+        );
+        let artifacts = CodeParser::new(None).parse(code);
+        assert_eq!(
+            artifacts.functions[0].decorators,
+            vec![
+                "app.route(\"/transfer\", methods=[\"POST\"])",
+                "csrf_exempt"
+            ]
+        );
+
+        let mut matcher = PatternMatcher::new();
+        matcher.add_construct_rule(
+            ConstructRule::new(
+                "csrf_exempt_view".to_string(),
+                Severity::Medium,
+                "authz".to_string(),
+                "View exempted from CSRF protection".to_string(),
+                "function_decorator".to_string(),
+                r"^csrf_exempt\b".to_string(),
+                None,
+            )
+            .unwrap(),
+        );
+        let matches = matcher.match_constructs(&artifacts);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 3);
+    }
+
+    #[test]
+    fn test_unknown_target_rejected() {
+        assert!(ConstructRule::new(
+            "r".to_string(),
+            Severity::Low,
+            "api".to_string(),
+            "d".to_string(),
+            "decorator".to_string(),
+            ".*".to_string(),
+            None,
+        )
+        .is_err());
+    }
+}
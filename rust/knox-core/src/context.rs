@@ -0,0 +1,218 @@
+//! Context lines around matches
+//!
+//! Context is sliced out of the content that was just matched (the mmap of
+//! the file, during the same pass) through a line start index, so files are
+//! never re-read to show surrounding code. A per-file byte budget keeps
+//! memory predictable on files with many findings.
+//!
+//! The same index backs `byte_to_linecol` and `extract_snippet`, exposed
+//! so Python detectors and report tooling get columns that agree with the
+//! scanner's.
+
+use crate::matcher::Match;
+use pyo3::prelude::*;
+
+/// Column in characters (Unicode scalar values) of a byte offset in a line,
+/// so multi-byte text before a finding doesn't shift its column
+pub fn char_column(line: &str, byte_offset: usize) -> usize {
+    line.char_indices()
+        .take_while(|(i, _)| *i < byte_offset)
+        .count()
+}
+
+/// Byte offsets of line starts in a piece of content
+pub struct LineIndex<'a> {
+    content: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(content: &'a str) -> Self {
+        let mut starts = vec![0];
+        starts.extend(
+            content
+                .bytes()
+                .enumerate()
+                .filter(|(_, b)| *b == b'\n')
+                .map(|(i, _)| i + 1)
+                .filter(|&start| start < content.len()),
+        );
+        LineIndex { content, starts }
+    }
+
+    pub fn line_count(&self) -> usize {
+        if self.content.is_empty() {
+            0
+        } else {
+            self.starts.len()
+        }
+    }
+
+    /// 1-based line and 0-based character column of a byte offset
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let line = self.starts.partition_point(|&start| start <= offset);
+        let start = self.starts[line - 1];
+        (line, char_column(&self.content[start..], offset - start))
+    }
+
+    /// Byte range of 1-based lines `first..=last`, including the last
+    /// line's terminator; lines past the end are clamped
+    pub fn lines_span(&self, first: usize, last: usize) -> (usize, usize) {
+        let len = self.content.len();
+        let start = self.starts.get(first.max(1) - 1).copied().unwrap_or(len);
+        let end = self.starts.get(last).copied().unwrap_or(len);
+        (start, end.max(start))
+    }
+
+    /// Text of a 1-based line, without its line terminator
+    pub fn line(&self, line_number: usize) -> Option<&'a str> {
+        let start = *self.starts.get(line_number.checked_sub(1)?)?;
+        let end = self
+            .starts
+            .get(line_number)
+            .map_or(self.content.len(), |next| next - 1);
+        // The last line keeps its terminator, since no line starts after it
+        let line = &self.content[start..end.max(start)];
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        Some(line.strip_suffix('\r').unwrap_or(line))
+    }
+}
+
+/// 1-based line and 0-based character column of a UTF-8 byte `offset`
+/// into `content`
+///
+/// Raises ValueError for offsets past the end or inside a multi-byte
+/// character.
+#[pyfunction]
+pub fn byte_to_linecol(content: &str, offset: usize) -> PyResult<(usize, usize)> {
+    if !content.is_char_boundary(offset) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Offset {} is not a character boundary in content of {} bytes",
+            offset,
+            content.len()
+        )));
+    }
+    Ok(LineIndex::new(content).line_col(offset))
+}
+
+/// Line `line` (1-based) with up to `context` lines (default 2) either
+/// side, as (line number, text) pairs without line terminators
+///
+/// Raises ValueError when `content` has no such line.
+#[pyfunction]
+pub fn extract_snippet(
+    content: &str,
+    line: usize,
+    context: Option<usize>,
+) -> PyResult<Vec<(usize, String)>> {
+    let index = LineIndex::new(content);
+    if line == 0 || line > index.line_count() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Line {} is out of range (content has {} lines)",
+            line,
+            index.line_count()
+        )));
+    }
+    let context = context.unwrap_or(2);
+    let first = line.saturating_sub(context).max(1);
+    let last = (line + context).min(index.line_count());
+    Ok((first..=last)
+        .filter_map(|n| index.line(n).map(|text| (n, text.to_string())))
+        .collect())
+}
+
+/// Fill `context_before`/`context_after` on each match
+///
+/// Once `max_bytes` of context has been attached, later matches get none.
+pub fn attach_context(
+    matches: &mut [Match],
+    content: &str,
+    lines: usize,
+    max_bytes: Option<usize>,
+) {
+    if lines == 0 || matches.is_empty() {
+        return;
+    }
+
+    let index = LineIndex::new(content);
+    let mut remaining = max_bytes.unwrap_or(usize::MAX);
+
+    for m in matches.iter_mut() {
+        let first = m.line_number.saturating_sub(lines).max(1);
+        let last = (m.line_number + lines).min(index.line_count());
+        let before: Vec<&str> = (first..m.line_number)
+            .filter_map(|n| index.line(n))
+            .collect();
+        let after: Vec<&str> = (m.line_number + 1..=last)
+            .filter_map(|n| index.line(n))
+            .collect();
+
+        let size: usize = before.iter().chain(&after).map(|l| l.len()).sum();
+        if size > remaining {
+            break;
+        }
+        remaining -= size;
+
+        m.context_before = before.into_iter().map(String::from).collect();
+        m.context_after = after.into_iter().map(String::from).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::PatternMatcher;
+
+    #[test]
+    fn test_line_index() {
+        let index = LineIndex::new("a\r\nbb\n\ncc");
+        assert_eq!(index.line_count(), 4);
+        assert_eq!(index.line(1), Some("a"));
+        assert_eq!(index.line(3), Some(""));
+        assert_eq!(index.line(4), Some("cc"));
+        assert_eq!(index.line(5), None);
+        assert_eq!(LineIndex::new("a\nb\r\n").line(2), Some("b"));
+    }
+
+    #[test]
+    fn test_python_utilities() {
+        let content = "naïve = 1\r\nkey = \"é\"\nend\n";
+        let key = content.find("key").unwrap();
+        assert_eq!(byte_to_linecol(content, key).unwrap(), (2, 0));
+        assert_eq!(byte_to_linecol(content, key + 7).unwrap(), (2, 7));
+        assert_eq!(byte_to_linecol(content, 4).unwrap(), (1, 3));
+        assert!(byte_to_linecol(content, 3).is_err());
+        assert!(byte_to_linecol(content, content.len() + 1).is_err());
+
+        assert_eq!(
+            extract_snippet(content, 3, Some(1)).unwrap(),
+            vec![(2, "key = \"é\"".to_string()), (3, "end".to_string())]
+        );
+        assert_eq!(extract_snippet(content, 1, Some(0)).unwrap().len(), 1);
+        assert!(extract_snippet(content, 4, None).is_err());
+    }
+
+    #[test]
+    fn test_context_respects_byte_budget() {
+        let content = "import os\nDEBUG = True\nx = 1\ny = 2\nDEBUG = 1\nz = 3\n";
+        let mut matches = PatternMatcher::new().match_content(content);
+        assert_eq!(matches.len(), 2);
+
+        attach_context(&mut matches, content, 1, Some(16));
+        assert_eq!(matches[0].context_before, vec!["import os"]);
+        assert_eq!(matches[0].context_after, vec!["x = 1"]);
+        assert!(matches[1].context_before.is_empty());
+    }
+
+    #[test]
+    fn test_matcher_context_lines() {
+        let content = "a = 1\nb = 2\nDEBUG = True\nc = 3\n";
+        let mut matcher = PatternMatcher::new();
+        assert!(matcher.match_content(content)[0].context_before.is_empty());
+
+        matcher.set_context_lines(2);
+        let matches = matcher.match_content(content);
+        assert_eq!(matches[0].context_before, vec!["a = 1", "b = 2"]);
+        assert_eq!(matches[0].context_after, vec!["c = 3"]);
+    }
+}
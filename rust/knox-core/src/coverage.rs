@@ -0,0 +1,126 @@
+//! Scan coverage
+//!
+//! Paths the walker could not enter and files that could not be read are
+//! reported as results carrying an `error` rather than dropped, so callers
+//! can tell a clean scan from one that silently skipped part of the tree.
+//! Compliance scans can require a minimum share of readable files.
+
+use crate::scanner::ScanResult;
+use pyo3::prelude::*;
+
+/// Unreadable paths listed in a coverage error before it is truncated
+const MAX_REPORTED_PATHS: usize = 5;
+
+/// Share of results that were read and scanned, 1.0 for an empty scan
+pub fn coverage(results: &[ScanResult]) -> f64 {
+    if results.is_empty() {
+        return 1.0;
+    }
+    let readable = results.iter().filter(|r| r.error.is_none()).count();
+    readable as f64 / results.len() as f64
+}
+
+/// Reject a `min_coverage` outside 0.0 - 1.0
+pub fn check_threshold(min_coverage: f64) -> PyResult<()> {
+    if !(0.0..=1.0).contains(&min_coverage) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "min_coverage must be between 0.0 and 1.0, got {}",
+            min_coverage
+        )));
+    }
+    Ok(())
+}
+
+/// Fail when fewer than `min_coverage` (0.0 - 1.0) of the files were read
+pub fn check_coverage(results: &[ScanResult], min_coverage: f64) -> PyResult<()> {
+    let unreadable: Vec<ScanResult> = results
+        .iter()
+        .filter(|r| r.error.is_some())
+        .cloned()
+        .collect();
+    check_unreadable(results.len(), &unreadable, min_coverage)
+}
+
+/// Like `check_coverage`, for a streamed scan that only kept its
+/// `unreadable` results out of `total`
+pub fn check_unreadable(
+    total: usize,
+    unreadable: &[ScanResult],
+    min_coverage: f64,
+) -> PyResult<()> {
+    check_threshold(min_coverage)?;
+    let actual = if total == 0 {
+        1.0
+    } else {
+        (total - unreadable.len()) as f64 / total as f64
+    };
+    if actual >= min_coverage {
+        return Ok(());
+    }
+
+    let mut listed: Vec<String> = unreadable
+        .iter()
+        .take(MAX_REPORTED_PATHS)
+        .map(|r| {
+            format!(
+                "{} ({})",
+                r.file_path,
+                r.error.as_deref().unwrap_or_default()
+            )
+        })
+        .collect();
+    if unreadable.len() > MAX_REPORTED_PATHS {
+        listed.push(format!(
+            "and {} more",
+            unreadable.len() - MAX_REPORTED_PATHS
+        ));
+    }
+    Err(pyo3::exceptions::PyIOError::new_err(format!(
+        "Scan coverage {:.1}% is below the required {:.1}%: {}",
+        actual * 100.0,
+        min_coverage * 100.0,
+        listed.join(", ")
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::scan_directory;
+    use crate::scanoptions::ScanOptions;
+    use std::fs::write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_unreadable_files_reported_and_gated() {
+        let temp_dir = TempDir::new().unwrap();
+        write(temp_dir.path().join("a.py"), "DEBUG = True\n").unwrap();
+        write(temp_dir.path().join("b.py"), [0xff, 0xfe, 0x00, 0x41]).unwrap();
+        let root = temp_dir.path().to_string_lossy().to_string();
+
+        let results = scan_directory(
+            root.clone(),
+            Some(ScanOptions {
+                parallel: false,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        assert_eq!(results.len(), 2);
+        let unreadable: Vec<_> = results.iter().filter(|r| r.error.is_some()).collect();
+        assert_eq!(unreadable.len(), 1);
+        assert!(unreadable[0].file_path.ends_with("b.py"));
+        assert_eq!(coverage(&results), 0.5);
+
+        assert!(scan_directory(
+            root,
+            Some(ScanOptions {
+                parallel: false,
+                min_coverage: Some(0.9),
+                ..Default::default()
+            })
+        )
+        .is_err());
+        assert!(check_coverage(&results, 0.5).is_ok());
+    }
+}
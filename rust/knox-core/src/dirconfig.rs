@@ -0,0 +1,220 @@
+//! Per-directory `knox.toml` configuration
+//!
+//! One central config can't serve every team in a monorepo, so any
+//! directory may carry a `knox.toml` layered over those above it. Directory
+//! walks load them as they descend: the nearest `min_severity` applies, rule
+//! toggles override the same rule's toggle further up, and exclude globs
+//! accumulate, each relative to the directory of the file declaring it.
+//!
+//! ```toml
+//! min_severity = "high"
+//! exclude = ["generated/**", "*.pb.go"]
+//!
+//! [rules]
+//! debug_mode = false
+//! ```
+
+use crate::pathfilter;
+use crate::severity::Severity;
+use globset::{GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Name of the config file looked for in every walked directory
+pub const CONFIG_FILE_NAME: &str = "knox.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    min_severity: Option<Severity>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    #[serde(default)]
+    rules: BTreeMap<String, bool>,
+}
+
+impl ConfigFile {
+    fn read(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        toml::from_str(&text).map_err(|e| e.to_string())
+    }
+}
+
+/// Settings in force for one directory: its own `knox.toml` applied over
+/// those of its ancestors
+#[derive(Debug, Default)]
+pub struct DirConfig {
+    pub min_severity: Option<Severity>,
+    /// Whether each toggled rule is enabled, the nearest config winning
+    pub rules: BTreeMap<String, bool>,
+    /// Exclude globs, with the directory they are relative to
+    excludes: Vec<(PathBuf, GlobSet)>,
+    /// Config files merged into this one, outermost first
+    pub sources: Vec<PathBuf>,
+}
+
+impl DirConfig {
+    /// Whether no `knox.toml` applies
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// Whether `path` matches an exclude glob of this or an enclosing config
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.excludes.iter().any(|(base, globs)| {
+            path.strip_prefix(base)
+                .is_ok_and(|relative| globs.is_match(relative))
+        })
+    }
+
+    /// This config with the `knox.toml` in `dir` applied on top
+    fn extended(parent: &Arc<Self>, dir: &Path) -> Result<Arc<Self>, String> {
+        let path = dir.join(CONFIG_FILE_NAME);
+        if !path.is_file() {
+            return Ok(Arc::clone(parent));
+        }
+        let file = ConfigFile::read(&path)?;
+
+        let mut excludes = parent.excludes.clone();
+        if !file.exclude.is_empty() {
+            let mut builder = GlobSetBuilder::new();
+            for pattern in &file.exclude {
+                builder.add(pathfilter::compile_glob(pattern).map_err(|e| e.to_string())?);
+            }
+            excludes.push((
+                dir.to_path_buf(),
+                builder.build().map_err(|e| e.to_string())?,
+            ));
+        }
+        let mut rules = parent.rules.clone();
+        rules.extend(file.rules);
+        let mut sources = parent.sources.clone();
+        sources.push(path);
+
+        Ok(Arc::new(DirConfig {
+            min_severity: file.min_severity.or(parent.min_severity),
+            rules,
+            excludes,
+            sources,
+        }))
+    }
+}
+
+/// Effective configs of the directories a walk has entered
+#[derive(Default)]
+pub struct ConfigTree {
+    dirs: HashMap<PathBuf, Arc<DirConfig>>,
+    empty: Arc<DirConfig>,
+    /// Config files that could not be read or parsed, with the reason;
+    /// their directories inherit the enclosing config
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+impl ConfigTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Config for `path`: that of its parent directory, which the walk has
+    /// entered before reaching it
+    pub fn for_path(&self, path: &Path) -> Arc<DirConfig> {
+        path.parent()
+            .and_then(|dir| self.dirs.get(dir))
+            .unwrap_or(&self.empty)
+            .clone()
+    }
+
+    /// Decide whether the walk skips `path`, loading the config of
+    /// directories it enters
+    pub fn excludes(&mut self, path: &Path, is_dir: bool) -> bool {
+        let config = self.for_path(path);
+        if config.is_excluded(path) {
+            return true;
+        }
+        if is_dir {
+            let own = DirConfig::extended(&config, path).unwrap_or_else(|e| {
+                self.errors.push((path.join(CONFIG_FILE_NAME), e));
+                config
+            });
+            self.dirs.insert(path.to_path_buf(), own);
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::scan_directory;
+    use crate::scanoptions::ScanOptions;
+    use std::fs::{create_dir_all, write};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_nested_configs_override_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let team = root.join("team_a");
+        create_dir_all(team.join("fixtures")).unwrap();
+        create_dir_all(root.join("team_b")).unwrap();
+        write(
+            root.join(CONFIG_FILE_NAME),
+            "min_severity = \"medium\"\nexclude = [\"*_gen.py\"]\n\n[rules]\ndebug_mode = false\n",
+        )
+        .unwrap();
+        write(
+            team.join(CONFIG_FILE_NAME),
+            "exclude = [\"fixtures/**\"]\n[rules]\ndebug_mode = true\n",
+        )
+        .unwrap();
+        write(
+            root.join("team_b").join(CONFIG_FILE_NAME),
+            "min_severity = 3\n",
+        )
+        .unwrap();
+
+        let code = "DEBUG = True\nhashlib.md5(x)\n";
+        for dir in [
+            root,
+            team.as_path(),
+            &team.join("fixtures"),
+            &root.join("team_b"),
+        ] {
+            write(dir.join("app.py"), code).unwrap();
+        }
+        write(team.join("models_gen.py"), code).unwrap();
+
+        let results = scan_directory(
+            root.to_string_lossy().to_string(),
+            Some(ScanOptions {
+                parallel: false,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let found: Vec<(String, Vec<String>)> = results
+            .iter()
+            .map(|r| {
+                let relative = Path::new(&r.file_path).strip_prefix(root).unwrap();
+                let rules = r.matches.iter().map(|m| m.pattern_name.clone()).collect();
+                (relative.to_string_lossy().replace('\\', "/"), rules)
+            })
+            .collect();
+        assert!(found.contains(&("app.py".to_string(), vec!["weak_crypto_md5".to_string()])));
+        assert!(found.contains(&(
+            "team_a/app.py".to_string(),
+            vec!["debug_mode".to_string(), "weak_crypto_md5".to_string()]
+        )));
+        // The broken config is reported and its directory keeps the root's
+        assert!(found.contains(&(
+            "team_b/app.py".to_string(),
+            vec!["weak_crypto_md5".to_string()]
+        )));
+        let broken = results.iter().find(|r| r.error.is_some()).unwrap();
+        assert!(broken.file_path.ends_with(CONFIG_FILE_NAME));
+        assert_eq!(results.len(), 4);
+    }
+}
@@ -0,0 +1,129 @@
+//! Warm-start engine for long-running services
+//!
+//! Building a `PatternMatcher` per request recompiles every rule and
+//! rebuilds the prefilters on the first line it matches. An `Engine` does
+//! that work once at startup, along with the construct parsers, and each
+//! `scan_content` call matches with a cheap copy of the warmed matcher, so
+//! calls can run concurrently from a thread pool without locking. Rules
+//! disabled for exceeding their time budget during a call stay enabled for
+//! later calls.
+
+use crate::guard::RuleDiagnostic;
+use crate::language;
+use crate::matcher::{Match, PatternMatcher};
+use crate::ordering;
+use crate::parser::CodeParser;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// Languages with construct parsers built at startup, besides registered ones
+const PARSER_LANGUAGES: &[&str] = &["python", "javascript", "typescript", "rust"];
+
+#[pyclass]
+pub struct Engine {
+    matcher: PatternMatcher,
+    parsers: HashMap<String, CodeParser>,
+}
+
+#[pymethods]
+impl Engine {
+    /// Warm an engine from a configured matcher, or the default rules
+    #[new]
+    pub fn new(matcher: Option<PatternMatcher>) -> Self {
+        let mut matcher = matcher.unwrap_or_default();
+        matcher.warm_up();
+        let parsers = PARSER_LANGUAGES
+            .iter()
+            .map(|lang| lang.to_string())
+            .chain(language::registered_languages())
+            .map(|lang| (lang.clone(), CodeParser::new(Some(lang))))
+            .collect();
+        Engine { matcher, parsers }
+    }
+
+    /// Scan one piece of content, releasing the GIL while matching
+    ///
+    /// With a `language` that has a parser, construct rules are evaluated
+    /// too, and config rules for "yaml" and "json".
+    pub fn scan_content(
+        &self,
+        py: Python<'_>,
+        content: &str,
+        file_path: Option<String>,
+        language: Option<String>,
+    ) -> Vec<Match> {
+        py.allow_threads(|| self.scan(content, file_path, language.as_deref()))
+    }
+
+    #[getter]
+    pub fn rule_count(&self) -> usize {
+        self.matcher.pattern_count()
+    }
+
+    /// Problems found in custom rules while warming up
+    pub fn diagnostics(&self) -> Vec<RuleDiagnostic> {
+        self.matcher.diagnostics()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Engine(rules={})", self.rule_count())
+    }
+}
+
+impl Engine {
+    pub fn scan(
+        &self,
+        content: &str,
+        file_path: Option<String>,
+        language: Option<&str>,
+    ) -> Vec<Match> {
+        let mut matcher = self.matcher.clone();
+        matcher.set_language(language.map(str::to_string));
+        let mut matches = matcher.match_content(content);
+        if matcher.has_construct_rules() {
+            if let Some(parser) = language.and_then(|lang| self.parsers.get(lang)) {
+                matches.extend(matcher.match_constructs(&parser.parse(content)));
+            }
+        }
+        if let Some(language) = language {
+            matches.extend(matcher.match_config(language, content));
+        }
+        ordering::sort_matches(&mut matches);
+        for m in &mut matches {
+            m.file_path = file_path.clone();
+        }
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_engine_scans_from_many_threads() {
+        assert_send_sync::<Engine>();
+        let engine = Engine::new(None);
+
+        let counts: Vec<usize> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|i| {
+                    let engine = &engine;
+                    scope.spawn(move || {
+                        let path = format!("req{}.py", i);
+                        let matches =
+                            engine.scan("DEBUG = True\nhashlib.md5(x)\n", Some(path.clone()), None);
+                        assert!(matches
+                            .iter()
+                            .all(|m| m.file_path.as_deref() == Some(path.as_str())));
+                        matches.len()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(counts, vec![2; 4]);
+    }
+}
@@ -0,0 +1,72 @@
+//! Built-in directory exclusion sets
+//!
+//! Dependency caches, virtualenvs and build outputs grouped by ecosystem.
+//! Directory scans skip all of them unless default excludes are disabled.
+
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+pub const DEFAULT_EXCLUDE_SETS: &[(&str, &[&str])] = &[
+    ("node", &["node_modules", "bower_components", ".next"]),
+    (
+        "python",
+        &[
+            ".venv",
+            "venv",
+            "site-packages",
+            "__pycache__",
+            ".tox",
+            ".eggs",
+        ],
+    ),
+    ("rust", &["target"]),
+    ("build", &["dist", "build"]),
+    ("vcs", &[".git", ".hg", ".svn"]),
+];
+
+/// Every directory name from the built-in exclusion sets
+pub fn default_excluded_dirs() -> Vec<String> {
+    let mut dirs: Vec<String> = Vec::new();
+    for (_, names) in DEFAULT_EXCLUDE_SETS {
+        for name in names.iter() {
+            if !dirs.iter().any(|d| d == name) {
+                dirs.push(name.to_string());
+            }
+        }
+    }
+    dirs
+}
+
+/// Built-in exclusion sets keyed by ecosystem
+#[pyfunction]
+pub fn default_exclude_sets() -> HashMap<String, Vec<String>> {
+    DEFAULT_EXCLUDE_SETS
+        .iter()
+        .map(|(set, names)| {
+            (
+                set.to_string(),
+                names.iter().map(|n| n.to_string()).collect(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_excludes_cover_common_ecosystems() {
+        let dirs = default_excluded_dirs();
+        for expected in [
+            "node_modules",
+            ".venv",
+            "site-packages",
+            "target",
+            "__pycache__",
+        ] {
+            assert!(dirs.contains(&expected.to_string()), "missing {}", expected);
+        }
+        assert_eq!(default_exclude_sets()["rust"], vec!["target".to_string()]);
+    }
+}
@@ -0,0 +1,128 @@
+//! Rule debugging
+//!
+//! `PatternMatcher::explain` runs a single rule against a line (or a small
+//! file) and reports the first stage that stopped it, so a rule author can see why a
+//! finding did or didn't appear without stepping through the matcher.
+
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The rule matched and the finding would be reported
+pub const OUTCOME_MATCHED: &str = "matched";
+/// No rule with the given name is loaded
+pub const OUTCOME_UNKNOWN_RULE: &str = "unknown_rule";
+/// The rule was disabled, e.g. for exceeding its time budget
+pub const OUTCOME_DISABLED: &str = "disabled";
+/// The rule is less confident than the matcher's minimum confidence
+pub const OUTCOME_BELOW_CONFIDENCE: &str = "below_confidence";
+/// The rule's pattern does not compile
+pub const OUTCOME_INVALID_PATTERN: &str = "invalid_pattern";
+/// The rule needs an import or language the content lacks, see `imports`
+pub const OUTCOME_PRUNED: &str = "pruned";
+/// None of the rule's required literals occur in the line, see `prefilter`
+pub const OUTCOME_PREFILTER_REJECTED: &str = "prefilter_rejected";
+/// The pattern did not match the (normalized) line
+pub const OUTCOME_NO_MATCH: &str = "no_match";
+/// The pattern matched but so did the rule's negative pattern
+pub const OUTCOME_NEGATED: &str = "negated";
+/// The pattern matched but a suppression comment removed the finding
+pub const OUTCOME_SUPPRESSED: &str = "suppressed";
+/// The pattern matched but the allowlist covers the matched text
+pub const OUTCOME_ALLOWLISTED: &str = "allowlisted";
+/// The pattern matched but the rule is below the requested severity
+pub const OUTCOME_BELOW_SEVERITY: &str = "below_severity";
+
+/// Why a finding was reported, filled in as it is produced
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct FindingExplanation {
+    /// What the rule looks for
+    #[pyo3(get)]
+    pub rule_description: String,
+    /// The condition that fired: the compiled regex, or a summary of the
+    /// clauses of a file-level rule
+    #[pyo3(get)]
+    pub clause: String,
+    /// Capture groups of the regex, empty for groups that took no part
+    #[pyo3(get)]
+    pub captures: Vec<String>,
+    /// Changes made after matching, e.g. severity raised by corroborating
+    /// rules or lowered for a test path
+    #[pyo3(get)]
+    pub adjustments: Vec<String>,
+    /// Suppression comments on the line that did not remove the finding,
+    /// and why
+    #[pyo3(get)]
+    pub suppressions: Vec<String>,
+}
+
+impl FindingExplanation {
+    pub fn new(rule_description: &str, clause: impl Into<String>) -> Self {
+        FindingExplanation {
+            rule_description: rule_description.to_string(),
+            clause: clause.into(),
+            ..Default::default()
+        }
+    }
+}
+
+#[pymethods]
+impl FindingExplanation {
+    fn __repr__(&self) -> String {
+        format!(
+            "FindingExplanation(clause={}, adjustments={:?}, suppressions={:?})",
+            self.clause, self.adjustments, self.suppressions
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct RuleExplanation {
+    #[pyo3(get)]
+    pub rule_name: String,
+    /// One of the `OUTCOME_*` values
+    #[pyo3(get)]
+    pub outcome: String,
+    #[pyo3(get)]
+    pub detail: String,
+    /// Text the pattern matched, even when a later stage dropped it
+    #[pyo3(get)]
+    pub matched_text: Option<String>,
+    #[pyo3(get)]
+    pub column: Option<usize>,
+}
+
+#[pymethods]
+impl RuleExplanation {
+    /// Whether the finding would be reported
+    #[getter]
+    pub fn matched(&self) -> bool {
+        self.outcome == OUTCOME_MATCHED
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RuleExplanation(rule={}, outcome={}, detail={})",
+            self.rule_name, self.outcome, self.detail
+        )
+    }
+}
+
+impl RuleExplanation {
+    pub fn new(rule_name: &str, outcome: &str, detail: impl Into<String>) -> Self {
+        RuleExplanation {
+            rule_name: rule_name.to_string(),
+            outcome: outcome.to_string(),
+            detail: detail.into(),
+            matched_text: None,
+            column: None,
+        }
+    }
+
+    pub fn with_match(mut self, matched_text: &str, column: usize) -> Self {
+        self.matched_text = Some(matched_text.to_string());
+        self.column = Some(column);
+        self
+    }
+}
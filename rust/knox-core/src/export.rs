@@ -0,0 +1,201 @@
+//! Arrow IPC and Parquet export of findings (`arrow` feature)
+//!
+//! Flattens scan results into one row per match with a fixed schema, so
+//! large outputs can be loaded straight into DuckDB, Spark or pandas
+//! without parsing JSON.
+
+use crate::scanner::ScanResult;
+use arrow_array::builder::{ListBuilder, StringBuilder, UInt64Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use pyo3::prelude::*;
+use std::fs::File;
+use std::sync::Arc;
+
+/// Schema of exported findings, one row per match
+pub fn findings_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("file_path", DataType::Utf8, false),
+        Field::new("line_number", DataType::UInt64, false),
+        Field::new("column", DataType::UInt64, false),
+        Field::new("end_column", DataType::UInt64, false),
+        Field::new("start_byte", DataType::UInt64, true),
+        Field::new("end_byte", DataType::UInt64, true),
+        Field::new("pattern_name", DataType::Utf8, false),
+        Field::new("severity", DataType::Utf8, false),
+        Field::new("category", DataType::Utf8, false),
+        Field::new("kind", DataType::Utf8, false),
+        Field::new("matched_text", DataType::Utf8, false),
+        Field::new(
+            "tags",
+            DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+            false,
+        ),
+        Field::new("path_class", DataType::Utf8, true),
+        Field::new("language", DataType::Utf8, true),
+        Field::new("rule_pack", DataType::Utf8, true),
+    ])
+}
+
+/// Flatten results into a single record batch
+pub fn findings_batch(results: &[ScanResult]) -> Result<RecordBatch, ArrowError> {
+    let mut file_path = StringBuilder::new();
+    let mut line_number = UInt64Builder::new();
+    let mut column = UInt64Builder::new();
+    let mut end_column = UInt64Builder::new();
+    let mut start_byte = UInt64Builder::new();
+    let mut end_byte = UInt64Builder::new();
+    let mut pattern_name = StringBuilder::new();
+    let mut severity = StringBuilder::new();
+    let mut category = StringBuilder::new();
+    let mut kind = StringBuilder::new();
+    let mut matched_text = StringBuilder::new();
+    let mut tags = ListBuilder::new(StringBuilder::new());
+    let mut path_class = StringBuilder::new();
+    let mut language = StringBuilder::new();
+    let mut rule_pack = StringBuilder::new();
+
+    for result in results {
+        for m in result.all_matches() {
+            file_path.append_value(&result.file_path);
+            line_number.append_value(m.line_number as u64);
+            column.append_value(m.column as u64);
+            end_column.append_value(m.end_column as u64);
+            start_byte.append_option(m.start_byte.map(|b| b as u64));
+            end_byte.append_option(m.end_byte.map(|b| b as u64));
+            pattern_name.append_value(&m.pattern_name);
+            severity.append_value(m.severity.as_str());
+            category.append_value(&m.category);
+            kind.append_value(&m.kind);
+            matched_text.append_value(&m.matched_text);
+            for tag in &m.tags {
+                tags.values().append_value(tag);
+            }
+            tags.append(true);
+            path_class.append_option(m.path_class.as_deref());
+            language.append_option(result.language.as_deref());
+            rule_pack.append_option(m.rule_source.as_ref().and_then(|s| s.pack.as_deref()));
+        }
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(file_path.finish()),
+        Arc::new(line_number.finish()),
+        Arc::new(column.finish()),
+        Arc::new(end_column.finish()),
+        Arc::new(start_byte.finish()),
+        Arc::new(end_byte.finish()),
+        Arc::new(pattern_name.finish()),
+        Arc::new(severity.finish()),
+        Arc::new(category.finish()),
+        Arc::new(kind.finish()),
+        Arc::new(matched_text.finish()),
+        Arc::new(tags.finish()),
+        Arc::new(path_class.finish()),
+        Arc::new(language.finish()),
+        Arc::new(rule_pack.finish()),
+    ];
+    RecordBatch::try_new(Arc::new(findings_schema()), columns)
+}
+
+fn to_py_err(e: impl std::fmt::Display) -> PyErr {
+    pyo3::exceptions::PyIOError::new_err(e.to_string())
+}
+
+/// Write findings to an Arrow IPC file, returning the number of rows
+#[pyfunction]
+pub fn export_arrow(results: Vec<ScanResult>, path: String) -> PyResult<usize> {
+    let batch = findings_batch(&results).map_err(to_py_err)?;
+    let file = File::create(&path).map_err(to_py_err)?;
+    let mut writer =
+        arrow_ipc::writer::FileWriter::try_new(file, &batch.schema()).map_err(to_py_err)?;
+    writer.write(&batch).map_err(to_py_err)?;
+    writer.finish().map_err(to_py_err)?;
+    Ok(batch.num_rows())
+}
+
+/// Write findings to a Parquet file, returning the number of rows
+#[pyfunction]
+pub fn export_parquet(results: Vec<ScanResult>, path: String) -> PyResult<usize> {
+    let batch = findings_batch(&results).map_err(to_py_err)?;
+    let file = File::create(&path).map_err(to_py_err)?;
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), None).map_err(to_py_err)?;
+    writer.write(&batch).map_err(to_py_err)?;
+    writer.close().map_err(to_py_err)?;
+    Ok(batch.num_rows())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::MemoryProvider;
+    use crate::scanner::FastScanner;
+    use arrow_array::{StringArray, UInt64Array};
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
+
+    fn u64_column(batch: &RecordBatch, name: &str) -> Vec<Option<u64>> {
+        batch
+            .column_by_name(name)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .unwrap()
+            .iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let content = "DEBUG = True\nhashlib.md5(x)\n";
+        let provider = MemoryProvider::new(Some(BTreeMap::from([(
+            "app.py".to_string(),
+            content.to_string(),
+        )])));
+        let results = FastScanner::new(None).scan_with_provider(&provider);
+
+        let batch = findings_batch(&results).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema().fields().len(), 15);
+
+        let temp_dir = TempDir::new().unwrap();
+        let arrow_path = temp_dir.path().join("findings.arrow");
+        let parquet_path = temp_dir.path().join("findings.parquet");
+        assert_eq!(
+            export_arrow(results.clone(), arrow_path.to_string_lossy().to_string()).unwrap(),
+            2
+        );
+        assert_eq!(
+            export_parquet(results, parquet_path.to_string_lossy().to_string()).unwrap(),
+            2
+        );
+
+        let reader =
+            arrow_ipc::reader::FileReader::try_new(File::open(&arrow_path).unwrap(), None).unwrap();
+        let batches: Vec<RecordBatch> = reader.map(|b| b.unwrap()).collect();
+        assert_eq!(batches.len(), 1);
+        let read = &batches[0];
+        assert_eq!(read.num_rows(), 2);
+
+        let texts = read
+            .column_by_name("matched_text")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        let columns = u64_column(read, "column");
+        let end_columns = u64_column(read, "end_column");
+        let start_bytes = u64_column(read, "start_byte");
+        let end_bytes = u64_column(read, "end_byte");
+        for row in 0..read.num_rows() {
+            let text = texts.value(row);
+            let (start, end) = (start_bytes[row].unwrap(), end_bytes[row].unwrap());
+            assert_eq!(&content[start as usize..end as usize], text);
+            assert_eq!(
+                end_columns[row].unwrap() - columns[row].unwrap(),
+                text.chars().count() as u64
+            );
+        }
+    }
+}
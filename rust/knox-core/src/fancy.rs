@@ -0,0 +1,109 @@
+//! Backtracking engine for rules that need lookaround
+//!
+//! The `regex` crate matches in linear time and so rejects lookahead,
+//! lookbehind and backreferences, which rules ported from other tools often
+//! rely on. A rule with `fancy` set is compiled with `fancy-regex` instead:
+//! it hands the parts of the pattern it can to the `regex` engine and only
+//! backtracks through the rest, giving up on a line after
+//! `BACKTRACK_LIMIT` steps. Fancy rules stay out of the single-pass rule
+//! set; every other rule keeps the fast engine.
+
+use crate::guard::{DFA_SIZE_LIMIT, REGEX_SIZE_LIMIT};
+use regex::Regex;
+
+/// Backtracking steps allowed per search before it counts as no match
+pub const BACKTRACK_LIMIT: usize = 100_000;
+
+/// Compile a fancy rule regex with the same size limits as the fast engine
+pub fn compile(pattern: &str) -> Result<fancy_regex::Regex, Box<fancy_regex::Error>> {
+    fancy_regex::RegexBuilder::new(pattern)
+        .backtrack_limit(BACKTRACK_LIMIT)
+        .delegate_size_limit(REGEX_SIZE_LIMIT)
+        .delegate_dfa_size_limit(DFA_SIZE_LIMIT)
+        .build()
+        .map_err(Box::new)
+}
+
+/// Compiled regex of a line pattern, on whichever engine the rule asked for
+#[derive(Clone, Copy)]
+pub enum RuleRegex<'a> {
+    Fast(&'a Regex),
+    Fancy(&'a fancy_regex::Regex),
+}
+
+impl RuleRegex<'_> {
+    /// Span of the first match and its capture groups, empty strings
+    /// standing for groups that didn't participate
+    pub fn captures(&self, text: &str) -> Option<(usize, usize, Vec<String>)> {
+        let groups: Vec<Option<(usize, usize)>> = match self {
+            RuleRegex::Fast(regex) => regex
+                .captures(text)?
+                .iter()
+                .map(|g| g.map(|g| (g.start(), g.end())))
+                .collect(),
+            RuleRegex::Fancy(regex) => regex
+                .captures(text)
+                .ok()??
+                .iter()
+                .map(|g| g.map(|g| (g.start(), g.end())))
+                .collect(),
+        };
+        let (start, end) = groups[0]?;
+        let captures = groups[1..]
+            .iter()
+            .map(|g| g.map_or_else(String::new, |(s, e)| text[s..e].to_string()))
+            .collect();
+        Some((start, end, captures))
+    }
+
+    /// Span of the first match
+    pub fn find(&self, text: &str) -> Option<(usize, usize)> {
+        match self {
+            RuleRegex::Fast(regex) => regex.find(text).map(|m| (m.start(), m.end())),
+            RuleRegex::Fancy(regex) => regex.find(text).ok()?.map(|m| (m.start(), m.end())),
+        }
+    }
+
+    /// Spans of every non-overlapping match; a fancy search that exceeds
+    /// the backtrack limit ends the iteration
+    pub fn find_iter(&self, text: &str) -> Vec<(usize, usize)> {
+        match self {
+            RuleRegex::Fast(regex) => regex
+                .find_iter(text)
+                .map(|m| (m.start(), m.end()))
+                .collect(),
+            RuleRegex::Fancy(regex) => regex
+                .find_iter(text)
+                .map_while(Result::ok)
+                .map(|m| (m.start(), m.end()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::matcher::{PatternMatcher, SecurityPattern};
+    use crate::severity::Severity;
+
+    #[test]
+    fn test_lookbehind_rule_compiles_and_matches() {
+        let mut rule = SecurityPattern::new(
+            "bare_secret_assignment".to_string(),
+            r#"(?<!os\.environ\.get\()(?<!getenv\()secret\s*=\s*"(\w+)""#.to_string(),
+            Severity::High,
+            "secrets".to_string(),
+            "Secret assigned outside an environment lookup".to_string(),
+        );
+        let mut matcher = PatternMatcher::new_empty();
+        assert!(matcher.add_pattern(rule.clone()).is_err());
+
+        rule.fancy = true;
+        matcher.add_pattern(rule).unwrap();
+        assert!(matcher.diagnostics().is_empty());
+        let matches = matcher.match_content("secret = \"abc\"\nx = getenv(secret = \"k\")\n");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 1);
+        assert_eq!(matches[0].explanation.captures, vec!["abc".to_string()]);
+    }
+}
@@ -0,0 +1,174 @@
+//! File-level rules
+//!
+//! Unlike line patterns these assert conditions over a whole file, such as
+//! "contains X but not Y" or "must start with header Z", and report a single
+//! finding anchored to line 1
+
+use crate::severity::{self, Severity};
+use pyo3::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Kind reported on matches produced by line patterns
+pub const KIND_LINE: &str = "line";
+/// Kind reported on matches produced by file-level rules
+pub const KIND_FILE: &str = "file";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct FileRule {
+    #[pyo3(get, set)]
+    pub name: String,
+    #[pyo3(get)]
+    pub severity: Severity,
+    #[pyo3(get, set)]
+    pub category: String,
+    #[pyo3(get, set)]
+    pub description: String,
+    /// Regex the file must contain for the rule to apply
+    #[pyo3(get, set)]
+    pub contains: Option<String>,
+    /// Regex whose presence anywhere in the file satisfies the rule
+    #[pyo3(get, set)]
+    pub absent: Option<String>,
+    /// Regex that must appear within the first `header_lines` lines
+    #[pyo3(get, set)]
+    pub header: Option<String>,
+    #[pyo3(get, set)]
+    pub header_lines: usize,
+}
+
+#[pymethods]
+impl FileRule {
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        name: String,
+        #[pyo3(from_py_with = "severity::from_py")] severity: Severity,
+        category: String,
+        description: String,
+        contains: Option<String>,
+        absent: Option<String>,
+        header: Option<String>,
+        header_lines: Option<usize>,
+    ) -> Self {
+        FileRule {
+            name,
+            severity,
+            category,
+            description,
+            contains,
+            absent,
+            header,
+            header_lines: header_lines.unwrap_or(5),
+        }
+    }
+
+    /// Accepts a `Severity` or its name
+    #[setter(severity)]
+    fn set_py_severity(&mut self, value: &PyAny) -> PyResult<()> {
+        self.severity = severity::from_py(value)?;
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("FileRule(name={}, severity={})", self.name, self.severity)
+    }
+}
+
+impl FileRule {
+    /// Summary of the conditions, for finding explanations
+    pub fn clause(&self) -> String {
+        let mut clauses = Vec::new();
+        if let Some(pattern) = &self.contains {
+            clauses.push(format!("contains `{}`", pattern));
+        }
+        if let Some(pattern) = &self.absent {
+            clauses.push(format!("lacks `{}`", pattern));
+        }
+        if let Some(pattern) = &self.header {
+            clauses.push(format!(
+                "no `{}` in the first {} lines",
+                pattern, self.header_lines
+            ));
+        }
+        clauses.join("; ")
+    }
+
+    /// Evaluate the rule, returning the text that triggered it if it fires
+    ///
+    /// `compile` resolves a regex source to a compiled regex; rules whose
+    /// regexes fail to compile never fire.
+    pub fn evaluate<'a, F>(&self, content: &'a str, mut compile: F) -> Option<&'a str>
+    where
+        F: FnMut(&str) -> Option<Regex>,
+    {
+        let trigger = match &self.contains {
+            Some(pattern) => compile(pattern)?.find(content)?.as_str(),
+            None => "",
+        };
+
+        if let Some(pattern) = &self.absent {
+            if compile(pattern)?.is_match(content) {
+                return None;
+            }
+        }
+
+        if let Some(pattern) = &self.header {
+            let regex = compile(pattern)?;
+            if content
+                .lines()
+                .take(self.header_lines)
+                .any(|line| regex.is_match(line))
+            {
+                return None;
+            }
+        }
+
+        Some(trigger)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(contains: Option<&str>, absent: Option<&str>, header: Option<&str>) -> FileRule {
+        FileRule::new(
+            "test_rule".to_string(),
+            Severity::Low,
+            "config".to_string(),
+            "Test rule".to_string(),
+            contains.map(String::from),
+            absent.map(String::from),
+            header.map(String::from),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_contains_but_not_absent() {
+        let shell = rule(Some(r"^#!.*\bbash\b"), Some(r"set -euo pipefail"), None);
+        let compile = |p: &str| Regex::new(p).ok();
+
+        assert_eq!(
+            shell.evaluate("#!/usr/bin/env bash\nrm -rf $DIR\n", compile),
+            Some("#!/usr/bin/env bash")
+        );
+        assert!(shell
+            .evaluate("#!/usr/bin/env bash\nset -euo pipefail\n", compile)
+            .is_none());
+        assert!(shell.evaluate("print('hi')\n", compile).is_none());
+    }
+
+    #[test]
+    fn test_missing_header() {
+        let license = rule(None, None, Some(r"SPDX-License-Identifier"));
+        let compile = |p: &str| Regex::new(p).ok();
+
+        assert!(license.evaluate("fn main() {}\n", compile).is_some());
+        assert!(license
+            .evaluate("// SPDX-License-Identifier: MIT\nfn main() {}\n", compile)
+            .is_none());
+    }
+}
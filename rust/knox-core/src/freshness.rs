@@ -0,0 +1,208 @@
+//! Rule content freshness
+//!
+//! Long-lived services load rule packs once and can go on scanning with
+//! them for months. Every pack records its version and release date, so
+//! callers can list what is loaded, compare it against a registry manifest,
+//! and have stale or outdated packs reported through Python's `logging`.
+
+use crate::registry;
+use crate::suppress;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// Age in days after which pack content is reported as stale
+pub const DEFAULT_MAX_AGE_DAYS: i64 = 90;
+/// Python logger that freshness warnings are sent to
+pub const LOGGER_NAME: &str = "knox_core";
+
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass]
+pub struct PackStatus {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub version: Option<String>,
+    /// Release date as `YYYY-MM-DD`
+    #[pyo3(get)]
+    pub released: Option<String>,
+    /// Days since release, if the pack is dated
+    #[pyo3(get)]
+    pub age_days: Option<i64>,
+    /// Latest version listed in the manifest it was checked against
+    #[pyo3(get)]
+    pub latest: Option<String>,
+}
+
+#[pymethods]
+impl PackStatus {
+    /// Whether the manifest lists a newer version than the one loaded
+    #[getter]
+    pub fn outdated(&self) -> bool {
+        match (&self.latest, &self.version) {
+            (Some(latest), Some(version)) => is_newer(latest, version),
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PackStatus(name={}, version={}, released={})",
+            self.name,
+            self.version.as_deref().unwrap_or("-"),
+            self.released.as_deref().unwrap_or("-")
+        )
+    }
+}
+
+impl PackStatus {
+    pub fn is_stale(&self, max_age_days: i64) -> bool {
+        self.age_days.is_some_and(|age| age > max_age_days)
+    }
+}
+
+/// Dotted numeric components of a version, ignoring a `v` prefix,
+/// pre-release suffixes and trailing zeros
+fn version_key(version: &str) -> Vec<u64> {
+    let mut key: Vec<u64> = version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .map(|part| {
+            let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().unwrap_or(0)
+        })
+        .collect();
+    while key.last() == Some(&0) {
+        key.pop();
+    }
+    key
+}
+
+/// Whether `candidate` is a later version than `current`
+pub fn is_newer(candidate: &str, current: &str) -> bool {
+    version_key(candidate) > version_key(current)
+}
+
+fn statuses(today: (i32, u32, u32), manifest: Option<&HashMap<String, String>>) -> Vec<PackStatus> {
+    let today = suppress::days_from_civil(today);
+    registry::pack_versions()
+        .into_iter()
+        .map(|pack| PackStatus {
+            latest: manifest.and_then(|m| m.get(&pack.name).cloned()),
+            released: pack
+                .released
+                .map(|(y, m, d)| format!("{:04}-{:02}-{:02}", y, m, d)),
+            age_days: pack
+                .released
+                .map(|date| today - suppress::days_from_civil(date)),
+            name: pack.name,
+            version: pack.version,
+        })
+        .collect()
+}
+
+/// Packs that are stale or outdated, each with the warning to report
+fn problems(
+    today: (i32, u32, u32),
+    max_age_days: i64,
+    manifest: Option<&HashMap<String, String>>,
+) -> Vec<(PackStatus, String)> {
+    statuses(today, manifest)
+        .into_iter()
+        .filter_map(|status| {
+            let message = if status.outdated() {
+                format!(
+                    "Rule pack {} is at version {} but {} is available",
+                    status.name,
+                    status.version.as_deref().unwrap_or("(unversioned)"),
+                    status.latest.as_deref().unwrap_or_default()
+                )
+            } else if status.is_stale(max_age_days) {
+                format!(
+                    "Rule pack {} was released {} days ago (maximum age {} days)",
+                    status.name,
+                    status.age_days.unwrap_or_default(),
+                    max_age_days
+                )
+            } else {
+                return None;
+            };
+            Some((status, message))
+        })
+        .collect()
+}
+
+/// Version and release date of the built-in rules and every registered pack
+#[pyfunction]
+pub fn pack_versions() -> Vec<PackStatus> {
+    statuses(suppress::today(), None)
+}
+
+/// Loaded packs for which `manifest` (pack name to latest version) lists a
+/// newer version
+#[pyfunction]
+pub fn check_pack_updates(manifest: HashMap<String, String>) -> Vec<PackStatus> {
+    statuses(suppress::today(), Some(&manifest))
+        .into_iter()
+        .filter(PackStatus::outdated)
+        .collect()
+}
+
+/// Warn through the `knox_core` logger about packs older than
+/// `max_age_days` or behind `manifest`, returning those packs
+#[pyfunction]
+pub fn check_rule_freshness(
+    py: Python<'_>,
+    max_age_days: Option<i64>,
+    manifest: Option<HashMap<String, String>>,
+) -> PyResult<Vec<PackStatus>> {
+    let found = problems(
+        suppress::today(),
+        max_age_days.unwrap_or(DEFAULT_MAX_AGE_DAYS),
+        manifest.as_ref(),
+    );
+    if !found.is_empty() {
+        let logger = py
+            .import("logging")?
+            .call_method1("getLogger", (LOGGER_NAME,))?;
+        for (_, message) in &found {
+            logger.call_method1("warning", (message,))?;
+        }
+    }
+    Ok(found.into_iter().map(|(status, _)| status).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::register_pattern_pack;
+
+    #[test]
+    fn test_stale_and_outdated_packs() {
+        assert!(is_newer("1.10.0", "1.9.3"));
+        assert!(!is_newer("v2.0", "2.0.0"));
+
+        register_pattern_pack(
+            "freshness_test_pack".to_string(),
+            Vec::new(),
+            Some("1.2.0".to_string()),
+            Some("2026-01-01".to_string()),
+        )
+        .unwrap();
+        let pack = |found: &[(PackStatus, String)]| {
+            found
+                .iter()
+                .find(|(s, _)| s.name == "freshness_test_pack")
+                .map(|(_, message)| message.clone())
+        };
+
+        assert_eq!(pack(&problems((2026, 1, 31), 90, None)), None);
+        let stale = pack(&problems((2026, 4, 11), 90, None)).unwrap();
+        assert!(stale.contains("released 100 days ago"));
+
+        let manifest = HashMap::from([("freshness_test_pack".to_string(), "1.3.0".to_string())]);
+        let outdated = pack(&problems((2026, 1, 31), 90, Some(&manifest))).unwrap();
+        assert!(outdated.contains("1.3.0 is available"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Guards for user-supplied rules
+//!
+//! Caps compiled regex size, flags patterns that look expensive when they
+//! are loaded, and records per-rule diagnostics so a bad custom rule is
+//! reported instead of stalling a shared scanning service
+
+use pyo3::prelude::*;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Upper bound on the compiled program size of a single rule
+pub const REGEX_SIZE_LIMIT: usize = 2 * 1024 * 1024;
+/// Upper bound on the lazy DFA cache of a single rule
+pub const DFA_SIZE_LIMIT: usize = 4 * 1024 * 1024;
+/// Patterns longer than this are flagged as suspicious
+const MAX_PATTERN_LENGTH: usize = 4096;
+/// Bounded repetitions above this count are flagged as suspicious
+const MAX_REPETITION: u32 = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct RuleDiagnostic {
+    #[pyo3(get)]
+    pub rule: String,
+    /// "warning" for suspicious rules, "error" for rules that were disabled
+    #[pyo3(get)]
+    pub level: String,
+    #[pyo3(get)]
+    pub message: String,
+}
+
+#[pymethods]
+impl RuleDiagnostic {
+    fn __repr__(&self) -> String {
+        format!(
+            "RuleDiagnostic(rule={}, level={}, message={})",
+            self.rule, self.level, self.message
+        )
+    }
+}
+
+impl RuleDiagnostic {
+    pub fn warning(rule: &str, message: String) -> Self {
+        RuleDiagnostic {
+            rule: rule.to_string(),
+            level: "warning".to_string(),
+            message,
+        }
+    }
+
+    pub fn error(rule: &str, message: String) -> Self {
+        RuleDiagnostic {
+            rule: rule.to_string(),
+            level: "error".to_string(),
+            message,
+        }
+    }
+}
+
+/// Compile a rule regex with size limits applied
+pub fn compile_guarded(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(DFA_SIZE_LIMIT)
+        .build()
+}
+
+/// Syntax error in a pattern, with the character offset it starts at
+///
+/// Only syntax is checked; a pattern that parses but exceeds the size
+/// limits fails in `compile_guarded` instead.
+pub fn syntax_error(pattern: &str) -> Option<String> {
+    let (offset, message) = match regex_syntax::Parser::new().parse(pattern) {
+        Ok(_) => return None,
+        Err(regex_syntax::Error::Parse(e)) => (e.span().start.offset, e.kind().to_string()),
+        Err(regex_syntax::Error::Translate(e)) => (e.span().start.offset, e.kind().to_string()),
+        Err(e) => (0, e.to_string()),
+    };
+    let column = pattern[..offset.min(pattern.len())].chars().count();
+    Some(format!("{} at position {}", message, column))
+}
+
+/// Load-time complexity checks, returning one message per problem found
+pub fn analyze_complexity(pattern: &str) -> Vec<String> {
+    static NESTED: OnceLock<Regex> = OnceLock::new();
+    static REPETITION: OnceLock<Regex> = OnceLock::new();
+    let nested = NESTED.get_or_init(|| Regex::new(r"\([^()]*[+*}][^()]*\)[+*{]").unwrap());
+    let repetition = REPETITION.get_or_init(|| Regex::new(r"\{(\d+)(?:,(\d*))?\}").unwrap());
+
+    let mut problems = Vec::new();
+
+    if pattern.len() > MAX_PATTERN_LENGTH {
+        problems.push(format!(
+            "pattern is {} bytes long (limit {})",
+            pattern.len(),
+            MAX_PATTERN_LENGTH
+        ));
+    }
+
+    if let Some(m) = nested.find(pattern) {
+        problems.push(format!("nested quantifier `{}`", m.as_str()));
+    }
+
+    for captures in repetition.captures_iter(pattern) {
+        let largest = [captures.get(1), captures.get(2)]
+            .iter()
+            .flatten()
+            .filter_map(|m| m.as_str().parse::<u32>().ok())
+            .max()
+            .unwrap_or(0);
+        if largest > MAX_REPETITION {
+            problems.push(format!(
+                "repetition count {} exceeds {}",
+                largest, MAX_REPETITION
+            ));
+        }
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_complexity_flags_nested_and_large_repetition() {
+        assert!(analyze_complexity(r"(?i)password\s*=\s*\w+").is_empty());
+        assert_eq!(analyze_complexity(r"(a+)+$").len(), 1);
+        assert_eq!(analyze_complexity(r"\w{1,5000}").len(), 1);
+    }
+
+    #[test]
+    fn test_compile_guarded_rejects_oversized_program() {
+        assert!(compile_guarded(r"\w{1000}\w{1000}\w{1000}").is_err());
+        assert!(compile_guarded(r"api_key\s*=").is_ok());
+        assert_eq!(syntax_error(r"\w{1000}\w{1000}\w{1000}"), None);
+    }
+
+    #[test]
+    fn test_syntax_error_reports_position() {
+        assert_eq!(syntax_error(r"(?i)secret\s*="), None);
+        assert_eq!(
+            syntax_error(r"é(password\s*="),
+            Some("unclosed group at position 1".to_string())
+        );
+        assert!(syntax_error(r"token[a-").unwrap().ends_with("position 5"));
+    }
+}
@@ -0,0 +1,147 @@
+//! Import-based rule pruning
+//!
+//! Rules can declare the modules they depend on (`requires_imports`), e.g.
+//! a pickle deserialization rule only applies to files importing `pickle`.
+//! Imported modules are read once per file with `CodeParser` (on its regex
+//! backend, so every build prunes alike), and rules whose modules are never
+//! imported are skipped for that file.
+//!
+//! Pruning fails open: when the language is unknown, or a line that looks
+//! like an import wasn't understood by the parser, nothing is pruned.
+
+use crate::parser::{CodeParser, ParsedImport, BACKEND_REGEX};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// Lines that look like an import in each language import pruning
+/// supports; one the parser doesn't return makes the file's imports
+/// unsure
+fn import_lines(language: &str) -> Option<&'static Regex> {
+    static REGEXES: OnceLock<HashMap<&str, Regex>> = OnceLock::new();
+    REGEXES
+        .get_or_init(|| {
+            [
+                ("python", r"^\s*(?:from\s+\S+\s+)?import\b"),
+                ("javascript", r#"^\s*import[\s{*'"]|\brequire\s*\("#),
+                ("typescript", r#"^\s*import[\s{*'"]|\brequire\s*\("#),
+                ("java", r"^\s*import\s"),
+                ("kotlin", r"^\s*import\s"),
+                ("swift", r"^\s*(?:@\w+\s+)*import\s"),
+                ("rust", r"^\s*(?:pub(?:\([^)]*\))?\s+)?use\s"),
+                // The opening line of an `import ( ... )` block imports
+                // nothing itself
+                ("go", r"^\s*import\s*[^\s(]"),
+                ("ruby", r"^\s*(?:require|require_relative|load)\b"),
+                ("php", r"^\s*(?:use|include|require)(?:_once)?\b"),
+            ]
+            .into_iter()
+            .map(|(language, pattern)| (language, Regex::new(pattern).unwrap()))
+            .collect()
+        })
+        .get(language)
+}
+
+/// Module names an import makes available: the module itself and, for
+/// `from a import b` style imports, `a.b`; aliases are dropped
+fn module_names(import: &ParsedImport, separator: &str) -> Vec<String> {
+    let items = import
+        .items
+        .iter()
+        .filter(|_| !import.is_wildcard)
+        .map(|item| item.split(" as ").next().unwrap_or(item).trim())
+        .filter(|item| !item.is_empty());
+    if import.module.is_empty() {
+        return items.map(str::to_string).collect();
+    }
+    std::iter::once(import.module.clone())
+        .chain(items.map(|item| format!("{}{}{}", import.module, separator, item)))
+        .collect()
+}
+
+/// Every module `content` imports, or None when that can't be told
+/// reliably and no rule should be pruned
+pub fn imported_modules(content: &str, language: Option<&str>) -> Option<HashSet<String>> {
+    let language = language?;
+    let looks_like_import = import_lines(language)?;
+    let mut parser = CodeParser::new(Some(language.to_string()));
+    // Always accepted
+    parser.set_backend(BACKEND_REGEX.to_string()).ok()?;
+    let imports = parser.extract_imports(content);
+
+    let parsed: HashSet<usize> = imports.iter().map(|i| i.line_number).collect();
+    let unsure = content
+        .lines()
+        .enumerate()
+        .any(|(i, line)| looks_like_import.is_match(line) && !parsed.contains(&(i + 1)));
+    if unsure {
+        return None;
+    }
+
+    let separator = if language == "rust" { "::" } else { "." };
+    Some(
+        imports
+            .iter()
+            .flat_map(|import| module_names(import, separator))
+            .collect(),
+    )
+}
+
+/// Whether `module` or one of its submodules is imported
+pub fn is_imported(module: &str, imports: &HashSet<String>) -> bool {
+    imports.iter().any(|import| {
+        import == module
+            || import
+                .strip_prefix(module)
+                .is_some_and(|rest| rest.starts_with(['.', '/', ':']))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imported_modules_across_languages() {
+        let python = imported_modules(
+            "import os as o, pickle\nfrom yaml.loader import Loader\n",
+            Some("python"),
+        )
+        .unwrap();
+        assert!(is_imported("pickle", &python));
+        assert!(is_imported("os", &python));
+        assert!(is_imported("yaml", &python));
+        assert!(is_imported("yaml.loader.Loader", &python));
+        assert!(!is_imported("subprocess", &python));
+
+        let js = imported_modules(
+            "const cp = require('child_process');\nimport fs from \"fs/promises\";\n",
+            Some("javascript"),
+        )
+        .unwrap();
+        assert!(is_imported("child_process", &js));
+        assert!(is_imported("fs", &js));
+
+        let rust = imported_modules("use std::process::Command;\n", Some("rust")).unwrap();
+        assert!(is_imported("std::process", &rust));
+        assert!(!is_imported("std::proc", &rust));
+
+        let go = imported_modules(
+            "package main\n\nimport (\n\t\"fmt\"\n\texec \"os/exec\"\n)\nimport \"net/http\"\n",
+            Some("go"),
+        )
+        .unwrap();
+        assert!(is_imported("os/exec", &go));
+        assert!(is_imported("fmt", &go));
+        assert!(is_imported("net/http", &go));
+        assert!(!is_imported("crypto/md5", &go));
+    }
+
+    #[test]
+    fn test_unsure_imports_fail_open() {
+        assert!(imported_modules("import pickle\n", None).is_none());
+        assert!(imported_modules("import pickle\n", Some("cobol")).is_none());
+        // `pub use` isn't read by the parser, so the file's imports are unsure
+        assert!(imported_modules("pub use std::process;\n", Some("rust")).is_none());
+    }
+}
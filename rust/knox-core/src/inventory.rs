@@ -0,0 +1,31 @@
+//! Third-party (vendored) code inventory
+//!
+//! Files under configured vendor roots are still scanned, but as inventory:
+//! each is hashed so the exact vendored content can be identified, and its
+//! findings are tagged `third_party` so policy gating can leave them out.
+
+use sha2::{Digest, Sha256};
+
+/// Tag added to findings in files under a third-party root
+pub const TAG_THIRD_PARTY: &str = "third_party";
+
+/// Hex-encoded SHA-256 of a file's content
+pub fn content_hash(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(
+            content_hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! Language detection for scanned files
+//!
+//! Maps file extensions (and, for extensionless scripts, the sniffed
+//! shebang) to a language name, so scans can report per-language file,
+//! line and byte counts without running a separate tool over the tree.
+//!
+//! Languages without built-in support can be registered at runtime with a
+//! `LanguageSpec` (extensions, comment markers, string delimiters and
+//! function/import regexes), from Python or from a JSON config file. The
+//! scanner's extension list, language detection, `CodeParser` and comment
+//! handling in the matcher all consult the registered specs.
+
+use crate::sniff::{self, KIND_BINARY};
+use pyo3::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{OnceLock, RwLock};
+
+/// Extension (without the dot, lowercase) to language name
+const EXTENSION_LANGUAGES: &[(&str, &str)] = &[
+    ("py", "python"),
+    ("pyi", "python"),
+    ("js", "javascript"),
+    ("jsx", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("ts", "typescript"),
+    ("tsx", "typescript"),
+    ("rs", "rust"),
+    ("go", "go"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("kts", "kotlin"),
+    ("swift", "swift"),
+    ("php", "php"),
+    ("rb", "ruby"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cpp", "cpp"),
+    ("cc", "cpp"),
+    ("hpp", "cpp"),
+    ("cs", "csharp"),
+    ("sh", "shell"),
+    ("bash", "shell"),
+    ("yml", "yaml"),
+    ("yaml", "yaml"),
+    ("json", "json"),
+    ("toml", "toml"),
+    ("html", "html"),
+    ("tf", "terraform"),
+    ("sql", "sql"),
+];
+
+/// Comment markers of languages a `#`-only default would get wrong
+const LINE_COMMENTS: &[(&str, &[&str])] = &[
+    ("javascript", &["//"]),
+    ("typescript", &["//"]),
+    ("rust", &["//"]),
+    ("go", &["//"]),
+    ("java", &["//"]),
+    ("kotlin", &["//"]),
+    ("swift", &["//"]),
+    ("php", &["//", "#"]),
+    ("c", &["//"]),
+    ("cpp", &["//"]),
+    ("csharp", &["//"]),
+    ("sql", &["--"]),
+];
+
+/// A language registered at runtime
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct LanguageSpec {
+    #[pyo3(get, set)]
+    pub name: String,
+    /// File extensions, with or without the leading dot
+    #[pyo3(get, set)]
+    pub extensions: Vec<String>,
+    /// Markers starting a comment that runs to the end of the line
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub line_comments: Vec<String>,
+    /// Quote strings delimiting string literals, e.g. `"` and `'`
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub string_delimiters: Vec<String>,
+    /// Regex for a function definition line, capturing `name` and
+    /// optionally `params` as named groups
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub function_pattern: Option<String>,
+    /// Regex for an import line, capturing `module` and optionally `items`
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub import_pattern: Option<String>,
+}
+
+#[pymethods]
+impl LanguageSpec {
+    #[new]
+    pub fn new(
+        name: String,
+        extensions: Vec<String>,
+        line_comments: Option<Vec<String>>,
+        string_delimiters: Option<Vec<String>>,
+        function_pattern: Option<String>,
+        import_pattern: Option<String>,
+    ) -> Self {
+        LanguageSpec {
+            name,
+            extensions,
+            line_comments: line_comments.unwrap_or_default(),
+            string_delimiters: string_delimiters
+                .unwrap_or_else(|| vec!["\"".to_string(), "'".to_string()]),
+            function_pattern,
+            import_pattern,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "LanguageSpec(name={}, extensions={:?})",
+            self.name, self.extensions
+        )
+    }
+}
+
+impl LanguageSpec {
+    /// Check the regexes, drop empty comment markers and normalize
+    /// extensions to lowercase without a dot
+    fn validated(mut self) -> Result<Self, String> {
+        if self.name.is_empty() {
+            return Err("A language needs a name".to_string());
+        }
+        for pattern in self.function_pattern.iter().chain(&self.import_pattern) {
+            Regex::new(pattern).map_err(|e| format!("Invalid pattern for {}: {}", self.name, e))?;
+        }
+        self.line_comments
+            .retain(|marker| !marker.trim().is_empty());
+        self.extensions = self
+            .extensions
+            .iter()
+            .map(|ext| ext.trim_start_matches('.').to_lowercase())
+            .filter(|ext| !ext.is_empty())
+            .collect();
+        Ok(self)
+    }
+
+    /// Regex matching a string literal with any of the delimiters, the
+    /// value captured in group 1
+    pub fn string_regex(&self) -> Option<Regex> {
+        let alternatives: Vec<String> = self
+            .string_delimiters
+            .iter()
+            .filter(|d| !d.is_empty())
+            .map(|d| {
+                let quote = regex::escape(d);
+                format!(r"{q}((?:[^\\]|\\.)*?){q}", q = quote)
+            })
+            .collect();
+        if alternatives.is_empty() {
+            return None;
+        }
+        Regex::new(&alternatives.join("|")).ok()
+    }
+}
+
+fn registered() -> &'static RwLock<Vec<LanguageSpec>> {
+    static LANGUAGES: OnceLock<RwLock<Vec<LanguageSpec>>> = OnceLock::new();
+    LANGUAGES.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Register a language, replacing any registered under the same name
+pub fn register(spec: LanguageSpec) -> Result<(), String> {
+    let spec = spec.validated()?;
+    let mut languages = registered().write().unwrap_or_else(|e| e.into_inner());
+    match languages.iter_mut().find(|l| l.name == spec.name) {
+        Some(existing) => *existing = spec,
+        None => languages.push(spec),
+    }
+    Ok(())
+}
+
+/// The registered spec for `name`, if any
+pub fn registered_spec(name: &str) -> Option<LanguageSpec> {
+    let languages = registered().read().unwrap_or_else(|e| e.into_inner());
+    languages.iter().find(|l| l.name == name).cloned()
+}
+
+/// `extensions` (".py") plus those of every registered language
+pub fn with_registered_extensions(extensions: &[String]) -> Vec<String> {
+    let mut all = extensions.to_vec();
+    let languages = registered().read().unwrap_or_else(|e| e.into_inner());
+    for ext in languages.iter().flat_map(|l| &l.extensions) {
+        let ext = format!(".{}", ext);
+        if !all.contains(&ext) {
+            all.push(ext);
+        }
+    }
+    all
+}
+
+/// Line comment markers for `language`; `#` when nothing more is known
+pub fn line_comment_markers(language: &str) -> Vec<String> {
+    if let Some(spec) = registered_spec(language) {
+        return spec.line_comments;
+    }
+    let markers = LINE_COMMENTS
+        .iter()
+        .find(|(name, _)| *name == language)
+        .map_or(&["#"][..], |(_, markers)| *markers);
+    markers.iter().map(|m| m.to_string()).collect()
+}
+
+/// Line comment markers of all registered languages
+pub fn registered_comment_markers() -> Vec<String> {
+    let languages = registered().read().unwrap_or_else(|e| e.into_inner());
+    languages
+        .iter()
+        .flat_map(|l| l.line_comments.iter().cloned())
+        .collect()
+}
+
+/// Register a language for all parsers and scanners created afterwards
+#[pyfunction]
+pub fn register_language(spec: LanguageSpec) -> PyResult<()> {
+    register(spec).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Register every language in a JSON file holding a list of specs,
+/// returning their names
+#[pyfunction]
+pub fn load_language_config(path: String) -> PyResult<Vec<String>> {
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("{}: {}", path, e)))?;
+    let specs: Vec<LanguageSpec> = serde_json::from_str(&text)
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("{}: {}", path, e)))?;
+    let mut names = Vec::with_capacity(specs.len());
+    for spec in specs {
+        names.push(spec.name.clone());
+        register_language(spec)?;
+    }
+    Ok(names)
+}
+
+/// Names of the registered languages, in registration order
+#[pyfunction]
+pub fn registered_languages() -> Vec<String> {
+    let languages = registered().read().unwrap_or_else(|e| e.into_inner());
+    languages.iter().map(|l| l.name.clone()).collect()
+}
+
+/// Language of a file from its extension, falling back to its leading bytes
+pub fn detect_language(path: &Path, head: &[u8]) -> Option<String> {
+    let by_extension = path.extension().and_then(|ext| {
+        let ext = ext.to_string_lossy().to_lowercase();
+        let builtin = EXTENSION_LANGUAGES
+            .iter()
+            .find(|(e, _)| *e == ext)
+            .map(|(_, language)| language.to_string());
+        builtin.or_else(|| {
+            let languages = registered().read().unwrap_or_else(|e| e.into_inner());
+            languages
+                .iter()
+                .find(|l| l.extensions.contains(&ext))
+                .map(|l| l.name.clone())
+        })
+    });
+
+    by_extension.or_else(|| {
+        sniff::sniff_kind(head)
+            .filter(|kind| *kind != KIND_BINARY)
+            .map(String::from)
+    })
+}
+
+/// Number of lines, counting a final line without a trailing newline
+pub fn count_lines(content: &str) -> usize {
+    let newlines = content.bytes().filter(|b| *b == b'\n').count();
+    newlines + usize::from(!content.is_empty() && !content.ends_with('\n'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(
+            detect_language(Path::new("a/app.PY"), b"").as_deref(),
+            Some("python")
+        );
+        assert_eq!(
+            detect_language(Path::new("bin/deploy"), b"#!/bin/bash\n").as_deref(),
+            Some("shell")
+        );
+        assert_eq!(detect_language(Path::new("notes"), b"hello"), None);
+        assert_eq!(count_lines("a\nb"), 2);
+        assert_eq!(count_lines("a\nb\n"), 2);
+        assert_eq!(count_lines(""), 0);
+    }
+
+    #[test]
+    fn test_registered_language() {
+        let spec = LanguageSpec::new(
+            "elixir".to_string(),
+            vec![".ex".to_string(), "EXS".to_string()],
+            Some(vec!["#".to_string()]),
+            None,
+            Some(
+                r"^\s*defp?\s+(?P<name>[a-z_][a-zA-Z0-9_?!]*)\s*\((?P<params>[^)]*)\)".to_string(),
+            ),
+            Some(r"^\s*(?:alias|import|use|require)\s+(?P<module>[A-Z][\w.]*)".to_string()),
+        );
+        register(spec).unwrap();
+        assert!(registered_languages().contains(&"elixir".to_string()));
+        assert_eq!(
+            detect_language(Path::new("lib/app.exs"), b"").as_deref(),
+            Some("elixir")
+        );
+        assert!(with_registered_extensions(&[]).contains(&".ex".to_string()));
+
+        let bad = LanguageSpec::new(
+            "bad".to_string(),
+            Vec::new(),
+            None,
+            None,
+            Some("(".to_string()),
+            None,
+        );
+        assert!(register(bad).is_err());
+    }
+}
@@ -3,9 +3,81 @@
 //! This module provides fast pattern matching and code parsing
 //! for security vulnerability detection.
 
+// pyo3 0.20's `#[pymethods]` expansion trips this lint on newer toolchains
+#![allow(non_local_definitions)]
+
+pub mod assignments;
+pub mod baseline;
+pub mod batch;
+pub mod calls;
+pub mod classes;
+pub mod comments;
+pub mod complexity;
+pub mod composite;
+pub mod config_rules;
+pub mod constructs;
+pub mod context;
+pub mod coverage;
+pub mod dedup;
+pub mod dirconfig;
+pub mod engine;
+pub mod entropy;
+pub mod excludes;
+pub mod explain;
+#[cfg(feature = "arrow")]
+pub mod export;
+pub mod fancy;
+pub mod file_rules;
+pub mod freshness;
+pub mod guard;
+pub mod imports;
+pub mod inventory;
+pub mod language;
+pub mod linecache;
+pub mod literals;
 pub mod matcher;
+pub mod metrics;
+pub mod network;
+pub mod normalize;
+pub mod ordering;
 pub mod parser;
+pub mod pathclass;
+pub mod pathfilter;
+pub mod prefilter;
+pub mod profile;
+pub mod provenance;
+pub mod provider;
+pub mod proximity;
+pub mod randomness;
+pub mod regexset;
+pub mod registry;
+pub mod routing;
+pub mod rulediff;
+pub mod rulepattern;
+pub mod ruleset;
+pub mod rulestats;
 pub mod scanner;
+pub mod scanoptions;
+pub mod scorecard;
+#[cfg(feature = "fuzzing")]
+pub mod selftest;
+pub mod sensitive;
+pub mod session;
+pub mod severity;
+pub mod sink;
+pub mod sniff;
+pub mod sourcemap;
+pub mod summary;
+pub mod suppress;
+pub mod taint;
+pub mod throttle;
+pub mod timing;
+pub mod tokens;
+#[cfg(feature = "tree-sitter")]
+pub mod treesitter;
+pub mod vault;
+pub mod verify;
+pub mod web_rules;
 
 use pyo3::prelude::*;
 
@@ -13,8 +85,77 @@ use pyo3::prelude::*;
 #[pymodule]
 fn knox_core(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<matcher::PatternMatcher>()?;
+    m.add_class::<engine::Engine>()?;
+    m.add_class::<file_rules::FileRule>()?;
+    m.add_class::<provenance::RuleSource>()?;
+    m.add_class::<composite::CompositeRule>()?;
+    m.add_class::<config_rules::ConfigRule>()?;
+    m.add_class::<proximity::ProximityRule>()?;
+    m.add_class::<constructs::ConstructRule>()?;
+    m.add_class::<constructs::ParseArtifacts>()?;
+    m.add_class::<parser::CodeParser>()?;
+    m.add_class::<language::LanguageSpec>()?;
+    m.add_class::<normalize::NormalizationOptions>()?;
+    m.add_class::<entropy::EntropyOptions>()?;
+    m.add_class::<guard::RuleDiagnostic>()?;
+    m.add_class::<explain::RuleExplanation>()?;
+    m.add_class::<explain::FindingExplanation>()?;
+    m.add_class::<rulediff::RuleSetDiff>()?;
+    m.add_class::<rulediff::RuleChange>()?;
+    m.add_class::<rulediff::FieldChange>()?;
+    m.add_class::<rulestats::RuleStatistics>()?;
+    m.add_class::<freshness::PackStatus>()?;
     m.add_class::<scanner::FastScanner>()?;
+    m.add_class::<scanoptions::ScanOptions>()?;
+    m.add_class::<provider::MemoryProvider>()?;
+    m.add_class::<batch::ResultBuffer>()?;
+    m.add_class::<sink::ScanSink>()?;
+    m.add_class::<baseline::Baseline>()?;
+    m.add_class::<baseline::BaselineDiff>()?;
+    m.add_class::<verify::Verifier>()?;
+    m.add_class::<vault::SecretInventory>()?;
+    m.add_class::<vault::SecretCrossCheck>()?;
+    m.add_class::<scorecard::Scorecard>()?;
+    m.add_class::<summary::ScanSummary>()?;
+    m.add_class::<metrics::FileMetrics>()?;
+    m.add_class::<complexity::FunctionComplexity>()?;
+    m.add_class::<routing::ScanConfig>()?;
+    m.add_class::<routing::Route>()?;
+    m.add_class::<summary::LanguageStats>()?;
+    m.add_class::<scorecard::ScoreDimension>()?;
+    m.add_class::<profile::ScanProfile>()?;
+    m.add_class::<pathclass::PathClassifier>()?;
+    m.add_class::<sourcemap::ExtractedContent>()?;
+    m.add_class::<severity::Severity>()?;
     m.add_function(wrap_pyfunction!(scanner::scan_file, m)?)?;
     m.add_function(wrap_pyfunction!(scanner::scan_directory, m)?)?;
+    m.add_function(wrap_pyfunction!(scanner::scan_to_sink, m)?)?;
+    m.add_function(wrap_pyfunction!(batch::scan_directory_buffer, m)?)?;
+    m.add_function(wrap_pyfunction!(registry::register_pattern_pack, m)?)?;
+    m.add_function(wrap_pyfunction!(registry::unregister_pattern_pack, m)?)?;
+    m.add_function(wrap_pyfunction!(registry::registered_packs, m)?)?;
+    m.add_function(wrap_pyfunction!(freshness::pack_versions, m)?)?;
+    m.add_function(wrap_pyfunction!(freshness::check_pack_updates, m)?)?;
+    m.add_function(wrap_pyfunction!(freshness::check_rule_freshness, m)?)?;
+    m.add_function(wrap_pyfunction!(language::register_language, m)?)?;
+    m.add_function(wrap_pyfunction!(language::load_language_config, m)?)?;
+    m.add_function(wrap_pyfunction!(language::registered_languages, m)?)?;
+    m.add_function(wrap_pyfunction!(excludes::default_exclude_sets, m)?)?;
+    m.add_function(wrap_pyfunction!(rulediff::diff_rule_sets, m)?)?;
+    m.add_function(wrap_pyfunction!(sniff::sniff_file_type, m)?)?;
+    m.add_function(wrap_pyfunction!(context::byte_to_linecol, m)?)?;
+    m.add_function(wrap_pyfunction!(context::extract_snippet, m)?)?;
+    m.add_function(wrap_pyfunction!(scorecard::build_scorecard, m)?)?;
+    m.add_function(wrap_pyfunction!(summary::summarize_results, m)?)?;
+    #[cfg(feature = "arrow")]
+    {
+        m.add_function(wrap_pyfunction!(export::export_arrow, m)?)?;
+        m.add_function(wrap_pyfunction!(export::export_parquet, m)?)?;
+    }
+    #[cfg(feature = "fuzzing")]
+    {
+        m.add_class::<selftest::SelfTestReport>()?;
+        m.add_function(wrap_pyfunction!(selftest::self_test, m)?)?;
+    }
     Ok(())
 }
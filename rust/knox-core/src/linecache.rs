@@ -0,0 +1,182 @@
+//! Per-line match cache for repeated scans
+//!
+//! Watch mode and editor integrations rescan the same file after small
+//! edits. Line results depend only on the line's text and the active rule
+//! set, so they are cached by (rule-set hash, line hash) and only changed
+//! lines are evaluated again. The cache is dropped whenever the rule set
+//! changes.
+
+use crate::entropy::EntropyOptions;
+use crate::matcher::{Match, SecurityPattern};
+use crate::normalize::NormalizationOptions;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// Default number of distinct lines kept
+pub const DEFAULT_LINE_CACHE_CAPACITY: usize = 100_000;
+
+#[derive(Clone)]
+pub struct LineCache {
+    rule_set: u64,
+    entries: HashMap<u64, Vec<Match>>,
+    capacity: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl LineCache {
+    pub fn new(capacity: usize) -> Self {
+        LineCache {
+            rule_set: 0,
+            entries: HashMap::new(),
+            capacity,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Drop all entries if they were computed under a different rule set
+    pub fn sync_rule_set(&mut self, rule_set: u64) {
+        if self.rule_set != rule_set {
+            self.entries.clear();
+            self.rule_set = rule_set;
+        }
+    }
+
+    /// Cached matches for `line`, renumbered to `line_number`
+    pub fn get(&mut self, line: &str, line_number: usize) -> Option<Vec<Match>> {
+        match self.entries.get(&hash_line(line)) {
+            Some(cached) => {
+                self.hits += 1;
+                let mut matches = cached.clone();
+                matches.iter_mut().for_each(|m| m.line_number = line_number);
+                Some(matches)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn insert(&mut self, line: &str, matches: &[Match]) {
+        // A full cache is cheaper to rebuild than to track recency for
+        if self.entries.len() >= self.capacity {
+            self.entries.clear();
+        }
+        self.entries.insert(hash_line(line), matches.to_vec());
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn hash_line(line: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    line.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Hash of everything that affects per-line results
+#[allow(clippy::too_many_arguments)]
+pub fn rule_set_hash<'a>(
+    patterns: &[SecurityPattern],
+    disabled_rules: impl Iterator<Item = &'a String>,
+    normalization: &NormalizationOptions,
+    entropy: Option<&EntropyOptions>,
+    min_confidence: Option<&str>,
+    enabled_categories: Option<&HashSet<String>>,
+    disabled_categories: &HashSet<String>,
+) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for pattern in patterns {
+        pattern.name.hash(&mut hasher);
+        pattern.regex_source().hash(&mut hasher);
+        pattern.severity.hash(&mut hasher);
+        pattern.category.hash(&mut hasher);
+        pattern.confidence.hash(&mut hasher);
+        pattern.negative_pattern.hash(&mut hasher);
+        pattern.multiline.hash(&mut hasher);
+        pattern.fancy.hash(&mut hasher);
+        pattern.scope.hash(&mut hasher);
+        pattern.cwe.hash(&mut hasher);
+        pattern.owasp.hash(&mut hasher);
+        pattern.references.hash(&mut hasher);
+    }
+
+    let mut disabled: Vec<&String> = disabled_rules.collect();
+    disabled.sort();
+    disabled.hash(&mut hasher);
+
+    normalization.strip_cr.hash(&mut hasher);
+    normalization.tab_width.hash(&mut hasher);
+    normalization.trim_trailing.hash(&mut hasher);
+
+    entropy
+        .map(|e| {
+            (
+                e.min_length,
+                e.hex_threshold.to_bits(),
+                e.base64_threshold.to_bits(),
+                &e.severity,
+            )
+        })
+        .hash(&mut hasher);
+    min_confidence.hash(&mut hasher);
+    enabled_categories
+        .map(|categories| {
+            let mut categories: Vec<&String> = categories.iter().collect();
+            categories.sort();
+            categories
+        })
+        .hash(&mut hasher);
+    let mut disabled: Vec<&String> = disabled_categories.iter().collect();
+    disabled.sort();
+    disabled.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::matcher::{PatternMatcher, SecurityPattern};
+    use crate::severity::Severity;
+
+    #[test]
+    fn test_unchanged_lines_served_from_cache() {
+        let mut matcher = PatternMatcher::new();
+        matcher.set_line_cache(true, None);
+
+        let before = "DEBUG = True\nx = 1\n";
+        let after = "y = 2\nDEBUG = True\nx = 1\n";
+        matcher.match_content(before);
+        let matches = matcher.match_content(after);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 2);
+        assert_eq!(matcher.line_cache_stats(), Some((2, 3)));
+    }
+
+    #[test]
+    fn test_rule_change_invalidates_cache() {
+        let mut matcher = PatternMatcher::new();
+        matcher.set_line_cache(true, None);
+        assert!(matcher.match_content("legacy_call()\n").is_empty());
+
+        matcher
+            .add_pattern(SecurityPattern::new(
+                "legacy_call".to_string(),
+                r"legacy_call\(".to_string(),
+                Severity::Low,
+                "api".to_string(),
+                "Deprecated API".to_string(),
+            ))
+            .unwrap();
+        assert_eq!(matcher.match_content("legacy_call()\n").len(), 1);
+    }
+}
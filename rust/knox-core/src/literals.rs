@@ -0,0 +1,708 @@
+//! String literal tokenizers for the regex backend of `CodeParser`
+//!
+//! One regex for every language gets string literals wrong in ways that
+//! matter to secret and injection rules: it stops at the first escaped
+//! quote of a raw string, reads Rust lifetimes and Go runes as strings,
+//! finds "strings" in comments and can't tell the code interpolated into
+//! an f-string or template literal from its text. Languages with a
+//! `Lexicon` here are read by a small tokenizer instead, which skips
+//! comments and char literals, knows each language's prefixes (`f`, `rb`,
+//! `r#`), its escape rules and its interpolation syntax, and reports the
+//! interpolated expressions next to each literal.
+//!
+//! Literals are read across lines where the language allows it: Python's
+//! triple-quoted strings, template literals, Go raw strings, Rust strings,
+//! Java text blocks, Ruby strings and heredocs, and any string continued
+//! with a backslash at the end of a line. Embedded SQL and keys are often written this way.
+
+use crate::comments;
+use crate::context::LineIndex;
+use crate::language;
+use crate::parser::StringLiteral;
+
+/// Interpolation syntax inside a string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Interpolation {
+    /// `{expr}`, `{{` escaping a brace (Python f-strings)
+    Braces,
+    /// `${expr}` (JavaScript template literals)
+    DollarBraces,
+    /// `#{expr}` (Ruby strings, commands and heredocs)
+    HashBraces,
+}
+
+/// How one kind of string opens and what it allows
+struct Quote {
+    /// Opening delimiter, after any prefix
+    open: &'static str,
+    /// Backslash escapes the next character
+    escapes: bool,
+    /// The string may span lines
+    multiline: bool,
+    /// Interpolation, whatever the prefix
+    interpolation: Option<Interpolation>,
+}
+
+/// String syntax of one language
+struct Lexicon {
+    quotes: &'static [Quote],
+    /// Prefixes, compared case-insensitively, that may precede a quote
+    prefixes: &'static [&'static str],
+    /// Delimiter of char literals, which aren't strings
+    char_quote: Option<u8>,
+}
+
+const fn quote(open: &'static str) -> Quote {
+    Quote {
+        open,
+        escapes: true,
+        multiline: false,
+        interpolation: None,
+    }
+}
+
+const fn multiline(open: &'static str) -> Quote {
+    Quote {
+        multiline: true,
+        ..quote(open)
+    }
+}
+
+const PYTHON: Lexicon = Lexicon {
+    quotes: &[
+        multiline("\"\"\""),
+        multiline("'''"),
+        quote("\""),
+        quote("'"),
+    ],
+    prefixes: &["rb", "br", "fr", "rf", "r", "b", "f", "u"],
+    char_quote: None,
+};
+
+const JAVASCRIPT: Lexicon = Lexicon {
+    quotes: &[
+        Quote {
+            interpolation: Some(Interpolation::DollarBraces),
+            ..multiline("`")
+        },
+        quote("\""),
+        quote("'"),
+    ],
+    prefixes: &[],
+    char_quote: None,
+};
+
+const RUST: Lexicon = Lexicon {
+    quotes: &[multiline("\"")],
+    // `r` and `br` raw strings are read by `raw_rust_string`
+    prefixes: &["b", "c"],
+    char_quote: Some(b'\''),
+};
+
+const GO: Lexicon = Lexicon {
+    quotes: &[
+        Quote {
+            escapes: false,
+            ..multiline("`")
+        },
+        quote("\""),
+    ],
+    prefixes: &[],
+    char_quote: Some(b'\''),
+};
+
+const JAVA: Lexicon = Lexicon {
+    // Text blocks
+    quotes: &[multiline("\"\"\""), quote("\"")],
+    prefixes: &[],
+    char_quote: Some(b'\''),
+};
+
+const RUBY: Lexicon = Lexicon {
+    // Heredocs are read by `ruby_heredoc`
+    quotes: &[
+        Quote {
+            interpolation: Some(Interpolation::HashBraces),
+            ..multiline("\"")
+        },
+        Quote {
+            interpolation: Some(Interpolation::HashBraces),
+            ..multiline("`")
+        },
+        multiline("'"),
+    ],
+    prefixes: &[],
+    char_quote: None,
+};
+
+const C_FAMILY: Lexicon = Lexicon {
+    quotes: &[quote("\"")],
+    prefixes: &["u8", "u", "U", "L"],
+    char_quote: Some(b'\''),
+};
+
+fn lexicon(language: &str) -> Option<&'static Lexicon> {
+    match language {
+        "python" => Some(&PYTHON),
+        "javascript" | "typescript" => Some(&JAVASCRIPT),
+        "rust" => Some(&RUST),
+        "go" => Some(&GO),
+        "java" => Some(&JAVA),
+        "ruby" => Some(&RUBY),
+        "c" | "cpp" | "csharp" => Some(&C_FAMILY),
+        _ => None,
+    }
+}
+
+/// Whether `language` has a tokenizer; others use the delimiter regex
+pub fn supports(language: &str) -> bool {
+    lexicon(language).is_some()
+}
+
+fn is_ident(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$'
+}
+
+/// Offset of the `}` closing an interpolation whose expression starts at
+/// `start`, skipping brackets and simple strings inside it; it may only
+/// span lines inside a `multiline` string
+fn interpolation_end(bytes: &[u8], start: usize, multiline: bool) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut offset = start;
+    while offset < bytes.len() {
+        match bytes[offset] {
+            b'{' | b'(' | b'[' => depth += 1,
+            b'}' if depth == 0 => return Some(offset),
+            b'}' | b')' | b']' => depth = depth.saturating_sub(1),
+            b'\n' if !multiline => return None,
+            quote @ (b'"' | b'\'' | b'`') => {
+                offset += 1;
+                while offset < bytes.len() && bytes[offset] != quote && bytes[offset] != b'\n' {
+                    offset += if bytes[offset] == b'\\' { 2 } else { 1 };
+                }
+            }
+            _ => {}
+        }
+        offset += 1;
+    }
+    None
+}
+
+/// An f-string replacement field without its `!r` conversion, `:` format
+/// spec or `=` self-documenting suffix
+fn python_expression(field: &str) -> &str {
+    let bytes = field.as_bytes();
+    let mut depth = 0usize;
+    let mut end = field.len();
+    for (i, &byte) in bytes.iter().enumerate() {
+        match byte {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+            b':' if depth == 0 => {
+                end = i;
+                break;
+            }
+            b'!' if depth == 0 && bytes.get(i + 1) != Some(&b'=') => {
+                end = i;
+                break;
+            }
+            _ => {}
+        }
+    }
+    let expression = field[..end].trim();
+    match expression.strip_suffix('=') {
+        Some(rest) if !rest.ends_with(['=', '!', '<', '>']) => rest.trim_end(),
+        _ => expression,
+    }
+}
+
+/// Where and how the string at the current offset opens
+struct Opening<'a> {
+    prefix: &'a str,
+    quote_type: String,
+    value_start: usize,
+    close: String,
+    escapes: bool,
+    multiline: bool,
+    interpolation: Option<Interpolation>,
+}
+
+/// The body of a string: the offset just past its value (before the
+/// closing delimiter) and its interpolated expressions, None when it
+/// doesn't close (on its line, unless it may span lines)
+fn string_body(content: &str, opening: &Opening) -> Option<(usize, Vec<String>)> {
+    let bytes = content.as_bytes();
+    let close = opening.close.as_bytes();
+    let interpolation = opening.interpolation;
+    let mut interpolations = Vec::new();
+    let mut offset = opening.value_start;
+    while offset < bytes.len() {
+        let rest = &bytes[offset..];
+        if rest.starts_with(close) {
+            return Some((offset, interpolations));
+        }
+        let expression = match (bytes[offset], interpolation) {
+            (b'\n', _) if !opening.multiline => return None,
+            // Also a line continuation inside a one-line string
+            (b'\\', _) if opening.escapes => {
+                offset += 2;
+                continue;
+            }
+            (b'{', Some(Interpolation::Braces)) if rest.starts_with(b"{{") => {
+                offset += 2;
+                continue;
+            }
+            (b'{', Some(Interpolation::Braces)) => offset + 1,
+            (b'$', Some(Interpolation::DollarBraces)) if rest.starts_with(b"${") => offset + 2,
+            (b'#', Some(Interpolation::HashBraces)) if rest.starts_with(b"#{") => offset + 2,
+            _ => {
+                offset += 1;
+                continue;
+            }
+        };
+        let end = interpolation_end(bytes, expression, opening.multiline)?;
+        let field = &content[expression..end];
+        interpolations.push(match interpolation {
+            Some(Interpolation::Braces) => python_expression(field).to_string(),
+            _ => field.trim().to_string(),
+        });
+        offset = end + 1;
+    }
+    None
+}
+
+/// A Rust raw string (`r"..."`, `br#"..."#`) at `start`: its prefix,
+/// opening delimiter and the offset its value starts at
+fn raw_rust_string(content: &str, start: usize) -> Option<(&str, &str, usize)> {
+    let rest = &content[start..];
+    let prefix = if rest.starts_with("br") || rest.starts_with("cr") {
+        &rest[..2]
+    } else if rest.starts_with('r') {
+        &rest[..1]
+    } else {
+        return None;
+    };
+    let hashes = rest[prefix.len()..]
+        .bytes()
+        .take_while(|&b| b == b'#')
+        .count();
+    let open_end = prefix.len() + hashes;
+    if rest.as_bytes().get(open_end) != Some(&b'"') {
+        return None;
+    }
+    Some((prefix, &rest[prefix.len()..=open_end], start + open_end + 1))
+}
+
+/// A Ruby heredoc opened at `start` (`<<~SQL`, `<<-'EOS'`, `<<EOS`)
+struct Heredoc<'a> {
+    open: &'a str,
+    interpolates: bool,
+    /// Byte range of the body, from the line after the opener (or after
+    /// an earlier heredoc's body) to its terminator line
+    body: (usize, usize),
+    /// Offset just past the terminator line, where code resumes
+    resume: usize,
+}
+
+/// The heredoc opened at `start` whose body starts at `body_start`, None
+/// when there is none or its terminator is missing
+fn ruby_heredoc(content: &str, start: usize, body_start: usize) -> Option<Heredoc<'_>> {
+    let rest = content[start..].strip_prefix("<<")?;
+    let indented = rest.starts_with(['~', '-']);
+    let rest = &rest[usize::from(indented)..];
+    let quote = rest
+        .chars()
+        .next()
+        .filter(|c| matches!(c, '\'' | '"' | '`'));
+    let ident_start = usize::from(quote.is_some());
+    let ident_end = ident_start
+        + rest[ident_start..]
+            .bytes()
+            .take_while(|&b| b.is_ascii_alphanumeric() || b == b'_')
+            .count();
+    let ident = &rest[ident_start..ident_end];
+    // `x << y` and `items <<value` are appends; a bare heredoc identifier
+    // is written in capitals
+    let first = ident.bytes().next()?;
+    if first.is_ascii_digit() || (!indented && quote.is_none() && !first.is_ascii_uppercase()) {
+        return None;
+    }
+    let close_len = match quote {
+        Some(quote) if rest[ident_end..].starts_with(quote) => 1,
+        Some(_) => return None,
+        None => 0,
+    };
+    let open_len = 2 + usize::from(indented) + ident_end + close_len;
+
+    let mut line_start = body_start;
+    while line_start < content.len() {
+        let line_end = content[line_start..]
+            .find('\n')
+            .map_or(content.len(), |i| line_start + i);
+        let line = content[line_start..line_end].trim_end_matches('\r');
+        let line = if indented { line.trim_start() } else { line };
+        if line == ident {
+            return Some(Heredoc {
+                open: &content[start..start + open_len],
+                interpolates: quote != Some('\''),
+                body: (body_start, line_start),
+                resume: (line_end + 1).min(content.len()),
+            });
+        }
+        line_start = line_end + 1;
+    }
+    None
+}
+
+/// Expressions interpolated with `#{...}` into `content[start..end]`
+fn hash_interpolations(content: &str, start: usize, end: usize) -> Vec<String> {
+    let bytes = &content.as_bytes()[..end];
+    let mut interpolations = Vec::new();
+    let mut offset = start;
+    while offset + 1 < end {
+        match bytes[offset] {
+            b'\\' => offset += 2,
+            b'#' if bytes[offset + 1] == b'{' => {
+                let Some(close) = interpolation_end(bytes, offset + 2, true) else {
+                    break;
+                };
+                interpolations.push(content[offset + 2..close].trim().to_string());
+                offset = close + 1;
+            }
+            _ => offset += 1,
+        }
+    }
+    interpolations
+}
+
+/// Length of the char literal at the start of `rest` (`'"'`, `'\''`,
+/// `'\u{1F600}'`), 1 for a quote that doesn't open one (a Rust lifetime)
+pub fn char_literal_len(rest: &str) -> usize {
+    let mut chars = rest.chars().skip(1);
+    match (chars.next(), chars.next()) {
+        // The longest escape, `\u{10FFFF}`, is 10 bytes
+        (Some('\\'), _) => rest
+            .get(3..)
+            .and_then(|escaped| escaped.find('\''))
+            .filter(|&i| i <= 10)
+            .map_or(1, |i| i + 4),
+        (Some(c), Some('\'')) => c.len_utf8() + 2,
+        _ => 1,
+    }
+}
+
+/// The string opening at `offset`, if any
+fn opening<'a>(
+    content: &'a str,
+    offset: usize,
+    language: &str,
+    lexicon: &Lexicon,
+) -> Option<Opening<'a>> {
+    let bytes = content.as_bytes();
+    let rest = &content[offset..];
+    let starts_word = offset == 0 || !is_ident(bytes[offset - 1]);
+    if language == "rust" && starts_word {
+        if let Some((prefix, open, value_start)) = raw_rust_string(content, offset) {
+            return Some(Opening {
+                prefix,
+                quote_type: format!("{}{}", prefix, open),
+                value_start,
+                close: format!("\"{}", &open[..open.len() - 1]),
+                escapes: false,
+                multiline: true,
+                interpolation: None,
+            });
+        }
+    }
+    let prefix_len = if starts_word {
+        lexicon
+            .prefixes
+            .iter()
+            .filter(|p| {
+                rest.as_bytes()
+                    .get(..p.len())
+                    .is_some_and(|b| b.eq_ignore_ascii_case(p.as_bytes()))
+            })
+            .find(|p| {
+                lexicon
+                    .quotes
+                    .iter()
+                    .any(|q| rest[p.len()..].starts_with(q.open))
+            })
+            .map_or(0, |p| p.len())
+    } else {
+        0
+    };
+    let quote = lexicon
+        .quotes
+        .iter()
+        .find(|q| rest[prefix_len..].starts_with(q.open))?;
+    let prefix = &rest[..prefix_len];
+    let interpolation = match language {
+        "python" if prefix.to_ascii_lowercase().contains('f') => Some(Interpolation::Braces),
+        _ => quote.interpolation,
+    };
+    Some(Opening {
+        prefix,
+        quote_type: quote.open.to_string(),
+        value_start: offset + prefix_len + quote.open.len(),
+        close: quote.open.to_string(),
+        escapes: quote.escapes,
+        multiline: quote.multiline,
+        interpolation,
+    })
+}
+
+/// Every string literal of `content` in a language `supports` accepts
+pub fn scan_strings(content: &str, language: &str) -> Vec<StringLiteral> {
+    let Some(lexicon) = lexicon(language) else {
+        return Vec::new();
+    };
+    let markers = language::line_comment_markers(language);
+    let blocks = comments::block_comments(language);
+    let index = LineIndex::new(content);
+    let bytes = content.as_bytes();
+    let mut strings = Vec::new();
+    // Where code resumes after the heredoc bodies following this line
+    let mut resume: Option<usize> = None;
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let byte = bytes[offset];
+        if byte == b'\n' {
+            if let Some(after_bodies) = resume.take() {
+                offset = after_bodies;
+                continue;
+            }
+        }
+        // Delimiters, markers and prefixes are ASCII
+        if !byte.is_ascii() {
+            offset += 1;
+            continue;
+        }
+        let rest = &content[offset..];
+        if markers.iter().any(|m| rest.starts_with(m.as_str())) {
+            offset += rest.find('\n').unwrap_or(rest.len());
+            continue;
+        }
+        if let Some((open, close)) = blocks.iter().find(|(open, _)| rest.starts_with(open)) {
+            offset += rest[open.len()..]
+                .find(close)
+                .map_or(rest.len(), |i| open.len() + i + close.len());
+            continue;
+        }
+        if lexicon.char_quote == Some(byte) {
+            offset += char_literal_len(rest);
+            continue;
+        }
+        if language == "ruby" && byte == b'<' {
+            let body_start =
+                resume.unwrap_or_else(|| rest.find('\n').map_or(bytes.len(), |i| offset + i + 1));
+            if let Some(heredoc) = ruby_heredoc(content, offset, body_start) {
+                let (start, end) = heredoc.body;
+                strings.push(StringLiteral {
+                    value: content[start..end].to_string(),
+                    line_number: index.line_col(offset).0,
+                    end_line: index.line_col(end).0,
+                    is_multiline: true,
+                    quote_type: heredoc.open.to_string(),
+                    start_byte: start,
+                    end_byte: end,
+                    prefix: String::new(),
+                    interpolations: if heredoc.interpolates {
+                        hash_interpolations(content, start, end)
+                    } else {
+                        Vec::new()
+                    },
+                });
+                resume = Some(heredoc.resume);
+                offset += heredoc.open.len();
+                continue;
+            }
+        }
+        let Some(opening) = opening(content, offset, language, lexicon) else {
+            // Skip the rest of an identifier so its tail isn't read as a
+            // prefix
+            offset += rest.bytes().take_while(|&b| is_ident(b)).count().max(1);
+            continue;
+        };
+        let Some((value_end, interpolations)) = string_body(content, &opening) else {
+            // An unterminated one-line string ends with its line; one that
+            // may span lines takes the rest of the content, as it would
+            // for the compiler
+            offset = if opening.multiline {
+                bytes.len()
+            } else {
+                opening.value_start
+            };
+            continue;
+        };
+        let value = &content[opening.value_start..value_end];
+        let line_number = index.line_col(offset).0;
+        strings.push(StringLiteral {
+            value: value.to_string(),
+            line_number,
+            end_line: line_number + value.matches('\n').count(),
+            is_multiline: value.contains('\n'),
+            quote_type: opening.quote_type,
+            start_byte: opening.value_start,
+            end_byte: value_end,
+            prefix: opening.prefix.to_string(),
+            interpolations,
+        });
+        offset = value_end + opening.close.len();
+    }
+    strings
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::matcher::PatternMatcher;
+    use crate::parser::CodeParser;
+
+    fn literals(language: &str, code: &str) -> Vec<(String, String, String, Vec<String>)> {
+        CodeParser::new(Some(language.to_string()))
+            .extract_strings(code)
+            .into_iter()
+            .map(|s| (s.prefix, s.quote_type, s.value, s.interpolations))
+            .collect()
+    }
+
+    fn literal(
+        prefix: &str,
+        quote: &str,
+        value: &str,
+        interpolations: &[&str],
+    ) -> (String, String, String, Vec<String>) {
+        (
+            prefix.to_string(),
+            quote.to_string(),
+            value.to_string(),
+            interpolations.iter().map(|i| i.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn test_python_prefixes_escapes_and_fields() {
+        let mut parser = CodeParser::new(Some("python".to_string()));
+        parser.set_backend("regex".to_string()).unwrap();
+        let code = concat!(
+            "# don't read this\n",
+            "q = f\"SELECT * FROM {table!r} WHERE id={uid:>5} {{raw}}\"\n",
+            "p = Rb'C:\\\\dir\\\\' + 'it\\'s' + u\"\"\n",
+            "name = dict_\"x\"\n",
+        );
+        let strings: Vec<(String, String, String, Vec<String>)> = parser
+            .extract_strings(code)
+            .into_iter()
+            .map(|s| (s.prefix, s.quote_type, s.value, s.interpolations))
+            .collect();
+        assert_eq!(
+            strings,
+            vec![
+                literal(
+                    "f",
+                    "\"",
+                    "SELECT * FROM {table!r} WHERE id={uid:>5} {{raw}}",
+                    &["table", "uid"]
+                ),
+                literal("Rb", "'", "C:\\\\dir\\\\", &[]),
+                literal("", "'", "it\\'s", &[]),
+                literal("u", "\"", "", &[]),
+                literal("", "\"", "x", &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_template_raw_and_char_literals() {
+        let mut parser = CodeParser::new(Some("typescript".to_string()));
+        parser.set_backend("regex".to_string()).unwrap();
+        let code = "const q = `SELECT ${cols.join(\",\")} FROM t WHERE id = ${id}`; // 'x'\n";
+        assert_eq!(
+            parser
+                .extract_strings(code)
+                .into_iter()
+                .map(|s| (s.prefix, s.quote_type, s.value, s.interpolations))
+                .collect::<Vec<_>>(),
+            vec![literal(
+                "",
+                "`",
+                "SELECT ${cols.join(\",\")} FROM t WHERE id = ${id}",
+                &["cols.join(\",\")", "id"]
+            )]
+        );
+
+        let mut parser = CodeParser::new(Some("rust".to_string()));
+        parser.set_backend("regex".to_string()).unwrap();
+        let code = "fn f<'a>(s: &'a str) -> bool { let c = '\"'; s == r#\"say \"hi\"\"# || s == b\"\\x00\" }\n";
+        let strings = parser.extract_strings(code);
+        let values: Vec<(&str, &str, &str)> = strings
+            .iter()
+            .map(|s| (s.prefix.as_str(), s.quote_type.as_str(), s.value.as_str()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![("r", "r#\"", "say \"hi\""), ("b", "\"", "\\x00")]
+        );
+        assert_eq!(
+            &code[strings[0].start_byte..strings[0].end_byte],
+            "say \"hi\""
+        );
+
+        let go = literals("go", "s := `C:\\path` + string('\"') // \"no\"\n");
+        assert_eq!(go, vec![literal("", "`", "C:\\path", &[])]);
+    }
+
+    #[test]
+    fn test_non_ascii_after_literal() {
+        let code = "s = 'ok' + ü\nt = ü'x'\n";
+        assert_eq!(
+            literals("python", code),
+            vec![literal("", "'", "ok", &[]), literal("", "'", "x", &[])]
+        );
+        PatternMatcher::new().match_content(code);
+    }
+
+    #[test]
+    fn test_literals_spanning_lines() {
+        let mut parser = CodeParser::new(Some("python".to_string()));
+        parser.set_backend("regex".to_string()).unwrap();
+        let code = concat!(
+            "QUERY = f\"\"\"\n",
+            "    SELECT * FROM users\n",
+            "    WHERE name = '{name}'\n",
+            "\"\"\"\n",
+            "KEY = \"AKIA\\\n",
+            "EXAMPLE\"\n",
+            "x = 'open\n",
+        );
+        let strings = parser.extract_strings(code);
+        let spans: Vec<(usize, usize, bool)> = strings
+            .iter()
+            .map(|s| (s.line_number, s.end_line, s.is_multiline))
+            .collect();
+        assert_eq!(spans, vec![(1, 4, true), (5, 6, true)]);
+        assert_eq!(
+            strings[0].value,
+            "\n    SELECT * FROM users\n    WHERE name = '{name}'\n"
+        );
+        assert_eq!(strings[0].interpolations, vec!["name"]);
+        assert_eq!(strings[1].value, "AKIA\\\nEXAMPLE");
+
+        let mut parser = CodeParser::new(Some("javascript".to_string()));
+        parser.set_backend("regex".to_string()).unwrap();
+        let code = "const q = `SELECT *\n  FROM t WHERE id = ${\n    id\n  }`;\nconst k = 'k';\n";
+        let strings = parser.extract_strings(code);
+        assert_eq!(strings.len(), 2);
+        assert_eq!((strings[0].line_number, strings[0].end_line), (1, 4));
+        assert_eq!(strings[0].interpolations, vec!["id"]);
+        assert_eq!(
+            (
+                strings[1].line_number,
+                strings[1].end_line,
+                strings[1].is_multiline
+            ),
+            (5, 5, false)
+        );
+    }
+}
@@ -1,143 +1,2563 @@
 //! High-performance pattern matching engine for security vulnerabilities
 //!
-//! Uses Aho-Corasick algorithm for efficient multi-pattern matching
+//! Uses Aho-Corasick literal prefiltering and a single-pass `RegexSet` to
+//! narrow the rules run on each line; see `prefilter` and `regexset`
 
+use crate::comments;
+use crate::composite::{CompositeRule, KIND_COMPOSITE};
+use crate::config_rules::{self, ConfigRule, KIND_CONFIG};
+use crate::constructs::{ConstructRule, ParseArtifacts, KIND_CONSTRUCT};
+use crate::context::{self, LineIndex};
+use crate::dedup;
+use crate::entropy::{self, EntropyOptions};
+use crate::explain::{self, FindingExplanation, RuleExplanation};
+use crate::fancy::{self, RuleRegex};
+use crate::file_rules::{FileRule, KIND_FILE, KIND_LINE};
+use crate::guard::{self, RuleDiagnostic};
+use crate::imports;
+use crate::linecache::{self, LineCache, DEFAULT_LINE_CACHE_CAPACITY};
+use crate::network;
+use crate::normalize::{NormalizationOptions, NormalizedLine};
+use crate::ordering;
+use crate::parser::CodeParser;
+use crate::prefilter::LiteralPrefilter;
+use crate::provenance::RuleSource;
+use crate::proximity::{ProximityRule, KIND_PROXIMITY};
+use crate::randomness;
+use crate::regexset::PatternSet;
+use crate::registry;
+use crate::rulepattern::RulePattern;
+use crate::ruleset::{self, RuleFormat, RuleSet, RULESET_FORMAT_VERSION};
+use crate::rulestats::{RuleStatistics, RuleStats};
+use crate::severity::{self, Severity};
+use crate::sink::MatchCallback;
+use crate::sourcemap::ExtractedContent;
+use crate::suppress::{self, Suppression, TAG_SUPPRESSION_EXPIRED};
+use crate::timing;
+use crate::tokens;
+use crate::web_rules;
 use pyo3::prelude::*;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Default time a user rule may spend on one line before it is disabled
+const DEFAULT_RULE_TIME_BUDGET_MS: u64 = 100;
+
+/// Rule scope: the pattern is matched against whole lines
+pub const SCOPE_ANY: &str = "any";
+/// Rule scope: the pattern is matched only inside string literals
+/// extracted by `CodeParser`
+pub const SCOPE_STRING: &str = "string";
+pub const SCOPES: &[&str] = &[SCOPE_ANY, SCOPE_STRING];
+
+/// Confidence levels a rule can be given, most confident first
+pub const CONFIDENCES: &[&str] = &["high", "medium", "low"];
+
+/// Confidence of rules that don't state one
+pub const DEFAULT_CONFIDENCE: &str = "medium";
+
+/// Numeric rank of a confidence string, higher is more confident
+pub fn confidence_rank(confidence: &str) -> u8 {
+    match confidence.to_lowercase().as_str() {
+        "high" => 3,
+        "medium" => 2,
+        "low" => 1,
+        _ => 0,
+    }
+}
+
+fn default_confidence() -> String {
+    DEFAULT_CONFIDENCE.to_string()
+}
+
+fn default_scope() -> String {
+    SCOPE_ANY.to_string()
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[pyclass]
 pub struct SecurityPattern {
     #[pyo3(get, set)]
     pub name: String,
+    /// A regex, or a list of regexes OR-ed together; see `rulepattern`
     #[pyo3(get, set)]
-    pub pattern: String,
-    #[pyo3(get, set)]
-    pub severity: String,
+    pub pattern: RulePattern,
+    #[pyo3(get)]
+    pub severity: Severity,
     #[pyo3(get, set)]
     pub category: String,
     #[pyo3(get, set)]
     pub description: String,
+    /// How reliably a hit is a real issue: "high", "medium" or "low"
+    #[pyo3(get, set)]
+    #[serde(default = "default_confidence")]
+    pub confidence: String,
+    /// Pack, version and definition site, filled in when the pack is registered
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub source: Option<RuleSource>,
+    /// Modules a file must import for the rule to apply; empty means always
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub requires_imports: Vec<String>,
+    /// Languages the rule applies to, as detected from the file; empty
+    /// means all. Content of unknown language is matched by every rule
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Match against the whole content instead of line by line, so the
+    /// regex can span lines; findings are reported at their first line
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub multiline: bool,
+    /// Compile the pattern case-insensitively, as if it began with `(?i)`
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub case_insensitive: bool,
+    /// Anchor the pattern at word boundaries on both ends, for patterns that
+    /// start and end with a word character such as keywords or identifiers
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub word_boundary: bool,
+    /// Compile the pattern with the backtracking engine, for rules that
+    /// need lookaround or backreferences; see `fancy`. The negative pattern
+    /// still uses the fast engine
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub fancy: bool,
+    /// Where the pattern is matched: `any` line text, or only the values
+    /// of `string` literals, e.g. SQL keywords that matter only inside
+    /// queries. String-scoped findings are reported where the match sits
+    /// in the literal
+    #[pyo3(get, set)]
+    #[serde(default = "default_scope")]
+    pub scope: String,
+    /// Regex that discards a match when it also matches the line, e.g.
+    /// `os\.environ` for a password rule
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub negative_pattern: Option<String>,
+    /// Lines the rule must flag, checked by `validate_patterns`
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub positive_examples: Vec<String>,
+    /// Lines the rule must not flag, checked by `validate_patterns`
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub negative_examples: Vec<String>,
+    /// CWE identifier, e.g. `CWE-798`
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub cwe: Option<String>,
+    /// OWASP Top 10 category, e.g. `A03:2021`
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub owasp: Option<String>,
+    /// Links to documentation for the weakness
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub references: Vec<String>,
+}
+
+#[pymethods]
+impl SecurityPattern {
+    #[new]
+    fn py_new(
+        name: String,
+        pattern: RulePattern,
+        #[pyo3(from_py_with = "severity::from_py")] severity: Severity,
+        category: String,
+        description: String,
+    ) -> Self {
+        Self::new(name, pattern, severity, category, description)
+    }
+
+    /// Accepts a `Severity` or its name
+    #[setter(severity)]
+    fn set_py_severity(&mut self, value: &PyAny) -> PyResult<()> {
+        self.severity = severity::from_py(value)?;
+        Ok(())
+    }
+
+    /// The regex actually compiled: `pattern` with the case and word
+    /// boundary flags applied
+    #[getter]
+    pub fn effective_pattern(&self) -> String {
+        self.regex_source().into_owned()
+    }
+
+    /// Pack namespace of the rule: `knox` for built-in rules, the pack
+    /// name for registered packs and `local` otherwise
+    #[getter]
+    pub fn namespace(&self) -> String {
+        RuleSource::namespace(self.source.as_ref()).to_string()
+    }
+
+    /// Namespaced id, e.g. `knox.secrets.hardcoded_api_key`, unique where
+    /// bare names collide across packs
+    #[getter]
+    pub fn id(&self) -> String {
+        format!("{}.{}.{}", self.namespace(), self.category, self.name)
+    }
+}
+
+impl SecurityPattern {
+    pub fn new(
+        name: String,
+        pattern: impl Into<RulePattern>,
+        severity: Severity,
+        category: String,
+        description: String,
+    ) -> Self {
+        SecurityPattern {
+            name,
+            pattern: pattern.into(),
+            severity,
+            category,
+            description,
+            confidence: default_confidence(),
+            source: None,
+            requires_imports: Vec::new(),
+            languages: Vec::new(),
+            multiline: false,
+            case_insensitive: false,
+            word_boundary: false,
+            fancy: false,
+            scope: SCOPE_ANY.to_string(),
+            negative_pattern: None,
+            positive_examples: Vec::new(),
+            negative_examples: Vec::new(),
+            cwe: None,
+            owasp: None,
+            references: Vec::new(),
+        }
+    }
+
+    /// `effective_pattern`, borrowed when no flag is set
+    pub fn regex_source(&self) -> Cow<'_, str> {
+        let pattern = self.pattern.source();
+        if !self.case_insensitive && !self.word_boundary {
+            return pattern;
+        }
+        let mut source = pattern.into_owned();
+        if self.word_boundary {
+            source = format!(r"\b(?:{})\b", source);
+        }
+        if self.case_insensitive {
+            source = format!("(?i:{})", source);
+        }
+        Cow::Owned(source)
+    }
+
+    /// Set the CWE and OWASP Top 10 ids, with `weakness_references` links
+    pub fn with_cwe(mut self, cwe: &str, owasp: &str) -> Self {
+        self.cwe = Some(cwe.to_string());
+        self.owasp = Some(owasp.to_string());
+        self.references = weakness_references(cwe, owasp);
+        self
+    }
+}
+
+/// Pages of the OWASP Top 10 (2021) categories rules are filed under
+const OWASP_TOP10: &[(&str, &str)] = &[
+    ("A01:2021", "A01_2021-Broken_Access_Control"),
+    ("A02:2021", "A02_2021-Cryptographic_Failures"),
+    ("A03:2021", "A03_2021-Injection"),
+    ("A05:2021", "A05_2021-Security_Misconfiguration"),
+    ("A07:2021", "A07_2021-Identification_and_Authentication_Failures"),
+    ("A08:2021", "A08_2021-Software_and_Data_Integrity_Failures"),
+    ("A10:2021", "A10_2021-Server-Side_Request_Forgery_%28SSRF%29"),
+];
+
+/// MITRE link for a CWE id (`CWE-798`) and OWASP link for a Top 10
+/// category (`A07:2021`)
+pub fn weakness_references(cwe: &str, owasp: &str) -> Vec<String> {
+    let mut references = vec![format!(
+        "https://cwe.mitre.org/data/definitions/{}.html",
+        cwe.trim_start_matches("CWE-")
+    )];
+    references.extend(
+        OWASP_TOP10
+            .iter()
+            .find(|(id, _)| *id == owasp)
+            .map(|(_, page)| format!("https://owasp.org/Top10/{}/", page)),
+    );
+    references
+}
+
+/// Reject a file rule whose `contains`, `absent` or `header` regex
+/// doesn't parse
+fn check_file_rule(rule: &FileRule) -> Result<(), String> {
+    let sources = [
+        ("contains", &rule.contains),
+        ("absent", &rule.absent),
+        ("header", &rule.header),
+    ];
+    for (field, source) in sources {
+        if let Some(error) = source.as_deref().and_then(guard::syntax_error) {
+            return Err(format!(
+                "Invalid regex in {} of rule '{}': {}",
+                field, rule.name, error
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Reject a pattern `add_pattern` can't load: an unknown scope or
+/// confidence, or a regex or negative regex that doesn't parse
+fn check_pattern(pattern: &SecurityPattern) -> Result<(), String> {
+    if !SCOPES.contains(&pattern.scope.as_str()) {
+        return Err(format!(
+            "Unknown scope '{}' for rule '{}' (expected one of {:?})",
+            pattern.scope, pattern.name, SCOPES
+        ));
+    }
+    if confidence_rank(&pattern.confidence) == 0 {
+        return Err(format!(
+            "Unknown confidence '{}' for rule '{}' (expected one of {:?})",
+            pattern.confidence, pattern.name, CONFIDENCES
+        ));
+    }
+    check_syntax(pattern)
+}
+
+/// Reject a pattern whose regex or negative regex doesn't parse
+///
+/// Each entry of a pattern list is checked on its own, so errors point
+/// into the entry the rule author wrote.
+fn check_syntax(pattern: &SecurityPattern) -> Result<(), String> {
+    let sources: Vec<(String, String)> = match &pattern.pattern {
+        RulePattern::Single(_) => vec![("pattern".to_string(), pattern.effective_pattern())],
+        RulePattern::AnyOf(entries) if entries.is_empty() => {
+            return Err(format!("Empty pattern list in rule '{}'", pattern.name));
+        }
+        RulePattern::AnyOf(entries) => entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (format!("pattern {}", i + 1), entry.clone()))
+            .collect(),
+    };
+    for (field, source) in &sources {
+        check_pattern_syntax(pattern, field, source)?;
+    }
+    if let Some(error) = pattern
+        .negative_pattern
+        .as_ref()
+        .and_then(|s| guard::syntax_error(s))
+    {
+        return Err(format!(
+            "Invalid regex in negative_pattern of rule '{}': {}",
+            pattern.name, error
+        ));
+    }
+    Ok(())
+}
+
+/// Parse one regex of a line pattern on the rule's engine
+fn check_pattern_syntax(
+    pattern: &SecurityPattern,
+    field: &str,
+    source: &str,
+) -> Result<(), String> {
+    if pattern.fancy {
+        let parse_error = fancy::compile(source).err().and_then(|e| match *e {
+            fancy_regex::Error::ParseError(offset, error) => Some((offset, error)),
+            _ => None,
+        });
+        if let Some((offset, error)) = parse_error {
+            let column = source[..offset.min(source.len())].chars().count();
+            return Err(format!(
+                "Invalid regex in {} of rule '{}': {} at position {}",
+                field, pattern.name, error, column
+            ));
+        }
+        return Ok(());
+    }
+    match guard::syntax_error(source) {
+        Some(error) => Err(format!(
+            "Invalid regex in {} of rule '{}': {}",
+            field, pattern.name, error
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Reject a pattern that `add_pattern` would refuse or couldn't compile
+///
+/// Stricter than `add_pattern`, which only reports compile failures
+/// through `diagnostics()`: callers that load rules for every matcher at
+/// once need a pattern that compiles within the guard's size limits.
+pub(crate) fn validate_pattern(pattern: &SecurityPattern) -> Result<(), String> {
+    check_pattern(pattern)?;
+    compile_rule_regex(pattern)
+        .map_err(|e| format!("Rule '{}' doesn't compile: {}", pattern.name, e))?;
+    if let Some(negative) = pattern.negative_pattern.as_deref() {
+        guard::compile_guarded(negative).map_err(|e| {
+            format!(
+                "Negative pattern of rule '{}' doesn't compile: {}",
+                pattern.name, e
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Compile a line pattern on its engine, to report why it can't be used
+fn compile_rule_regex(pattern: &SecurityPattern) -> Result<(), String> {
+    let source = pattern.regex_source();
+    if pattern.fancy {
+        fancy::compile(&source)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    } else {
+        guard::compile_guarded(&source)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct Match {
+    #[pyo3(get)]
+    pub line_number: usize,
+    /// 0-based column in characters, not bytes
+    #[pyo3(get)]
+    pub column: usize,
+    /// 0-based column just past the match on its last line, in characters
+    #[pyo3(get)]
+    #[serde(default)]
+    pub end_column: usize,
+    /// Byte offset of the match in the text given to the matcher: the
+    /// line for `match_line`, the whole content otherwise. None for
+    /// findings not tied to a span of it, such as file rules and findings
+    /// mapped back from extracted content
+    #[pyo3(get)]
+    #[serde(default)]
+    pub start_byte: Option<usize>,
+    /// Byte offset just past the match, see `start_byte`
+    #[pyo3(get)]
+    #[serde(default)]
+    pub end_byte: Option<usize>,
+    #[pyo3(get)]
+    pub pattern_name: String,
+    #[pyo3(get)]
+    pub severity: Severity,
+    /// Confidence of the rule that produced the finding
+    #[pyo3(get)]
+    #[serde(default = "default_confidence")]
+    pub confidence: String,
+    #[pyo3(get)]
+    pub matched_text: String,
+    #[pyo3(get)]
+    pub category: String,
+    #[pyo3(get)]
+    pub file_path: Option<String>,
+    #[pyo3(get)]
+    pub origin: Option<String>,
+    #[pyo3(get)]
+    pub kind: String,
+    #[pyo3(get)]
+    pub tags: Vec<String>,
+    #[pyo3(get)]
+    pub corroborating_rules: Vec<String>,
+    #[pyo3(get)]
+    pub path_class: Option<String>,
+    #[pyo3(get)]
+    pub context_before: Vec<String>,
+    #[pyo3(get)]
+    pub context_after: Vec<String>,
+    /// Where the triggering rule was defined
+    #[pyo3(get)]
+    pub rule_source: Option<RuleSource>,
+    #[pyo3(get)]
+    #[serde(default)]
+    pub cwe: Option<String>,
+    #[pyo3(get)]
+    #[serde(default)]
+    pub owasp: Option<String>,
+    #[pyo3(get)]
+    #[serde(default)]
+    pub references: Vec<String>,
+    /// Later lines holding the same literal for the same rule, when
+    /// duplicate collapsing is enabled
+    #[pyo3(get)]
+    #[serde(default)]
+    pub duplicate_lines: Vec<usize>,
+    /// Whether the credential was confirmed live (`True`) or rejected
+    /// (`False`) by a `Verifier`; `None` when unchecked
+    #[pyo3(get)]
+    #[serde(default)]
+    pub verified: Option<bool>,
+    /// Rule, clause and captures behind the finding, and the adjustments
+    /// and suppressions applied to it since
+    #[pyo3(get)]
+    #[serde(default)]
+    pub explanation: FindingExplanation,
+}
+
+#[pymethods]
+impl Match {
+    /// Number of lines this finding stands for
+    #[getter]
+    pub fn occurrences(&self) -> usize {
+        1 + self.duplicate_lines.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Match(line={}, col={}, pattern={}, severity={})",
+            self.line_number, self.column, self.pattern_name, self.severity
+        )
+    }
+}
+
+/// Fast pattern matcher using Aho-Corasick algorithm
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+#[pyclass]
+pub struct PatternMatcher {
+    patterns: Arc<Vec<SecurityPattern>>,
+    file_rules: Vec<FileRule>,
+    construct_rules: Vec<ConstructRule>,
+    config_rules: Vec<ConfigRule>,
+    composite_rules: Vec<CompositeRule>,
+    proximity_rules: Vec<ProximityRule>,
+    normalization: NormalizationOptions,
+    #[serde(skip)]
+    regex_cache: HashMap<String, Regex>,
+    /// Compiled patterns of rules on the backtracking engine
+    #[serde(skip)]
+    fancy_cache: HashMap<String, fancy_regex::Regex>,
+    user_rules: HashSet<String>,
+    disabled_rules: HashSet<String>,
+    diagnostics: Vec<RuleDiagnostic>,
+    rule_time_budget: Duration,
+    collapse_corroborating: bool,
+    /// Keep only the strongest of overlapping findings of any category
+    collapse_overlapping: bool,
+    /// Drop line findings inside comments and docstrings, see `comments`
+    skip_comments: bool,
+    /// Minimum repeats of a literal before they collapse into one finding
+    collapse_duplicates: Option<usize>,
+    /// Lines of surrounding code attached to `match_content` findings
+    context_lines: usize,
+    /// Flag high-entropy string literals, see `entropy`
+    entropy: Option<EntropyOptions>,
+    /// Pattern rules below this confidence are not evaluated
+    min_confidence: Option<String>,
+    /// When set, rules outside these categories are not evaluated
+    enabled_categories: Option<HashSet<String>>,
+    /// Rules in these categories are not evaluated, including rules added
+    /// after the category was disabled
+    disabled_categories: HashSet<String>,
+    /// Regexes of known dummy values; findings whose text matches one are
+    /// dropped
+    allowlist: Vec<String>,
+    /// Hosts network findings may name, see `network`
+    allowed_hosts: Vec<String>,
+    foreign_suppressions: bool,
+    import_pruning: bool,
+    /// Language of the content being matched, for rules with `languages`
+    #[serde(skip)]
+    language: Option<String>,
+    /// Rules skipped for the content being matched, see `imports`
+    #[serde(skip)]
+    pruned_rules: HashSet<String>,
+    #[serde(skip)]
+    line_cache: Option<LineCache>,
+    /// Per-rule counters, shared with clones; see `rulestats`
+    #[serde(skip)]
+    statistics: Option<RuleStats>,
+    #[serde(skip)]
+    on_match: Option<MatchCallback>,
+    /// Built on first use; dropped whenever the pattern list changes
+    #[serde(skip)]
+    pattern_set: Option<Option<Arc<PatternSet>>>,
+    #[serde(skip)]
+    prefilter: Option<Option<Arc<LiteralPrefilter>>>,
+}
+
+#[pymethods]
+impl PatternMatcher {
+    #[new]
+    pub fn new() -> Self {
+        PatternMatcher {
+            patterns: registry::shared_patterns(),
+            file_rules: Vec::new(),
+            construct_rules: Vec::new(),
+            config_rules: config_rules::default_config_rules(),
+            composite_rules: Vec::new(),
+            proximity_rules: Vec::new(),
+            normalization: NormalizationOptions::default(),
+            regex_cache: HashMap::new(),
+            fancy_cache: HashMap::new(),
+            user_rules: HashSet::new(),
+            disabled_rules: HashSet::new(),
+            diagnostics: Vec::new(),
+            rule_time_budget: Duration::from_millis(DEFAULT_RULE_TIME_BUDGET_MS),
+            collapse_corroborating: true,
+            collapse_overlapping: false,
+            skip_comments: false,
+            collapse_duplicates: None,
+            context_lines: 0,
+            entropy: None,
+            min_confidence: None,
+            enabled_categories: None,
+            disabled_categories: HashSet::new(),
+            allowlist: Vec::new(),
+            allowed_hosts: network::DEFAULT_ALLOWED_HOSTS
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+            foreign_suppressions: false,
+            import_pruning: true,
+            language: None,
+            pruned_rules: HashSet::new(),
+            line_cache: None,
+            statistics: None,
+            on_match: None,
+            pattern_set: None,
+            prefilter: None,
+        }
+    }
+
+    /// A matcher with no line patterns, to be filled with `add_pattern`
+    #[staticmethod]
+    pub fn new_empty() -> Self {
+        let mut matcher = Self::new();
+        matcher.clear_patterns();
+        matcher
+    }
+
+    /// The full default rule catalog, to pick from for `new_empty` matchers
+    #[staticmethod]
+    pub fn builtin_patterns() -> Vec<SecurityPattern> {
+        registry::builtin_patterns()
+    }
+
+    /// Add a custom security pattern
+    ///
+    /// A pattern (or negative pattern) that isn't a valid regex, or an
+    /// unknown scope or confidence, raises ValueError naming the rule and
+    /// the problem; confidence is stored lowercased. Otherwise the
+    /// pattern is checked for signs of catastrophic cost; problems are
+    /// reported through `diagnostics()` and it is timed while matching.
+    pub fn add_pattern(&mut self, mut pattern: SecurityPattern) -> PyResult<()> {
+        check_pattern(&pattern).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        pattern.confidence = pattern.confidence.to_lowercase();
+        for problem in guard::analyze_complexity(&pattern.regex_source()) {
+            self.diagnostics
+                .push(RuleDiagnostic::warning(&pattern.name, problem));
+        }
+        if let Err(e) = compile_rule_regex(&pattern) {
+            self.diagnostics
+                .push(RuleDiagnostic::error(&pattern.name, e));
+        }
+        if let Some(Err(e)) = pattern
+            .negative_pattern
+            .as_deref()
+            .map(guard::compile_guarded)
+        {
+            self.diagnostics
+                .push(RuleDiagnostic::error(&pattern.name, e.to_string()));
+        }
+        let clashes: Vec<String> = self
+            .patterns
+            .iter()
+            .filter(|p| p.name == pattern.name)
+            .map(SecurityPattern::id)
+            .collect();
+        if !clashes.is_empty() {
+            self.diagnostics.push(RuleDiagnostic::warning(
+                &pattern.name,
+                format!(
+                    "Rule name already loaded as {}; look it up by id `{}`",
+                    clashes.join(", "),
+                    pattern.id()
+                ),
+            ));
+        }
+        self.user_rules.insert(pattern.name.clone());
+        self.pattern_set = None;
+        self.prefilter = None;
+
+        // Copy-on-write: detaches this matcher from the shared registry set
+        Arc::make_mut(&mut self.patterns).push(pattern);
+        Ok(())
+    }
+
+    /// Look up a loaded line pattern by id, or by bare name when only one
+    /// rule has it
+    ///
+    /// A bare name loaded more than once raises ValueError listing the ids
+    /// to choose from.
+    pub fn get_pattern(&self, rule: &str) -> PyResult<Option<SecurityPattern>> {
+        if let Some(pattern) = self.patterns.iter().find(|p| p.id() == rule) {
+            return Ok(Some(pattern.clone()));
+        }
+        let named: Vec<&SecurityPattern> =
+            self.patterns.iter().filter(|p| p.name == rule).collect();
+        match named.as_slice() {
+            [] => Ok(None),
+            [pattern] => Ok(Some((*pattern).clone())),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Rule name '{}' is ambiguous, use one of {:?}",
+                rule,
+                named.iter().map(|p| p.id()).collect::<Vec<_>>()
+            ))),
+        }
+    }
+
+    /// Ids of the loaded line patterns in load order, only those in
+    /// `namespace` when given
+    pub fn rule_ids(&self, namespace: Option<&str>) -> Vec<String> {
+        self.patterns
+            .iter()
+            .filter(|p| namespace.is_none_or(|ns| RuleSource::namespace(p.source.as_ref()) == ns))
+            .map(SecurityPattern::id)
+            .collect()
+    }
+
+    /// Line pattern names loaded more than once, mapped to the ids that
+    /// define them; findings of these rules can't be told apart by name
+    pub fn rule_collisions(&self) -> HashMap<String, Vec<String>> {
+        let mut ids: HashMap<String, Vec<String>> = HashMap::new();
+        for pattern in self.patterns.iter() {
+            ids.entry(pattern.name.clone())
+                .or_default()
+                .push(pattern.id());
+        }
+        ids.retain(|_, ids| ids.len() > 1);
+        ids
+    }
+
+    /// Remove every rule named `name`, returning whether one was loaded
+    pub fn remove_pattern(&mut self, name: &str) -> bool {
+        let before = self.rule_count();
+        if self.patterns.iter().any(|p| p.name == name) {
+            Arc::make_mut(&mut self.patterns).retain(|p| p.name != name);
+            self.pattern_set = None;
+            self.prefilter = None;
+        }
+        self.file_rules.retain(|r| r.name != name);
+        self.construct_rules.retain(|r| r.name != name);
+        self.config_rules.retain(|r| r.name != name);
+        self.composite_rules.retain(|r| r.name != name);
+        self.proximity_rules.retain(|r| r.name != name);
+        self.user_rules.remove(name);
+        self.rule_count() < before
+    }
+
+    /// Stop evaluating rules in `category`, including rules added later,
+    /// returning how many loaded rules it covers
+    pub fn disable_category(&mut self, category: &str) -> usize {
+        self.disabled_categories.insert(category.to_string());
+        self.rules().filter(|(_, c)| *c == category).count()
+    }
+
+    /// Evaluate rules in `category` again, returning whether it was disabled
+    pub fn enable_category(&mut self, category: &str) -> bool {
+        self.disabled_categories.remove(category)
+    }
+
+    /// Stop reporting findings of the rule `name`, returning whether it was
+    /// enabled
+    pub fn disable_rule(&mut self, name: &str) -> bool {
+        self.disabled_rules.insert(name.to_string())
+    }
+
+    /// Report findings of the rule `name` again, returning whether it was
+    /// disabled
+    pub fn enable_rule(&mut self, name: &str) -> bool {
+        self.disabled_rules.remove(name)
+    }
+
+    /// Drop all line patterns, built-in ones included, so a curated set
+    /// can be added with `add_pattern`
+    pub fn clear_patterns(&mut self) {
+        self.patterns = Arc::new(Vec::new());
+        self.user_rules.clear();
+        self.pattern_set = None;
+        self.prefilter = None;
+    }
+
+    /// Write every loaded rule, with severity overrides applied, and the
+    /// names of disabled rules to `path` for `load_rules`
+    ///
+    /// `format` is `json` or `yaml`, by default chosen from the extension
+    /// of `path`. Returns the number of rules written.
+    pub fn export_rules(&self, path: String, format: Option<String>) -> PyResult<usize> {
+        let format = match format {
+            Some(name) => RuleFormat::parse(&name).ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown rule set format: {} (expected json or yaml)",
+                    name
+                ))
+            })?,
+            None => RuleFormat::from_path(Path::new(&path)),
+        };
+        let rules = self.rule_set();
+        ruleset::save(&rules, Path::new(&path), format)
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("{}: {}", path, e)))?;
+        Ok(rules.len())
+    }
+
+    /// Replace every rule with those exported to `path` by `export_rules`,
+    /// returning how many were loaded
+    ///
+    /// Patterns are checked as by `add_pattern`; if one is invalid,
+    /// ValueError is raised and the matcher is left unchanged. Patterns
+    /// identical to a built-in or registered one keep the fast path, the
+    /// others are timed like custom rules.
+    pub fn load_rules(&mut self, path: String) -> PyResult<usize> {
+        let rules = ruleset::load(Path::new(&path)).map_err(|e| match e.kind() {
+            std::io::ErrorKind::InvalidData => {
+                pyo3::exceptions::PyValueError::new_err(format!("{}: {}", path, e))
+            }
+            _ => pyo3::exceptions::PyIOError::new_err(format!("{}: {}", path, e)),
+        })?;
+        for pattern in &rules.patterns {
+            check_pattern(pattern).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        }
+        for rule in &rules.file_rules {
+            check_file_rule(rule).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        }
+        let count = rules.len();
+
+        let shared = registry::shared_patterns();
+        self.clear_patterns();
+        self.file_rules.clear();
+        self.construct_rules.clear();
+        self.config_rules.clear();
+        self.composite_rules.clear();
+        self.proximity_rules.clear();
+        self.diagnostics.clear();
+        for pattern in rules.patterns {
+            let registered = shared
+                .iter()
+                .any(|p| p.id() == pattern.id() && p.regex_source() == pattern.regex_source());
+            if registered {
+                Arc::make_mut(&mut self.patterns).push(pattern);
+            } else {
+                self.add_pattern(pattern)?;
+            }
+        }
+        for rule in rules.file_rules {
+            self.add_file_rule(rule)?;
+        }
+        rules
+            .construct_rules
+            .into_iter()
+            .for_each(|r| self.add_construct_rule(r));
+        rules
+            .config_rules
+            .into_iter()
+            .for_each(|r| self.add_config_rule(r));
+        rules
+            .composite_rules
+            .into_iter()
+            .for_each(|r| self.add_composite_rule(r));
+        rules
+            .proximity_rules
+            .into_iter()
+            .for_each(|r| self.add_proximity_rule(r));
+        self.disabled_rules = rules.disabled_rules.into_iter().collect();
+        self.disabled_categories = rules.disabled_categories.into_iter().collect();
+        Ok(count)
+    }
+
+    /// Time a custom rule may spend on a single line before it is disabled
+    pub fn set_rule_time_budget(&mut self, budget_ms: u64) {
+        self.rule_time_budget = Duration::from_millis(budget_ms);
+    }
+
+    /// Merge overlapping secret findings into the most specific one
+    pub fn set_collapse_corroborating(&mut self, enabled: bool) {
+        self.collapse_corroborating = enabled;
+    }
+
+    /// Keep only the most severe, then most confident, of overlapping
+    /// findings on a line, whatever their category; off by default
+    pub fn set_collapse_overlapping(&mut self, enabled: bool) {
+        self.collapse_overlapping = enabled;
+    }
+
+    /// Drop line-rule findings that start inside a comment or docstring,
+    /// for code rules that commented-out or documented code shouldn't
+    /// trigger; off by default
+    pub fn set_skip_comments(&mut self, enabled: bool) {
+        self.skip_comments = enabled;
+    }
+
+    /// Report a literal that one rule finds on `min_occurrences` or more
+    /// lines (default 2) as a single finding listing the other lines in
+    /// `duplicate_lines`
+    pub fn set_collapse_duplicates(&mut self, enabled: bool, min_occurrences: Option<usize>) {
+        self.collapse_duplicates = enabled.then(|| min_occurrences.unwrap_or(2).max(2));
+    }
+
+    /// Lines of code before and after each `match_content` finding that are
+    /// attached as `context_before`/`context_after`; 0 (default) attaches none
+    #[getter]
+    pub fn context_lines(&self) -> usize {
+        self.context_lines
+    }
+
+    #[setter]
+    pub fn set_context_lines(&mut self, lines: usize) {
+        self.context_lines = lines;
+    }
+
+    /// Flag string literals whose Shannon entropy exceeds the cutoff for
+    /// their alphabet as `high_entropy_string` secrets
+    pub fn set_entropy_detection(&mut self, enabled: bool, options: Option<EntropyOptions>) {
+        self.entropy = enabled.then(|| options.unwrap_or_default());
+    }
+
+    /// Only evaluate rules in `categories`, including rules added later;
+    /// None evaluates every category again
+    ///
+    /// `disable_category` still applies to categories in the list.
+    pub fn set_enabled_categories(&mut self, categories: Option<Vec<String>>) {
+        self.enabled_categories = categories.map(|c| c.into_iter().collect());
+    }
+
+    #[getter]
+    pub fn enabled_categories(&self) -> Option<Vec<String>> {
+        self.enabled_categories.as_ref().map(|categories| {
+            let mut categories: Vec<String> = categories.iter().cloned().collect();
+            categories.sort();
+            categories
+        })
+    }
+
+    /// Drop findings of rules less confident than `confidence` ("high",
+    /// "medium" or "low"); None reports everything
+    pub fn set_min_confidence(&mut self, confidence: Option<String>) -> PyResult<()> {
+        if let Some(level) = &confidence {
+            if confidence_rank(level) == 0 {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown confidence '{}', expected one of {:?}",
+                    level, CONFIDENCES
+                )));
+            }
+        }
+        self.min_confidence = confidence.map(|c| c.to_lowercase());
+        Ok(())
+    }
+
+    #[getter]
+    pub fn min_confidence(&self) -> Option<String> {
+        self.min_confidence.clone()
+    }
+
+    /// Drop findings whose matched text contains a match of `pattern`,
+    /// for test fixtures' dummy credentials like `sk-test-xxxxxxxx`
+    ///
+    /// Raises ValueError when the regex doesn't compile.
+    pub fn add_allowlist_pattern(&mut self, pattern: String) -> PyResult<()> {
+        guard::compile_guarded(&pattern).map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid allowlist regex {}: {}",
+                pattern, e
+            ))
+        })?;
+        if !self.allowlist.contains(&pattern) {
+            self.allowlist.push(pattern);
+        }
+        Ok(())
+    }
+
+    pub fn clear_allowlist(&mut self) {
+        self.allowlist.clear();
+    }
+
+    #[getter]
+    pub fn allowlist(&self) -> Vec<String> {
+        self.allowlist.clone()
+    }
+
+    /// Stop reporting network findings on `host`: a hostname or address,
+    /// `*.domain` for a domain and its subdomains, or an IPv4 CIDR block
+    ///
+    /// Raises ValueError for a malformed CIDR block.
+    pub fn add_allowed_host(&mut self, host: String) -> PyResult<()> {
+        network::check_entry(&host).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        if !self.allowed_hosts.contains(&host) {
+            self.allowed_hosts.push(host);
+        }
+        Ok(())
+    }
+
+    /// Forget the allowed hosts, the defaults (loopback, documentation
+    /// domains, XML namespaces) included
+    pub fn clear_allowed_hosts(&mut self) {
+        self.allowed_hosts.clear();
+    }
+
+    #[getter]
+    pub fn allowed_hosts(&self) -> Vec<String> {
+        self.allowed_hosts.clone()
+    }
+
+    /// Also honor suppressions written for other tools (`# nosec`,
+    /// `// eslint-disable-next-line`, `#[allow(knox::rule)]`, ...)
+    pub fn set_foreign_suppressions(&mut self, enabled: bool) {
+        self.foreign_suppressions = enabled;
+    }
+
+    /// Language of the content passed to later `match_content` calls, as
+    /// named by `detect_language`; rules whose `languages` exclude it are
+    /// skipped. With none set, every rule runs
+    pub fn set_language(&mut self, language: Option<String>) {
+        self.language = language.map(|l| l.to_lowercase());
+    }
+
+    #[getter]
+    pub fn language(&self) -> Option<String> {
+        self.language.clone()
+    }
+
+    /// Skip rules with `requires_imports` in files that import none of the
+    /// listed modules (on by default); needs the language, and prunes
+    /// nothing when the file's imports can't be read reliably
+    pub fn set_import_pruning(&mut self, enabled: bool) {
+        self.import_pruning = enabled;
+    }
+
+    /// Cache per-line results across `match_content` calls, so rescans of
+    /// an edited file only evaluate the lines that changed
+    pub fn set_line_cache(&mut self, enabled: bool, capacity: Option<usize>) {
+        self.line_cache =
+            enabled.then(|| LineCache::new(capacity.unwrap_or(DEFAULT_LINE_CACHE_CAPACITY)));
+    }
+
+    /// (hits, misses) of the line cache, or None when it is disabled
+    pub fn line_cache_stats(&self) -> Option<(u64, u64)> {
+        self.line_cache.as_ref().map(|c| (c.hits, c.misses))
+    }
+
+    /// Call `callback(match)` with each finding of `match_content` and
+    /// `match_content_range` once the content is matched, before the
+    /// findings are returned; None removes the callback
+    #[pyo3(signature = (callback))]
+    pub fn set_on_match(&mut self, py: Python<'_>, callback: Option<PyObject>) -> PyResult<()> {
+        self.on_match = callback
+            .map(|callback| MatchCallback::new(py, callback))
+            .transpose()?;
+        Ok(())
+    }
+
+    /// Count evaluations, findings and time per rule, for `statistics`
+    ///
+    /// Disabling drops the counters gathered so far.
+    pub fn set_statistics(&mut self, enabled: bool) {
+        match (enabled, self.statistics.is_some()) {
+            (true, false) => self.statistics = Some(RuleStats::default()),
+            (false, _) => self.statistics = None,
+            _ => {}
+        }
+    }
+
+    /// Per-rule evaluation and match counts and cumulative time, most
+    /// expensive rule first; empty unless `set_statistics` enabled them
+    pub fn statistics(&mut self) -> Vec<RuleStatistics> {
+        self.statistics
+            .as_mut()
+            .map(RuleStats::snapshot)
+            .unwrap_or_default()
+    }
+
+    /// Zero the statistics counters
+    pub fn reset_statistics(&mut self) {
+        if let Some(statistics) = &mut self.statistics {
+            statistics.reset();
+        }
+    }
+
+    /// Problems found in custom rules at load time or while matching
+    pub fn diagnostics(&self) -> Vec<RuleDiagnostic> {
+        self.diagnostics.clone()
+    }
+
+    /// Add a file-level rule evaluated once per `match_content` call
+    ///
+    /// A regex that doesn't parse raises ValueError naming the rule, the
+    /// field and the error position, as `add_pattern` does; other compile
+    /// problems are reported through `diagnostics()`.
+    pub fn add_file_rule(&mut self, rule: FileRule) -> PyResult<()> {
+        check_file_rule(&rule).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        for source in [&rule.contains, &rule.absent, &rule.header]
+            .into_iter()
+            .flatten()
+        {
+            if let Err(e) = guard::compile_guarded(source) {
+                self.diagnostics
+                    .push(RuleDiagnostic::error(&rule.name, e.to_string()));
+            }
+        }
+        self.file_rules.push(rule);
+        Ok(())
+    }
+
+    /// Add a rule evaluated against parsed constructs by `match_constructs`
+    pub fn add_construct_rule(&mut self, rule: ConstructRule) {
+        if let Err(e) = guard::compile_guarded(&rule.pattern) {
+            self.diagnostics
+                .push(RuleDiagnostic::error(&rule.name, e.to_string()));
+        }
+        self.construct_rules.push(rule);
+    }
+
+    /// Add a rule combining a trigger regex with file-level required and
+    /// forbidden regexes, evaluated once per `match_content` call
+    pub fn add_composite_rule(&mut self, rule: CompositeRule) {
+        for source in rule.sources() {
+            if let Err(e) = guard::compile_guarded(source) {
+                self.diagnostics
+                    .push(RuleDiagnostic::error(&rule.name, e.to_string()));
+            }
+        }
+        self.composite_rules.push(rule);
+    }
+
+    /// Add a rule firing where two regexes match within a window of
+    /// lines, evaluated once per `match_content` call
+    pub fn add_proximity_rule(&mut self, rule: ProximityRule) {
+        for source in [&rule.anchor, &rule.near] {
+            if let Err(e) = guard::compile_guarded(source) {
+                self.diagnostics
+                    .push(RuleDiagnostic::error(&rule.name, e.to_string()));
+            }
+        }
+        self.proximity_rules.push(rule);
+    }
+
+    /// Add a rule evaluated against YAML and JSON key paths by
+    /// `match_config`
+    pub fn add_config_rule(&mut self, rule: ConfigRule) {
+        if let Some(pattern) = &rule.value_pattern {
+            if let Err(e) = guard::compile_guarded(pattern) {
+                self.diagnostics
+                    .push(RuleDiagnostic::error(&rule.name, e.to_string()));
+            }
+        }
+        self.config_rules.push(rule);
+    }
+
+    /// Control CRLF, tab and trailing whitespace handling before matching
+    pub fn set_normalization(&mut self, options: NormalizationOptions) {
+        self.normalization = options;
+    }
+
+    /// Override the severity of a rule, e.g. to report `debug_mode` as "info"
+    pub fn set_severity(&mut self, name: &str, severity: &str) -> PyResult<()> {
+        self.set_severities(HashMap::from([(name.to_string(), severity.to_string())]))
+    }
+
+    /// Override severities in bulk from a rule name to severity map;
+    /// nothing changes unless every entry is valid
+    pub fn set_severities(&mut self, overrides: HashMap<String, String>) -> PyResult<()> {
+        self.override_severities(&overrides)
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Match patterns in a single line of code
+    pub fn match_line(&mut self, line: &str, line_number: usize) -> Vec<Match> {
+        // A single line carries no imports to prune by, and pruning left
+        // over from the last `match_content` call doesn't apply to it
+        self.pruned_rules = self.rules_outside_language().into_iter().collect();
+        self.evaluate_line(line, line_number)
+    }
+
+    /// Match patterns across multiple lines efficiently
+    ///
+    /// Honors `knox:ignore` comments on the matched line or alone on the
+    /// line above it, and other tools' markers when foreign suppressions
+    /// are enabled. Findings come back in the order defined by `ordering`.
+    pub fn match_content(&mut self, content: &str) -> Vec<Match> {
+        self.prepare(content);
+        let mut all_matches = self.match_lines(content, 1);
+        if let Some(min_occurrences) = self.collapse_duplicates {
+            all_matches = dedup::collapse_duplicates(all_matches, min_occurrences);
+        }
+        all_matches.extend(self.match_file_rules(content));
+        all_matches.extend(self.match_composite_rules(content));
+        all_matches.extend(self.match_proximity_rules(content));
+        self.apply_allowlist(&mut all_matches);
+        ordering::sort_matches(&mut all_matches);
+        context::attach_context(&mut all_matches, content, self.context_lines, None);
+        if let Some(on_match) = &self.on_match {
+            on_match.deliver(&all_matches);
+        }
+        all_matches
+    }
+
+    /// Match only lines `start_line..=end_line` (1-based) of `content`, for
+    /// re-scanning an edited region
+    ///
+    /// The line above the region is read for suppressions, and imports are
+    /// taken from the whole content. File rules are not evaluated, and
+    /// multiline findings must start inside the region.
+    pub fn match_content_range(
+        &mut self,
+        content: &str,
+        start_line: usize,
+        end_line: usize,
+    ) -> Vec<Match> {
+        let start_line = start_line.max(1);
+        if end_line < start_line {
+            return Vec::new();
+        }
+        self.prepare(content);
+
+        let first_line = start_line - usize::from(start_line > 1);
+        let (start, end) = LineIndex::new(content).lines_span(first_line, end_line);
+        let mut all_matches = self.match_lines(&content[start..end], first_line);
+        all_matches.retain(|m| m.line_number >= start_line);
+        for m in &mut all_matches {
+            m.start_byte = m.start_byte.map(|b| b + start);
+            m.end_byte = m.end_byte.map(|b| b + start);
+        }
+        if let Some(min_occurrences) = self.collapse_duplicates {
+            all_matches = dedup::collapse_duplicates(all_matches, min_occurrences);
+        }
+        self.apply_allowlist(&mut all_matches);
+        ordering::sort_matches(&mut all_matches);
+        context::attach_context(&mut all_matches, content, self.context_lines, None);
+        if let Some(on_match) = &self.on_match {
+            on_match.deliver(&all_matches);
+        }
+        all_matches
+    }
+
+    /// Evaluate file-level rules against the whole content
+    pub fn match_file_rules(&mut self, content: &str) -> Vec<Match> {
+        let rules: Vec<FileRule> = self
+            .file_rules
+            .iter()
+            .filter(|rule| self.rule_enabled(&rule.name, &rule.category))
+            .cloned()
+            .collect();
+
+        rules
+            .iter()
+            .filter_map(|rule| {
+                let started = Instant::now();
+                let trigger = rule.evaluate(content, |p| self.get_or_compile_regex(p).cloned());
+                self.record_evaluation(&rule.name, started);
+                let trigger = trigger?;
+                self.record_matches(&rule.name, 1);
+                Some(Match {
+                    line_number: 1,
+                    column: 0,
+                    end_column: 0,
+                    start_byte: None,
+                    end_byte: None,
+                    pattern_name: rule.name.clone(),
+                    severity: rule.severity,
+                    confidence: DEFAULT_CONFIDENCE.to_string(),
+                    matched_text: trigger.to_string(),
+                    category: rule.category.clone(),
+                    file_path: None,
+                    origin: None,
+                    kind: KIND_FILE.to_string(),
+                    tags: Vec::new(),
+                    corroborating_rules: Vec::new(),
+                    path_class: None,
+                    context_before: Vec::new(),
+                    context_after: Vec::new(),
+                    rule_source: None,
+                    cwe: None,
+                    owasp: None,
+                    references: Vec::new(),
+                    duplicate_lines: Vec::new(),
+                    verified: None,
+                    explanation: FindingExplanation::new(&rule.description, rule.clause()),
+                })
+            })
+            .collect()
+    }
+
+    /// Evaluate composite rules against the whole content, reporting each
+    /// line their trigger matches
+    pub fn match_composite_rules(&mut self, content: &str) -> Vec<Match> {
+        let rules: Vec<CompositeRule> = self
+            .composite_rules
+            .iter()
+            .filter(|rule| self.rule_enabled(&rule.name, &rule.category))
+            .cloned()
+            .collect();
+        let mut matches = Vec::new();
+        for rule in rules.iter() {
+            let started = Instant::now();
+            let hits = rule.evaluate(content, |p| self.get_or_compile_regex(p).cloned());
+            self.record_evaluation(&rule.name, started);
+            self.record_matches(&rule.name, hits.len());
+            matches.extend(Self::hit_matches(
+                content,
+                hits,
+                (&rule.name, rule.severity, &rule.category),
+                KIND_COMPOSITE,
+                &FindingExplanation::new(&rule.description, rule.clause()),
+            ));
+        }
+        matches.sort_by_key(|m| m.line_number);
+        matches
+    }
+
+    /// Evaluate proximity rules, reporting each anchor line with a `near`
+    /// match within the rule's window
+    pub fn match_proximity_rules(&mut self, content: &str) -> Vec<Match> {
+        let rules: Vec<ProximityRule> = self
+            .proximity_rules
+            .iter()
+            .filter(|rule| self.rule_enabled(&rule.name, &rule.category))
+            .cloned()
+            .collect();
+        let mut matches = Vec::new();
+        for rule in rules.iter() {
+            let started = Instant::now();
+            let hits = rule.evaluate(content, |p| self.get_or_compile_regex(p).cloned());
+            self.record_evaluation(&rule.name, started);
+            self.record_matches(&rule.name, hits.len());
+            matches.extend(Self::hit_matches(
+                content,
+                hits,
+                (&rule.name, rule.severity, &rule.category),
+                KIND_PROXIMITY,
+                &FindingExplanation::new(&rule.description, rule.clause()),
+            ));
+        }
+        matches.sort_by_key(|m| m.line_number);
+        matches
+    }
+
+    /// Run construct rules over the output of `CodeParser.parse`
+    pub fn match_constructs(&mut self, artifacts: &ParseArtifacts) -> Vec<Match> {
+        let rules = self.construct_rules.clone();
+        let mut matches = Vec::new();
+
+        for rule in rules.iter() {
+            if self.disabled_rules.contains(&rule.name) || !self.category_enabled(&rule.category) {
+                continue;
+            }
+            let started = Instant::now();
+            let before = matches.len();
+            let regex = match self.get_or_compile_regex(&rule.pattern) {
+                Some(regex) => regex,
+                None => continue,
+            };
+
+            for (line_number, text) in rule.candidates(artifacts) {
+                if regex.is_match(&text) {
+                    matches.push(Match {
+                        line_number,
+                        column: 0,
+                        end_column: 0,
+                        start_byte: None,
+                        end_byte: None,
+                        pattern_name: rule.name.clone(),
+                        severity: rule.severity,
+                        confidence: DEFAULT_CONFIDENCE.to_string(),
+                        matched_text: text.to_string(),
+                        category: rule.category.clone(),
+                        file_path: None,
+                        origin: None,
+                        kind: KIND_CONSTRUCT.to_string(),
+                        tags: Vec::new(),
+                        corroborating_rules: Vec::new(),
+                        path_class: None,
+                        context_before: Vec::new(),
+                        context_after: Vec::new(),
+                        rule_source: None,
+                        cwe: None,
+                        owasp: None,
+                        references: Vec::new(),
+                        duplicate_lines: Vec::new(),
+                        verified: None,
+                        explanation: FindingExplanation::new(&rule.description, rule.clause()),
+                    });
+                }
+            }
+            self.record_evaluation(&rule.name, started);
+            self.record_matches(&rule.name, matches.len() - before);
+        }
+
+        self.apply_allowlist(&mut matches);
+        matches.sort_by_key(|m| m.line_number);
+        matches
+    }
+
+    /// Run config rules over a document in `language` ("yaml" or "json");
+    /// other languages yield nothing
+    ///
+    /// Each finding reports the key path and value, on the value's line.
+    /// `knox:ignore` comments on that line apply as for line rules.
+    pub fn match_config(&mut self, language: &str, content: &str) -> Vec<Match> {
+        if !config_rules::LANGUAGES.contains(&language) || self.config_rules.is_empty() {
+            return Vec::new();
+        }
+        let entries = config_rules::extract_entries(language, content);
+        let rules = self.config_rules.clone();
+        let lines: Vec<&str> = content.lines().collect();
+        let index = LineIndex::new(content);
+        let today = suppress::today();
+        let mut matches = Vec::new();
+
+        for rule in rules.iter() {
+            if self.disabled_rules.contains(&rule.name) || !self.category_enabled(&rule.category) {
+                continue;
+            }
+            let started = Instant::now();
+            let before = matches.len();
+            let value_regex = match &rule.value_pattern {
+                Some(pattern) => match self.get_or_compile_regex(pattern) {
+                    Some(regex) => Some(regex.clone()),
+                    None => continue,
+                },
+                None => None,
+            };
+
+            for entry in rule.candidates(&entries) {
+                if value_regex
+                    .as_ref()
+                    .is_some_and(|r| !r.is_match(&entry.value))
+                {
+                    continue;
+                }
+                let line = lines.get(entry.line_number - 1).copied().unwrap_or("");
+                let span = line
+                    .find(entry.value.as_str())
+                    .map(|start| (start, start + entry.value.len()));
+                let line_start = index.lines_span(entry.line_number, entry.line_number).0;
+                let mut found = vec![Match {
+                    line_number: entry.line_number,
+                    column: span.map_or(0, |(start, _)| context::char_column(line, start)),
+                    end_column: span.map_or(0, |(_, end)| context::char_column(line, end)),
+                    start_byte: span.map(|(start, _)| line_start + start),
+                    end_byte: span.map(|(_, end)| line_start + end),
+                    pattern_name: rule.name.clone(),
+                    severity: rule.severity,
+                    confidence: DEFAULT_CONFIDENCE.to_string(),
+                    matched_text: format!("{}: {}", entry.dotted_path(), entry.value),
+                    category: rule.category.clone(),
+                    file_path: None,
+                    origin: None,
+                    kind: KIND_CONFIG.to_string(),
+                    tags: Vec::new(),
+                    corroborating_rules: Vec::new(),
+                    path_class: None,
+                    context_before: Vec::new(),
+                    context_after: Vec::new(),
+                    rule_source: None,
+                    cwe: rule.cwe.clone(),
+                    owasp: rule.owasp.clone(),
+                    references: rule.references.clone(),
+                    duplicate_lines: Vec::new(),
+                    verified: None,
+                    explanation: FindingExplanation::new(&rule.description, rule.clause()),
+                }];
+                if let Some(suppression) = Suppression::parse(line) {
+                    Self::apply_suppression(&mut found, &suppression, today);
+                }
+                matches.extend(found);
+            }
+            self.record_evaluation(&rule.name, started);
+            self.record_matches(&rule.name, matches.len() - before);
+        }
+
+        self.apply_allowlist(&mut matches);
+        matches.sort_by_key(|m| m.line_number);
+        matches
+    }
+
+    /// Match extracted content, mapping results back to the original source
+    pub fn match_extracted(
+        &mut self,
+        extracted: &ExtractedContent,
+        file_path: Option<String>,
+    ) -> Vec<Match> {
+        let mut matches = self.match_content(extracted.content());
+        for m in &mut matches {
+            extracted.remap(m);
+            m.file_path = file_path.clone();
+        }
+        matches
+    }
+
+    /// Run every pattern against its own `positive_examples` and
+    /// `negative_examples`, returning an error diagnostic per example it
+    /// gets wrong
+    ///
+    /// Each rule runs alone with this matcher's normalization, so other
+    /// rules, suppression settings and import pruning don't affect it.
+    pub fn validate_patterns(&self) -> Vec<RuleDiagnostic> {
+        let mut failures = Vec::new();
+        for pattern in self.patterns.iter() {
+            if pattern.positive_examples.is_empty() && pattern.negative_examples.is_empty() {
+                continue;
+            }
+            let mut probe = PatternMatcher::new_empty();
+            probe.normalization = self.normalization.clone();
+            probe.import_pruning = false;
+            probe.patterns = Arc::new(vec![pattern.clone()]);
+
+            let cases = pattern
+                .positive_examples
+                .iter()
+                .map(|example| (example, true))
+                .chain(
+                    pattern
+                        .negative_examples
+                        .iter()
+                        .map(|example| (example, false)),
+                );
+            for (example, should_match) in cases {
+                let matched = probe
+                    .match_content(example)
+                    .iter()
+                    .any(|m| m.pattern_name == pattern.name);
+                if matched != should_match {
+                    let message = if should_match {
+                        format!("positive example not flagged: {}", example)
+                    } else {
+                        format!("negative example flagged: {}", example)
+                    };
+                    failures.push(RuleDiagnostic::error(&pattern.name, message));
+                }
+            }
+        }
+        failures
+    }
+
+    /// Explain why `rule_name` does or doesn't report a finding on `line`
+    ///
+    /// Walks the same stages as `match_content` for that one rule, with
+    /// `line` standing in for the whole content: disabled rules, pattern
+    /// compilation, import and language pruning, the literal prefilter, the
+    /// match itself (over the whole text for multiline and string-scoped
+    /// rules), the negative pattern, suppression comments, the allowlist
+    /// and, if given, a minimum severity, which raises ValueError when it
+    /// isn't a severity name.
+    pub fn explain(
+        &mut self,
+        line: &str,
+        rule_name: &str,
+        min_severity: Option<String>,
+    ) -> PyResult<RuleExplanation> {
+        let min_severity = min_severity
+            .map(|s| s.parse::<Severity>())
+            .transpose()
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let Some(index) = self.patterns.iter().position(|p| p.name == rule_name) else {
+            return Ok(RuleExplanation::new(
+                rule_name,
+                explain::OUTCOME_UNKNOWN_RULE,
+                format!("no rule named '{}' is loaded", rule_name),
+            ));
+        };
+        let pattern = self.patterns[index].clone();
+        if self.disabled_rules.contains(rule_name) {
+            let reason = self
+                .diagnostics
+                .iter()
+                .rev()
+                .find(|d| d.rule == rule_name)
+                .map(|d| d.message.clone())
+                .unwrap_or_else(|| "rule is disabled".to_string());
+            return Ok(RuleExplanation::new(
+                rule_name,
+                explain::OUTCOME_DISABLED,
+                reason,
+            ));
+        }
+        if !self.category_enabled(&pattern.category) {
+            return Ok(RuleExplanation::new(
+                rule_name,
+                explain::OUTCOME_DISABLED,
+                format!("category {} is not enabled", pattern.category),
+            ));
+        }
+        if self.below_confidence(&pattern.confidence) {
+            return Ok(RuleExplanation::new(
+                rule_name,
+                explain::OUTCOME_BELOW_CONFIDENCE,
+                format!(
+                    "confidence {} is below {}",
+                    pattern.confidence,
+                    self.min_confidence.as_deref().unwrap_or_default()
+                ),
+            ));
+        }
+        if let Err(e) = compile_rule_regex(&pattern) {
+            return Ok(RuleExplanation::new(
+                rule_name,
+                explain::OUTCOME_INVALID_PATTERN,
+                e,
+            ));
+        }
+
+        self.prepare(line);
+        if self.pruned_rules.contains(rule_name) {
+            let reason = match &self.language {
+                Some(language) if self.rules_outside_language().contains(&pattern.name) => {
+                    format!("rule only applies to {:?}, not {}", pattern.languages, language)
+                }
+                _ => format!(
+                    "none of the modules {:?} it requires are imported",
+                    pattern.requires_imports
+                ),
+            };
+            return Ok(RuleExplanation::new(
+                rule_name,
+                explain::OUTCOME_PRUNED,
+                reason,
+            ));
+        }
+
+        // Multiline and string-scoped rules run over the whole text, as in
+        // `match_content`; their own passes already apply the negative pattern
+        let (matched_text, line_number, column) =
+            if pattern.multiline || pattern.scope == SCOPE_STRING {
+                let hits = if pattern.multiline {
+                    self.match_multiline(line)
+                } else {
+                    self.match_string_literals(line)
+                };
+                let Some(hit) = hits.into_iter().find(|m| m.pattern_name == rule_name) else {
+                    let target = if pattern.multiline {
+                        "the text"
+                    } else {
+                        "any string literal"
+                    };
+                    let negated = match &pattern.negative_pattern {
+                        Some(negative) => {
+                            format!(" (or negative pattern {} also matches)", negative)
+                        }
+                        None => String::new(),
+                    };
+                    return Ok(RuleExplanation::new(
+                        rule_name,
+                        explain::OUTCOME_NO_MATCH,
+                        format!(
+                            "pattern {} does not match {}{}",
+                            pattern.pattern, target, negated
+                        ),
+                    ));
+                };
+                (hit.matched_text, hit.line_number, hit.column)
+            } else {
+                let normalized = NormalizedLine::new(line, &self.normalization);
+                if let Some(prefilter) = self.prefilter() {
+                    if !prefilter.candidates(&normalized.text)[index] {
+                        return Ok(RuleExplanation::new(
+                            rule_name,
+                            explain::OUTCOME_PREFILTER_REJECTED,
+                            format!(
+                                "none of the literals {:?} occur in the line",
+                                prefilter.literals(index).unwrap_or_default()
+                            ),
+                        ));
+                    }
+                }
+                let found = self
+                    .rule_regex(&pattern)
+                    .and_then(|regex| regex.find(&normalized.text));
+                let Some((start, end)) = found else {
+                    return Ok(RuleExplanation::new(
+                        rule_name,
+                        explain::OUTCOME_NO_MATCH,
+                        format!("pattern {} does not match the line", pattern.pattern),
+                    ));
+                };
+                let (byte_start, _) = normalized.original_range(start, end);
+                let column = context::char_column(line, byte_start);
+                let matched_text = normalized.original_slice(start, end);
+
+                if self.is_negated(&pattern, &normalized.text) {
+                    return Ok(RuleExplanation::new(
+                        rule_name,
+                        explain::OUTCOME_NEGATED,
+                        format!(
+                            "negative pattern {} also matches the line",
+                            pattern.negative_pattern.as_deref().unwrap_or_default()
+                        ),
+                    )
+                    .with_match(matched_text, column));
+                }
+                (matched_text.to_string(), 1, column)
+            };
+
+        let hit_line = line.lines().nth(line_number - 1).unwrap_or(line);
+        let mut suppression = Suppression::parse(hit_line);
+        if suppression.is_none() && self.foreign_suppressions {
+            suppression = Suppression::parse_foreign(hit_line)
+                .filter(|(_, next_line)| !next_line)
+                .map(|(foreign, _)| foreign);
+        }
+        if let Some(suppression) = suppression.filter(|s| s.applies_to(rule_name)) {
+            if !suppression.is_expired(suppress::today()) {
+                return Ok(RuleExplanation::new(
+                    rule_name,
+                    explain::OUTCOME_SUPPRESSED,
+                    "suppressed by a comment on the line",
+                )
+                .with_match(&matched_text, column));
+            }
+        }
+
+        if let Some(entry) = self.allowlist_entry(&matched_text) {
+            return Ok(RuleExplanation::new(
+                rule_name,
+                explain::OUTCOME_ALLOWLISTED,
+                format!("matched text is covered by allowlist entry {}", entry),
+            )
+            .with_match(&matched_text, column));
+        }
+
+        if let Some(min) = min_severity.filter(|min| pattern.severity < *min) {
+            return Ok(RuleExplanation::new(
+                rule_name,
+                explain::OUTCOME_BELOW_SEVERITY,
+                format!("severity {} is below {}", pattern.severity, min),
+            )
+            .with_match(&matched_text, column));
+        }
+
+        Ok(RuleExplanation::new(
+            rule_name,
+            explain::OUTCOME_MATCHED,
+            format!("{} finding reported", pattern.severity),
+        )
+        .with_match(&matched_text, column))
+    }
+
+    /// Get pattern statistics
+    pub fn pattern_count(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Compile every rule and build the prefilters now rather than on the
+    /// first match
+    pub fn warm_up(&mut self) {
+        let patterns = Arc::clone(&self.patterns);
+        for pattern in patterns.iter() {
+            self.rule_regex(pattern);
+        }
+        let mut sources: Vec<String> = self
+            .construct_rules
+            .iter()
+            .map(|r| r.pattern.clone())
+            .collect();
+        sources.extend(
+            self.config_rules
+                .iter()
+                .filter_map(|r| r.value_pattern.clone()),
+        );
+        sources.extend(
+            self.composite_rules
+                .iter()
+                .flat_map(|r| r.sources().cloned()),
+        );
+        for rule in &self.proximity_rules {
+            sources.extend([rule.anchor.clone(), rule.near.clone()]);
+        }
+        sources.extend(self.allowlist.iter().cloned());
+        for rule in &self.file_rules {
+            sources.extend(
+                [&rule.contains, &rule.absent, &rule.header]
+                    .into_iter()
+                    .flatten()
+                    .cloned(),
+            );
+        }
+        for source in &sources {
+            self.get_or_compile_regex(source);
+        }
+        self.prefilter();
+        self.pattern_set();
+    }
 }
 
-#[pymethods]
-impl SecurityPattern {
-    #[new]
-    pub fn new(
-        name: String,
-        pattern: String,
-        severity: String,
-        category: String,
-        description: String,
-    ) -> Self {
-        SecurityPattern {
-            name,
-            pattern,
-            severity,
-            category,
-            description,
+impl PatternMatcher {
+    pub fn has_construct_rules(&self) -> bool {
+        !self.construct_rules.is_empty()
+    }
+
+    /// Drop suppressed matches, tagging those whose suppression expired
+    fn apply_suppression(
+        matches: &mut Vec<Match>,
+        suppression: &Suppression,
+        today: (i32, u32, u32),
+    ) {
+        matches.retain_mut(|m| {
+            if !suppression.applies_to(&m.pattern_name) {
+                m.explanation.suppressions.push(format!(
+                    "knox:ignore on this line names only {}",
+                    suppression.rules.join(", ")
+                ));
+                return true;
+            }
+            if let Some(until) = &suppression.malformed_until {
+                m.tags.push(TAG_SUPPRESSION_EXPIRED.to_string());
+                m.explanation.suppressions.push(format!(
+                    "knox:ignore on this line has an invalid until={} and counts as expired",
+                    until
+                ));
+                return true;
+            }
+            if let Some((year, month, day)) =
+                suppression.until.filter(|_| suppression.is_expired(today))
+            {
+                m.tags.push(TAG_SUPPRESSION_EXPIRED.to_string());
+                m.explanation.suppressions.push(format!(
+                    "knox:ignore on this line expired {:04}-{:02}-{:02}",
+                    year, month, day
+                ));
+                return true;
+            }
+            false
+        });
+    }
+
+    fn disable_slow_rule(&mut self, name: &str, elapsed: Duration) {
+        self.disabled_rules.insert(name.to_string());
+        self.diagnostics.push(RuleDiagnostic::error(
+            name,
+            format!(
+                "disabled after taking {}ms on one line (budget {}ms)",
+                elapsed.as_millis(),
+                self.rule_time_budget.as_millis()
+            ),
+        ));
+    }
+
+    /// Matches of multiline rules over the whole content
+    fn match_multiline(&mut self, content: &str) -> Vec<Match> {
+        let patterns = Arc::clone(&self.patterns);
+        if !patterns
+            .iter()
+            .any(|p| p.multiline && p.scope != SCOPE_STRING)
+        {
+            return Vec::new();
+        }
+
+        let index = LineIndex::new(content);
+        let mut matches = Vec::new();
+        for pattern in patterns
+            .iter()
+            .filter(|p| p.multiline && p.scope != SCOPE_STRING)
+        {
+            if self.disabled_rules.contains(&pattern.name)
+                || self.pruned_rules.contains(&pattern.name)
+                || self.below_confidence(&pattern.confidence)
+                || !self.category_enabled(&pattern.category)
+            {
+                continue;
+            }
+            let timed = self.user_rules.contains(&pattern.name);
+            let started = Instant::now();
+
+            let found = match self.rule_regex(pattern) {
+                Some(regex) => regex.find_iter(content),
+                None => continue,
+            };
+
+            if timed && started.elapsed() > self.rule_time_budget {
+                self.disable_slow_rule(&pattern.name, started.elapsed());
+            }
+
+            let before = matches.len();
+            for (start, end) in found {
+                if self.is_negated(pattern, &content[start..end]) {
+                    continue;
+                }
+                let (line_number, column) = index.line_col(start);
+                matches.push(Match {
+                    line_number,
+                    column,
+                    end_column: index.line_col(end).1,
+                    start_byte: Some(start),
+                    end_byte: Some(end),
+                    pattern_name: pattern.name.clone(),
+                    severity: pattern.severity,
+                    confidence: pattern.confidence.clone(),
+                    matched_text: content[start..end].to_string(),
+                    category: pattern.category.clone(),
+                    file_path: None,
+                    origin: None,
+                    kind: KIND_LINE.to_string(),
+                    tags: Vec::new(),
+                    corroborating_rules: Vec::new(),
+                    path_class: None,
+                    context_before: Vec::new(),
+                    context_after: Vec::new(),
+                    rule_source: pattern.source.clone(),
+                    cwe: pattern.cwe.clone(),
+                    owasp: pattern.owasp.clone(),
+                    references: pattern.references.clone(),
+                    duplicate_lines: Vec::new(),
+                    verified: None,
+                    explanation: FindingExplanation::new(
+                        &pattern.description,
+                        pattern.effective_pattern(),
+                    ),
+                });
+            }
+            self.record_evaluation(&pattern.name, started);
+            self.record_matches(&pattern.name, matches.len() - before);
+        }
+        matches
+    }
+
+    /// Every loaded rule and the disabled rule and category names, for
+    /// `export_rules`
+    pub fn rule_set(&self) -> RuleSet {
+        let mut disabled_rules: Vec<String> = self.disabled_rules.iter().cloned().collect();
+        disabled_rules.sort();
+        let mut disabled_categories: Vec<String> =
+            self.disabled_categories.iter().cloned().collect();
+        disabled_categories.sort();
+        RuleSet {
+            format_version: RULESET_FORMAT_VERSION,
+            patterns: self.patterns.to_vec(),
+            file_rules: self.file_rules.clone(),
+            construct_rules: self.construct_rules.clone(),
+            config_rules: self.config_rules.clone(),
+            composite_rules: self.composite_rules.clone(),
+            proximity_rules: self.proximity_rules.clone(),
+            disabled_rules,
+            disabled_categories,
+        }
+    }
+
+    /// Name and category of every loaded rule, whatever its family
+    fn rules(&self) -> impl Iterator<Item = (&str, &str)> {
+        let patterns = self
+            .patterns
+            .iter()
+            .map(|p| (p.name.as_str(), p.category.as_str()));
+        let file = self
+            .file_rules
+            .iter()
+            .map(|r| (r.name.as_str(), r.category.as_str()));
+        let construct = self
+            .construct_rules
+            .iter()
+            .map(|r| (r.name.as_str(), r.category.as_str()));
+        let config = self
+            .config_rules
+            .iter()
+            .map(|r| (r.name.as_str(), r.category.as_str()));
+        let composite = self
+            .composite_rules
+            .iter()
+            .map(|r| (r.name.as_str(), r.category.as_str()));
+        let proximity = self
+            .proximity_rules
+            .iter()
+            .map(|r| (r.name.as_str(), r.category.as_str()));
+        patterns
+            .chain(file)
+            .chain(construct)
+            .chain(config)
+            .chain(composite)
+            .chain(proximity)
+    }
+
+    /// Name and severity of every loaded rule except line patterns, which
+    /// are shared copy-on-write and updated separately
+    fn rule_severities_mut(&mut self) -> impl Iterator<Item = (&str, &mut Severity)> {
+        let file = self
+            .file_rules
+            .iter_mut()
+            .map(|r| (r.name.as_str(), &mut r.severity));
+        let construct = self
+            .construct_rules
+            .iter_mut()
+            .map(|r| (r.name.as_str(), &mut r.severity));
+        let config = self
+            .config_rules
+            .iter_mut()
+            .map(|r| (r.name.as_str(), &mut r.severity));
+        let composite = self
+            .composite_rules
+            .iter_mut()
+            .map(|r| (r.name.as_str(), &mut r.severity));
+        let proximity = self
+            .proximity_rules
+            .iter_mut()
+            .map(|r| (r.name.as_str(), &mut r.severity));
+        file.chain(construct)
+            .chain(config)
+            .chain(composite)
+            .chain(proximity)
+    }
+
+    fn rule_count(&self) -> usize {
+        self.rules().count()
+    }
+
+    fn override_severities(&mut self, overrides: &HashMap<String, String>) -> Result<(), String> {
+        let mut parsed = HashMap::new();
+        for (name, severity) in overrides {
+            let severity: Severity = severity
+                .parse()
+                .map_err(|e| format!("Invalid severity for {}: {}", name, e))?;
+            parsed.insert(name.as_str(), severity);
+            if !self.rules().any(|(rule, _)| rule == name) {
+                return Err(format!("No rule named '{}' is loaded", name));
+            }
+        }
+
+        let severity_of = |name: &str| parsed.get(name).copied();
+        if self
+            .patterns
+            .iter()
+            .any(|p| overrides.contains_key(&p.name))
+        {
+            // Copy-on-write, as in `add_pattern`
+            for pattern in Arc::make_mut(&mut self.patterns) {
+                if let Some(severity) = severity_of(&pattern.name) {
+                    pattern.severity = severity;
+                }
+            }
+        }
+        for (name, severity) in self.rule_severities_mut() {
+            if let Some(overridden) = severity_of(name) {
+                *severity = overridden;
+            }
+        }
+        Ok(())
+    }
+
+    /// Append high-entropy literals on `line` not already covered by a
+    /// secrets finding, so the generic rule never outranks a specific one
+    fn match_entropy(&self, line: &str, line_number: usize, matches: &mut Vec<Match>) {
+        let Some(options) = &self.entropy else {
+            return;
+        };
+        if self.disabled_rules.contains(entropy::RULE_NAME)
+            || self.below_confidence(entropy::CONFIDENCE)
+            || !self.category_enabled(entropy::CATEGORY)
+        {
+            return;
+        }
+        for hit in entropy::find_high_entropy(line, options) {
+            let start = context::char_column(line, hit.start);
+            let end = context::char_column(line, hit.end);
+            let covered = matches.iter().any(|m| {
+                m.category == entropy::CATEGORY
+                    && m.column < end
+                    && start < m.column + m.matched_text.chars().count()
+            });
+            if covered {
+                continue;
+            }
+            matches.push(Match {
+                line_number,
+                column: start,
+                end_column: end,
+                start_byte: Some(hit.start),
+                end_byte: Some(hit.end),
+                pattern_name: entropy::RULE_NAME.to_string(),
+                severity: options.severity,
+                confidence: entropy::CONFIDENCE.to_string(),
+                matched_text: line[hit.start..hit.end].to_string(),
+                category: entropy::CATEGORY.to_string(),
+                file_path: None,
+                origin: None,
+                kind: KIND_LINE.to_string(),
+                tags: Vec::new(),
+                corroborating_rules: Vec::new(),
+                path_class: None,
+                context_before: Vec::new(),
+                context_after: Vec::new(),
+                rule_source: None,
+                cwe: Some("CWE-798".to_string()),
+                owasp: Some("A07:2021".to_string()),
+                references: Vec::new(),
+                duplicate_lines: Vec::new(),
+                verified: None,
+                explanation: FindingExplanation::new(
+                    entropy::DESCRIPTION,
+                    format!(
+                        "string literal with {:.2} bits of entropy per character",
+                        hit.entropy
+                    ),
+                ),
+            });
+        }
+    }
+
+    /// Drop findings whose text the allowlist covers, and network
+    /// findings on allowed hosts
+    fn apply_allowlist(&mut self, matches: &mut Vec<Match>) {
+        network::apply_allowed_hosts(matches, &self.allowed_hosts);
+        if self.allowlist.is_empty() {
+            return;
+        }
+        matches.retain(|m| self.allowlist_entry(&m.matched_text).is_none());
+    }
+
+    /// The allowlist regex covering `text`, if any
+    fn allowlist_entry(&mut self, text: &str) -> Option<String> {
+        self.allowlist
+            .clone()
+            .into_iter()
+            .find(|pattern| {
+                self.get_or_compile_regex(pattern)
+                    .is_some_and(|regex| regex.is_match(text))
+            })
+    }
+
+    fn rule_enabled(&self, name: &str, category: &str) -> bool {
+        !self.disabled_rules.contains(name) && self.category_enabled(category)
+    }
+
+    /// Findings for the (line, byte offset, text) hits of a content-wide
+    /// rule identified by (name, severity, category), honoring
+    /// `knox:ignore` on the hit's line
+    fn hit_matches(
+        content: &str,
+        hits: Vec<(usize, usize, &str)>,
+        (name, severity, category): (&str, Severity, &str),
+        kind: &str,
+        explanation: &FindingExplanation,
+    ) -> Vec<Match> {
+        let today = suppress::today();
+        let lines: Vec<&str> = content.lines().collect();
+        let index = LineIndex::new(content);
+        let mut matches = Vec::new();
+        for (line_number, start, text) in hits {
+            let line = lines.get(line_number - 1).copied().unwrap_or("");
+            let line_start = index.lines_span(line_number, line_number).0;
+            let mut found = vec![Match {
+                line_number,
+                column: context::char_column(line, start),
+                end_column: context::char_column(line, start + text.len()),
+                start_byte: Some(line_start + start),
+                end_byte: Some(line_start + start + text.len()),
+                pattern_name: name.to_string(),
+                severity,
+                confidence: DEFAULT_CONFIDENCE.to_string(),
+                matched_text: text.to_string(),
+                category: category.to_string(),
+                file_path: None,
+                origin: None,
+                kind: kind.to_string(),
+                tags: Vec::new(),
+                corroborating_rules: Vec::new(),
+                path_class: None,
+                context_before: Vec::new(),
+                context_after: Vec::new(),
+                rule_source: None,
+                cwe: None,
+                owasp: None,
+                references: Vec::new(),
+                duplicate_lines: Vec::new(),
+                verified: None,
+                explanation: explanation.clone(),
+            }];
+            if let Some(suppression) = Suppression::parse(line) {
+                Self::apply_suppression(&mut found, &suppression, today);
+            }
+            matches.extend(found);
+        }
+        matches
+    }
+
+    fn category_enabled(&self, category: &str) -> bool {
+        !self.disabled_categories.contains(category)
+            && self
+                .enabled_categories
+                .as_ref()
+                .is_none_or(|categories| categories.contains(category))
+    }
+
+    /// Whether rules of `confidence` fall below the minimum confidence
+    fn below_confidence(&self, confidence: &str) -> bool {
+        self.min_confidence
+            .as_deref()
+            .is_some_and(|min| confidence_rank(confidence) < confidence_rank(min))
+    }
+
+    /// Refresh import and language pruning and the line cache's rule set
+    /// for `content`
+    fn prepare(&mut self, content: &str) {
+        self.pruned_rules = self.rules_pruned_by_imports(content);
+        self.pruned_rules.extend(self.rules_outside_language());
+
+        if self.line_cache.is_some() {
+            let rule_set = linecache::rule_set_hash(
+                &self.patterns,
+                self.disabled_rules.union(&self.pruned_rules),
+                &self.normalization,
+                self.entropy.as_ref(),
+                self.min_confidence.as_deref(),
+                self.enabled_categories.as_ref(),
+                &self.disabled_categories,
+            );
+            if let Some(cache) = self.line_cache.as_mut() {
+                cache.sync_rule_set(rule_set);
+            }
+        }
+    }
+
+    /// Matches of string-scoped rules inside the literals of `content`
+    ///
+    /// Each rule runs against every literal value on its own, so anchors
+    /// apply to the literal rather than the line.
+    fn match_string_literals(&mut self, content: &str) -> Vec<Match> {
+        let patterns = Arc::clone(&self.patterns);
+        if !patterns.iter().any(|p| p.scope == SCOPE_STRING) {
+            return Vec::new();
+        }
+        let literals = CodeParser::new(self.language.clone()).extract_strings(content);
+        if literals.is_empty() {
+            return Vec::new();
+        }
+
+        let index = LineIndex::new(content);
+        let mut matches = Vec::new();
+        for pattern in patterns.iter().filter(|p| p.scope == SCOPE_STRING) {
+            if self.disabled_rules.contains(&pattern.name)
+                || self.pruned_rules.contains(&pattern.name)
+                || self.below_confidence(&pattern.confidence)
+                || !self.category_enabled(&pattern.category)
+            {
+                continue;
+            }
+            for literal in &literals {
+                let started = Instant::now();
+                let found = match self.rule_regex(pattern) {
+                    Some(regex) => regex.captures(&literal.value),
+                    None => break,
+                };
+                let found = found.filter(|_| !self.is_negated(pattern, &literal.value));
+                self.record_evaluation(&pattern.name, started);
+                let Some((start, end, captures)) = found else {
+                    continue;
+                };
+                self.record_matches(&pattern.name, 1);
+                let (start_byte, end_byte) = (literal.start_byte + start, literal.start_byte + end);
+                let (line_number, column) = index.line_col(start_byte);
+                matches.push(Match {
+                    line_number,
+                    column,
+                    end_column: index.line_col(end_byte).1,
+                    start_byte: Some(start_byte),
+                    end_byte: Some(end_byte),
+                    pattern_name: pattern.name.clone(),
+                    severity: pattern.severity,
+                    confidence: pattern.confidence.clone(),
+                    matched_text: literal.value[start..end].to_string(),
+                    category: pattern.category.clone(),
+                    file_path: None,
+                    origin: None,
+                    kind: KIND_LINE.to_string(),
+                    tags: Vec::new(),
+                    corroborating_rules: Vec::new(),
+                    path_class: None,
+                    context_before: Vec::new(),
+                    context_after: Vec::new(),
+                    rule_source: pattern.source.clone(),
+                    cwe: pattern.cwe.clone(),
+                    owasp: pattern.owasp.clone(),
+                    references: pattern.references.clone(),
+                    duplicate_lines: Vec::new(),
+                    verified: None,
+                    explanation: FindingExplanation {
+                        captures,
+                        ..FindingExplanation::new(
+                            &pattern.description,
+                            format!("`{}` inside a string literal", pattern.effective_pattern()),
+                        )
+                    },
+                });
+            }
+        }
+        matches
+    }
+
+    /// Line and multiline matches in `content` with suppressions applied,
+    /// numbering its first line `first_line`
+    fn match_lines(&mut self, content: &str, first_line: usize) -> Vec<Match> {
+        let mut all_matches = Vec::new();
+        let today = suppress::today();
+        let mut previous: Option<Suppression> = None;
+        // Suppression in effect on each line, for multiline findings
+        let mut line_suppressions: Vec<Option<Suppression>> = Vec::new();
+
+        let mut line_start = 0;
+        for (i, line) in content.split_terminator('\n').enumerate() {
+            let mut line_matches = self.match_line_cached(line, first_line + i);
+            for m in &mut line_matches {
+                m.start_byte = m.start_byte.map(|b| b + line_start);
+                m.end_byte = m.end_byte.map(|b| b + line_start);
+            }
+            line_start += line.len() + 1;
+            let mut suppression = Suppression::parse(line);
+            let mut next = suppression
+                .clone()
+                .filter(|_| suppress::is_comment_only(line));
+            if suppression.is_none() && self.foreign_suppressions {
+                match Suppression::parse_foreign(line) {
+                    Some((foreign, true)) => next = Some(foreign),
+                    Some((foreign, false)) => suppression = Some(foreign),
+                    None => {}
+                }
+            }
+            let active = suppression.or_else(|| previous.take());
+            if let Some(active) = &active {
+                Self::apply_suppression(&mut line_matches, active, today);
+            }
+            line_suppressions.push(active);
+            previous = next;
+            all_matches.extend(line_matches);
+        }
+
+        let mut content_matches = self.match_multiline(content);
+        content_matches.extend(self.match_string_literals(content));
+        for mut m in content_matches {
+            let active = line_suppressions
+                .get(m.line_number - 1)
+                .and_then(Option::as_ref);
+            m.line_number += first_line - 1;
+            let mut single = vec![m];
+            if let Some(active) = active {
+                Self::apply_suppression(&mut single, active, today);
+            }
+            all_matches.extend(single);
         }
+        // Collapsing groups findings in line order, so slot the multiline and
+        // string-scope matches in among the line matches
+        all_matches.sort_by_key(|m| (m.line_number, m.column));
+
+        if self.skip_comments {
+            comments::drop_in_comments(&mut all_matches, content, self.language.clone());
+        }
+        timing::drop_safe_comparisons(&mut all_matches, content, self.language.clone());
+        if self.collapse_corroborating {
+            all_matches = dedup::collapse_corroborating(all_matches);
+        }
+        if self.collapse_overlapping {
+            all_matches = dedup::collapse_overlapping(all_matches);
+        }
+        all_matches
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[pyclass]
-pub struct Match {
-    #[pyo3(get)]
-    pub line_number: usize,
-    #[pyo3(get)]
-    pub column: usize,
-    #[pyo3(get)]
-    pub pattern_name: String,
-    #[pyo3(get)]
-    pub severity: String,
-    #[pyo3(get)]
-    pub matched_text: String,
-    #[pyo3(get)]
-    pub category: String,
-}
+    fn record_evaluation(&mut self, rule: &str, started: Instant) {
+        if let Some(statistics) = &mut self.statistics {
+            statistics.record_evaluation(rule, started.elapsed());
+        }
+    }
 
-#[pymethods]
-impl Match {
-    fn __repr__(&self) -> String {
-        format!(
-            "Match(line={}, col={}, pattern={}, severity={})",
-            self.line_number, self.column, self.pattern_name, self.severity
-        )
+    fn record_matches(&mut self, rule: &str, count: usize) {
+        if let Some(statistics) = &mut self.statistics {
+            statistics.record_matches(rule, count);
+        }
     }
-}
 
-/// Fast pattern matcher using Aho-Corasick algorithm
-#[pyclass]
-pub struct PatternMatcher {
-    patterns: Vec<SecurityPattern>,
-    regex_cache: HashMap<String, Regex>,
-}
+    /// Whether the pattern's negative pattern matches `text`
+    fn is_negated(&mut self, pattern: &SecurityPattern, text: &str) -> bool {
+        pattern
+            .negative_pattern
+            .as_ref()
+            .and_then(|negative| self.get_or_compile_regex(negative))
+            .is_some_and(|regex| regex.is_match(text))
+    }
 
-#[pymethods]
-impl PatternMatcher {
-    #[new]
-    pub fn new() -> Self {
-        PatternMatcher {
-            patterns: Self::default_patterns(),
-            regex_cache: HashMap::new(),
+    /// Rules whose required modules `content` never imports
+    fn rules_pruned_by_imports(&self, content: &str) -> HashSet<String> {
+        if !self.import_pruning || self.patterns.iter().all(|p| p.requires_imports.is_empty()) {
+            return HashSet::new();
         }
+
+        let Some(imported) = imports::imported_modules(content, self.language.as_deref()) else {
+            return HashSet::new();
+        };
+        self.patterns
+            .iter()
+            .filter(|p| {
+                !p.requires_imports.is_empty()
+                    && !p
+                        .requires_imports
+                        .iter()
+                        .any(|module| imports::is_imported(module, &imported))
+            })
+            .map(|p| p.name.clone())
+            .collect()
     }
 
-    /// Add a custom security pattern
-    pub fn add_pattern(&mut self, pattern: SecurityPattern) {
-        self.patterns.push(pattern);
+    /// Rules scoped to languages other than the content's
+    fn rules_outside_language(&self) -> Vec<String> {
+        let Some(language) = &self.language else {
+            return Vec::new();
+        };
+        self.patterns
+            .iter()
+            .filter(|p| {
+                !p.languages.is_empty()
+                    && !p.languages.iter().any(|l| l.eq_ignore_ascii_case(language))
+            })
+            .map(|p| p.name.clone())
+            .collect()
     }
 
-    /// Match patterns in a single line of code
-    pub fn match_line(&mut self, line: &str, line_number: usize) -> Vec<Match> {
+    /// Line-rule matches on `line`, with the pruning set by the caller
+    fn evaluate_line(&mut self, line: &str, line_number: usize) -> Vec<Match> {
         let mut matches = Vec::new();
+        let normalized = NormalizedLine::new(line, &self.normalization);
 
-        // Clone patterns to avoid borrow checker issues
-        let patterns = self.patterns.clone();
+        // Share the pattern list to avoid borrow checker issues
+        let patterns = Arc::clone(&self.patterns);
+        let candidates = self.candidates(&normalized.text);
 
-        for pattern in &patterns {
-            if let Some(regex) = self.get_or_compile_regex(&pattern.pattern) {
-                if let Some(capture) = regex.find(line) {
-                    matches.push(Match {
-                        line_number,
-                        column: capture.start(),
-                        pattern_name: pattern.name.clone(),
-                        severity: pattern.severity.clone(),
-                        matched_text: capture.as_str().to_string(),
-                        category: pattern.category.clone(),
-                    });
-                }
+        for (i, pattern) in patterns.iter().enumerate() {
+            if pattern.multiline
+                || pattern.scope == SCOPE_STRING
+                || self.disabled_rules.contains(&pattern.name)
+                || self.pruned_rules.contains(&pattern.name)
+                || self.below_confidence(&pattern.confidence)
+                || !self.category_enabled(&pattern.category)
+                || candidates.as_ref().is_some_and(|c| !c[i])
+            {
+                continue;
+            }
+            let timed = self.user_rules.contains(&pattern.name);
+            let started = Instant::now();
+
+            let found = match self.rule_regex(pattern) {
+                Some(regex) => regex.captures(&normalized.text),
+                None => continue,
+            };
+
+            if timed && started.elapsed() > self.rule_time_budget {
+                self.disable_slow_rule(&pattern.name, started.elapsed());
+            }
+            let found = found.filter(|_| !self.is_negated(pattern, &normalized.text));
+            self.record_evaluation(&pattern.name, started);
+
+            if let Some((start, end, captures)) = found {
+                let (byte_start, byte_end) = normalized.original_range(start, end);
+                let column = context::char_column(line, byte_start);
+                let matched_text = normalized.original_slice(start, end).to_string();
+                let tags = match tokens::validate(&pattern.name, &matched_text) {
+                    Some(false) => continue,
+                    Some(true) => vec![tokens::TAG_VALIDATED.to_string()],
+                    None => Vec::new(),
+                };
+                self.record_matches(&pattern.name, 1);
+                matches.push(Match {
+                    line_number,
+                    column,
+                    end_column: context::char_column(line, byte_end),
+                    start_byte: Some(byte_start),
+                    end_byte: Some(byte_end),
+                    pattern_name: pattern.name.clone(),
+                    severity: pattern.severity,
+                    confidence: pattern.confidence.clone(),
+                    matched_text,
+                    category: pattern.category.clone(),
+                    file_path: None,
+                    origin: None,
+                    kind: KIND_LINE.to_string(),
+                    tags,
+                    corroborating_rules: Vec::new(),
+                    path_class: None,
+                    context_before: Vec::new(),
+                    context_after: Vec::new(),
+                    rule_source: pattern.source.clone(),
+                    cwe: pattern.cwe.clone(),
+                    owasp: pattern.owasp.clone(),
+                    references: pattern.references.clone(),
+                    duplicate_lines: Vec::new(),
+                    verified: None,
+                    explanation: FindingExplanation {
+                        captures,
+                        ..FindingExplanation::new(&pattern.description, pattern.effective_pattern())
+                    },
+                });
             }
         }
 
+        self.match_entropy(line, line_number, &mut matches);
         matches
     }
 
-    /// Match patterns across multiple lines efficiently
-    pub fn match_content(&mut self, content: &str) -> Vec<Match> {
-        let mut all_matches = Vec::new();
+    fn match_line_cached(&mut self, line: &str, line_number: usize) -> Vec<Match> {
+        if let Some(hit) = self
+            .line_cache
+            .as_mut()
+            .and_then(|cache| cache.get(line, line_number))
+        {
+            return hit;
+        }
 
-        for (line_num, line) in content.lines().enumerate() {
-            let line_matches = self.match_line(line, line_num + 1);
-            all_matches.extend(line_matches);
+        let matches = self.evaluate_line(line, line_number);
+        if let Some(cache) = self.line_cache.as_mut() {
+            cache.insert(line, &matches);
         }
+        matches
+    }
 
-        all_matches
+    /// Per pattern index, whether the regex has to run on `text`: ruled out
+    /// first by literals, then by the single-pass set if any set member is
+    /// left. None means every rule runs.
+    fn candidates(&mut self, text: &str) -> Option<Vec<bool>> {
+        let mut candidates = self.prefilter().map(|p| p.candidates(text));
+        if let Some(set) = self.pattern_set() {
+            if candidates.as_ref().is_none_or(|c| set.any_member(c)) {
+                let hits = set.candidates(text);
+                candidates = Some(match candidates {
+                    Some(c) => c.iter().zip(hits).map(|(&a, b)| a && b).collect(),
+                    None => hits,
+                });
+            }
+        }
+        candidates
     }
 
-    /// Get pattern statistics
-    pub fn pattern_count(&self) -> usize {
-        self.patterns.len()
+    fn prefilter(&mut self) -> Option<Arc<LiteralPrefilter>> {
+        self.prefilter
+            .get_or_insert_with(|| LiteralPrefilter::shared(&self.patterns))
+            .clone()
+    }
+
+    /// Single-pass set over all rules except custom ones, see `regexset`
+    fn pattern_set(&mut self) -> Option<Arc<PatternSet>> {
+        self.pattern_set
+            .get_or_insert_with(|| PatternSet::shared(&self.patterns, &self.user_rules))
+            .clone()
+    }
+
+    /// Compiled regex of a line pattern, on the engine the rule asks for
+    fn rule_regex(&mut self, pattern: &SecurityPattern) -> Option<RuleRegex<'_>> {
+        if !pattern.fancy {
+            return self
+                .get_or_compile_regex(&pattern.regex_source())
+                .map(RuleRegex::Fast);
+        }
+        let source = pattern.regex_source();
+        if !self.fancy_cache.contains_key(source.as_ref()) {
+            let regex = fancy::compile(&source).ok()?;
+            self.fancy_cache.insert(source.to_string(), regex);
+        }
+        self.fancy_cache.get(source.as_ref()).map(RuleRegex::Fancy)
     }
-}
 
-impl PatternMatcher {
     fn get_or_compile_regex(&mut self, pattern: &str) -> Option<&Regex> {
         if !self.regex_cache.contains_key(pattern) {
-            if let Ok(regex) = Regex::new(pattern) {
+            if let Ok(regex) = guard::compile_guarded(pattern) {
                 self.regex_cache.insert(pattern.to_string(), regex);
             } else {
                 return None;
@@ -147,81 +2567,282 @@ impl PatternMatcher {
     }
 
     /// Default security patterns for common vulnerabilities
-    fn default_patterns() -> Vec<SecurityPattern> {
-        vec![
+    pub(crate) fn default_patterns() -> Vec<SecurityPattern> {
+        let mut patterns = vec![
             SecurityPattern {
                 name: "hardcoded_api_key".to_string(),
-                pattern: r#"(?i)(api[_-]?key|apikey)\s*[:=]\s*["']([a-zA-Z0-9_\-]{20,})["']"#
-                    .to_string(),
-                severity: "critical".to_string(),
+                pattern: r#"(api[_-]?key|apikey)\s*[:=]\s*["']([a-zA-Z0-9_\-]{20,})["']"#.into(),
+                severity: Severity::Critical,
                 category: "secrets".to_string(),
                 description: "Hardcoded API key detected".to_string(),
+                confidence: "medium".to_string(),
+                source: None,
+                requires_imports: Vec::new(),
+                languages: Vec::new(),
+                multiline: false,
+                case_insensitive: true,
+                word_boundary: false,
+                fancy: false,
+                scope: SCOPE_ANY.to_string(),
+                negative_pattern: None,
+                positive_examples: vec![r#"api_key = "a1b2c3d4e5f6g7h8i9j0k1""#.to_string()],
+                negative_examples: vec![r#"api_key = os.environ["API_KEY"]"#.to_string()],
+                cwe: Some("CWE-798".to_string()),
+                owasp: Some("A07:2021".to_string()),
+                references: vec![
+                    "https://cwe.mitre.org/data/definitions/798.html".to_string(),
+                    "https://owasp.org/Top10/A07_2021-Identification_and_Authentication_Failures/"
+                        .to_string(),
+                ],
             },
             SecurityPattern {
                 name: "hardcoded_password".to_string(),
-                pattern: r#"(?i)(password|passwd|pwd)\s*[:=]\s*["']([^"']{8,})["']"#.to_string(),
-                severity: "critical".to_string(),
+                pattern: r#"(password|passwd|pwd)\s*[:=]\s*["']([^"']{8,})["']"#.into(),
+                severity: Severity::Critical,
                 category: "secrets".to_string(),
                 description: "Hardcoded password detected".to_string(),
+                confidence: "medium".to_string(),
+                source: None,
+                requires_imports: Vec::new(),
+                languages: Vec::new(),
+                multiline: false,
+                case_insensitive: true,
+                word_boundary: false,
+                fancy: false,
+                scope: SCOPE_ANY.to_string(),
+                negative_pattern: None,
+                positive_examples: vec![r#"password = "hunter2hunter2""#.to_string()],
+                negative_examples: vec!["password = get_password()".to_string()],
+                cwe: Some("CWE-798".to_string()),
+                owasp: Some("A07:2021".to_string()),
+                references: vec![
+                    "https://cwe.mitre.org/data/definitions/798.html".to_string(),
+                    "https://owasp.org/Top10/A07_2021-Identification_and_Authentication_Failures/"
+                        .to_string(),
+                ],
             },
             SecurityPattern {
                 name: "sql_injection".to_string(),
-                pattern: r#"(?i)(execute|query)\s*\(\s*["'].*\+.*["']"#.to_string(),
-                severity: "high".to_string(),
+                pattern: r#"(execute|query)\s*\(\s*["'][^"']*["']\s*\+"#.into(),
+                severity: Severity::High,
                 category: "injection".to_string(),
                 description: "Potential SQL injection vulnerability".to_string(),
+                confidence: "medium".to_string(),
+                source: None,
+                requires_imports: Vec::new(),
+                languages: Vec::new(),
+                multiline: false,
+                case_insensitive: true,
+                word_boundary: false,
+                fancy: false,
+                scope: SCOPE_ANY.to_string(),
+                negative_pattern: None,
+                positive_examples: vec![
+                    r#"cursor.execute("SELECT * FROM users WHERE id = " + user_id)"#.to_string(),
+                ],
+                negative_examples: vec![
+                    r#"cursor.execute("SELECT * FROM users WHERE id = %s", (user_id,))"#
+                        .to_string(),
+                ],
+                cwe: Some("CWE-89".to_string()),
+                owasp: Some("A03:2021".to_string()),
+                references: vec![
+                    "https://cwe.mitre.org/data/definitions/89.html".to_string(),
+                    "https://owasp.org/Top10/A03_2021-Injection/".to_string(),
+                ],
             },
             SecurityPattern {
                 name: "command_injection".to_string(),
-                pattern: r#"(?i)(os\.system|subprocess\.call|exec)\s*\("#.to_string(),
-                severity: "high".to_string(),
+                pattern: r#"(os\.system|subprocess\.call|exec)\s*\("#.into(),
+                severity: Severity::High,
                 category: "injection".to_string(),
                 description: "Potential command injection risk".to_string(),
+                confidence: "medium".to_string(),
+                source: None,
+                requires_imports: Vec::new(),
+                languages: vec!["python".to_string()],
+                multiline: false,
+                case_insensitive: true,
+                word_boundary: false,
+                fancy: false,
+                scope: SCOPE_ANY.to_string(),
+                negative_pattern: None,
+                positive_examples: vec!["os.system(cmd)".to_string()],
+                negative_examples: vec![r#"subprocess.run(["ls", path])"#.to_string()],
+                cwe: Some("CWE-78".to_string()),
+                owasp: Some("A03:2021".to_string()),
+                references: vec![
+                    "https://cwe.mitre.org/data/definitions/78.html".to_string(),
+                    "https://owasp.org/Top10/A03_2021-Injection/".to_string(),
+                ],
             },
             SecurityPattern {
                 name: "weak_crypto_md5".to_string(),
-                pattern: r#"(?i)(md5|hashlib\.md5)\s*\("#.to_string(),
-                severity: "medium".to_string(),
+                pattern: r#"(md5|hashlib\.md5)\s*\("#.into(),
+                severity: Severity::Medium,
                 category: "crypto".to_string(),
                 description: "Weak cryptographic algorithm MD5".to_string(),
+                confidence: "high".to_string(),
+                source: None,
+                requires_imports: Vec::new(),
+                languages: Vec::new(),
+                multiline: false,
+                case_insensitive: true,
+                word_boundary: false,
+                fancy: false,
+                scope: SCOPE_ANY.to_string(),
+                negative_pattern: None,
+                positive_examples: vec!["digest = hashlib.md5(data)".to_string()],
+                negative_examples: vec!["digest = hashlib.sha256(data)".to_string()],
+                cwe: Some("CWE-328".to_string()),
+                owasp: Some("A02:2021".to_string()),
+                references: vec![
+                    "https://cwe.mitre.org/data/definitions/328.html".to_string(),
+                    "https://owasp.org/Top10/A02_2021-Cryptographic_Failures/".to_string(),
+                ],
             },
             SecurityPattern {
                 name: "weak_crypto_sha1".to_string(),
-                pattern: r#"(?i)(sha1|hashlib\.sha1)\s*\("#.to_string(),
-                severity: "medium".to_string(),
+                pattern: r#"(sha1|hashlib\.sha1)\s*\("#.into(),
+                severity: Severity::Medium,
                 category: "crypto".to_string(),
                 description: "Weak cryptographic algorithm SHA1".to_string(),
+                confidence: "high".to_string(),
+                source: None,
+                requires_imports: Vec::new(),
+                languages: Vec::new(),
+                multiline: false,
+                case_insensitive: true,
+                word_boundary: false,
+                fancy: false,
+                scope: SCOPE_ANY.to_string(),
+                negative_pattern: None,
+                positive_examples: vec!["digest = hashlib.sha1(data)".to_string()],
+                negative_examples: vec!["digest = hashlib.sha512(data)".to_string()],
+                cwe: Some("CWE-328".to_string()),
+                owasp: Some("A02:2021".to_string()),
+                references: vec![
+                    "https://cwe.mitre.org/data/definitions/328.html".to_string(),
+                    "https://owasp.org/Top10/A02_2021-Cryptographic_Failures/".to_string(),
+                ],
             },
             SecurityPattern {
                 name: "insecure_deserialization".to_string(),
-                pattern: r#"(?i)(pickle\.loads?|yaml\.load)\s*\("#.to_string(),
-                severity: "high".to_string(),
+                pattern: r#"(pickle\.loads?|yaml\.load)\s*\("#.into(),
+                severity: Severity::High,
                 category: "deserialization".to_string(),
                 description: "Insecure deserialization detected".to_string(),
+                confidence: "high".to_string(),
+                source: None,
+                requires_imports: vec![
+                    "pickle".to_string(),
+                    "cPickle".to_string(),
+                    "yaml".to_string(),
+                ],
+                languages: vec!["python".to_string()],
+                multiline: false,
+                case_insensitive: true,
+                word_boundary: false,
+                fancy: false,
+                scope: SCOPE_ANY.to_string(),
+                negative_pattern: None,
+                positive_examples: vec!["obj = pickle.loads(data)".to_string()],
+                negative_examples: vec!["obj = yaml.safe_load(data)".to_string()],
+                cwe: Some("CWE-502".to_string()),
+                owasp: Some("A08:2021".to_string()),
+                references: vec![
+                    "https://cwe.mitre.org/data/definitions/502.html".to_string(),
+                    "https://owasp.org/Top10/A08_2021-Software_and_Data_Integrity_Failures/"
+                        .to_string(),
+                ],
             },
             SecurityPattern {
                 name: "xss_vulnerability".to_string(),
-                pattern: r#"(?i)(innerHTML|dangerouslySetInnerHTML|document\.write)\s*="#
-                    .to_string(),
-                severity: "high".to_string(),
+                pattern: r#"(innerHTML|dangerouslySetInnerHTML|document\.write)\s*="#.into(),
+                severity: Severity::High,
                 category: "xss".to_string(),
                 description: "Potential XSS vulnerability".to_string(),
+                confidence: "medium".to_string(),
+                source: None,
+                requires_imports: Vec::new(),
+                languages: vec![
+                    "javascript".to_string(),
+                    "typescript".to_string(),
+                    "html".to_string(),
+                ],
+                multiline: false,
+                case_insensitive: true,
+                word_boundary: false,
+                fancy: false,
+                scope: SCOPE_ANY.to_string(),
+                negative_pattern: None,
+                positive_examples: vec!["el.innerHTML = userInput".to_string()],
+                negative_examples: vec!["el.textContent = userInput".to_string()],
+                cwe: Some("CWE-79".to_string()),
+                owasp: Some("A03:2021".to_string()),
+                references: vec![
+                    "https://cwe.mitre.org/data/definitions/79.html".to_string(),
+                    "https://owasp.org/Top10/A03_2021-Injection/".to_string(),
+                ],
             },
             SecurityPattern {
                 name: "debug_mode".to_string(),
-                pattern: r#"(?i)(DEBUG|debug)\s*=\s*(True|true|1)"#.to_string(),
-                severity: "medium".to_string(),
+                pattern: r#"(DEBUG|debug)\s*=\s*(True|true|1)"#.into(),
+                severity: Severity::Medium,
                 category: "config".to_string(),
                 description: "Debug mode enabled".to_string(),
+                confidence: entropy::CONFIDENCE.to_string(),
+                source: None,
+                requires_imports: Vec::new(),
+                languages: Vec::new(),
+                multiline: false,
+                case_insensitive: true,
+                word_boundary: false,
+                fancy: false,
+                scope: SCOPE_ANY.to_string(),
+                negative_pattern: None,
+                positive_examples: vec!["DEBUG = True".to_string()],
+                negative_examples: vec!["DEBUG = False".to_string()],
+                cwe: Some("CWE-489".to_string()),
+                owasp: Some("A05:2021".to_string()),
+                references: vec![
+                    "https://cwe.mitre.org/data/definitions/489.html".to_string(),
+                    "https://owasp.org/Top10/A05_2021-Security_Misconfiguration/".to_string(),
+                ],
             },
             SecurityPattern {
                 name: "ssl_verification_disabled".to_string(),
-                pattern: r#"(?i)verify\s*=\s*(False|false|0)"#.to_string(),
-                severity: "high".to_string(),
+                pattern: r#"verify\s*=\s*(False|false|0)"#.into(),
+                severity: Severity::High,
                 category: "crypto".to_string(),
                 description: "SSL certificate verification disabled".to_string(),
+                confidence: "medium".to_string(),
+                source: None,
+                requires_imports: Vec::new(),
+                languages: Vec::new(),
+                multiline: false,
+                case_insensitive: true,
+                word_boundary: false,
+                fancy: false,
+                scope: SCOPE_ANY.to_string(),
+                negative_pattern: None,
+                positive_examples: vec!["requests.get(url, verify=False)".to_string()],
+                negative_examples: vec!["requests.get(url, verify=True)".to_string()],
+                cwe: Some("CWE-295".to_string()),
+                owasp: Some("A07:2021".to_string()),
+                references: vec![
+                    "https://cwe.mitre.org/data/definitions/295.html".to_string(),
+                    "https://owasp.org/Top10/A07_2021-Identification_and_Authentication_Failures/"
+                        .to_string(),
+                ],
             },
-        ]
+        ];
+        patterns.extend(tokens::token_patterns());
+        patterns.extend(web_rules::web_patterns());
+        patterns.extend(network::network_patterns());
+        patterns.extend(timing::timing_patterns());
+        patterns.extend(randomness::random_patterns());
+        patterns
     }
 }
 
@@ -261,4 +2882,751 @@ mod tests {
         assert!(!matches.is_empty());
         assert_eq!(matches[0].category, "crypto");
     }
+
+    #[test]
+    fn test_inline_suppression_and_expiry() {
+        let mut matcher = PatternMatcher::new();
+        let code = r#"DEBUG = True  # knox:ignore[debug_mode] reason="local only"
+# knox:ignore[ssl_verification_disabled] until=2000-01-01
+requests.get(url, verify=False)
+hashlib.md5(data)
+hashlib.md5(salt)  # knox:ignore until=2999/12/31
+"#;
+        let matches = matcher.match_content(code);
+        assert_eq!(matches.len(), 3);
+        assert_eq!(matches[0].pattern_name, "ssl_verification_disabled");
+        assert_eq!(matches[0].tags, vec!["suppression_expired"]);
+        assert_eq!(matches[1].pattern_name, "weak_crypto_md5");
+        assert!(matches[1].tags.is_empty());
+        assert_eq!(matches[2].tags, vec!["suppression_expired"]);
+        assert!(matches[2].explanation.suppressions[0].contains("until=2999/12/31"));
+    }
+
+    #[test]
+    fn test_rules_pruned_without_import() {
+        let mut matcher = PatternMatcher::new();
+        let mentioned = "# never call pickle.loads(data) here\n";
+        // Without a language the imports are unknown and nothing is pruned
+        assert_eq!(matcher.match_content(mentioned).len(), 1);
+        matcher.set_language(Some("python".to_string()));
+        assert!(matcher.match_content(mentioned).is_empty());
+
+        let imported = "import pickle\nobj = pickle.loads(data)\n";
+        let matches = matcher.match_content(imported);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].pattern_name, "insecure_deserialization");
+
+        // Pruning for the last file doesn't carry over to single lines
+        assert!(matcher.match_content(mentioned).is_empty());
+        assert_eq!(matcher.match_line("obj = pickle.loads(data)", 1).len(), 1);
+
+        matcher.set_import_pruning(false);
+        assert_eq!(matcher.match_content(mentioned).len(), 1);
+    }
+
+    #[test]
+    fn test_language_scoped_patterns() {
+        let code = "Runtime.getRuntime().exec(cmd);\nos.system(cmd)\n";
+        let mut matcher = PatternMatcher::new();
+        assert_eq!(matcher.match_content(code).len(), 2);
+
+        matcher.set_language(Some("java".to_string()));
+        assert!(matcher.match_content(code).is_empty());
+
+        matcher.set_language(Some("Python".to_string()));
+        let matches = matcher.match_content(code);
+        assert_eq!(matches.len(), 2);
+        assert!(matches
+            .iter()
+            .all(|m| m.pattern_name == "command_injection"));
+    }
+
+    #[test]
+    fn test_allowlist_drops_dummy_secrets() {
+        let code = "api_key = \"sk-test-xxxxxxxxxxxxxxxxxxxx\"\napi_key = \"sk-live-9f8a7b6c5d4e3f2a1b0c\"\n";
+        let mut matcher = PatternMatcher::new();
+        assert_eq!(matcher.match_content(code).len(), 2);
+
+        assert!(matcher.add_allowlist_pattern("(".to_string()).is_err());
+        matcher
+            .add_allowlist_pattern(r"sk-test-x+".to_string())
+            .unwrap();
+        let matches = matcher.match_content(code);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 2);
+
+        matcher.clear_allowlist();
+        assert_eq!(matcher.match_content(code).len(), 2);
+    }
+
+    #[test]
+    fn test_foreign_suppressions_behind_flag() {
+        let code = concat!(
+            "DEBUG = True  # nosec\n",
+            "// eslint-disable-next-line knox/weak_crypto_md5\n",
+            "hashlib.md5(data)\n",
+            "// eslint-disable-next-line no-console\n",
+            "password = \"hunter2hunter2\"\n",
+        );
+        let mut matcher = PatternMatcher::new();
+        assert_eq!(matcher.match_content(code).len(), 3);
+
+        matcher.set_foreign_suppressions(true);
+        let matches = matcher.match_content(code);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].pattern_name, "hardcoded_password");
+    }
+
+    #[test]
+    fn test_invalid_custom_rule_rejected() {
+        let mut pattern = SecurityPattern::new(
+            "broken".to_string(),
+            r"token\s*=\s*([a-z".to_string(),
+            Severity::Low,
+            "custom".to_string(),
+            "Broken rule".to_string(),
+        );
+        assert_eq!(
+            check_syntax(&pattern).unwrap_err(),
+            "Invalid regex in pattern of rule 'broken': unclosed character class at position 13"
+        );
+
+        let mut matcher = PatternMatcher::new();
+        assert!(matcher.add_pattern(pattern.clone()).is_err());
+        assert_eq!(
+            matcher.pattern_count(),
+            PatternMatcher::new().pattern_count()
+        );
+
+        pattern.pattern = r"token\s*=".into();
+        pattern.negative_pattern = Some("(".to_string());
+        assert!(check_syntax(&pattern)
+            .unwrap_err()
+            .starts_with("Invalid regex in negative_pattern"));
+    }
+
+    #[test]
+    fn test_suspicious_custom_rule_reported() {
+        let mut matcher = PatternMatcher::new();
+        matcher
+            .add_pattern(SecurityPattern::new(
+                "nested_quantifier".to_string(),
+                r"(a+)+b".to_string(),
+                Severity::Low,
+                "custom".to_string(),
+                "Nested quantifier".to_string(),
+            ))
+            .unwrap();
+        matcher
+            .add_pattern(SecurityPattern::new(
+                "oversized".to_string(),
+                r"\w{1000}\w{1000}\w{1000}".to_string(),
+                Severity::Low,
+                "custom".to_string(),
+                "Oversized program".to_string(),
+            ))
+            .unwrap();
+
+        let diagnostics = matcher.diagnostics();
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.rule == "nested_quantifier" && d.level == "warning"));
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.rule == "oversized" && d.level == "error"));
+        assert_eq!(matcher.match_line("aaaab", 1).len(), 1);
+    }
+
+    #[test]
+    fn test_crlf_line_matches_anchored_pattern() {
+        let mut matcher = PatternMatcher::new();
+        matcher
+            .add_pattern(SecurityPattern::new(
+                "trailing_debug".to_string(),
+                r"debug_enabled$".to_string(),
+                Severity::Low,
+                "config".to_string(),
+                "Debug flag at end of line".to_string(),
+            ))
+            .unwrap();
+        let matches = matcher.match_content("x = 1\r\nfeature debug_enabled\r\n");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 2);
+        assert_eq!(matches[0].column, 8);
+    }
+
+    #[test]
+    fn test_file_rule_anchored_to_first_line() {
+        let mut matcher = PatternMatcher::new();
+        matcher.add_file_rule(FileRule::new(
+            "missing_license_header".to_string(),
+            Severity::Low,
+            "compliance".to_string(),
+            "File lacks a license header".to_string(),
+            None,
+            None,
+            Some("Copyright".to_string()),
+            None,
+        ))
+        .unwrap();
+        let matches = matcher.match_content("import os\n\nos.getcwd()\n");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].kind, "file");
+        assert_eq!(matches[0].line_number, 1);
+
+        let broken = FileRule::new(
+            "broken_header".to_string(),
+            Severity::Low,
+            "compliance".to_string(),
+            "Header check with a bad regex".to_string(),
+            None,
+            None,
+            Some("Copyright (".to_string()),
+            None,
+        );
+        assert_eq!(
+            check_file_rule(&broken).unwrap_err(),
+            "Invalid regex in header of rule 'broken_header': unclosed group at position 10"
+        );
+        assert!(matcher.add_file_rule(broken).is_err());
+    }
+
+    #[test]
+    fn test_explain_reports_stopping_stage() {
+        let mut matcher = PatternMatcher::new();
+        let line = "hashlib.md5(data)";
+
+        let matched = matcher.explain(line, "weak_crypto_md5", None).unwrap();
+        assert!(matched.matched());
+        assert_eq!(matched.matched_text.as_deref(), Some("hashlib.md5("));
+
+        let below = matcher
+            .explain(line, "weak_crypto_md5", Some("high".to_string()))
+            .unwrap();
+        assert_eq!(below.outcome, explain::OUTCOME_BELOW_SEVERITY);
+
+        let suppressed = matcher
+            .explain(
+                "hashlib.md5(data)  # knox:ignore weak_crypto_md5",
+                "weak_crypto_md5",
+                None,
+            )
+            .unwrap();
+        assert_eq!(suppressed.outcome, explain::OUTCOME_SUPPRESSED);
+
+        assert_eq!(
+            matcher.explain(line, "debug_mode", None).unwrap().outcome,
+            explain::OUTCOME_PREFILTER_REJECTED
+        );
+        assert_eq!(
+            matcher
+                .explain("debug = False", "debug_mode", None)
+                .unwrap()
+                .outcome,
+            explain::OUTCOME_NO_MATCH
+        );
+        assert_eq!(
+            matcher.explain(line, "no_such_rule", None).unwrap().outcome,
+            explain::OUTCOME_UNKNOWN_RULE
+        );
+        assert!(matcher
+            .explain(line, "weak_crypto_md5", Some("severe".to_string()))
+            .is_err());
+
+        matcher
+            .add_allowlist_pattern(r"^hashlib\.md5\($".to_string())
+            .unwrap();
+        assert_eq!(
+            matcher.explain(line, "weak_crypto_md5", None).unwrap().outcome,
+            explain::OUTCOME_ALLOWLISTED
+        );
+    }
+
+    #[test]
+    fn test_explain_follows_content_level_stages() {
+        let mut matcher = PatternMatcher::new_empty();
+        let mut pattern = SecurityPattern::new(
+            "credentials_dict".to_string(),
+            r#"(?s)\{\s*"user":\s*"[^"]+",\s*"password":\s*"[^"]+""#.to_string(),
+            Severity::High,
+            "secrets".to_string(),
+            "Credentials in a dict literal".to_string(),
+        );
+        pattern.multiline = true;
+        pattern.languages = vec!["python".to_string()];
+        matcher.add_pattern(pattern).unwrap();
+
+        let content = "creds = {\n    \"user\": \"admin\",\n    \"password\": \"s3cret!\"\n}\n";
+        let matched = matcher.explain(content, "credentials_dict", None).unwrap();
+        assert!(matched.matched());
+        assert_eq!(matched.column, Some(8));
+
+        matcher.set_language(Some("go".to_string()));
+        assert_eq!(
+            matcher
+                .explain(content, "credentials_dict", None)
+                .unwrap()
+                .outcome,
+            explain::OUTCOME_PRUNED
+        );
+    }
+
+    #[test]
+    fn test_multiline_pattern_spans_lines() {
+        let mut matcher = PatternMatcher::new();
+        let mut pattern = SecurityPattern::new(
+            "credentials_dict".to_string(),
+            r#"(?s)\{\s*"user":\s*"[^"]+",\s*"password":\s*"[^"]+""#.to_string(),
+            Severity::High,
+            "secrets".to_string(),
+            "Credentials in a dict literal".to_string(),
+        );
+        pattern.multiline = true;
+        matcher.add_pattern(pattern).unwrap();
+
+        let content =
+            "x = 1\ncreds = {\n    \"user\": \"admin\",\n    \"password\": \"s3cret!\"\n}\n";
+        let matches = matcher.match_content(content);
+        let found: Vec<_> = matches
+            .iter()
+            .filter(|m| m.pattern_name == "credentials_dict")
+            .collect();
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].line_number, found[0].column), (2, 8));
+        assert!(found[0].matched_text.contains('\n'));
+
+        let suppressed = content.replace("creds = {", "creds = {  # knox:ignore");
+        assert!(!matcher
+            .match_content(&suppressed)
+            .iter()
+            .any(|m| m.pattern_name == "credentials_dict"));
+    }
+
+    #[test]
+    fn test_string_scoped_pattern_ignores_code() {
+        let mut matcher = PatternMatcher::new_empty();
+        let mut pattern = SecurityPattern::new(
+            "sql_delete".to_string(),
+            r"(?i)^\s*delete\s+from\b".to_string(),
+            Severity::Medium,
+            "injection".to_string(),
+            "DELETE statement built in code".to_string(),
+        );
+        pattern.scope = SCOPE_STRING.to_string();
+        matcher.add_pattern(pattern.clone()).unwrap();
+
+        let content = "# delete from users later\nq = run(\"DELETE FROM users WHERE id=\" + uid)\n";
+        let matches = matcher.match_content(content);
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].line_number, matches[0].column), (2, 9));
+        assert_eq!(matches[0].matched_text, "DELETE FROM");
+
+        pattern.scope = "comment".to_string();
+        assert!(PatternMatcher::new_empty().add_pattern(pattern).is_err());
+    }
+
+    #[test]
+    fn test_default_rules_carry_cwe_and_owasp() {
+        assert!(PatternMatcher::default_patterns()
+            .iter()
+            .all(|p| p.cwe.is_some() && p.owasp.is_some() && !p.references.is_empty()));
+
+        let mut matcher = PatternMatcher::new();
+        let matches = matcher.match_line("cursor.execute(\"SELECT * FROM t WHERE id=\" + uid)", 1);
+        let m = matches
+            .iter()
+            .find(|m| m.pattern_name == "sql_injection")
+            .unwrap();
+        assert_eq!(m.cwe.as_deref(), Some("CWE-89"));
+        assert_eq!(m.owasp.as_deref(), Some("A03:2021"));
+    }
+
+    #[test]
+    fn test_curated_builtin_subset() {
+        let catalog = PatternMatcher::builtin_patterns();
+        assert_eq!(catalog.len(), PatternMatcher::default_patterns().len());
+        assert!(catalog.iter().all(|p| p.source.is_some()));
+
+        let mut matcher = PatternMatcher::new_empty();
+        assert!(matcher.match_line("DEBUG = True", 1).is_empty());
+        for pattern in catalog.into_iter().filter(|p| p.category == "config") {
+            matcher.add_pattern(pattern).unwrap();
+        }
+        assert_eq!(matcher.pattern_count(), 1);
+        assert_eq!(
+            matcher.match_line("DEBUG = True", 1)[0].pattern_name,
+            "debug_mode"
+        );
+    }
+
+    #[test]
+    fn test_entropy_detection() {
+        let line = r#"upload_token = "Zm9vYmFyYmF6cXV4MTIzNDU2Nzg5MGFiY2Rl""#;
+        let mut matcher = PatternMatcher::new();
+        assert!(matcher.match_line(line, 1).is_empty());
+
+        matcher.set_entropy_detection(true, None);
+        let matches = matcher.match_line(line, 1);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].pattern_name, entropy::RULE_NAME);
+        assert_eq!(matches[0].column, 16);
+        assert!(matcher
+            .match_line(r#"padding = "abababababababababababab""#, 1)
+            .is_empty());
+
+        // A specific secrets rule covering the literal takes precedence
+        let keyed = r#"api_key = "Zm9vYmFyYmF6cXV4MTIzNDU2Nzg5MGFiY2Rl""#;
+        let names: Vec<String> = matcher
+            .match_line(keyed, 1)
+            .into_iter()
+            .map(|m| m.pattern_name)
+            .collect();
+        assert_eq!(names, vec!["hardcoded_api_key"]);
+    }
+
+    #[test]
+    fn test_case_and_word_boundary_flags() {
+        let mut pattern = SecurityPattern::new(
+            "eval_call".to_string(),
+            "eval".to_string(),
+            Severity::High,
+            "injection".to_string(),
+            "eval call".to_string(),
+        );
+        pattern.case_insensitive = true;
+        pattern.word_boundary = true;
+        assert_eq!(pattern.effective_pattern(), r"(?i:\b(?:eval)\b)");
+
+        let mut matcher = PatternMatcher::new_empty();
+        matcher.add_pattern(pattern).unwrap();
+        assert_eq!(matcher.match_line("EVAL(x)", 1).len(), 1);
+        assert!(matcher.match_line("evaluate(x)", 1).is_empty());
+        assert!(matcher.match_line("retrieval(x)", 1).is_empty());
+
+        // Built-in rules carry the flag instead of an inline `(?i)`
+        let debug = &PatternMatcher::builtin_patterns()[8];
+        assert_eq!(debug.name, "debug_mode");
+        assert!(debug.case_insensitive && !debug.pattern.source().starts_with("(?i)"));
+    }
+
+    #[test]
+    fn test_enabled_categories() {
+        let content = "hashlib.md5(x)\napi_key = \"abcdefghijklmnopqrstuvwxyz\"\nDEBUG = True\n";
+        let mut matcher = PatternMatcher::new();
+        assert_eq!(matcher.match_content(content).len(), 3);
+
+        matcher.set_enabled_categories(Some(vec!["secrets".to_string()]));
+        matcher
+            .add_pattern(SecurityPattern::new(
+                "print_call".to_string(),
+                r"print\(".to_string(),
+                Severity::Low,
+                "style".to_string(),
+                "print call".to_string(),
+            ))
+            .unwrap();
+        let names: Vec<String> = matcher
+            .match_content(&format!("{}print(x)\n", content))
+            .into_iter()
+            .map(|m| m.pattern_name)
+            .collect();
+        assert_eq!(names, vec!["hardcoded_api_key"]);
+
+        matcher.set_enabled_categories(None);
+        assert_eq!(matcher.match_content(content).len(), 3);
+    }
+
+    #[test]
+    fn test_min_confidence() {
+        let line = "hashlib.md5(x); DEBUG = True";
+        let mut matcher = PatternMatcher::new();
+        let matches = matcher.match_line(line, 1);
+        let confidences: Vec<&str> = matches.iter().map(|m| m.confidence.as_str()).collect();
+        assert_eq!(confidences, vec!["high", "low"]);
+
+        matcher
+            .set_min_confidence(Some("Medium".to_string()))
+            .unwrap();
+        let matches = matcher.match_line(line, 1);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].pattern_name, "weak_crypto_md5");
+        assert_eq!(
+            matcher.explain(line, "debug_mode", None).unwrap().outcome,
+            explain::OUTCOME_BELOW_CONFIDENCE
+        );
+        assert!(matcher
+            .set_min_confidence(Some("certain".to_string()))
+            .is_err());
+
+        let mut rule = SecurityPattern::new(
+            "guess".to_string(),
+            "guess".to_string(),
+            Severity::Low,
+            "custom".to_string(),
+            "Guess".to_string(),
+        );
+        rule.confidence = "certain".to_string();
+        assert_eq!(
+            check_pattern(&rule).unwrap_err(),
+            "Unknown confidence 'certain' for rule 'guess' (expected one of [\"high\", \"medium\", \"low\"])"
+        );
+        assert!(matcher.add_pattern(rule.clone()).is_err());
+        rule.confidence = "High".to_string();
+        matcher.add_pattern(rule).unwrap();
+        assert_eq!(matcher.match_line("guess", 1)[0].confidence, "high");
+    }
+
+    #[test]
+    fn test_remove_and_disable_rules() {
+        let line = "hashlib.md5(x); DEBUG = True";
+        let names = |matcher: &mut PatternMatcher| -> Vec<String> {
+            matcher
+                .match_line(line, 1)
+                .into_iter()
+                .map(|m| m.pattern_name)
+                .collect()
+        };
+
+        let mut matcher = PatternMatcher::new();
+        assert_eq!(names(&mut matcher), vec!["weak_crypto_md5", "debug_mode"]);
+        assert!(matcher.remove_pattern("debug_mode"));
+        assert!(!matcher.remove_pattern("debug_mode"));
+        assert_eq!(names(&mut matcher), vec!["weak_crypto_md5"]);
+
+        assert_eq!(matcher.disable_category("crypto"), 6);
+        assert!(names(&mut matcher).is_empty());
+
+        let mut matcher = PatternMatcher::new();
+        matcher.clear_patterns();
+        assert_eq!(matcher.pattern_count(), 0);
+        assert!(names(&mut matcher).is_empty());
+        assert_eq!(PatternMatcher::new().match_line(line, 1).len(), 2);
+
+        let script = "#!/usr/bin/env bash\nrm -rf \"$DIR\"\n";
+        let file_rule = || {
+            FileRule::new(
+                "bash_no_strict".to_string(),
+                Severity::Low,
+                "config".to_string(),
+                "Bash script without strict mode".to_string(),
+                Some(r"^#!.*\bbash\b".to_string()),
+                Some(r"set -euo pipefail".to_string()),
+                None,
+                None,
+            )
+        };
+        let fired = |matcher: &mut PatternMatcher| {
+            matcher
+                .match_content(script)
+                .iter()
+                .any(|m| m.pattern_name == "bash_no_strict")
+        };
+        let mut matcher = PatternMatcher::new();
+        matcher.add_file_rule(file_rule()).unwrap();
+        assert!(fired(&mut matcher));
+        assert!(matcher.disable_rule("bash_no_strict"));
+        assert!(!fired(&mut matcher));
+        assert!(matcher.enable_rule("bash_no_strict"));
+        assert!(fired(&mut matcher));
+        matcher.disable_category("config");
+        assert!(!fired(&mut matcher));
+
+        // The category stays disabled for rules added after it
+        let mut matcher = PatternMatcher::new_empty();
+        matcher.disable_category("config");
+        matcher.add_file_rule(file_rule()).unwrap();
+        assert!(!fired(&mut matcher));
+        assert_eq!(matcher.rule_set().disabled_categories, vec!["config"]);
+        assert!(matcher.enable_category("config"));
+        assert!(!matcher.enable_category("config"));
+        assert!(fired(&mut matcher));
+    }
+
+    #[test]
+    fn test_findings_carry_end_column_and_byte_offsets() {
+        let mut matcher = PatternMatcher::new_empty();
+        matcher
+            .add_pattern(SecurityPattern::new(
+                "secret_word".to_string(),
+                "secret".to_string(),
+                Severity::Low,
+                "secrets".to_string(),
+                "Secret".to_string(),
+            ))
+            .unwrap();
+        let content = "é = 1\npassword = \"é-secret\"\n";
+        let span = |m: &Match| (m.column, m.end_column, m.start_byte, m.end_byte);
+
+        let matches = matcher.match_content(content);
+        assert_eq!(span(&matches[0]), (14, 20, Some(22), Some(28)));
+        assert_eq!(&content[22..28], "secret");
+        let matches = matcher.match_content_range(content, 2, 2);
+        assert_eq!(span(&matches[0]), (14, 20, Some(22), Some(28)));
+        let matches = matcher.match_line("password = \"é-secret\"", 2);
+        assert_eq!(span(&matches[0]), (14, 20, Some(15), Some(21)));
+    }
+
+    #[test]
+    fn test_rule_ids_and_collisions() {
+        let mut matcher = PatternMatcher::new();
+        let builtin = matcher
+            .get_pattern("knox.secrets.hardcoded_api_key")
+            .unwrap()
+            .unwrap();
+        assert_eq!(builtin.name, "hardcoded_api_key");
+        assert!(matcher.rule_collisions().is_empty());
+
+        let mut org_rule = builtin.clone();
+        org_rule.source = Some(RuleSource::stamped(None, "acme", Some("1.2")));
+        org_rule.pattern = r"acme_key_\w{16}".into();
+        matcher.add_pattern(org_rule).unwrap();
+        assert!(matcher.diagnostics()[0]
+            .message
+            .contains("knox.secrets.hardcoded_api_key"));
+        assert_eq!(
+            matcher.rule_ids(Some("acme")),
+            vec!["acme.secrets.hardcoded_api_key"]
+        );
+        assert_eq!(
+            matcher.rule_collisions()["hardcoded_api_key"],
+            vec![
+                "knox.secrets.hardcoded_api_key",
+                "acme.secrets.hardcoded_api_key"
+            ]
+        );
+        assert!(matcher.get_pattern("hardcoded_api_key").is_err());
+        let org_rule = matcher
+            .get_pattern("acme.secrets.hardcoded_api_key")
+            .unwrap()
+            .unwrap();
+        assert_eq!(org_rule.pattern, r"acme_key_\w{16}");
+        assert!(matcher.get_pattern("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_severity_overrides() {
+        let mut matcher = PatternMatcher::new();
+        matcher
+            .override_severities(&HashMap::from([(
+                "debug_mode".to_string(),
+                "Info".to_string(),
+            )]))
+            .unwrap();
+        let matches = matcher.match_line("DEBUG = True", 1);
+        assert_eq!(matches[0].severity, "info");
+        assert_eq!(
+            PatternMatcher::new().match_line("DEBUG = True", 1)[0].severity,
+            "medium"
+        );
+
+        let bad = HashMap::from([
+            ("debug_mode".to_string(), "high".to_string()),
+            ("no_such_rule".to_string(), "low".to_string()),
+        ]);
+        assert!(matcher.override_severities(&bad).is_err());
+        assert_eq!(matcher.match_line("DEBUG = True", 1)[0].severity, "info");
+    }
+
+    #[test]
+    fn test_findings_carry_explanation() {
+        let code = "password = \"hunter2hunter2\"  # knox:ignore[debug_mode]\n";
+        let matches = PatternMatcher::new().match_content(code);
+        assert_eq!(matches.len(), 1);
+
+        let explanation = &matches[0].explanation;
+        assert_eq!(explanation.rule_description, "Hardcoded password detected");
+        assert!(explanation.clause.contains("password|passwd|pwd"));
+        assert_eq!(explanation.captures, vec!["password", "hunter2hunter2"]);
+        assert_eq!(
+            explanation.suppressions,
+            vec!["knox:ignore on this line names only debug_mode"]
+        );
+        assert!(explanation.adjustments.is_empty());
+    }
+
+    #[test]
+    fn test_validate_patterns_against_examples() {
+        let mut matcher = PatternMatcher::new();
+        assert!(matcher.validate_patterns().is_empty());
+
+        let mut rule = SecurityPattern::new(
+            "internal_token".to_string(),
+            r"itk_[a-z0-9]{8}".to_string(),
+            Severity::High,
+            "secrets".to_string(),
+            "Internal service token".to_string(),
+        );
+        rule.positive_examples = vec![
+            "token = 'itk_a1b2c3d4'".to_string(),
+            "ITK_A1B2C3D4".to_string(),
+        ];
+        rule.negative_examples = vec!["itk_short".to_string()];
+        matcher.add_pattern(rule).unwrap();
+
+        let failures = matcher.validate_patterns();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].rule, "internal_token");
+        assert_eq!(
+            failures[0].message,
+            "positive example not flagged: ITK_A1B2C3D4"
+        );
+    }
+
+    #[test]
+    fn test_negative_pattern_discards_match() {
+        let mut matcher = PatternMatcher::new();
+        let mut pattern = SecurityPattern::new(
+            "plain_password".to_string(),
+            r"password\s*=".to_string(),
+            Severity::High,
+            "secrets".to_string(),
+            "Password assignment".to_string(),
+        );
+        pattern.negative_pattern = Some(r"os\.environ".to_string());
+        matcher.add_pattern(pattern).unwrap();
+
+        let hits = |matcher: &mut PatternMatcher, line: &str| {
+            matcher
+                .match_line(line, 1)
+                .iter()
+                .filter(|m| m.pattern_name == "plain_password")
+                .count()
+        };
+        assert_eq!(hits(&mut matcher, "password = 'hunter2'"), 1);
+        assert_eq!(
+            hits(&mut matcher, "password = os.environ['DB_PASSWORD']"),
+            0
+        );
+        assert_eq!(
+            matcher
+                .explain(
+                    "password = os.environ['DB_PASSWORD']",
+                    "plain_password",
+                    None
+                )
+                .unwrap()
+                .outcome,
+            explain::OUTCOME_NEGATED
+        );
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_secret_column_counts_unicode_chars(
+            prefix in "[\t\u{80}-\u{10FFFF}]{0,12}",
+            suffix in "\\PC{0,20}",
+        ) {
+            let secret = r#"api_key = "sk_live_0123456789abcdefghij""#;
+            let mut matcher = PatternMatcher::new();
+            matcher.set_normalization(NormalizationOptions::new(None, Some(4), None));
+
+            let matches = matcher.match_line(&format!("{}{} {}", prefix, secret, suffix), 1);
+            let found = matches
+                .iter()
+                .find(|m| m.pattern_name == "hardcoded_api_key")
+                .unwrap();
+            proptest::prop_assert_eq!(&found.matched_text, secret);
+            proptest::prop_assert_eq!(found.column, prefix.chars().count());
+        }
+    }
 }
@@ -0,0 +1,158 @@
+//! Per-file code metrics gathered during a scan
+//!
+//! Code-health dashboards want line counts and function sizes for the same
+//! files a security scan reads. With metrics enabled the scanner computes
+//! them from the content it already holds, so the tree isn't walked twice.
+
+use crate::language;
+use crate::parser::CodeParser;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct FileMetrics {
+    #[pyo3(get)]
+    pub language: Option<String>,
+    #[pyo3(get)]
+    pub total_lines: usize,
+    #[pyo3(get)]
+    pub code_lines: usize,
+    #[pyo3(get)]
+    pub comment_lines: usize,
+    #[pyo3(get)]
+    pub blank_lines: usize,
+    #[pyo3(get)]
+    pub functions: usize,
+    #[pyo3(get)]
+    pub imports: usize,
+    /// Name of the longest function, if the language has a parser
+    #[pyo3(get)]
+    pub largest_function: Option<String>,
+    /// Length of the longest function in lines, including its definition
+    #[pyo3(get)]
+    pub largest_function_lines: usize,
+}
+
+#[pymethods]
+impl FileMetrics {
+    fn __repr__(&self) -> String {
+        format!(
+            "FileMetrics(language={}, code_lines={}, functions={})",
+            self.language.as_deref().unwrap_or("-"),
+            self.code_lines,
+            self.functions
+        )
+    }
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Number of lines in the function defined at `start` (0-based): up to the
+/// brace closing its body, or for indentation-scoped code up to the last
+/// line indented deeper than the definition
+fn function_length(lines: &[&str], start: usize) -> usize {
+    let header = lines[start];
+    let body = &lines[start..];
+
+    if body.iter().take(2).any(|l| l.contains('{')) {
+        let mut depth = 0i64;
+        let mut opened = false;
+        for (i, line) in body.iter().enumerate() {
+            for c in line.chars() {
+                match c {
+                    '{' => {
+                        depth += 1;
+                        opened = true;
+                    }
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+            }
+            if opened && depth <= 0 {
+                return i + 1;
+            }
+        }
+        return body.len();
+    }
+
+    let mut length = 1;
+    for (i, line) in body.iter().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if indent(line) <= indent(header) {
+            break;
+        }
+        length = i + 1;
+    }
+    length
+}
+
+/// Metrics for `content`, parsed as `language` where a parser exists
+pub fn collect(language: Option<&str>, content: &str) -> FileMetrics {
+    let comments = language
+        .map(language::line_comment_markers)
+        .unwrap_or_default();
+    let lines: Vec<&str> = content.lines().collect();
+
+    let mut metrics = FileMetrics {
+        language: language.map(str::to_string),
+        total_lines: language::count_lines(content),
+        ..FileMetrics::default()
+    };
+    for line in lines.iter().map(|l| l.trim()) {
+        if line.is_empty() {
+            metrics.blank_lines += 1;
+        } else if comments.iter().any(|c| line.starts_with(c.as_str())) {
+            metrics.comment_lines += 1;
+        } else {
+            metrics.code_lines += 1;
+        }
+    }
+
+    let Some(language) = language else {
+        return metrics;
+    };
+    let parser = CodeParser::new(Some(language.to_string()));
+    let functions = parser.extract_functions(content);
+    metrics.functions = functions.len();
+    metrics.imports = parser.extract_imports(content).len();
+    for function in functions {
+        let length = function_length(&lines, function.line_number - 1);
+        if length > metrics.largest_function_lines {
+            metrics.largest_function_lines = length;
+            metrics.largest_function = Some(function.name);
+        }
+    }
+    metrics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_metrics() {
+        let python = "import os\n\n# helpers\ndef short():\n    return 1\n\ndef long(a, b):\n    x = a\n\n    return x + b\nprint(long(1, 2))\n";
+        let metrics = collect(Some("python"), python);
+        assert_eq!(metrics.total_lines, 11);
+        assert_eq!(metrics.blank_lines, 3);
+        assert_eq!(metrics.comment_lines, 1);
+        assert_eq!(metrics.code_lines, 7);
+        assert_eq!(metrics.functions, 2);
+        assert_eq!(metrics.imports, 1);
+        assert_eq!(metrics.largest_function.as_deref(), Some("long"));
+        assert_eq!(metrics.largest_function_lines, 4);
+
+        let rust = "fn main() {\n    let x = 1;\n    if x > 0 {\n        run();\n    }\n}\n\nfn run() {}\n";
+        let metrics = collect(Some("rust"), rust);
+        assert_eq!(metrics.largest_function.as_deref(), Some("main"));
+        assert_eq!(metrics.largest_function_lines, 6);
+
+        let unknown = collect(None, "a\n\nb\n");
+        assert_eq!((unknown.code_lines, unknown.functions), (2, 0));
+    }
+}
@@ -0,0 +1,222 @@
+//! Hardcoded network locations
+//!
+//! Private IP addresses, internal hostnames and cleartext `http://`
+//! endpoints written into source tie a build to one environment and leak
+//! its layout, and plain HTTP sends whatever travels over it in the clear.
+//! These rules only look inside string literals, so comments and version
+//! numbers don't trip them.
+//!
+//! Hosts that are meant to appear in code (loopback, documentation
+//! domains, XML namespace URIs) are allowed by default; a matcher's allowed
+//! hosts can be extended or replaced. An entry is a hostname or address,
+//! `*.domain` for a domain and its subdomains, or an IPv4 CIDR block such
+//! as `10.20.0.0/16`.
+
+use crate::matcher::{Match, SecurityPattern, SCOPE_STRING};
+use crate::severity::Severity;
+use std::net::Ipv4Addr;
+
+pub const CATEGORY_NETWORK: &str = "network";
+
+/// Hosts whose findings are dropped unless the allowed hosts are replaced
+pub const DEFAULT_ALLOWED_HOSTS: &[&str] = &[
+    "localhost",
+    "*.localhost",
+    "127.0.0.1",
+    "0.0.0.0",
+    "::1",
+    "*.example.com",
+    "*.example.org",
+    "*.example.net",
+    "www.w3.org",
+    "schemas.xmlsoap.org",
+    "schemas.microsoft.com",
+    "json-schema.org",
+    "purl.org",
+    "xmlns.com",
+];
+
+fn network_rule(
+    name: &str,
+    pattern: &str,
+    severity: Severity,
+    description: &str,
+    cwe: &str,
+) -> SecurityPattern {
+    let mut rule = SecurityPattern::new(
+        name.to_string(),
+        pattern.to_string(),
+        severity,
+        CATEGORY_NETWORK.to_string(),
+        description.to_string(),
+    );
+    rule.scope = SCOPE_STRING.to_string();
+    let owasp = if cwe == "CWE-319" {
+        "A02:2021"
+    } else {
+        "A05:2021"
+    };
+    rule.with_cwe(cwe, owasp)
+}
+
+/// Built-in network rules, appended to the default rules
+pub fn network_patterns() -> Vec<SecurityPattern> {
+    let mut private_ip = network_rule(
+        "private_ip_address",
+        r"\b(?:10\.\d{1,3}|172\.(?:1[6-9]|2\d|3[01])|192\.168)\.\d{1,3}\.\d{1,3}\b",
+        Severity::Low,
+        "Hardcoded private IP address",
+        "CWE-547",
+    );
+    private_ip.positive_examples = vec![r#"DB_HOST = "10.12.0.7""#.to_string()];
+    private_ip.negative_examples = vec![r#"SERVER = "8.8.8.8""#.to_string()];
+
+    let mut cleartext = network_rule(
+        "cleartext_http_url",
+        r"(?i)\bhttp://[^\s/?#]+",
+        Severity::Medium,
+        "Endpoint reached over plain HTTP instead of HTTPS",
+        "CWE-319",
+    );
+    cleartext.confidence = "high".to_string();
+    cleartext.positive_examples = vec![r#"API_URL = "http://payments.acme.io/v2""#.to_string()];
+    cleartext.negative_examples = vec![r#"API_URL = "https://payments.acme.io/v2""#.to_string()];
+
+    let mut internal = network_rule(
+        "internal_hostname",
+        r"(?i)\b(?:[a-z0-9](?:[a-z0-9-]*[a-z0-9])?\.)+(?:internal|intranet|corp|lan|localdomain|home\.arpa)\b",
+        Severity::Low,
+        "Hardcoded internal hostname",
+        "CWE-547",
+    );
+    internal.positive_examples = vec![r#"BROKER = "kafka-1.prod.internal:9092""#.to_string()];
+    internal.negative_examples = vec![r#"BROKER = "kafka.acme.io:9092""#.to_string()];
+
+    vec![private_ip, cleartext, internal]
+}
+
+/// Host named by a finding's text: the text itself, or the host part of a
+/// URL, lowercased and without port or brackets
+pub fn host_of(text: &str) -> String {
+    let rest = text.split_once("://").map_or(text, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match host.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None if host.matches(':').count() == 1 => host.split(':').next().unwrap_or_default(),
+        None => host,
+    };
+    host.to_lowercase()
+}
+
+/// Check an allowed-host entry, returning why it can't be used
+pub fn check_entry(entry: &str) -> Result<(), String> {
+    if entry.trim().is_empty() {
+        return Err("empty allowed host".to_string());
+    }
+    if let Some((address, prefix)) = entry.split_once('/') {
+        address
+            .parse::<Ipv4Addr>()
+            .map_err(|e| format!("invalid CIDR address {}: {}", address, e))?;
+        match prefix.parse::<u32>() {
+            Ok(bits) if bits <= 32 => {}
+            _ => return Err(format!("invalid CIDR prefix length {}", prefix)),
+        }
+    }
+    Ok(())
+}
+
+/// Whether `host` is covered by the allowed-host `entry`
+pub fn host_allowed(host: &str, entry: &str) -> bool {
+    let entry = entry.trim().to_lowercase();
+    if let Some((address, prefix)) = entry.split_once('/') {
+        let (Ok(network), Ok(bits), Ok(ip)) = (
+            address.parse::<Ipv4Addr>(),
+            prefix.parse::<u32>(),
+            host.parse::<Ipv4Addr>(),
+        ) else {
+            return false;
+        };
+        let mask = u32::MAX.checked_shl(32 - bits).unwrap_or(0);
+        return u32::from(ip) & mask == u32::from(network) & mask;
+    }
+    match entry.strip_prefix("*.") {
+        Some(domain) => {
+            host == domain
+                || host
+                    .strip_suffix(domain)
+                    .is_some_and(|sub| sub.ends_with('.'))
+        }
+        None => host == entry,
+    }
+}
+
+/// Drop network findings on hosts that `allowed` covers
+pub fn apply_allowed_hosts(matches: &mut Vec<Match>, allowed: &[String]) {
+    matches.retain(|m| {
+        if m.category != CATEGORY_NETWORK {
+            return true;
+        }
+        let host = host_of(&m.matched_text);
+        !allowed.iter().any(|entry| host_allowed(&host, entry))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::PatternMatcher;
+
+    #[test]
+    fn test_host_entries() {
+        assert_eq!(host_of("http://User@API.corp:8080/x?y"), "api.corp");
+        assert_eq!(host_of("http://[::1]:3000/"), "::1");
+        assert!(host_allowed("10.20.3.4", "10.20.0.0/16"));
+        assert!(!host_allowed("10.21.3.4", "10.20.0.0/16"));
+        assert!(host_allowed("10.21.3.4", "0.0.0.0/0"));
+        assert!(host_allowed("docs.example.com", "*.example.com"));
+        assert!(host_allowed("example.com", "*.example.com"));
+        assert!(!host_allowed("badexample.com", "*.example.com"));
+        assert!(check_entry("10.0.0.0/33").is_err());
+        assert!(check_entry("10.0.0/8").is_err());
+    }
+
+    #[test]
+    fn test_network_rules_honor_allowed_hosts() {
+        let content = concat!(
+            "# see http://wiki.corp/setup\n",
+            "DB = \"10.0.4.2\"\n",
+            "XMLNS = \"http://www.w3.org/2000/svg\"\n",
+            "CACHE = \"http://cache.svc.internal:6379\"\n",
+            "VERSION = \"10.2.3\"\n",
+        );
+        let mut matcher = PatternMatcher::new();
+        let flagged = |matcher: &mut PatternMatcher| -> Vec<(usize, String)> {
+            matcher
+                .match_content(content)
+                .into_iter()
+                .filter(|m| m.category == CATEGORY_NETWORK)
+                .map(|m| (m.line_number, m.pattern_name))
+                .collect()
+        };
+        assert_eq!(
+            flagged(&mut matcher),
+            vec![
+                (2, "private_ip_address".to_string()),
+                (4, "cleartext_http_url".to_string()),
+                (4, "internal_hostname".to_string()),
+            ]
+        );
+
+        matcher.add_allowed_host("*.internal".to_string()).unwrap();
+        matcher.add_allowed_host("10.0.0.0/8".to_string()).unwrap();
+        assert!(flagged(&mut matcher).is_empty());
+        assert!(matcher.add_allowed_host("10.0.0.0/40".to_string()).is_err());
+
+        matcher.clear_allowed_hosts();
+        assert!(matcher.allowed_hosts().is_empty());
+        assert_eq!(flagged(&mut matcher).len(), 4);
+    }
+}
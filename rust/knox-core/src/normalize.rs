@@ -0,0 +1,169 @@
+//! Line normalization applied before matching
+//!
+//! Strips carriage returns, expands tabs and trims trailing whitespace
+//! according to `NormalizationOptions`, keeping a byte offset map so that
+//! columns found in the normalized text point at the original bytes
+
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct NormalizationOptions {
+    /// Drop a trailing `\r` so CRLF files behave like LF files
+    #[pyo3(get, set)]
+    pub strip_cr: bool,
+    /// Expand tabs to this many spaces before matching
+    #[pyo3(get, set)]
+    pub tab_width: Option<usize>,
+    /// Remove trailing spaces and tabs before matching
+    #[pyo3(get, set)]
+    pub trim_trailing: bool,
+}
+
+#[pymethods]
+impl NormalizationOptions {
+    #[new]
+    pub fn new(
+        strip_cr: Option<bool>,
+        tab_width: Option<usize>,
+        trim_trailing: Option<bool>,
+    ) -> Self {
+        NormalizationOptions {
+            strip_cr: strip_cr.unwrap_or(true),
+            tab_width,
+            trim_trailing: trim_trailing.unwrap_or(false),
+        }
+    }
+}
+
+impl Default for NormalizationOptions {
+    fn default() -> Self {
+        Self::new(None, None, None)
+    }
+}
+
+/// A line after normalization, with the mapping back to the original
+pub struct NormalizedLine<'a> {
+    original: &'a str,
+    pub text: Cow<'a, str>,
+    /// Original byte offset for every byte of `text`; `None` when unchanged
+    offsets: Option<Vec<usize>>,
+}
+
+impl<'a> NormalizedLine<'a> {
+    pub fn new(line: &'a str, options: &NormalizationOptions) -> Self {
+        let mut end = line.len();
+        if options.strip_cr && line[..end].ends_with('\r') {
+            end -= 1;
+        }
+        if options.trim_trailing {
+            end = line[..end].trim_end_matches([' ', '\t']).len();
+        }
+        let trimmed = &line[..end];
+
+        let tab_width = match options.tab_width {
+            Some(width) if trimmed.contains('\t') => width.max(1),
+            _ => {
+                return NormalizedLine {
+                    original: line,
+                    text: Cow::Borrowed(trimmed),
+                    offsets: None,
+                }
+            }
+        };
+
+        let mut text = String::with_capacity(trimmed.len());
+        let mut offsets = Vec::with_capacity(trimmed.len());
+        let mut visual_column = 0;
+        for (index, ch) in trimmed.char_indices() {
+            if ch == '\t' {
+                let spaces = tab_width - visual_column % tab_width;
+                for _ in 0..spaces {
+                    text.push(' ');
+                    offsets.push(index);
+                }
+                visual_column += spaces;
+            } else {
+                text.push(ch);
+                offsets.extend(std::iter::repeat_n(index, ch.len_utf8()));
+                visual_column += 1;
+            }
+        }
+
+        NormalizedLine {
+            original: line,
+            text: Cow::Owned(text),
+            offsets: Some(offsets),
+        }
+    }
+
+    /// Map a byte range in the normalized text to a range in the original line
+    pub fn original_range(&self, start: usize, end: usize) -> (usize, usize) {
+        let offsets = match &self.offsets {
+            Some(offsets) => offsets,
+            None => return (start, end),
+        };
+        let map = |i: usize| offsets.get(i).copied().unwrap_or(self.original.len());
+
+        let original_start = map(start);
+        let original_end = if end > start {
+            let last = map(end - 1);
+            last + self.original[last..]
+                .chars()
+                .next()
+                .map_or(0, char::len_utf8)
+        } else {
+            original_start
+        };
+        (original_start, original_end)
+    }
+
+    /// The original text covering a normalized byte range
+    pub fn original_slice(&self, start: usize, end: usize) -> &'a str {
+        let (start, end) = self.original_range(start, end);
+        &self.original[start..end]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crlf_stripped_by_default() {
+        let line = NormalizedLine::new("DEBUG = True\r", &NormalizationOptions::default());
+        assert_eq!(line.text, "DEBUG = True");
+    }
+
+    #[test]
+    fn test_tab_expansion_maps_columns_back() {
+        let options = NormalizationOptions::new(None, Some(4), Some(true));
+        let line = NormalizedLine::new("\tkey =\tvalue  ", &options);
+        assert_eq!(line.text, "    key =   value");
+
+        let start = line.text.find("value").unwrap();
+        assert_eq!(line.original_range(start, start + 5), (7, 12));
+        assert_eq!(line.original_slice(start, start + 5), "value");
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_ranges_map_to_char_boundaries(
+            line in "[\\PC\t ]{0,40}",
+            tab_width in 1usize..8,
+        ) {
+            let options = NormalizationOptions::new(None, Some(tab_width), Some(true));
+            let normalized = NormalizedLine::new(&line, &options);
+
+            let whole = normalized.original_slice(0, normalized.text.len());
+            proptest::prop_assert_eq!(whole, line.trim_end_matches([' ', '\t']));
+            for (start, ch) in normalized.text.char_indices() {
+                let (from, to) = normalized.original_range(start, start + ch.len_utf8());
+                proptest::prop_assert!(line.is_char_boundary(from) && line.is_char_boundary(to));
+                proptest::prop_assert!(from < to);
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Reporting order of findings
+//!
+//! Findings are reported in an order that depends only on the findings
+//! themselves, never on the order rules were loaded, so adding a rule or
+//! reshuffling a pack doesn't churn result diffs. Matches sort by:
+//!
+//! 1. file path, then line, then column of the match start;
+//! 2. pack priority: built-in rules, then rules from named packs, then
+//!    rules added without a pack;
+//! 3. pack name, then rule name;
+//! 4. the wider match first, then matched text.
+//!
+//! The same key breaks ties wherever one finding is chosen over another,
+//! such as when overlapping secrets collapse into one.
+
+use crate::matcher::Match;
+use crate::provenance::BUILTIN_PACK;
+use std::cmp::{Ordering, Reverse};
+
+/// Rank of the pack a finding's rule came from, lowest first
+fn pack_rank(m: &Match) -> (u8, Option<&str>) {
+    match m.rule_source.as_ref().and_then(|s| s.pack.as_deref()) {
+        Some(BUILTIN_PACK) => (0, None),
+        Some(pack) => (1, Some(pack)),
+        None => (2, None),
+    }
+}
+
+/// Order of two findings of the same location, by rule then span
+pub fn compare_rules(a: &Match, b: &Match) -> Ordering {
+    pack_rank(a)
+        .cmp(&pack_rank(b))
+        .then_with(|| a.pattern_name.cmp(&b.pattern_name))
+        .then_with(|| {
+            Reverse(a.matched_text.chars().count()).cmp(&Reverse(b.matched_text.chars().count()))
+        })
+        .then_with(|| a.matched_text.cmp(&b.matched_text))
+}
+
+/// Full reporting order of two findings
+pub fn compare(a: &Match, b: &Match) -> Ordering {
+    a.file_path
+        .cmp(&b.file_path)
+        .then(a.line_number.cmp(&b.line_number))
+        .then(a.column.cmp(&b.column))
+        .then_with(|| compare_rules(a, b))
+}
+
+/// Sort findings into reporting order
+pub fn sort_matches(matches: &mut [Match]) {
+    matches.sort_by(compare);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::matcher::{PatternMatcher, SecurityPattern};
+    use crate::severity::Severity;
+
+    #[test]
+    fn test_order_independent_of_rule_order() {
+        let rule = |name: &str| {
+            SecurityPattern::new(
+                name.to_string(),
+                r"token_\w+".to_string(),
+                Severity::High,
+                "custom".to_string(),
+                "Test rule".to_string(),
+            )
+        };
+        let code = "x = token_abc\nDEBUG = True\n";
+        let run = |names: &[&str]| {
+            let mut matcher = PatternMatcher::new();
+            for name in names {
+                matcher.add_pattern(rule(name)).unwrap();
+            }
+            matcher
+                .match_content(code)
+                .into_iter()
+                .map(|m| (m.line_number, m.pattern_name))
+                .collect::<Vec<_>>()
+        };
+
+        let forward = run(&["zeta_token", "alpha_token"]);
+        assert_eq!(forward, run(&["alpha_token", "zeta_token"]));
+        assert_eq!(
+            forward,
+            vec![
+                (1, "alpha_token".to_string()),
+                (1, "zeta_token".to_string()),
+                (2, "debug_mode".to_string()),
+            ]
+        );
+    }
+}
@@ -3,10 +3,36 @@
 //! Provides language-aware parsing for extracting security-relevant
 //! code constructs like function calls, imports, and string literals
 
+use crate::assignments;
+use crate::calls;
+use crate::classes::{self, Block};
+use crate::comments;
+use crate::complexity::{self, FunctionComplexity};
+use crate::context::LineIndex;
+use crate::constructs::ParseArtifacts;
+use crate::language;
+use crate::literals;
+use crate::taint;
+#[cfg(feature = "tree-sitter")]
+use crate::treesitter;
 use pyo3::prelude::*;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Words a Java-style `Type name(...)` regex takes for a return type in
+/// statements such as `return check(x);` or `else if (x)`, and in record
+/// declarations
+const STATEMENT_KEYWORDS: &[&str] = &[
+    "return", "new", "throw", "else", "case", "yield", "await", "record",
+];
+
+/// Backend reading code line by line with regexes, for every language
+pub const BACKEND_REGEX: &str = "regex";
+/// Backend walking a syntax tree, with the `tree-sitter` feature and for
+/// the languages `treesitter` has a grammar for
+pub const BACKEND_TREE_SITTER: &str = "tree-sitter";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[pyclass]
@@ -19,6 +45,14 @@ pub struct ParsedFunction {
     pub parameters: Vec<String>,
     #[pyo3(get)]
     pub is_async: bool,
+    /// Decorator or annotation expressions without their `@`, top first
+    #[pyo3(get)]
+    #[serde(default)]
+    pub decorators: Vec<String>,
+    /// Declaration modifiers as written (`public`, `static`), for Java
+    #[pyo3(get)]
+    #[serde(default)]
+    pub modifiers: Vec<String>,
 }
 
 #[pymethods]
@@ -54,6 +88,142 @@ impl ParsedImport {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ParsedClass {
+    #[pyo3(get)]
+    pub name: String,
+    /// Base classes as written, then implemented interfaces
+    #[pyo3(get)]
+    pub bases: Vec<String>,
+    /// Decorator expressions without their `@`, top first
+    #[pyo3(get)]
+    pub decorators: Vec<String>,
+    /// Line of the `class` keyword
+    #[pyo3(get)]
+    pub line_number: usize,
+    /// Last line of the class body
+    #[pyo3(get)]
+    pub end_line: usize,
+}
+
+#[pymethods]
+impl ParsedClass {
+    fn __repr__(&self) -> String {
+        format!(
+            "Class(name={}, bases={:?}, lines={}-{})",
+            self.name, self.bases, self.line_number, self.end_line
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ParsedComment {
+    /// Text without comment markers or docstring quotes
+    #[pyo3(get)]
+    pub text: String,
+    #[pyo3(get)]
+    pub line_number: usize,
+    #[pyo3(get)]
+    pub end_line: usize,
+    /// Byte offset of the comment, markers included, in the parsed content
+    #[pyo3(get)]
+    pub start_byte: usize,
+    /// Byte offset just past the comment
+    #[pyo3(get)]
+    pub end_byte: usize,
+    /// A Python docstring rather than a comment
+    #[pyo3(get)]
+    pub is_docstring: bool,
+}
+
+#[pymethods]
+impl ParsedComment {
+    fn __repr__(&self) -> String {
+        format!(
+            "Comment(lines={}-{}, docstring={}, text={:?})",
+            self.line_number, self.end_line, self.is_docstring, self.text
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ParsedCall {
+    /// Called name as written, dotted for methods (`subprocess.run`), with
+    /// a trailing `!` for Rust macros
+    #[pyo3(get)]
+    pub callee: String,
+    /// Source text between the parentheses
+    #[pyo3(get)]
+    pub arguments: String,
+    #[pyo3(get)]
+    pub line_number: usize,
+    /// 0-based character column where the callee starts
+    #[pyo3(get)]
+    pub column: usize,
+}
+
+#[pymethods]
+impl ParsedCall {
+    fn __repr__(&self) -> String {
+        format!(
+            "Call(callee={}, line={}, column={})",
+            self.callee, self.line_number, self.column
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ParsedAssignment {
+    /// Assigned name as written: `api_key`, `self.secret`, `user, err`
+    #[pyo3(get)]
+    pub name: String,
+    /// Source text of the assigned expression
+    #[pyo3(get)]
+    pub value: String,
+    #[pyo3(get)]
+    pub line_number: usize,
+}
+
+#[pymethods]
+impl ParsedAssignment {
+    fn __repr__(&self) -> String {
+        format!(
+            "Assignment(name={}, line={}, value={:?})",
+            self.name, self.line_number, self.value
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ParsedSource {
+    /// Source variable read (`$_GET`)
+    #[pyo3(get)]
+    pub name: String,
+    /// The read with its subscripts as written (`$_GET['id']`)
+    #[pyo3(get)]
+    pub expression: String,
+    #[pyo3(get)]
+    pub line_number: usize,
+    /// 0-based character column where the read starts
+    #[pyo3(get)]
+    pub column: usize,
+}
+
+#[pymethods]
+impl ParsedSource {
+    fn __repr__(&self) -> String {
+        format!(
+            "Source(expression={}, line={}, column={})",
+            self.expression, self.line_number, self.column
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[pyclass]
 pub struct StringLiteral {
@@ -63,8 +233,30 @@ pub struct StringLiteral {
     pub line_number: usize,
     #[pyo3(get)]
     pub is_multiline: bool,
+    /// Line the literal closes on, `line_number` for a one-line literal
+    #[pyo3(get)]
+    #[serde(default)]
+    pub end_line: usize,
     #[pyo3(get)]
     pub quote_type: String,
+    /// Byte offset of the value, after the opening quote, in the parsed content
+    #[pyo3(get)]
+    #[serde(default)]
+    pub start_byte: usize,
+    /// Byte offset just past the value, before the closing quote
+    #[pyo3(get)]
+    #[serde(default)]
+    pub end_byte: usize,
+    /// Prefix before the opening quote as written (`f`, `rb`, `r`), empty
+    /// for none
+    #[pyo3(get)]
+    #[serde(default)]
+    pub prefix: String,
+    /// Source of each interpolated expression: `{name}` in f-strings,
+    /// `${name}` in template literals
+    #[pyo3(get)]
+    #[serde(default)]
+    pub interpolations: Vec<String>,
 }
 
 /// Code parser for extracting security-relevant constructs
@@ -73,42 +265,153 @@ pub struct CodeParser {
     language: String,
     function_regex: HashMap<String, Regex>,
     import_regex: HashMap<String, Regex>,
+    class_regex: HashMap<String, Regex>,
+    string_regex: Regex,
+    /// Quote string for each capture group of `string_regex`
+    string_quotes: Vec<String>,
+    backend: &'static str,
 }
 
 #[pymethods]
 impl CodeParser {
+    /// Parser for `language`, defaulting to Python; a language the
+    /// syntax-tree backend supports is parsed with it when it is compiled in
     #[new]
     pub fn new(language: Option<String>) -> Self {
+        let backend = match &language {
+            Some(language) if tree_sitter_supports(language) => BACKEND_TREE_SITTER,
+            _ => BACKEND_REGEX,
+        };
         let lang = language.unwrap_or_else(|| "python".to_string());
         let mut parser = CodeParser {
+            backend,
             language: lang.clone(),
             function_regex: HashMap::new(),
             import_regex: HashMap::new(),
+            class_regex: HashMap::new(),
+            string_regex: Regex::new(r#""((?:[^"\\]|\\.)*)"|'((?:[^'\\]|\\.)*)'"#).unwrap(),
+            string_quotes: vec!["\"".to_string(), "'".to_string()],
         };
         parser.compile_patterns(&lang);
         parser
     }
 
+    /// Backends this build can parse with
+    #[staticmethod]
+    pub fn backends() -> Vec<&'static str> {
+        let mut backends = vec![BACKEND_REGEX];
+        if cfg!(feature = "tree-sitter") {
+            backends.push(BACKEND_TREE_SITTER);
+        }
+        backends
+    }
+
+    /// Backend in use, `regex` or `tree-sitter`
+    #[getter]
+    pub fn backend(&self) -> &'static str {
+        self.backend
+    }
+
+    /// Switch backends; raises ValueError for a backend this build lacks or
+    /// that doesn't support the parser's language
+    pub fn set_backend(&mut self, backend: String) -> PyResult<()> {
+        self.backend = match backend.as_str() {
+            BACKEND_REGEX => BACKEND_REGEX,
+            BACKEND_TREE_SITTER if tree_sitter_supports(&self.language) => BACKEND_TREE_SITTER,
+            BACKEND_TREE_SITTER if cfg!(feature = "tree-sitter") => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "No tree-sitter grammar for {}",
+                    self.language
+                )));
+            }
+            BACKEND_TREE_SITTER => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "knox_core was built without the tree-sitter feature",
+                ));
+            }
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown parser backend: {} (expected one of {:?})",
+                    backend,
+                    Self::backends()
+                )));
+            }
+        };
+        Ok(())
+    }
+
     /// Extract all function definitions from code
     pub fn extract_functions(&self, content: &str) -> Vec<ParsedFunction> {
+        #[cfg(feature = "tree-sitter")]
+        if self.backend == BACKEND_TREE_SITTER {
+            if let Some(functions) = treesitter::extract_functions(&self.language, content) {
+                return functions;
+            }
+        }
         let mut functions = Vec::new();
 
         if let Some(regex) = self.function_regex.get(&self.language) {
+            let index = LineIndex::new(content);
+            let typed = regex.capture_names().any(|name| name == Some("type"));
             for (line_num, line) in content.lines().enumerate() {
                 if let Some(captures) = regex.captures(line) {
-                    let is_async = captures
-                        .get(1)
+                    // Registered languages name their groups; built-ins are positional
+                    let group = |name: &str, index: usize| match captures.name("name") {
+                        Some(_) => captures.name(name),
+                        None => captures.get(index),
+                    };
+                    // A declaration has a return type or, for constructors,
+                    // modifiers; `check(x);` and `return check(x);` don't
+                    if typed {
+                        let modifiers = captures.name("modifiers").map_or("", |m| m.as_str());
+                        match captures.name("type") {
+                            Some(t) if STATEMENT_KEYWORDS.contains(&t.as_str()) => continue,
+                            None if modifiers.is_empty() => continue,
+                            _ => {}
+                        }
+                    }
+                    let modifiers: Vec<String> = captures
+                        .name("modifiers")
+                        .map(|m| m.as_str().split_whitespace().map(str::to_string).collect())
+                        .unwrap_or_default();
+                    // Kotlin's coroutines are `suspend` functions
+                    let is_async = group("async", 1)
                         .map(|m| m.as_str().contains("async"))
-                        .unwrap_or(false);
-                    let name = captures.get(2).map(|m| m.as_str()).unwrap_or("").to_string();
-                    let params_str = captures.get(3).map(|m| m.as_str()).unwrap_or("");
-                    let parameters = Self::parse_parameters(params_str);
+                        .unwrap_or(false)
+                        || modifiers.iter().any(|m| m == "suspend");
+                    let name = group("name", 2).map(|m| m.as_str()).unwrap_or("").to_string();
+                    let params_str = group("params", 3).map(|m| m.as_str()).unwrap_or("");
+                    let parameters = match self.language.as_str() {
+                        "java" | "php" => Self::typed_parameters(params_str),
+                        "kotlin" | "swift" => Self::annotated_parameters(params_str),
+                        // Splats, blocks and keyword arguments: `*args`, `&block`, `key:`
+                        "ruby" => Self::parse_parameters(params_str)
+                            .into_iter()
+                            .map(|p| p.trim_start_matches(['*', '&']).to_string())
+                            .filter(|p| !p.is_empty())
+                            .collect(),
+                        _ => Self::parse_parameters(params_str),
+                    };
+                    let mut decorators = classes::decorators_above(&index, line_num + 1);
+                    // Annotations written on the declaration's own line
+                    if let Some(annotations) = captures.name("annotations") {
+                        decorators.extend(
+                            annotations
+                                .as_str()
+                                .split('@')
+                                .map(str::trim)
+                                .filter(|a| !a.is_empty())
+                                .map(str::to_string),
+                        );
+                    }
 
                     functions.push(ParsedFunction {
                         name,
                         line_number: line_num + 1,
                         parameters,
                         is_async,
+                        decorators,
+                        modifiers,
                     });
                 }
             }
@@ -119,20 +422,42 @@ impl CodeParser {
 
     /// Extract all import statements from code
     pub fn extract_imports(&self, content: &str) -> Vec<ParsedImport> {
+        #[cfg(feature = "tree-sitter")]
+        if self.backend == BACKEND_TREE_SITTER {
+            if let Some(imports) = treesitter::extract_imports(&self.language, content) {
+                return imports;
+            }
+        }
+        if self.language == "go" {
+            return Self::go_imports(content);
+        }
         let mut imports = Vec::new();
 
         if let Some(regex) = self.import_regex.get(&self.language) {
+            let named = regex.capture_names().any(|name| name == Some("module"));
             for (line_num, line) in content.lines().enumerate() {
                 if let Some(captures) = regex.captures(line) {
-                    let module = captures.get(1).map(|m| m.as_str()).unwrap_or("").to_string();
-                    let items_str = captures.get(2).map(|m| m.as_str()).unwrap_or("");
+                    let group = |name: &str, index: usize| {
+                        if named {
+                            captures.name(name)
+                        } else {
+                            captures.get(index)
+                        }
+                    };
+                    // PHP's `include`/`require` name a path instead, as
+                    // written unless it is a single literal
+                    let module = match captures.name("path") {
+                        Some(path) => Self::unquoted(path.as_str().trim()).to_string(),
+                        None => group("module", 1).map(|m| m.as_str()).unwrap_or("").to_string(),
+                    };
+                    let items_str = group("items", 2).map(|m| m.as_str()).unwrap_or("");
                     let is_wildcard = items_str.contains('*');
                     let items = if is_wildcard {
                         vec!["*".to_string()]
                     } else {
                         items_str
                             .split(',')
-                            .map(|s| s.trim().to_string())
+                            .map(|s| s.trim().trim_matches(['{', '}']).trim().to_string())
                             .filter(|s| !s.is_empty())
                             .collect()
                     };
@@ -152,43 +477,190 @@ impl CodeParser {
 
     /// Extract all string literals from code
     pub fn extract_strings(&self, content: &str) -> Vec<StringLiteral> {
+        #[cfg(feature = "tree-sitter")]
+        if self.backend == BACKEND_TREE_SITTER {
+            if let Some(strings) = treesitter::extract_strings(&self.language, content) {
+                return strings;
+            }
+        }
+        if literals::supports(&self.language) {
+            return literals::scan_strings(content, &self.language);
+        }
         let mut strings = Vec::new();
-        let string_regex = Regex::new(r#"(['"])((?:[^\1\\]|\\.)*)(\1)"#).unwrap();
 
-        for (line_num, line) in content.lines().enumerate() {
-            for capture in string_regex.captures_iter(line) {
-                let quote_type = capture.get(1).map(|m| m.as_str()).unwrap_or("\"");
-                let value = capture.get(2).map(|m| m.as_str()).unwrap_or("");
+        let mut line_start = 0;
+        for (line_num, raw_line) in content.split_inclusive('\n').enumerate() {
+            let line = raw_line.trim_end_matches('\n').trim_end_matches('\r');
+            for capture in self.string_regex.captures_iter(line) {
+                let Some((quote_type, value)) = self
+                    .string_quotes
+                    .iter()
+                    .enumerate()
+                    .find_map(|(i, quote)| Some((quote, capture.get(i + 1)?)))
+                else {
+                    continue;
+                };
 
                 strings.push(StringLiteral {
-                    value: value.to_string(),
+                    value: value.as_str().to_string(),
                     line_number: line_num + 1,
                     is_multiline: false,
+                    end_line: line_num + 1,
                     quote_type: quote_type.to_string(),
+                    start_byte: line_start + value.start(),
+                    end_byte: line_start + value.end(),
+                    prefix: String::new(),
+                    interpolations: Vec::new(),
                 });
             }
+            line_start += raw_line.len();
         }
 
         strings
     }
 
+    /// Extract all class definitions from code, nested classes included
+    pub fn extract_classes(&self, content: &str) -> Vec<ParsedClass> {
+        #[cfg(feature = "tree-sitter")]
+        if self.backend == BACKEND_TREE_SITTER {
+            if let Some(classes) = treesitter::extract_classes(&self.language, content) {
+                return classes;
+            }
+        }
+        let Some(regex) = self.class_regex.get(&self.language) else {
+            return Vec::new();
+        };
+        let block = match self.language.as_str() {
+            "python" => Block::Indent,
+            _ => Block::Braces,
+        };
+        classes::scan_classes(content, regex, block, &self.extract_strings(content))
+    }
+
+    /// Extract all comments and docstrings from code
+    pub fn extract_comments(&self, content: &str) -> Vec<ParsedComment> {
+        #[cfg(feature = "tree-sitter")]
+        if self.backend == BACKEND_TREE_SITTER {
+            if let Some(comments) = treesitter::extract_comments(&self.language, content) {
+                return comments;
+            }
+        }
+        comments::scan_comments(content, &self.language)
+    }
+
+    /// Extract every call expression from code, nested calls included
+    pub fn extract_calls(&self, content: &str) -> Vec<ParsedCall> {
+        #[cfg(feature = "tree-sitter")]
+        if self.backend == BACKEND_TREE_SITTER {
+            if let Some(calls) = treesitter::extract_calls(&self.language, content) {
+                return calls;
+            }
+        }
+        let comments = language::line_comment_markers(&self.language);
+        calls::scan_calls(content, &self.extract_strings(content), &comments)
+    }
+
+    /// Extract every variable, attribute and constant assignment from code;
+    /// augmented assignments (`+=`) are left out
+    pub fn extract_assignments(&self, content: &str) -> Vec<ParsedAssignment> {
+        #[cfg(feature = "tree-sitter")]
+        if self.backend == BACKEND_TREE_SITTER {
+            if let Some(assignments) = treesitter::extract_assignments(&self.language, content) {
+                return assignments;
+            }
+        }
+        assignments::scan_assignments(
+            content,
+            &self.extract_strings(content),
+            &self.extract_comments(content),
+        )
+    }
+
+    /// Extract every read of request data (PHP superglobals), for
+    /// languages with known taint sources
+    pub fn extract_sources(&self, content: &str) -> Vec<ParsedSource> {
+        taint::scan_sources(
+            content,
+            &self.language,
+            &self.extract_strings(content),
+            &self.extract_comments(content),
+        )
+    }
+
+    /// Extract every construct kind at once, for `PatternMatcher.match_constructs`
+    pub fn parse(&self, content: &str) -> ParseArtifacts {
+        ParseArtifacts {
+            functions: self.extract_functions(content),
+            imports: self.extract_imports(content),
+            strings: self.extract_strings(content),
+            calls: self.extract_calls(content),
+            classes: self.extract_classes(content),
+            comments: self.extract_comments(content),
+            assignments: self.extract_assignments(content),
+            sources: self.extract_sources(content),
+        }
+    }
+
     /// Analyze code complexity
     pub fn analyze_complexity(&self, content: &str) -> PyResult<HashMap<String, usize>> {
         let mut metrics = HashMap::new();
 
+        let comments = language::line_comment_markers(&self.language);
         metrics.insert("total_lines".to_string(), content.lines().count());
         metrics.insert(
             "code_lines".to_string(),
             content
                 .lines()
-                .filter(|l| !l.trim().is_empty() && !l.trim().starts_with('#'))
+                .map(str::trim)
+                .filter(|l| !l.is_empty() && !comments.iter().any(|c| l.starts_with(c.as_str())))
                 .count(),
         );
         metrics.insert("functions".to_string(), self.extract_functions(content).len());
         metrics.insert("imports".to_string(), self.extract_imports(content).len());
+        let functions = self.analyze_functions(content);
+        metrics.insert(
+            "max_complexity".to_string(),
+            functions
+                .iter()
+                .map(|f| f.cyclomatic)
+                .max()
+                .unwrap_or(0),
+        );
+        metrics.insert(
+            "max_nesting_depth".to_string(),
+            functions
+                .iter()
+                .map(|f| f.nesting_depth)
+                .max()
+                .unwrap_or(0),
+        );
 
         Ok(metrics)
     }
+
+    /// Cyclomatic complexity and nesting depth of every function, nested
+    /// ones included
+    pub fn analyze_functions(&self, content: &str) -> Vec<FunctionComplexity> {
+        complexity::analyze_functions(
+            content,
+            &self.language,
+            &self.extract_functions(content),
+            &self.extract_strings(content),
+            &self.extract_comments(content),
+        )
+    }
+}
+
+/// Whether the syntax-tree backend is compiled in and has a grammar for
+/// `language`
+fn tree_sitter_supports(language: &str) -> bool {
+    #[cfg(feature = "tree-sitter")]
+    return treesitter::supports(language);
+    #[cfg(not(feature = "tree-sitter"))]
+    {
+        let _ = language;
+        false
+    }
 }
 
 impl CodeParser {
@@ -205,6 +677,11 @@ impl CodeParser {
                     Regex::new(r"^\s*(?:from\s+([a-zA-Z0-9_.]+)\s+)?import\s+(.+)")
                         .unwrap(),
                 );
+                self.class_regex.insert(
+                    language.to_string(),
+                    Regex::new(r"^\s*class\s+(?P<name>[a-zA-Z_][a-zA-Z0-9_]*)\s*(?:\((?P<bases>.*)\))?\s*:")
+                        .unwrap(),
+                );
             }
             "javascript" | "typescript" => {
                 self.function_regex.insert(
@@ -216,7 +693,117 @@ impl CodeParser {
                 );
                 self.import_regex.insert(
                     language.to_string(),
-                    Regex::new(r"^\s*import\s+(?:\{([^}]+)\}|([a-zA-Z_$][a-zA-Z0-9_$]*))\s+from")
+                    Regex::new(concat!(
+                        r#"^\s*import\s+(?:(?P<items>[^'"]*?)\s+from\s+)?['"](?P<module>[^'"]+)['"]"#,
+                        r#"|\brequire\s*\(\s*(?P<path>'[^']*'|"[^"]*")\s*\)"#,
+                    ))
+                    .unwrap(),
+                );
+                self.class_regex.insert(
+                    language.to_string(),
+                    Regex::new(concat!(
+                        r"^\s*(?:export\s+)?(?:default\s+)?(?:abstract\s+)?class\s+(?P<name>[a-zA-Z_$][a-zA-Z0-9_$]*)",
+                        r"(?:\s*<[^{]*?>)?(?:\s+extends\s+(?P<bases>[a-zA-Z0-9_$.]+(?:<[^{]*?>)?))?",
+                        r"(?:\s+implements\s+(?P<implements>[^{]+?))?\s*\{",
+                    ))
+                    .unwrap(),
+                );
+            }
+            "java" => {
+                self.function_regex.insert(
+                    language.to_string(),
+                    Regex::new(concat!(
+                        r"^\s*(?P<annotations>(?:@[\w.]+(?:\([^)]*\))?\s+)*)",
+                        r"(?P<modifiers>(?:(?:public|protected|private|static|final|abstract|synchronized|native|default|strictfp)\s+)*)",
+                        r"(?:<[^>]*>\s+)?(?:(?P<type>[\w$.]+(?:<[^(){};=]*>)?(?:\[\])*)\s+)?",
+                        r"(?P<name>[a-zA-Z_$][\w$]*)\s*\((?P<params>(?:[^()]|\([^()]*\))*)",
+                        // Parameters continued on the next line are left out
+                        r"(?:\)\s*(?:throws\s+[\w$.,\s]+?)?\s*(?:\{|;|$)|,\s*$)",
+                    ))
+                    .unwrap(),
+                );
+                self.import_regex.insert(
+                    language.to_string(),
+                    Regex::new(r"^\s*import\s+(?:static\s+)?(?P<module>[\w$.]+)\.(?P<items>[\w$]+|\*)\s*;")
+                        .unwrap(),
+                );
+                self.class_regex.insert(
+                    language.to_string(),
+                    Regex::new(concat!(
+                        r"^\s*(?:@[\w.]+(?:\([^)]*\))?\s+)*(?:(?:public|protected|private|static|final|abstract|sealed|non-sealed|strictfp)\s+)*",
+                        r"(?:class|interface|enum|record|@interface)\s+(?P<name>[a-zA-Z_$][\w$]*)(?:\s*<[^{]*?>)?(?:\s*\([^)]*\))?",
+                        r"(?:\s+extends\s+(?P<bases>[^{]+?))?(?:\s+implements\s+(?P<implements>[^{]+?))?",
+                        r"(?:\s+permits\s+[^{]+?)?\s*\{",
+                    ))
+                    .unwrap(),
+                );
+            }
+            "php" => {
+                self.function_regex.insert(
+                    language.to_string(),
+                    Regex::new(concat!(
+                        r"^\s*(?P<modifiers>(?:(?:public|protected|private|static|final|abstract)\s+)*)",
+                        r"function\s+&?(?P<name>[a-zA-Z_]\w*)\s*\((?P<params>[^)]*)\)?",
+                    ))
+                    .unwrap(),
+                );
+                self.import_regex.insert(
+                    language.to_string(),
+                    Regex::new(concat!(
+                        r"^\s*(?:(?:\$\w+\s*=\s*)?(?:include|require)(?:_once)?\b\s*\(?\s*(?P<path>[^;]+?)\s*\)?\s*;",
+                        r"|use\s+(?:function\s+|const\s+)?(?:(?P<module>[\w\\]+?)\\)?\{?(?P<items>[^;{}\\]+)\}?\s*;)",
+                    ))
+                    .unwrap(),
+                );
+            }
+            "ruby" => {
+                self.function_regex.insert(
+                    language.to_string(),
+                    Regex::new(concat!(
+                        r"^\s*(?P<modifiers>(?:(?:private|protected|public|module_function)\s+)*)",
+                        r"def\s+(?:self\.)?(?P<name>[a-zA-Z_]\w*[?!=]?)",
+                        // Parenthesized or not; `def value = 1` has none
+                        r"(?:\(|[ \t]+)(?P<params>[^)#;\n=][^)#;\n]*)?",
+                    ))
+                    .unwrap(),
+                );
+                self.import_regex.insert(
+                    language.to_string(),
+                    Regex::new(r"^\s*(?:require|require_relative|load)\b\s*\(?\s*(?P<path>[^)#;\n]+?)\s*\)?\s*(?:[#;].*)?$")
+                        .unwrap(),
+                );
+            }
+            "kotlin" => {
+                self.function_regex.insert(
+                    language.to_string(),
+                    Regex::new(concat!(
+                        r"^\s*(?P<annotations>(?:@[\w.]+(?:\([^)]*\))?\s+)*)",
+                        r"(?P<modifiers>(?:(?:public|private|protected|internal|override|open|abstract|final|suspend|inline|operator|infix|tailrec|external|actual|expect)\s+)*)",
+                        // Extension functions name their receiver: `fun String.isEmail()`
+                        r"fun\s+(?:<[^>]*>\s*)?(?:[\w.<>?, ]+\.)?(?P<name>[a-zA-Z_]\w*)\s*\((?P<params>(?:[^()]|\([^()]*\))*)",
+                    ))
+                    .unwrap(),
+                );
+                self.import_regex.insert(
+                    language.to_string(),
+                    Regex::new(r"^\s*import\s+(?P<module>[\w.]+)\.(?P<items>\*|\w+(?:\s+as\s+\w+)?)\s*;?\s*$")
+                        .unwrap(),
+                );
+            }
+            "swift" => {
+                self.function_regex.insert(
+                    language.to_string(),
+                    Regex::new(concat!(
+                        r"^\s*(?P<annotations>(?:@\w+(?:\([^)]*\))?\s+)*)",
+                        r"(?P<modifiers>(?:(?:public|private|fileprivate|internal|open|static|class|final|override|mutating|nonmutating|convenience|required|dynamic|nonisolated)\s+)*)",
+                        r"func\s+(?P<name>[a-zA-Z_]\w*)\s*(?:<[^>]*>)?\s*\((?P<params>(?:[^()]|\([^()]*\))*)",
+                        r"(?:\)\s*(?P<async>async)?)?",
+                    ))
+                    .unwrap(),
+                );
+                self.import_regex.insert(
+                    language.to_string(),
+                    Regex::new(r"^\s*(?:@\w+\s+)*import\s+(?:(?:typealias|struct|class|enum|protocol|let|var|func)\s+)?(?P<module>[\w.]+)")
                         .unwrap(),
                 );
             }
@@ -231,10 +818,136 @@ impl CodeParser {
                     Regex::new(r"^\s*use\s+([a-zA-Z0-9_:]+)(?:::\{([^}]+)\})?").unwrap(),
                 );
             }
-            _ => {}
+            _ => {
+                let Some(spec) = language::registered_spec(language) else {
+                    return;
+                };
+                // Patterns were validated when the language was registered
+                let compiled =
+                    |pattern: &Option<String>| pattern.as_deref().and_then(|p| Regex::new(p).ok());
+                if let Some(regex) = compiled(&spec.function_pattern) {
+                    self.function_regex.insert(language.to_string(), regex);
+                }
+                if let Some(regex) = compiled(&spec.import_pattern) {
+                    self.import_regex.insert(language.to_string(), regex);
+                }
+                if let Some(regex) = spec.string_regex() {
+                    self.string_regex = regex;
+                    self.string_quotes = spec
+                        .string_delimiters
+                        .into_iter()
+                        .filter(|d| !d.is_empty())
+                        .collect();
+                }
+            }
         }
     }
 
+    /// `text` without its quotes when it is a single string literal
+    /// Go imports, including the specs of an `import ( ... )` block, which
+    /// carry no `import` keyword of their own
+    fn go_imports(content: &str) -> Vec<ParsedImport> {
+        static SPEC: OnceLock<Regex> = OnceLock::new();
+        let spec = SPEC.get_or_init(|| {
+            Regex::new(r#"^\s*(?:(?P<alias>[\w.]+)\s+)?"(?P<module>[^"]+)"\s*(?://.*)?$"#).unwrap()
+        });
+        let parse = |text: &str, line_number: usize| {
+            let captures = spec.captures(text)?;
+            let alias = captures.name("alias").map_or("", |a| a.as_str());
+            Some(ParsedImport {
+                module: captures["module"].to_string(),
+                items: match alias {
+                    "" | "_" => Vec::new(),
+                    "." => vec!["*".to_string()],
+                    alias => vec![alias.to_string()],
+                },
+                line_number,
+                is_wildcard: alias == ".",
+            })
+        };
+
+        let mut imports = Vec::new();
+        let mut in_block = false;
+        for (i, line) in content.lines().enumerate() {
+            let trimmed = line.trim();
+            if in_block {
+                match trimmed.strip_prefix(')') {
+                    Some(_) => in_block = false,
+                    None => imports.extend(parse(trimmed, i + 1)),
+                }
+                continue;
+            }
+            let Some(rest) = trimmed.strip_prefix("import") else {
+                continue;
+            };
+            match rest.trim_start().strip_prefix('(') {
+                // `import ("fmt"; "os")` on one line, or the start of a block
+                Some(block) => match block.split_once(')') {
+                    Some((specs, _)) => {
+                        imports.extend(specs.split(';').filter_map(|s| parse(s, i + 1)))
+                    }
+                    None => in_block = true,
+                },
+                None if rest.starts_with(char::is_whitespace) => imports.extend(parse(rest, i + 1)),
+                None => {}
+            }
+        }
+        imports
+    }
+
+    fn unquoted(text: &str) -> &str {
+        ['\'', '"']
+            .into_iter()
+            .find_map(|quote| {
+                let inner = text.strip_prefix(quote)?.strip_suffix(quote)?;
+                (!inner.contains(quote)).then_some(inner)
+            })
+            .unwrap_or(text)
+    }
+
+    /// Last word of each parameter in a list split at commas outside
+    /// brackets, taken from the text before `end` (the type or default)
+    fn last_words(params_str: &str, end: char) -> Vec<&str> {
+        let mut words = Vec::new();
+        let mut depth = 0usize;
+        let mut start = 0;
+        for (i, c) in params_str.char_indices().chain([(params_str.len(), ',')]) {
+            match c {
+                '<' | '(' => depth += 1,
+                '>' | ')' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    let before = params_str[start..i].split(end).next().unwrap_or_default();
+                    words.push(before.split_whitespace().last().unwrap_or_default());
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        words
+    }
+
+    /// Names from a `Type name` parameter list: the last word of each
+    /// parameter before its default, whatever annotations, modifiers and
+    /// generic types precede it (PHP's keep their `$`)
+    fn typed_parameters(params_str: &str) -> Vec<String> {
+        Self::last_words(params_str, '=')
+            .into_iter()
+            .map(|name| name.trim_end_matches("[]").trim_start_matches(['.', '&']))
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Names from a `name: Type` parameter list, after any annotations,
+    /// Kotlin's `vararg` or a Swift argument label
+    fn annotated_parameters(params_str: &str) -> Vec<String> {
+        Self::last_words(params_str, ':')
+            .into_iter()
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
     fn parse_parameters(params_str: &str) -> Vec<String> {
         params_str
             .split(',')
@@ -287,6 +1000,34 @@ async def fetch_data(url):
         assert!(imports.len() >= 2);
     }
 
+    #[test]
+    fn test_go_and_javascript_import_extraction() {
+        let go = CodeParser::new(Some("go".to_string()));
+        let code = "import (\n\t\"fmt\"\n\t_ \"embed\"\n\tx \"os/exec\" // run\n)\nimport \"net/http\"\n";
+        let imports: Vec<(String, Vec<String>, usize)> = go
+            .extract_imports(code)
+            .into_iter()
+            .map(|i| (i.module, i.items, i.line_number))
+            .collect();
+        assert_eq!(
+            imports,
+            vec![
+                ("fmt".to_string(), vec![], 2),
+                ("embed".to_string(), vec![], 3),
+                ("os/exec".to_string(), vec!["x".to_string()], 4),
+                ("net/http".to_string(), vec![], 6),
+            ]
+        );
+
+        let mut js = CodeParser::new(Some("javascript".to_string()));
+        js.set_backend(BACKEND_REGEX.to_string()).unwrap();
+        let code = "import express, { Router } from \"express\";\nconst cp = require('child_process');\n";
+        let imports = js.extract_imports(code);
+        assert_eq!(imports[0].module, "express");
+        assert_eq!(imports[0].items, vec!["express", "Router"]);
+        assert_eq!(imports[1].module, "child_process");
+    }
+
     #[test]
     fn test_string_extraction() {
         let parser = CodeParser::new(None);
@@ -295,4 +1036,234 @@ async def fetch_data(url):
         assert!(!strings.is_empty());
         assert!(strings[0].value.contains("sk-"));
     }
+
+    #[test]
+    fn test_registered_language_parsing() {
+        language::register(language::LanguageSpec::new(
+            "scala".to_string(),
+            vec!["scala".to_string()],
+            Some(vec!["//".to_string()]),
+            Some(vec!["\"\"\"".to_string(), "\"".to_string()]),
+            Some(r"^\s*def\s+(?P<name>\w+)\s*\((?P<params>[^)]*)\)".to_string()),
+            Some(r"^\s*import\s+(?P<module>[\w.]+)".to_string()),
+        ))
+        .unwrap();
+
+        let parser = CodeParser::new(Some("scala".to_string()));
+        let code =
+            "import scala.sys.process\n// helper\ndef run(cmd: String, env: Map) = \"\"\"sh -c\"\"\"\n";
+        let functions = parser.extract_functions(code);
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].name, "run");
+        assert_eq!(functions[0].parameters, vec!["cmd", "env"]);
+        assert_eq!(parser.extract_imports(code)[0].module, "scala.sys.process");
+
+        let strings = parser.extract_strings(code);
+        assert_eq!(strings.len(), 1);
+        assert_eq!(strings[0].value, "sh -c");
+        assert_eq!(strings[0].quote_type, "\"\"\"");
+        assert_eq!(parser.analyze_complexity(code).unwrap()["code_lines"], 2);
+    }
+
+    #[test]
+    fn test_java_parsing() {
+        let parser = CodeParser::new(Some("java".to_string()));
+        let code = concat!(
+            "import java.util.*;\n",
+            "import static org.owasp.Encoder.forHtml;\n",
+            "@RestController\n",
+            "public class UserController extends Base implements Api, Serializable {\n",
+            "    public UserController(Repo repo) { super(repo); }\n",
+            "    @Override\n",
+            "    @GetMapping(\"/users\") public static <T> List<T> find(@RequestParam(\"q\") String q,\n",
+            "            Map<String, Integer> limits, int... ids) throws IOException {\n",
+            "        if (q.isEmpty()) return load(q);\n",
+            "        String sql = \"\"\"\n",
+            "            SELECT * FROM users WHERE name = '%s'\n",
+            "            \"\"\".formatted(q);\n",
+            "        return query(sql);\n",
+            "    }\n",
+            "    private void audit(String event);\n",
+            "}\n",
+        );
+
+        let functions = parser.extract_functions(code);
+        let names: Vec<(&str, usize)> = functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.line_number))
+            .collect();
+        assert_eq!(names, vec![("UserController", 5), ("find", 7), ("audit", 15)]);
+        assert_eq!(functions[1].modifiers, vec!["public", "static"]);
+        assert_eq!(
+            functions[1].decorators,
+            vec!["Override", "GetMapping(\"/users\")"]
+        );
+        assert_eq!(functions[1].parameters, vec!["q"]);
+        assert_eq!(functions[2].parameters, vec!["event"]);
+        assert_eq!(
+            CodeParser::typed_parameters(
+                "Map<String, Integer> limits, final int[] ids, String... rest"
+            ),
+            vec!["limits", "ids", "rest"]
+        );
+
+        let imports = parser.extract_imports(code);
+        assert_eq!(
+            (imports[0].module.as_str(), imports[0].is_wildcard),
+            ("java.util", true)
+        );
+        assert_eq!(imports[1].module, "org.owasp.Encoder");
+        assert_eq!(imports[1].items, vec!["forHtml"]);
+
+        let classes = parser.extract_classes(code);
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[0].bases, vec!["Base", "Api", "Serializable"]);
+        assert_eq!((classes[0].line_number, classes[0].end_line), (4, 16));
+
+        let strings = parser.extract_strings(code);
+        assert_eq!(strings[2].quote_type, "\"\"\"");
+        assert_eq!((strings[2].line_number, strings[2].end_line), (10, 12));
+        assert_eq!(parser.analyze_complexity(code).unwrap()["functions"], 3);
+    }
+
+    #[test]
+    fn test_ruby_parsing() {
+        let parser = CodeParser::new(Some("ruby".to_string()));
+        let code = concat!(
+            "require 'json'\n",
+            "require_relative \"../lib/db\" # local\n",
+            "class UsersController < ApplicationController\n",
+            "  def self.find_by(name, *rest, limit: 10, &block)\n",
+            "    sql = <<~SQL.strip + \" LIMIT #{limit}\"\n",
+            "      SELECT * FROM users\n",
+            "      WHERE name = '#{name}'\n",
+            "    SQL\n",
+            "    items << value\n",
+            "    run(<<-'A', <<B)\n",
+            "raw #{x}\n",
+            "    A\n",
+            "b\n",
+            "B\n",
+            "  end\n",
+            "  private def token = ENV.fetch(\"TOKEN\")\n",
+            "  def update name, value = nil\n",
+            "=begin\n",
+            "  def legacy(x)\n",
+            "=end\n",
+            "end\n",
+        );
+
+        let functions = parser.extract_functions(code);
+        let names: Vec<(&str, usize)> = functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.line_number))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("find_by", 4), ("token", 16), ("update", 17), ("legacy", 19)]
+        );
+        assert_eq!(functions[0].parameters, vec!["name", "rest", "limit", "block"]);
+        assert!(functions[1].parameters.is_empty());
+        assert_eq!(functions[1].modifiers, vec!["private"]);
+        assert_eq!(functions[2].parameters, vec!["name", "value"]);
+
+        let modules: Vec<String> = parser
+            .extract_imports(code)
+            .into_iter()
+            .map(|i| i.module)
+            .collect();
+        assert_eq!(modules, vec!["json", "../lib/db"]);
+
+        let strings = parser.extract_strings(code);
+        let literals: Vec<(&str, &str, usize, usize)> = strings
+            .iter()
+            .map(|s| (s.quote_type.as_str(), s.value.as_str(), s.line_number, s.end_line))
+            .collect();
+        assert_eq!(
+            literals,
+            vec![
+                ("'", "json", 1, 1),
+                ("\"", "../lib/db", 2, 2),
+                (
+                    "<<~SQL",
+                    "      SELECT * FROM users\n      WHERE name = '#{name}'\n",
+                    5,
+                    8
+                ),
+                ("\"", " LIMIT #{limit}", 5, 5),
+                ("<<-'A'", "raw #{x}\n", 10, 12),
+                ("<<B", "b\n", 10, 14),
+                ("\"", "TOKEN", 16, 16),
+            ]
+        );
+        assert_eq!(strings[2].interpolations, vec!["name"]);
+        assert_eq!(strings[3].interpolations, vec!["limit"]);
+        assert!(strings[4].interpolations.is_empty());
+        let comments = parser.extract_comments(code);
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[1].text, "def legacy(x)");
+    }
+
+    #[test]
+    fn test_kotlin_and_swift_parsing() {
+        let kotlin = CodeParser::new(Some("kotlin".to_string()));
+        let code = concat!(
+            "import okhttp3.OkHttpClient\n",
+            "import kotlinx.coroutines.*\n",
+            "import java.security.MessageDigest as Digest\n",
+            "@GET(\"users/{id}\") override suspend fun fetch(@Path(\"id\") id: String, headers: Map<String, String> = emptyMap()): User\n",
+            "fun <T> List<T>.second(vararg fallback: T) = this[1]\n",
+        );
+        let functions = kotlin.extract_functions(code);
+        assert_eq!(functions.len(), 2);
+        assert_eq!(functions[0].name, "fetch");
+        assert_eq!(functions[0].parameters, vec!["id", "headers"]);
+        assert!(functions[0].is_async);
+        assert_eq!(functions[0].modifiers, vec!["override", "suspend"]);
+        assert_eq!(functions[0].decorators, vec!["GET(\"users/{id}\")"]);
+        assert_eq!(functions[1].name, "second");
+        assert_eq!(functions[1].parameters, vec!["fallback"]);
+        let imports: Vec<(String, Vec<String>, bool)> = kotlin
+            .extract_imports(code)
+            .into_iter()
+            .map(|i| (i.module, i.items, i.is_wildcard))
+            .collect();
+        assert_eq!(
+            imports,
+            vec![
+                ("okhttp3".to_string(), vec!["OkHttpClient".to_string()], false),
+                ("kotlinx.coroutines".to_string(), vec!["*".to_string()], true),
+                (
+                    "java.security".to_string(),
+                    vec!["MessageDigest as Digest".to_string()],
+                    false
+                ),
+            ]
+        );
+
+        let swift = CodeParser::new(Some("swift".to_string()));
+        let code = concat!(
+            "import Foundation\n",
+            "@testable import struct Security.SecKey\n",
+            "\n",
+            "@MainActor\n",
+            "@objc public static func load(from url: URL, _ key: String, completion: @escaping (Data?) -> Void) async throws -> Data {\n",
+            "  func helper() {}\n",
+        );
+        let functions = swift.extract_functions(code);
+        let names: Vec<(&str, bool)> = functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.is_async))
+            .collect();
+        assert_eq!(names, vec![("load", true), ("helper", false)]);
+        assert_eq!(functions[0].parameters, vec!["url", "key", "completion"]);
+        assert_eq!(functions[0].modifiers, vec!["public", "static"]);
+        assert_eq!(functions[0].decorators, vec!["MainActor", "objc"]);
+        let modules: Vec<String> = swift
+            .extract_imports(code)
+            .into_iter()
+            .map(|i| i.module)
+            .collect();
+        assert_eq!(modules, vec!["Foundation", "Security.SecKey"]);
+    }
 }
@@ -0,0 +1,155 @@
+//! Deployment-relevance classification of file paths
+//!
+//! Classifies a path as prod, staging or dev from its directory names and
+//! shifts finding severity accordingly, so a debug flag in `examples/`
+//! doesn't carry the same weight as one in `deploy/`
+
+use crate::severity::Severity;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct PathClassifier {
+    /// (class, directory or file-stem names, severity steps), checked in order
+    classes: Vec<(String, Vec<String>, i8)>,
+    /// Paths are classified relative to this root when set
+    root: Option<PathBuf>,
+}
+
+#[pymethods]
+impl PathClassifier {
+    #[new]
+    pub fn new() -> Self {
+        let names = |list: &[&str]| list.iter().map(|n| n.to_string()).collect();
+        PathClassifier {
+            classes: vec![
+                (
+                    "dev".to_string(),
+                    names(&[
+                        "test", "tests", "spec", "examples", "example", "samples", "docs",
+                        "fixtures", "demo", "scripts",
+                    ]),
+                    -1,
+                ),
+                ("staging".to_string(), names(&["staging", "stage", "qa"]), 0),
+                (
+                    "prod".to_string(),
+                    names(&["prod", "production", "deploy", "release", "k8s", "helm"]),
+                    1,
+                ),
+            ],
+            root: None,
+        }
+    }
+
+    /// Classify paths containing a directory (or file stem) with this name,
+    /// compared case-insensitively
+    pub fn add_name(&mut self, class: &str, name: String) {
+        let name = name.to_lowercase();
+        match self.classes.iter_mut().find(|(c, _, _)| c == class) {
+            Some((_, names, _)) => names.push(name),
+            None => self.classes.push((class.to_string(), vec![name], 0)),
+        }
+    }
+
+    /// Number of severity levels to raise (positive) or lower (negative) findings by
+    pub fn set_adjustment(&mut self, class: &str, steps: i8) {
+        match self.classes.iter_mut().find(|(c, _, _)| c == class) {
+            Some((_, _, adjustment)) => *adjustment = steps,
+            None => self.classes.push((class.to_string(), vec![], steps)),
+        }
+    }
+
+    /// Class of a path, or None when no rule applies
+    pub fn classify(&self, path: &str) -> Option<String> {
+        let path = Path::new(path);
+        let relative = self
+            .root
+            .as_deref()
+            .and_then(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path);
+
+        let components: Vec<String> = relative
+            .iter()
+            .map(|c| {
+                Path::new(c)
+                    .file_stem()
+                    .unwrap_or(c)
+                    .to_string_lossy()
+                    .to_lowercase()
+            })
+            .collect();
+
+        self.classes
+            .iter()
+            .find(|(_, names, _)| components.iter().any(|c| names.contains(c)))
+            .map(|(class, _, _)| class.clone())
+    }
+}
+
+impl PathClassifier {
+    pub fn with_root(mut self, root: &Path) -> Self {
+        self.root = Some(root.to_path_buf());
+        self
+    }
+
+    /// Severity after applying the adjustment for `class`
+    pub fn adjust_severity(&self, class: &str, severity: Severity) -> Severity {
+        let steps = self
+            .classes
+            .iter()
+            .find(|(c, _, _)| c == class)
+            .map_or(0, |(_, _, steps)| *steps);
+        severity.shifted(steps)
+    }
+}
+
+impl Default for PathClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_paths() {
+        let mut classifier = PathClassifier::new().with_root(Path::new("/repo"));
+        assert_eq!(
+            classifier.classify("/repo/examples/app.py").as_deref(),
+            Some("dev")
+        );
+        assert_eq!(
+            classifier.classify("/repo/deploy/settings.py").as_deref(),
+            Some("prod")
+        );
+        assert_eq!(classifier.classify("/repo/src/app.py"), None);
+
+        classifier.add_name("prod", "Live".to_string());
+        assert_eq!(
+            classifier.classify("/repo/live/app.py").as_deref(),
+            Some("prod")
+        );
+    }
+
+    #[test]
+    fn test_adjust_severity_clamps() {
+        let classifier = PathClassifier::new();
+        assert_eq!(
+            classifier.adjust_severity("dev", Severity::Medium),
+            Severity::Low
+        );
+        assert_eq!(
+            classifier.adjust_severity("prod", Severity::Critical),
+            Severity::Critical
+        );
+        assert_eq!(
+            classifier.adjust_severity("staging", Severity::High),
+            Severity::High
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! Glob-based file selection for directory walks
+//!
+//! Extensions and user include/exclude globs are compiled once into
+//! `GlobSet`s, so each directory entry is tested against a single automaton
+//! instead of formatting and comparing strings. Globs without a `/` match
+//! the file name at any depth (`Dockerfile*`, `*.min.js`).
+
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::Path;
+
+pub struct PathFilter {
+    include: GlobSet,
+    exclude: GlobSet,
+}
+
+impl PathFilter {
+    /// Build a filter from extensions (".py") and include/exclude globs
+    pub fn new(
+        extensions: &[String],
+        include: &[String],
+        exclude: &[String],
+    ) -> Result<Self, globset::Error> {
+        let extension_globs: Vec<String> = extensions
+            .iter()
+            .map(|e| format!("*{}", globset::escape(e)))
+            .collect();
+        Ok(PathFilter {
+            include: build_set(extension_globs.iter().chain(include))?,
+            exclude: build_set(exclude.iter())?,
+        })
+    }
+
+    /// Whether a file is selected by an extension or include glob
+    pub fn is_included(&self, path: &Path) -> bool {
+        self.include.is_match(path)
+    }
+
+    /// Whether a file or directory matches an exclude glob
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        !self.exclude.is_empty() && self.exclude.is_match(path)
+    }
+}
+
+/// Compile a single glob, anchoring slash-free globs to the file name
+pub fn compile_glob(pattern: &str) -> Result<Glob, globset::Error> {
+    let anchored = if pattern.contains('/') {
+        pattern.to_string()
+    } else {
+        format!("**/{}", pattern)
+    };
+    GlobBuilder::new(&anchored).literal_separator(true).build()
+}
+
+fn build_set<'a>(patterns: impl Iterator<Item = &'a String>) -> Result<GlobSet, globset::Error> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(compile_glob(pattern)?);
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_include_and_exclude_globs() {
+        let filter = PathFilter::new(
+            &[".js".to_string()],
+            &["Dockerfile*".to_string()],
+            &["**/*.min.js".to_string()],
+        )
+        .unwrap();
+
+        assert!(filter.is_included(Path::new("/repo/src/app.js")));
+        assert!(filter.is_included(Path::new("/repo/docker/Dockerfile.prod")));
+        assert!(!filter.is_included(Path::new("/repo/src/app.py")));
+        assert!(filter.is_excluded(Path::new("/repo/dist/app.min.js")));
+        assert!(!filter.is_excluded(Path::new("/repo/src/app.js")));
+    }
+}
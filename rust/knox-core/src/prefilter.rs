@@ -0,0 +1,157 @@
+//! Literal prefiltering with Aho-Corasick
+//!
+//! Most rules can only match where one of a few literal strings occurs,
+//! e.g. `(md5|hashlib\.md5)\s*\(` needs `md5` or `hashlib.md5`. Those
+//! literals are extracted from each rule's regex and searched for all at
+//! once with a single Aho-Corasick automaton; a rule's regex only runs on
+//! lines containing one of its literals. The search ignores ASCII case and
+//! only considers ASCII literals, so lines with any non-ASCII text skip the
+//! prefilter; it never rules out a line the regex could match. Rules
+//! without a usable literal (e.g. a leading character class) always run.
+
+use crate::matcher::SecurityPattern;
+use aho_corasick::AhoCorasick;
+use regex_syntax::hir::literal::Extractor;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, OnceLock};
+
+pub struct LiteralPrefilter {
+    automaton: AhoCorasick,
+    /// Pattern index owning each automaton literal
+    owners: Vec<usize>,
+    /// Literals per pattern, None for patterns that are always evaluated
+    literals: Vec<Option<Vec<String>>>,
+}
+
+/// Lowercased ASCII literals one of which every match of `pattern` on ASCII
+/// text starts with, or None when the regex has no such finite set
+pub fn required_literals(pattern: &str) -> Option<Vec<String>> {
+    let hir = regex_syntax::Parser::new().parse(pattern).ok()?;
+    let seq = Extractor::new().extract(&hir);
+
+    let mut literals: Vec<String> = Vec::new();
+    for literal in seq.literals()? {
+        let text = std::str::from_utf8(literal.as_bytes()).ok()?;
+        if text.is_empty() {
+            return None;
+        }
+        // Unicode case variants such as `ſ` for `s` can't occur in ASCII text
+        if !text.is_ascii() {
+            continue;
+        }
+        let lower = text.to_ascii_lowercase();
+        if !literals.contains(&lower) {
+            literals.push(lower);
+        }
+    }
+    (!literals.is_empty()).then_some(literals)
+}
+
+impl LiteralPrefilter {
+    pub fn build(patterns: &[SecurityPattern]) -> Option<Self> {
+        let literals: Vec<Option<Vec<String>>> = patterns
+            .iter()
+            .map(|p| required_literals(&p.regex_source()))
+            .collect();
+
+        let mut needles: Vec<&str> = Vec::new();
+        let mut owners = Vec::new();
+        for (i, pattern_literals) in literals.iter().enumerate() {
+            for literal in pattern_literals.iter().flatten() {
+                needles.push(literal);
+                owners.push(i);
+            }
+        }
+        if needles.is_empty() {
+            return None;
+        }
+
+        let automaton = AhoCorasick::builder()
+            .ascii_case_insensitive(true)
+            .build(&needles)
+            .ok()?;
+        Some(LiteralPrefilter {
+            automaton,
+            owners,
+            literals,
+        })
+    }
+
+    /// Like `build`, reusing a prefilter already built for the same rules
+    pub fn shared(patterns: &[SecurityPattern]) -> Option<Arc<Self>> {
+        static CACHE: OnceLock<Mutex<HashMap<u64, Option<Arc<LiteralPrefilter>>>>> =
+            OnceLock::new();
+
+        let mut hasher = DefaultHasher::new();
+        for pattern in patterns {
+            pattern.regex_source().hash(&mut hasher);
+        }
+        let key = hasher.finish();
+
+        let mut cache = CACHE
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        cache
+            .entry(key)
+            .or_insert_with(|| Self::build(patterns).map(Arc::new))
+            .clone()
+    }
+
+    /// For each pattern index, whether its regex has to run on `text`
+    pub fn candidates(&self, text: &str) -> Vec<bool> {
+        if !text.is_ascii() {
+            return vec![true; self.literals.len()];
+        }
+        let mut candidates: Vec<bool> = self.literals.iter().map(Option::is_none).collect();
+        for found in self.automaton.find_overlapping_iter(text) {
+            candidates[self.owners[found.pattern().as_usize()]] = true;
+        }
+        candidates
+    }
+
+    /// Literals the pattern at `index` is filtered on, if any
+    pub fn literals(&self, index: usize) -> Option<&[String]> {
+        self.literals.get(index)?.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::severity::Severity;
+
+    #[test]
+    fn test_required_literals() {
+        assert_eq!(
+            required_literals(r"(?i)(md5|hashlib\.md5)\s*\("),
+            Some(vec!["md5".to_string(), "hashlib.".to_string()])
+        );
+        assert_eq!(required_literals(r"\w+secret"), None);
+    }
+
+    #[test]
+    fn test_candidates_ignore_ascii_case() {
+        let patterns = vec![
+            SecurityPattern::new(
+                "md5".to_string(),
+                r"(?i)md5\(".to_string(),
+                Severity::Low,
+                "crypto".to_string(),
+                String::new(),
+            ),
+            SecurityPattern::new(
+                "any_word".to_string(),
+                r"\w+=".to_string(),
+                Severity::Low,
+                "test".to_string(),
+                String::new(),
+            ),
+        ];
+        let prefilter = LiteralPrefilter::build(&patterns).unwrap();
+        assert_eq!(prefilter.candidates("x = MD5(data)"), vec![true, true]);
+        assert_eq!(prefilter.candidates("x = sha256(data)"), vec![false, true]);
+    }
+}
@@ -0,0 +1,242 @@
+//! Built-in scan profiles
+//!
+//! Bundles the scanner settings integrators would otherwise hand-tune
+//! (severity floor, file size cap, excluded directories, finding cap and
+//! time budget) under a single name such as "quick", "full" or "deep".
+//! The "strict", "standard" and "relaxed" profiles also choose rule
+//! categories, a confidence threshold and whether the default directory
+//! exclusions apply, for teams adopting one policy across repositories.
+
+use crate::severity::Severity;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Categories a relaxed scan reports: exploitable issues and leaked secrets
+const RELAXED_CATEGORIES: &[&str] = &["secrets", "injection", "deserialization", "sensitive_file"];
+
+/// Directories holding vendored or generated code that quick scans skip
+const VENDORED_DIRS: &[&str] = &[
+    "node_modules",
+    "vendor",
+    "third_party",
+    "bower_components",
+    ".git",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ScanProfile {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub min_severity: Option<Severity>,
+    #[pyo3(get)]
+    pub max_file_size: u64,
+    #[pyo3(get)]
+    pub excluded_dirs: Vec<String>,
+    #[pyo3(get)]
+    pub max_findings: Option<usize>,
+    #[pyo3(get)]
+    pub time_budget_ms: Option<u64>,
+    #[pyo3(get)]
+    pub sniff_content: bool,
+    /// Findings of rules less confident than this are dropped
+    #[pyo3(get)]
+    #[serde(default)]
+    pub min_confidence: Option<String>,
+    /// Rule categories evaluated; None means all
+    #[pyo3(get)]
+    #[serde(default)]
+    pub enabled_categories: Option<Vec<String>>,
+    /// Whether the scanner's default excluded directories apply, on top
+    /// of `excluded_dirs`
+    #[pyo3(get)]
+    #[serde(default = "default_excludes")]
+    pub default_excludes: bool,
+}
+
+fn default_excludes() -> bool {
+    true
+}
+
+#[pymethods]
+impl ScanProfile {
+    /// Look up a built-in profile by name
+    #[staticmethod]
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        Self::by_name(name).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown scan profile: {} (expected one of {:?})",
+                name,
+                Self::available()
+            ))
+        })
+    }
+
+    /// Names of all built-in profiles
+    #[staticmethod]
+    pub fn available() -> Vec<String> {
+        ["quick", "full", "deep", "strict", "standard", "relaxed"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ScanProfile(name={}, min_severity={:?}, max_file_size={})",
+            self.name, self.min_severity, self.max_file_size
+        )
+    }
+}
+
+impl ScanProfile {
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "quick" => Some(Self::quick()),
+            "full" => Some(Self::full()),
+            "deep" => Some(Self::deep()),
+            "strict" => Some(Self::strict()),
+            "standard" => Some(Self::standard()),
+            "relaxed" => Some(Self::relaxed()),
+            _ => None,
+        }
+    }
+
+    /// Critical rules only, small files, no vendored code, capped output
+    pub fn quick() -> Self {
+        ScanProfile {
+            name: "quick".to_string(),
+            min_severity: Some(Severity::Critical),
+            max_file_size: 256 * 1024,
+            excluded_dirs: VENDORED_DIRS.iter().map(|d| d.to_string()).collect(),
+            max_findings: Some(100),
+            time_budget_ms: Some(1000),
+            sniff_content: false,
+            min_confidence: None,
+            enabled_categories: None,
+            default_excludes: true,
+        }
+    }
+
+    /// Every rule over every file up to the default size limit
+    pub fn full() -> Self {
+        ScanProfile {
+            name: "full".to_string(),
+            min_severity: None,
+            max_file_size: 10 * 1024 * 1024,
+            excluded_dirs: vec![],
+            max_findings: None,
+            time_budget_ms: None,
+            sniff_content: false,
+            min_confidence: None,
+            enabled_categories: None,
+            default_excludes: true,
+        }
+    }
+
+    /// Like "full" but also scans very large files and sniffs the content
+    /// of files without a known extension
+    pub fn deep() -> Self {
+        ScanProfile {
+            name: "deep".to_string(),
+            max_file_size: 100 * 1024 * 1024,
+            sniff_content: true,
+            ..Self::full()
+        }
+    }
+
+    /// Every rule at every confidence, sniffing unknown files and scanning
+    /// vendored and generated directories too; only `.git` is skipped
+    pub fn strict() -> Self {
+        ScanProfile {
+            name: "strict".to_string(),
+            excluded_dirs: vec![".git".to_string()],
+            sniff_content: true,
+            default_excludes: false,
+            ..Self::full()
+        }
+    }
+
+    /// Every category, without low-confidence heuristics such as debug
+    /// flags and entropy findings
+    pub fn standard() -> Self {
+        ScanProfile {
+            name: "standard".to_string(),
+            min_confidence: Some("medium".to_string()),
+            ..Self::full()
+        }
+    }
+
+    /// High and critical secrets, injection, deserialization and sensitive
+    /// files outside vendored code
+    pub fn relaxed() -> Self {
+        ScanProfile {
+            name: "relaxed".to_string(),
+            min_severity: Some(Severity::High),
+            excluded_dirs: VENDORED_DIRS.iter().map(|d| d.to_string()).collect(),
+            min_confidence: Some("medium".to_string()),
+            enabled_categories: Some(RELAXED_CATEGORIES.iter().map(|c| c.to_string()).collect()),
+            ..Self::full()
+        }
+    }
+}
+
+impl Default for ScanProfile {
+    fn default() -> Self {
+        Self::full()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::MemoryProvider;
+    use crate::scanner::FastScanner;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_builtin_profiles_resolve() {
+        for name in ScanProfile::available() {
+            assert_eq!(ScanProfile::by_name(&name).unwrap().name, name);
+        }
+        assert!(ScanProfile::by_name("turbo").is_none());
+    }
+
+    #[test]
+    fn test_policy_profiles_configure_scanner() {
+        let code = "DEBUG = True\nhashlib.md5(x)\npassword = \"hunter2hunter2\"\n";
+        let provider = MemoryProvider::new(Some(BTreeMap::from([(
+            "app.py".to_string(),
+            code.to_string(),
+        )])));
+        let found = |profile: &str| -> Vec<String> {
+            FastScanner::with_profile(profile)
+                .unwrap()
+                .scan_with_provider(&provider)
+                .iter()
+                .flat_map(|r| r.matches.iter().map(|m| m.pattern_name.clone()))
+                .collect()
+        };
+
+        assert_eq!(
+            found("strict"),
+            vec!["debug_mode", "weak_crypto_md5", "hardcoded_password"]
+        );
+        assert_eq!(
+            found("standard"),
+            vec!["weak_crypto_md5", "hardcoded_password"]
+        );
+        assert_eq!(found("relaxed"), vec!["hardcoded_password"]);
+        assert!(!ScanProfile::strict().default_excludes);
+    }
+
+    #[test]
+    fn test_quick_profile_is_restrictive() {
+        let quick = ScanProfile::quick();
+        assert_eq!(quick.min_severity, Some(Severity::Critical));
+        assert_eq!(quick.max_file_size, 256 * 1024);
+        assert!(quick.excluded_dirs.contains(&"node_modules".to_string()));
+        assert!(quick.max_findings.is_some());
+    }
+}
@@ -0,0 +1,95 @@
+//! Rule provenance
+//!
+//! Records where a rule came from (rule pack, pack version, and the file
+//! and line it was defined at) so a finding can be traced back through
+//! merged packs to the definition that produced it.
+
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Pack name given to the patterns compiled into the engine
+pub const BUILTIN_PACK: &str = "builtin";
+/// Release date of the built-in rule content, bumped whenever it changes
+pub const BUILTIN_RELEASED: &str = "2026-10-01";
+/// Namespace of the built-in patterns in rule ids, e.g.
+/// `knox.secrets.hardcoded_api_key`
+pub const BUILTIN_NAMESPACE: &str = "knox";
+/// Namespace of rules added directly to a matcher rather than through a pack
+pub const LOCAL_NAMESPACE: &str = "local";
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[pyclass]
+pub struct RuleSource {
+    #[pyo3(get, set)]
+    pub pack: Option<String>,
+    #[pyo3(get, set)]
+    pub pack_version: Option<String>,
+    /// File the rule was loaded from, e.g. a YAML rule pack
+    #[pyo3(get, set)]
+    pub source_file: Option<String>,
+    #[pyo3(get, set)]
+    pub source_line: Option<usize>,
+}
+
+#[pymethods]
+impl RuleSource {
+    #[new]
+    pub fn new(
+        pack: Option<String>,
+        pack_version: Option<String>,
+        source_file: Option<String>,
+        source_line: Option<usize>,
+    ) -> Self {
+        RuleSource {
+            pack,
+            pack_version,
+            source_file,
+            source_line,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        let location = match (&self.source_file, self.source_line) {
+            (Some(file), Some(line)) => format!("{}:{}", file, line),
+            (Some(file), None) => file.clone(),
+            _ => "-".to_string(),
+        };
+        format!(
+            "RuleSource(pack={}, version={}, at={})",
+            self.pack.as_deref().unwrap_or("-"),
+            self.pack_version.as_deref().unwrap_or("-"),
+            location
+        )
+    }
+}
+
+impl RuleSource {
+    /// Source of the built-in patterns, versioned with the engine
+    pub fn builtin() -> Self {
+        RuleSource {
+            pack: Some(BUILTIN_PACK.to_string()),
+            pack_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            source_file: None,
+            source_line: None,
+        }
+    }
+
+    /// Namespace a rule from `source` is identified under: its pack name,
+    /// `knox` for the built-in pack and `local` for rules without one
+    pub fn namespace(source: Option<&RuleSource>) -> &str {
+        match source.and_then(|s| s.pack.as_deref()) {
+            Some(BUILTIN_PACK) => BUILTIN_NAMESPACE,
+            Some(pack) => pack,
+            None => LOCAL_NAMESPACE,
+        }
+    }
+
+    /// Fill in the pack name and version, keeping any file and line the
+    /// rule author already recorded
+    pub fn stamped(source: Option<&RuleSource>, pack: &str, version: Option<&str>) -> Self {
+        let mut stamped = source.cloned().unwrap_or_default();
+        stamped.pack = Some(pack.to_string());
+        stamped.pack_version = version.map(String::from);
+        stamped
+    }
+}
@@ -0,0 +1,292 @@
+//! File sources for scanning
+//!
+//! `FileProvider` abstracts where file listings and contents come from, so
+//! the scanner's selection and matching logic runs unchanged over the real
+//! filesystem (directory scans walk an `FsProvider`) or an in-memory map of
+//! path to content (tests, services that receive code over the wire).
+//! Archive or VCS-backed sources plug in by implementing the same trait.
+
+use memmap2::Mmap;
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A listed file, or a path that couldn't be listed with the reason
+pub type Listed = Result<PathBuf, (PathBuf, io::Error)>;
+
+/// File content as a provider hands it out
+pub enum Content<'a> {
+    Borrowed(&'a [u8]),
+    Owned(Vec<u8>),
+    Mapped(Mmap),
+}
+
+impl Deref for Content<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Content::Borrowed(bytes) => bytes,
+            Content::Owned(bytes) => bytes,
+            Content::Mapped(mmap) => mmap,
+        }
+    }
+}
+
+pub trait FileProvider: Sync {
+    /// Every file the provider holds, in a stable order
+    ///
+    /// `skip` is asked about each directory and file below the root, with
+    /// whether it is a directory; a skipped directory is not descended
+    /// into.
+    fn list_files(&self, skip: &mut dyn FnMut(&Path, bool) -> bool) -> Vec<Listed>;
+
+    /// Size of one file in bytes, known without reading it
+    fn file_size(&self, path: &Path) -> io::Result<u64>;
+
+    /// Full content of one file, read into memory
+    fn read(&self, path: &Path) -> io::Result<Content<'_>>;
+
+    /// Full content of one file for scanning, which may be mapped rather
+    /// than read
+    fn load(&self, path: &Path) -> io::Result<Content<'_>> {
+        self.read(path)
+    }
+
+    /// At most the first `length` bytes of one file
+    fn read_head(&self, path: &Path, length: usize) -> io::Result<Vec<u8>> {
+        let content = self.read(path)?;
+        Ok(content[..content.len().min(length)].to_vec())
+    }
+}
+
+/// Files under a directory on disk
+pub struct FsProvider {
+    root: PathBuf,
+    max_depth: Option<usize>,
+}
+
+impl FsProvider {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FsProvider {
+            root: root.into(),
+            max_depth: None,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Only list files at most `max_depth` levels below the root
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+}
+
+impl FileProvider for FsProvider {
+    fn list_files(&self, skip: &mut dyn FnMut(&Path, bool) -> bool) -> Vec<Listed> {
+        let mut walker = WalkDir::new(&self.root).sort_by_file_name();
+        if let Some(depth) = self.max_depth {
+            walker = walker.max_depth(depth);
+        }
+        walker
+            .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !skip(e.path(), e.file_type().is_dir()))
+            .filter_map(|entry| match entry {
+                Ok(e) => e.file_type().is_file().then(|| Ok(e.into_path())),
+                Err(e) => {
+                    let path = e.path().unwrap_or(&self.root).to_path_buf();
+                    Some(Err((path, e.into())))
+                }
+            })
+            .collect()
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        Ok(std::fs::metadata(path)?.len())
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Content<'_>> {
+        std::fs::read(path).map(Content::Owned)
+    }
+
+    /// Maps the file, falling back to reading it where mapping fails
+    fn load(&self, path: &Path) -> io::Result<Content<'_>> {
+        let file = File::open(path)?;
+        match unsafe { Mmap::map(&file) } {
+            Ok(mmap) => Ok(Content::Mapped(mmap)),
+            Err(_) => self.read(path),
+        }
+    }
+
+    fn read_head(&self, path: &Path, length: usize) -> io::Result<Vec<u8>> {
+        let mut head = Vec::new();
+        File::open(path)?
+            .take(length as u64)
+            .read_to_end(&mut head)?;
+        Ok(head)
+    }
+}
+
+/// Files held in memory, keyed by path
+#[derive(Debug, Clone, Default)]
+#[pyclass]
+pub struct MemoryProvider {
+    files: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+#[pymethods]
+impl MemoryProvider {
+    #[new]
+    pub fn new(files: Option<BTreeMap<String, String>>) -> Self {
+        let mut provider = MemoryProvider::default();
+        for (path, content) in files.unwrap_or_default() {
+            provider.add_file(path, content);
+        }
+        provider
+    }
+
+    pub fn add_file(&mut self, path: String, content: String) {
+        self.files.insert(PathBuf::from(path), content.into_bytes());
+    }
+
+    fn __len__(&self) -> usize {
+        self.files.len()
+    }
+}
+
+impl FileProvider for MemoryProvider {
+    /// Directories are the paths' parents, offered to `skip` outermost first
+    fn list_files(&self, skip: &mut dyn FnMut(&Path, bool) -> bool) -> Vec<Listed> {
+        self.files
+            .keys()
+            .filter(|path| {
+                let mut dirs: Vec<&Path> = path
+                    .ancestors()
+                    .skip(1)
+                    .filter(|dir| !dir.as_os_str().is_empty())
+                    .collect();
+                dirs.reverse();
+                !dirs.into_iter().any(|dir| skip(dir, true)) && !skip(path, false)
+            })
+            .map(|path| Ok(path.clone()))
+            .collect()
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        self.read(path).map(|content| content.len() as u64)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Content<'_>> {
+        self.files
+            .get(path)
+            .map(|content| Content::Borrowed(content.as_slice()))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.display().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::FastScanner;
+    use std::fs::{create_dir, write};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    /// Memory provider that counts full reads
+    struct CountingProvider {
+        inner: MemoryProvider,
+        reads: AtomicUsize,
+    }
+
+    impl FileProvider for CountingProvider {
+        fn list_files(&self, skip: &mut dyn FnMut(&Path, bool) -> bool) -> Vec<Listed> {
+            self.inner.list_files(skip)
+        }
+
+        fn file_size(&self, path: &Path) -> io::Result<u64> {
+            self.inner.file_size(path)
+        }
+
+        fn read(&self, path: &Path) -> io::Result<Content<'_>> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            self.inner.read(path)
+        }
+    }
+
+    #[test]
+    fn test_memory_provider_scan() {
+        let provider = MemoryProvider::new(Some(BTreeMap::from([
+            ("src/app.py".to_string(), "DEBUG = True\n".to_string()),
+            (
+                "node_modules/lib/x.js".to_string(),
+                "DEBUG = true\n".to_string(),
+            ),
+            ("README.md".to_string(), "DEBUG = True\n".to_string()),
+        ])));
+
+        let results = FastScanner::new(None).scan_with_provider(&provider);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].file_path, "src/app.py");
+        assert_eq!(results[0].matches.len(), 1);
+        assert_eq!(
+            results[0].matches[0].file_path.as_deref(),
+            Some("src/app.py")
+        );
+    }
+
+    #[test]
+    fn test_fs_provider_scan() {
+        let temp_dir = TempDir::new().unwrap();
+        create_dir(temp_dir.path().join("src")).unwrap();
+        write(temp_dir.path().join("src/app.py"), "DEBUG = True\n").unwrap();
+
+        let provider = FsProvider::new(temp_dir.path());
+        let results = FastScanner::new(None).scan_with_provider(&provider);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].matches[0].pattern_name, "debug_mode");
+    }
+
+    #[test]
+    fn test_fs_provider_reports_unreadable_files() {
+        let temp_dir = TempDir::new().unwrap();
+        write(temp_dir.path().join("app.py"), "DEBUG = True\n").unwrap();
+        write(temp_dir.path().join("latin1.py"), [b'#', 0xe9, b'\n']).unwrap();
+
+        let provider = FsProvider::new(temp_dir.path());
+        let results = FastScanner::new(None).scan_with_provider(&provider);
+        assert_eq!(results.len(), 2);
+        assert!(results[0].error.is_none());
+        assert!(results[1].file_path.ends_with("latin1.py"));
+        assert!(results[1].error.is_some());
+
+        let missing = FsProvider::new(temp_dir.path().join("missing"));
+        let results = FastScanner::new(None).scan_with_provider(&missing);
+        assert_eq!(results.len(), 1);
+        assert!(results[0].error.is_some());
+    }
+
+    #[test]
+    fn test_oversized_files_are_not_read() {
+        let mut inner = MemoryProvider::default();
+        inner.add_file("small.py".to_string(), "DEBUG = True\n".to_string());
+        inner.add_file("big.py".to_string(), "x = 1\n".repeat(400_000));
+        let provider = CountingProvider {
+            inner,
+            reads: AtomicUsize::new(0),
+        };
+
+        let results = FastScanner::new(Some(1)).scan_with_provider(&provider);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].file_path, "big.py");
+        assert!(results[0].matches.is_empty());
+        assert_eq!(results[1].matches.len(), 1);
+        assert_eq!(provider.reads.load(Ordering::SeqCst), 1);
+    }
+}
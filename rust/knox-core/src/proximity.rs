@@ -0,0 +1,156 @@
+//! Proximity rules
+//!
+//! Some findings only mean something together: `requests.post(` is fine
+//! until `verify=False` shows up next to it, and a username literal is only
+//! interesting near a password literal. A proximity rule fires at each line
+//! its `anchor` regex matches when its `near` regex matches within `window`
+//! lines, before or after.
+
+use crate::severity::{self, Severity};
+use pyo3::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Kind reported on matches produced by proximity rules
+pub const KIND_PROXIMITY: &str = "proximity";
+/// Lines either side of the anchor searched when no window is given
+pub const DEFAULT_WINDOW: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ProximityRule {
+    #[pyo3(get, set)]
+    pub name: String,
+    #[pyo3(get)]
+    pub severity: Severity,
+    #[pyo3(get, set)]
+    pub category: String,
+    #[pyo3(get, set)]
+    pub description: String,
+    /// Regex reported at each line it matches when `near` is close by
+    #[pyo3(get, set)]
+    pub anchor: String,
+    /// Regex that must match within `window` lines of the anchor
+    #[pyo3(get, set)]
+    pub near: String,
+    /// Lines either side of the anchor, 0 meaning the same line
+    #[pyo3(get, set)]
+    pub window: usize,
+}
+
+#[pymethods]
+impl ProximityRule {
+    #[new]
+    pub fn new(
+        name: String,
+        #[pyo3(from_py_with = "severity::from_py")] severity: Severity,
+        category: String,
+        description: String,
+        anchor: String,
+        near: String,
+        window: Option<usize>,
+    ) -> Self {
+        ProximityRule {
+            name,
+            severity,
+            category,
+            description,
+            anchor,
+            near,
+            window: window.unwrap_or(DEFAULT_WINDOW),
+        }
+    }
+
+    /// Accepts a `Severity` or its name
+    #[setter(severity)]
+    fn set_py_severity(&mut self, value: &PyAny) -> PyResult<()> {
+        self.severity = severity::from_py(value)?;
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ProximityRule(name={}, severity={}, window={})",
+            self.name, self.severity, self.window
+        )
+    }
+}
+
+impl ProximityRule {
+    /// Summary of the condition, for finding explanations
+    pub fn clause(&self) -> String {
+        format!(
+            "`{}` within {} lines of `{}`",
+            self.anchor, self.window, self.near
+        )
+    }
+
+    /// Anchor hits with a `near` hit in range, as (1-based line, byte
+    /// offset in the line, text)
+    ///
+    /// `compile` resolves a regex source to a compiled regex; rules whose
+    /// regexes fail to compile never fire.
+    pub fn evaluate<'a, F>(&self, content: &'a str, mut compile: F) -> Vec<(usize, usize, &'a str)>
+    where
+        F: FnMut(&str) -> Option<Regex>,
+    {
+        let (Some(anchor), Some(near)) = (compile(&self.anchor), compile(&self.near)) else {
+            return Vec::new();
+        };
+        if !anchor.is_match(content) || !near.is_match(content) {
+            return Vec::new();
+        }
+
+        let lines: Vec<&str> = content.lines().collect();
+        // Ascending, so the nearest candidates can be found by binary search
+        let near_lines: Vec<usize> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| near.is_match(line))
+            .map(|(i, _)| i)
+            .collect();
+
+        lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let found = anchor.find(line)?;
+                let first_in_window = near_lines.partition_point(|&n| n + self.window < i);
+                near_lines
+                    .get(first_in_window)
+                    .is_some_and(|&n| n <= i + self.window)
+                    .then(|| (i + 1, found.start(), found.as_str()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fires_only_within_window() {
+        let rule = ProximityRule::new(
+            "post_without_verification".to_string(),
+            Severity::High,
+            "network".to_string(),
+            "requests.post with certificate checks disabled".to_string(),
+            r"requests\.post\(".to_string(),
+            r"verify\s*=\s*False".to_string(),
+            Some(2),
+        );
+        let compile = |p: &str| Regex::new(p).ok();
+
+        let near = "resp = requests.post(\n    url,\n    verify=False,\n)\n";
+        assert_eq!(rule.evaluate(near, compile), vec![(1, 7, "requests.post(")]);
+        let after = "verify = False\n\n\nresp = requests.post(url)\n";
+        assert!(rule.evaluate(after, compile).is_empty());
+        let same_line = ProximityRule { window: 0, ..rule };
+        assert_eq!(
+            same_line.evaluate("requests.post(url, verify=False)\n", compile),
+            vec![(1, 0, "requests.post(")]
+        );
+        assert!(same_line.evaluate(near, compile).is_empty());
+    }
+}
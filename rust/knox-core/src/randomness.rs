@@ -0,0 +1,126 @@
+//! Insecure randomness
+//!
+//! General-purpose generators (`random`, `Math.random`, C's `rand`) are
+//! seeded predictably or leak their state after a few outputs, so a token
+//! drawn from one can be guessed. Using them is fine for shuffling a
+//! playlist; these rules flag them on a line that also names a secret
+//! (`token`, `password`, `session_id`, ...), where the value is almost
+//! certainly meant to be unguessable. C's `rand` is flagged anywhere, at
+//! low confidence. The rules report under the `crypto` category and name
+//! the CSPRNG to use instead.
+
+use crate::matcher::SecurityPattern;
+use crate::severity::Severity;
+
+/// Identifiers that hold values an attacker must not guess
+const SECRET_NAMES: &str =
+    r"\w*(?:token|password|passwd|secret|nonce|salt|api_?key|session_?id|csrf|otp)\w*";
+
+fn random_rule(
+    name: &str,
+    pattern: String,
+    severity: Severity,
+    description: &str,
+    languages: &[&str],
+) -> SecurityPattern {
+    let mut rule = SecurityPattern::new(
+        name.to_string(),
+        pattern,
+        severity,
+        "crypto".to_string(),
+        description.to_string(),
+    );
+    rule.languages = languages.iter().map(|l| l.to_string()).collect();
+    rule.with_cwe("CWE-338", "A02:2021")
+}
+
+/// `call` on a line that names a secret before or after it
+fn near_secret(call: &str) -> String {
+    format!(
+        r"(?i)\b{names}\b.*{call}|{call}.*\b{names}\b",
+        names = SECRET_NAMES,
+        call = call
+    )
+}
+
+/// Built-in insecure randomness rules, appended to the default rules
+pub fn random_patterns() -> Vec<SecurityPattern> {
+    let mut python = random_rule(
+        "insecure_random_python",
+        near_secret(
+            r"\brandom\.(?:random|randint|randrange|choice|choices|sample|getrandbits|uniform)\s*\(",
+        ),
+        Severity::Medium,
+        "Secret drawn from the random module; use secrets.token_urlsafe or secrets.choice",
+        &["python"],
+    );
+    python.positive_examples =
+        vec!["reset_token = ''.join(random.choices(string.ascii_letters, k=32))".to_string()];
+    python.negative_examples = vec![
+        "reset_token = secrets.token_urlsafe(32)".to_string(),
+        "delay = random.uniform(0.5, 1.5)".to_string(),
+    ];
+
+    let mut javascript = random_rule(
+        "insecure_random_js",
+        near_secret(r"\bMath\.random\s*\("),
+        Severity::Medium,
+        "Secret drawn from Math.random(); use crypto.randomUUID or crypto.getRandomValues",
+        &["javascript", "typescript"],
+    );
+    javascript.positive_examples =
+        vec!["const sessionId = Math.random().toString(36).slice(2);".to_string()];
+    javascript.negative_examples = vec![
+        "const sessionId = crypto.randomUUID();".to_string(),
+        "const jitter = Math.random() * 100;".to_string(),
+    ];
+
+    let mut c = random_rule(
+        "insecure_random_c",
+        r"\b(?:rand|random|drand48|lrand48)\s*\(\s*\)|\bsrand\s*\(\s*time\s*\(".to_string(),
+        Severity::Low,
+        "Predictable rand(); use getrandom(), arc4random_buf() or a CSPRNG for secrets",
+        &["c", "cpp"],
+    );
+    c.confidence = "low".to_string();
+    c.positive_examples = vec!["srand(time(NULL));".to_string()];
+    c.negative_examples = vec!["getrandom(buf, sizeof(buf), 0);".to_string()];
+
+    vec![python, javascript, c]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::matcher::PatternMatcher;
+
+    #[test]
+    fn test_random_rules_need_a_secret_nearby() {
+        let content = concat!(
+            "import random\n",
+            "random.shuffle(deck)\n",
+            "password = str(random.randint(100000, 999999))\n",
+            "pick = random.choice(colors)\n",
+        );
+        let mut matcher = PatternMatcher::new();
+        matcher.set_language(Some("python".to_string()));
+        let flagged: Vec<(usize, String, String)> = matcher
+            .match_content(content)
+            .into_iter()
+            .filter(|m| m.pattern_name.starts_with("insecure_random"))
+            .map(|m| (m.line_number, m.pattern_name, m.category))
+            .collect();
+        assert_eq!(
+            flagged,
+            vec![(
+                3,
+                "insecure_random_python".to_string(),
+                "crypto".to_string()
+            )]
+        );
+
+        matcher.set_language(Some("c".to_string()));
+        let matches = matcher.match_content("int r = rand();\n");
+        assert_eq!(matches[0].pattern_name, "insecure_random_c");
+        assert_eq!(matches[0].cwe.as_deref(), Some("CWE-338"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Single-pass evaluation of the rule set
+//!
+//! All built-in and pack rules are compiled into one `RegexSet`, so a line
+//! is checked against every rule in a single pass. Only the rules the set
+//! reports as hits are run individually, to find match positions.
+//!
+//! Custom rules added with `add_pattern` stay out of the set: each is timed
+//! on its own against the rule time budget, which a shared pass can't do.
+//! Rules on the backtracking engine (see `fancy`) and rules that fail to
+//! compile are left out too, and a set that exceeds the size limits falls
+//! back to matching every rule individually.
+//!
+//! Matchers with the same rules share one compiled set. The most recently
+//! used sets are kept, up to `SHARED_SET_CAPACITY`.
+
+use crate::guard::{self, DFA_SIZE_LIMIT, REGEX_SIZE_LIMIT};
+use crate::matcher::SecurityPattern;
+use regex::{RegexSet, RegexSetBuilder};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Distinct rule sets whose compiled set is kept for reuse
+pub const SHARED_SET_CAPACITY: usize = 8;
+
+/// Shared sets by rule-set hash, least recently used first
+type SharedSets = Vec<(u64, Option<Arc<PatternSet>>)>;
+
+fn shared_sets() -> &'static Mutex<SharedSets> {
+    static CACHE: OnceLock<Mutex<SharedSets>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+pub struct PatternSet {
+    set: RegexSet,
+    /// Pattern index of each set member
+    members: Vec<usize>,
+    pattern_count: usize,
+}
+
+impl PatternSet {
+    /// Build a set over the patterns not named in `excluded`
+    pub fn build(patterns: &[SecurityPattern], excluded: &HashSet<String>) -> Option<Self> {
+        let members: Vec<usize> = patterns
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| !excluded.contains(&p.name) && !p.fancy)
+            .filter(|(_, p)| guard::compile_guarded(&p.regex_source()).is_ok())
+            .map(|(i, _)| i)
+            .collect();
+        if members.is_empty() {
+            return None;
+        }
+
+        let set = RegexSetBuilder::new(members.iter().map(|&i| patterns[i].regex_source()))
+            .size_limit(REGEX_SIZE_LIMIT * members.len())
+            .dfa_size_limit(DFA_SIZE_LIMIT)
+            .build()
+            .ok()?;
+        Some(PatternSet {
+            set,
+            members,
+            pattern_count: patterns.len(),
+        })
+    }
+
+    /// Like `build`, reusing a set already built for the same rules
+    pub fn shared(patterns: &[SecurityPattern], excluded: &HashSet<String>) -> Option<Arc<Self>> {
+        let mut hasher = DefaultHasher::new();
+        for pattern in patterns {
+            pattern.regex_source().hash(&mut hasher);
+            pattern.fancy.hash(&mut hasher);
+            excluded.contains(&pattern.name).hash(&mut hasher);
+        }
+        let key = hasher.finish();
+
+        let mut cache = shared_sets().lock().unwrap_or_else(|e| e.into_inner());
+        let entry = match cache.iter().position(|(k, _)| *k == key) {
+            Some(i) => cache.remove(i),
+            None => {
+                if cache.len() >= SHARED_SET_CAPACITY {
+                    cache.remove(0);
+                }
+                (key, Self::build(patterns, excluded).map(Arc::new))
+            }
+        };
+        let set = entry.1.clone();
+        cache.push(entry);
+        set
+    }
+
+    /// Whether any set member is still marked in `candidates`
+    pub fn any_member(&self, candidates: &[bool]) -> bool {
+        self.members.iter().any(|&i| candidates[i])
+    }
+
+    /// For each pattern index, whether it still has to be run on `text`:
+    /// false only for set members the set ruled out
+    pub fn candidates(&self, text: &str) -> Vec<bool> {
+        let mut candidates = vec![true; self.pattern_count];
+        for &i in &self.members {
+            candidates[i] = false;
+        }
+        for hit in self.set.matches(text).iter() {
+            candidates[self.members[hit]] = true;
+        }
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::severity::Severity;
+
+    fn pattern(name: &str, regex: &str) -> SecurityPattern {
+        SecurityPattern::new(
+            name.to_string(),
+            regex.to_string(),
+            Severity::Low,
+            "test".to_string(),
+            String::new(),
+        )
+    }
+
+    #[test]
+    fn test_candidates_from_single_pass() {
+        let patterns = vec![
+            pattern("a", r"alpha\d"),
+            pattern("b", r"beta"),
+            pattern("custom", r"gamma"),
+        ];
+        let excluded = HashSet::from(["custom".to_string()]);
+        let set = PatternSet::build(&patterns, &excluded).unwrap();
+
+        assert_eq!(set.candidates("alpha1 only"), vec![true, false, true]);
+        assert_eq!(set.candidates("nothing"), vec![false, false, true]);
+    }
+
+    #[test]
+    fn test_shared_sets_are_bounded() {
+        let excluded = HashSet::new();
+        for i in 0..SHARED_SET_CAPACITY * 2 {
+            let patterns = vec![pattern("a", &format!("shared_{}", i))];
+            assert!(PatternSet::shared(&patterns, &excluded).is_some());
+        }
+        assert!(shared_sets().lock().unwrap().len() <= SHARED_SET_CAPACITY);
+    }
+}
@@ -0,0 +1,237 @@
+//! Process-wide pattern registry
+//!
+//! Rule packs are registered once and shared through an `Arc` by every
+//! matcher created afterwards. Matchers only copy the pattern list when
+//! they customize it (copy-on-write), so creating many scanners is cheap.
+
+use crate::matcher::{self, PatternMatcher, SecurityPattern};
+use crate::provenance::{RuleSource, BUILTIN_PACK, BUILTIN_RELEASED};
+use crate::suppress;
+use pyo3::prelude::*;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Name, version and release date of a loaded pack
+#[derive(Debug, Clone)]
+pub struct PackVersion {
+    pub name: String,
+    pub version: Option<String>,
+    /// Release date of the pack content as (year, month, day)
+    pub released: Option<(i32, u32, u32)>,
+}
+
+struct Pack {
+    info: PackVersion,
+    patterns: Vec<SecurityPattern>,
+}
+
+struct Registry {
+    packs: Vec<Pack>,
+    combined: Arc<Vec<SecurityPattern>>,
+}
+
+impl Registry {
+    fn rebuild(&mut self) {
+        let mut combined = builtin_patterns();
+        for pack in &self.packs {
+            combined.extend(pack.patterns.iter().cloned());
+        }
+        self.combined = Arc::new(combined);
+    }
+}
+
+/// The default rules, stamped with the built-in pack source
+pub fn builtin_patterns() -> Vec<SecurityPattern> {
+    let source = RuleSource::builtin();
+    let mut patterns = PatternMatcher::default_patterns();
+    for pattern in &mut patterns {
+        pattern.source = Some(source.clone());
+    }
+    patterns
+}
+
+fn registry() -> &'static RwLock<Registry> {
+    static REGISTRY: OnceLock<RwLock<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        RwLock::new(Registry {
+            packs: Vec::new(),
+            combined: Arc::new(builtin_patterns()),
+        })
+    })
+}
+
+/// Snapshot of the built-in patterns plus every registered pack
+pub fn shared_patterns() -> Arc<Vec<SecurityPattern>> {
+    let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+    Arc::clone(&registry.combined)
+}
+
+/// Register a rule pack for all matchers created afterwards
+///
+/// Registering a pack under an existing name replaces it. A pattern that
+/// doesn't compile raises ValueError and leaves the registry unchanged.
+/// Each pattern is stamped with the pack name and `version`; a source file
+/// and line already set on a pattern are kept. `released` (`YYYY-MM-DD`)
+/// dates the pack content for `check_rule_freshness`.
+#[pyfunction]
+pub fn register_pattern_pack(
+    name: String,
+    mut patterns: Vec<SecurityPattern>,
+    version: Option<String>,
+    released: Option<String>,
+) -> PyResult<()> {
+    let released = match released {
+        Some(date) => Some(suppress::parse_date(&date).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid release date for pack {}: {} (expected YYYY-MM-DD)",
+                name, date
+            ))
+        })?),
+        None => None,
+    };
+    for pattern in &patterns {
+        matcher::validate_pattern(pattern).map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid pattern in pack {}: {}",
+                name, e
+            ))
+        })?;
+    }
+    for pattern in &mut patterns {
+        pattern.source = Some(RuleSource::stamped(
+            pattern.source.as_ref(),
+            &name,
+            version.as_deref(),
+        ));
+    }
+
+    let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+    let pack = Pack {
+        info: PackVersion {
+            name,
+            version,
+            released,
+        },
+        patterns,
+    };
+    match registry
+        .packs
+        .iter_mut()
+        .find(|p| p.info.name == pack.info.name)
+    {
+        Some(existing) => *existing = pack,
+        None => registry.packs.push(pack),
+    }
+    registry.rebuild();
+    Ok(())
+}
+
+/// Remove a registered rule pack; returns whether it was registered
+///
+/// Matchers created before the call keep the patterns they started with.
+#[pyfunction]
+pub fn unregister_pattern_pack(name: &str) -> bool {
+    let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+    let before = registry.packs.len();
+    registry.packs.retain(|pack| pack.info.name != name);
+    let removed = registry.packs.len() != before;
+    if removed {
+        registry.rebuild();
+    }
+    removed
+}
+
+/// Names of the registered rule packs, in registration order
+#[pyfunction]
+pub fn registered_packs() -> Vec<String> {
+    let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+    registry
+        .packs
+        .iter()
+        .map(|pack| pack.info.name.clone())
+        .collect()
+}
+
+/// Name, version and release date of the built-in rules and every
+/// registered pack, built-ins first
+pub fn pack_versions() -> Vec<PackVersion> {
+    let mut versions = vec![PackVersion {
+        name: BUILTIN_PACK.to_string(),
+        version: RuleSource::builtin().pack_version,
+        released: suppress::parse_date(BUILTIN_RELEASED),
+    }];
+    let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+    versions.extend(registry.packs.iter().map(|pack| pack.info.clone()));
+    versions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::severity::Severity;
+
+    fn marker(name: &str, regex: &str) -> SecurityPattern {
+        SecurityPattern::new(
+            name.to_string(),
+            regex.to_string(),
+            Severity::Low,
+            "custom".to_string(),
+            "Registry test marker".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_registered_pack_is_shared_by_new_matchers() {
+        let pack = "registry_test_shared_pack";
+        register_pattern_pack(
+            pack.to_string(),
+            vec![marker(
+                "registry_test_marker",
+                r"knox_registry_test_marker\(",
+            )],
+            Some("1.2.0".to_string()),
+            None,
+        )
+        .unwrap();
+        assert!(registered_packs().contains(&pack.to_string()));
+
+        let first = shared_patterns();
+        let second = shared_patterns();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let mut matcher = PatternMatcher::new();
+        let matches = matcher.match_content("knox_registry_test_marker()");
+        assert_eq!(matches.len(), 1);
+
+        let source = matches[0].rule_source.as_ref().unwrap();
+        assert_eq!(source.pack.as_deref(), Some(pack));
+        assert_eq!(source.pack_version.as_deref(), Some("1.2.0"));
+
+        assert!(unregister_pattern_pack(pack));
+        assert!(!unregister_pattern_pack(pack));
+        assert!(!registered_packs().contains(&pack.to_string()));
+        let mut matcher = PatternMatcher::new();
+        assert!(matcher
+            .match_content("knox_registry_test_marker()")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_invalid_pack_is_rejected() {
+        let pack = "registry_test_invalid_pack";
+        let mut bad_negative = marker("registry_test_negative", "knox_registry_ok");
+        bad_negative.negative_pattern = Some("(unclosed".to_string());
+        for bad in [marker("registry_test_broken", "(unclosed"), bad_negative] {
+            assert!(matcher::validate_pattern(&bad)
+                .unwrap_err()
+                .contains(&bad.name));
+            assert!(register_pattern_pack(
+                pack.to_string(),
+                vec![marker("registry_test_fine", "knox_registry_fine"), bad],
+                None,
+                None,
+            )
+            .is_err());
+            assert!(!registered_packs().contains(&pack.to_string()));
+        }
+    }
+}
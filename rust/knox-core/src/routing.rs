@@ -0,0 +1,428 @@
+//! Per-category routing of findings to output sinks
+//!
+//! A `ScanConfig` maps finding categories to output files, each with its
+//! own format: e.g. secrets to a JSON Lines queue file, config issues to
+//! SARIF, PII to a restricted file only the owner can read. A
+//! `RoutingSink` (`ScanSink.routed` from Python) dispatches each file's
+//! findings as `scan_to_sink` scans it, so callers don't split and
+//! re-serialize results in Python.
+//!
+//! Restricted files get owner-only permissions but are not encrypted;
+//! findings that must be encrypted at rest belong on an encrypted volume.
+
+use crate::matcher::Match;
+use crate::scanner::ScanResult;
+use crate::severity::Severity;
+use crate::sink::ResultSink;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// One finding per line
+pub const FORMAT_JSONL: &str = "jsonl";
+/// A single JSON array of findings
+pub const FORMAT_JSON: &str = "json";
+/// A SARIF 2.1.0 log
+pub const FORMAT_SARIF: &str = "sarif";
+/// Category wildcard matching findings no other route takes
+pub const CATCH_ALL: &str = "*";
+
+const FORMATS: &[&str] = &[FORMAT_JSONL, FORMAT_JSON, FORMAT_SARIF];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct Route {
+    /// Categories sent to this sink; `*` takes everything left unrouted
+    #[pyo3(get)]
+    pub categories: Vec<String>,
+    #[pyo3(get)]
+    pub path: String,
+    #[pyo3(get)]
+    pub format: String,
+    /// Make the file readable by its owner only (unix)
+    #[pyo3(get)]
+    pub restricted: bool,
+}
+
+#[pymethods]
+impl Route {
+    fn __repr__(&self) -> String {
+        format!(
+            "Route(categories={:?}, path={}, format={})",
+            self.categories, self.path, self.format
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[pyclass]
+pub struct ScanConfig {
+    #[pyo3(get)]
+    pub routes: Vec<Route>,
+}
+
+#[pymethods]
+impl ScanConfig {
+    #[new]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send findings in `categories` to `path` ("jsonl", "json" or "sarif");
+    /// a `restricted` file is made readable by its owner only
+    pub fn add_route(
+        &mut self,
+        categories: Vec<String>,
+        path: String,
+        format: Option<String>,
+        restricted: Option<bool>,
+    ) -> PyResult<()> {
+        let format = format.unwrap_or_else(|| FORMAT_JSONL.to_string());
+        if !FORMATS.contains(&format.as_str()) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown output format: {} (expected one of {:?})",
+                format, FORMATS
+            )));
+        }
+        if categories.is_empty() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "A route needs at least one category",
+            ));
+        }
+        let restricted = restricted.unwrap_or(false);
+        if restricted && cfg!(not(unix)) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Restricted routes need owner-only file permissions, which this platform lacks",
+            ));
+        }
+        self.routes.push(Route {
+            categories,
+            path,
+            format,
+            restricted,
+        });
+        Ok(())
+    }
+}
+
+impl ScanConfig {
+    /// Indices of the routes a finding in `category` goes to
+    fn routes_for(&self, category: &str) -> Vec<usize> {
+        let explicit: Vec<usize> = self
+            .routes
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.categories.iter().any(|c| c == category))
+            .map(|(i, _)| i)
+            .collect();
+        if !explicit.is_empty() {
+            return explicit;
+        }
+        self.routes
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.categories.iter().any(|c| c == CATCH_ALL))
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+enum Sink {
+    /// Written as findings arrive
+    Lines(BufWriter<File>),
+    /// Collected and written once all findings are routed
+    Document {
+        file: File,
+        format: String,
+        matches: Vec<Match>,
+    },
+}
+
+/// Open a route's file, emptying it; a restricted file is made owner-only
+/// before anything is written, whether it was just created or not
+#[cfg(unix)]
+fn create(route: &Route) -> io::Result<File> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    if route.restricted {
+        options.mode(0o600);
+    }
+    let file = options.open(Path::new(&route.path))?;
+    if route.restricted {
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn create(route: &Route) -> io::Result<File> {
+    if route.restricted {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "{}: owner-only permissions are not supported here",
+                route.path
+            ),
+        ));
+    }
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(Path::new(&route.path))
+}
+
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low | Severity::Info => "note",
+    }
+}
+
+fn sarif_log(matches: &[Match]) -> serde_json::Value {
+    let mut rules: BTreeMap<&str, &Match> = BTreeMap::new();
+    for m in matches {
+        rules.entry(&m.pattern_name).or_insert(m);
+    }
+    let rules: Vec<serde_json::Value> = rules
+        .values()
+        .map(|m| {
+            let mut tags = vec![m.category.clone()];
+            tags.extend(
+                m.cwe
+                    .iter()
+                    .map(|cwe| format!("external/cwe/{}", cwe.to_lowercase())),
+            );
+            tags.extend(
+                m.owasp
+                    .iter()
+                    .map(|owasp| format!("external/owasp/{}", owasp)),
+            );
+            let mut rule = json!({
+                "id": m.pattern_name,
+                "properties": {"category": m.category, "tags": tags},
+            });
+            if let Some(uri) = m.references.first() {
+                rule["helpUri"] = json!(uri);
+            }
+            rule
+        })
+        .collect();
+    let results: Vec<serde_json::Value> = matches
+        .iter()
+        .map(|m| {
+            json!({
+                "ruleId": m.pattern_name,
+                "level": sarif_level(m.severity),
+                "message": {"text": format!("{} ({})", m.pattern_name, m.severity)},
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {"uri": m.file_path.clone().unwrap_or_default()},
+                        "region": {"startLine": m.line_number, "startColumn": m.column + 1},
+                    }
+                }],
+            })
+        })
+        .collect();
+
+    json!({
+        "version": "2.1.0",
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "runs": [{
+            "tool": {"driver": {"name": "knox", "rules": rules}},
+            "results": results,
+        }],
+    })
+}
+
+/// Writes each finding to the outputs its category is routed to, as
+/// results arrive
+pub struct RoutingSink {
+    config: ScanConfig,
+    sinks: Vec<Sink>,
+    routes_by_category: HashMap<String, Vec<usize>>,
+    counts: BTreeMap<String, usize>,
+}
+
+impl RoutingSink {
+    /// Open every route's file, emptying it
+    pub fn new(config: ScanConfig) -> io::Result<Self> {
+        let mut sinks = Vec::with_capacity(config.routes.len());
+        for route in &config.routes {
+            let file = create(route)?;
+            sinks.push(match route.format.as_str() {
+                FORMAT_JSONL => Sink::Lines(BufWriter::new(file)),
+                format => Sink::Document {
+                    file,
+                    format: format.to_string(),
+                    matches: Vec::new(),
+                },
+            });
+        }
+        let counts = config.routes.iter().map(|r| (r.path.clone(), 0)).collect();
+        Ok(RoutingSink {
+            config,
+            sinks,
+            routes_by_category: HashMap::new(),
+            counts,
+        })
+    }
+
+    /// Findings written so far, per output path
+    pub fn counts(&self) -> &BTreeMap<String, usize> {
+        &self.counts
+    }
+}
+
+impl ResultSink for RoutingSink {
+    fn accept(&mut self, result: ScanResult) -> io::Result<()> {
+        for m in result.all_matches() {
+            let config = &self.config;
+            let targets = self
+                .routes_by_category
+                .entry(m.category.clone())
+                .or_insert_with(|| config.routes_for(&m.category));
+            for &i in targets.iter() {
+                match &mut self.sinks[i] {
+                    Sink::Lines(writer) => {
+                        serde_json::to_writer(&mut *writer, m)?;
+                        writer.write_all(b"\n")?;
+                    }
+                    Sink::Document { matches, .. } => matches.push(m.clone()),
+                }
+                *self
+                    .counts
+                    .entry(self.config.routes[i].path.clone())
+                    .or_default() += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flush line outputs and write the collected JSON and SARIF documents
+    fn finish(&mut self) -> io::Result<()> {
+        for sink in std::mem::take(&mut self.sinks) {
+            match sink {
+                Sink::Lines(mut writer) => writer.flush()?,
+                Sink::Document {
+                    file,
+                    format,
+                    matches,
+                } => {
+                    let writer = BufWriter::new(file);
+                    if format == FORMAT_SARIF {
+                        serde_json::to_writer_pretty(writer, &sarif_log(&matches))?;
+                    } else {
+                        serde_json::to_writer_pretty(writer, &matches)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::MemoryProvider;
+    use crate::scanner::FastScanner;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_findings_routed_by_category() {
+        let provider = MemoryProvider::new(Some(BTreeMap::from([(
+            "app.py".to_string(),
+            "DEBUG = True\npassword = \"hunter2hunter2\"\nhashlib.md5(x)\n".to_string(),
+        )])));
+        let results = FastScanner::new(None).scan_with_provider(&provider);
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = |name: &str| temp_dir.path().join(name).to_string_lossy().to_string();
+        let mut config = ScanConfig::new();
+        config
+            .add_route(
+                vec!["secrets".to_string()],
+                path("secrets.jsonl"),
+                None,
+                Some(true),
+            )
+            .unwrap();
+        config
+            .add_route(
+                vec!["config".to_string()],
+                path("config.sarif"),
+                Some("sarif".to_string()),
+                None,
+            )
+            .unwrap();
+        config
+            .add_route(
+                vec!["*".to_string()],
+                path("rest.json"),
+                Some("json".to_string()),
+                None,
+            )
+            .unwrap();
+
+        let mut sink = RoutingSink::new(config).unwrap();
+        for result in results {
+            sink.accept(result).unwrap();
+        }
+        sink.finish().unwrap();
+        let counts = sink.counts();
+        assert_eq!(counts[&path("secrets.jsonl")], 1);
+        assert_eq!(counts[&path("config.sarif")], 1);
+        assert_eq!(counts[&path("rest.json")], 1);
+
+        let sarif: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path("config.sarif")).unwrap()).unwrap();
+        assert_eq!(sarif["runs"][0]["results"][0]["ruleId"], "debug_mode");
+        assert_eq!(
+            sarif["runs"][0]["tool"]["driver"]["rules"][0]["properties"]["tags"][1],
+            "external/cwe/cwe-489"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(path("secrets.jsonl"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_restricted_route_tightens_existing_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("pii.jsonl");
+        std::fs::write(&path, "stale\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let mut config = ScanConfig::new();
+        config
+            .add_route(
+                vec!["*".to_string()],
+                path.to_string_lossy().to_string(),
+                None,
+                Some(true),
+            )
+            .unwrap();
+        RoutingSink::new(config).unwrap().finish().unwrap();
+
+        let metadata = std::fs::metadata(&path).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        assert_eq!(metadata.len(), 0);
+    }
+}
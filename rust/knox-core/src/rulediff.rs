@@ -0,0 +1,212 @@
+//! Machine-readable diff between two rule sets
+//!
+//! Used by change-review gates to see exactly which detection content a
+//! pack update adds, removes or modifies before it ships
+
+use crate::matcher::SecurityPattern;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct FieldChange {
+    #[pyo3(get)]
+    pub field: String,
+    #[pyo3(get)]
+    pub old: String,
+    #[pyo3(get)]
+    pub new: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct RuleChange {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct RuleSetDiff {
+    #[pyo3(get)]
+    pub added: Vec<String>,
+    #[pyo3(get)]
+    pub removed: Vec<String>,
+    #[pyo3(get)]
+    pub modified: Vec<RuleChange>,
+}
+
+#[pymethods]
+impl RuleSetDiff {
+    /// Whether the two rule sets are identical
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+    /// Rules whose severity changed, as (name, old, new)
+    pub fn severity_changes(&self) -> Vec<(String, String, String)> {
+        self.modified
+            .iter()
+            .flat_map(|rule| {
+                rule.changes
+                    .iter()
+                    .filter(|c| c.field == "severity")
+                    .map(|c| (rule.name.clone(), c.old.clone(), c.new.clone()))
+            })
+            .collect()
+    }
+
+    pub fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RuleSetDiff(added={}, removed={}, modified={})",
+            self.added.len(),
+            self.removed.len(),
+            self.modified.len()
+        )
+    }
+}
+
+/// Every field that changes what a rule reports, rendered for display
+///
+/// Provenance (`source`) and the rule's own examples are left out; the
+/// fields hashed by `linecache::rule_set_hash` are all covered.
+fn semantic_fields(rule: &SecurityPattern) -> Vec<(&'static str, String)> {
+    let optional = |value: &Option<String>| value.clone().unwrap_or_default();
+    vec![
+        // Compared as compiled, so toggling `case_insensitive` shows up too
+        ("pattern", rule.effective_pattern()),
+        ("negative_pattern", optional(&rule.negative_pattern)),
+        ("severity", rule.severity.to_string()),
+        ("confidence", rule.confidence.clone()),
+        ("category", rule.category.clone()),
+        ("description", rule.description.clone()),
+        ("scope", rule.scope.clone()),
+        ("multiline", rule.multiline.to_string()),
+        ("fancy", rule.fancy.to_string()),
+        ("languages", rule.languages.join(", ")),
+        ("requires_imports", rule.requires_imports.join(", ")),
+        ("cwe", optional(&rule.cwe)),
+        ("owasp", optional(&rule.owasp)),
+        ("references", rule.references.join(", ")),
+    ]
+}
+
+fn field_changes(old: &SecurityPattern, new: &SecurityPattern) -> Vec<FieldChange> {
+    semantic_fields(old)
+        .into_iter()
+        .zip(semantic_fields(new))
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|((field, old), (_, new))| FieldChange {
+            field: field.to_string(),
+            old,
+            new,
+        })
+        .collect()
+}
+
+/// Compare two rule packs by rule name
+#[pyfunction]
+pub fn diff_rule_sets(
+    old_pack: Vec<SecurityPattern>,
+    new_pack: Vec<SecurityPattern>,
+) -> RuleSetDiff {
+    let old_by_name: HashMap<&str, &SecurityPattern> =
+        old_pack.iter().map(|p| (p.name.as_str(), p)).collect();
+    let new_by_name: HashMap<&str, &SecurityPattern> =
+        new_pack.iter().map(|p| (p.name.as_str(), p)).collect();
+
+    let added = new_pack
+        .iter()
+        .filter(|p| !old_by_name.contains_key(p.name.as_str()))
+        .map(|p| p.name.clone())
+        .collect();
+    let removed = old_pack
+        .iter()
+        .filter(|p| !new_by_name.contains_key(p.name.as_str()))
+        .map(|p| p.name.clone())
+        .collect();
+    let modified = new_pack
+        .iter()
+        .filter_map(|new| {
+            let old = old_by_name.get(new.name.as_str())?;
+            let changes = field_changes(old, new);
+            (!changes.is_empty()).then(|| RuleChange {
+                name: new.name.clone(),
+                changes,
+            })
+        })
+        .collect();
+
+    RuleSetDiff {
+        added,
+        removed,
+        modified,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str, pattern: &str, severity: &str) -> SecurityPattern {
+        SecurityPattern::new(
+            name.to_string(),
+            pattern.to_string(),
+            severity.parse().unwrap(),
+            "custom".to_string(),
+            "Test rule".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_diff_rule_sets() {
+        let old = vec![rule("a", "foo", "low"), rule("b", "bar", "high")];
+        let new = vec![rule("a", "foo\\(", "medium"), rule("c", "baz", "low")];
+        let diff = diff_rule_sets(old, new);
+
+        assert_eq!(diff.added, vec!["c"]);
+        assert_eq!(diff.removed, vec!["b"]);
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].changes.len(), 2);
+        assert_eq!(
+            diff.severity_changes(),
+            vec![("a".to_string(), "low".to_string(), "medium".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_diff_covers_matching_options() {
+        let old = rule("a", "foo", "low");
+        let mut new = old.clone();
+        new.negative_pattern = Some("foo_test".to_string());
+        new.languages = vec!["python".to_string()];
+        new.scope = "string".to_string();
+        new.fancy = true;
+
+        let diff = diff_rule_sets(vec![old], vec![new]);
+        let fields: Vec<&str> = diff.modified[0]
+            .changes
+            .iter()
+            .map(|c| c.field.as_str())
+            .collect();
+        assert_eq!(
+            fields,
+            vec!["negative_pattern", "scope", "fancy", "languages"]
+        );
+        assert_eq!(diff.modified[0].changes[0].old, "");
+    }
+
+    #[test]
+    fn test_identical_sets_produce_empty_diff() {
+        let rules = vec![rule("a", "foo", "low")];
+        assert!(diff_rule_sets(rules.clone(), rules).is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+//! Line pattern regexes
+//!
+//! A rule's `pattern` is one regex or a list of them. A list reads better
+//! than one giant alternation when a detection has several unrelated
+//! shapes: each entry is written and checked on its own, then they are
+//! OR-ed into a single regex at compile time, so the rule still reports
+//! under one name and runs as one member of the rule set. Capture groups
+//! are numbered across the entries, the groups of entries that didn't
+//! match coming back empty.
+
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+
+/// Regex of a line pattern, given as a string or a list of strings
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RulePattern {
+    Single(String),
+    AnyOf(Vec<String>),
+}
+
+impl RulePattern {
+    /// The regexes OR-ed together, one for a single pattern
+    pub fn alternatives(&self) -> &[String] {
+        match self {
+            RulePattern::Single(pattern) => std::slice::from_ref(pattern),
+            RulePattern::AnyOf(patterns) => patterns,
+        }
+    }
+
+    /// The combined regex, each entry of a list in its own group so inline
+    /// flags and alternations stay inside it
+    pub fn source(&self) -> Cow<'_, str> {
+        match self.alternatives() {
+            [pattern] => Cow::Borrowed(pattern),
+            patterns => Cow::Owned(
+                patterns
+                    .iter()
+                    .map(|p| format!("(?:{})", p))
+                    .collect::<Vec<_>>()
+                    .join("|"),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for RulePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source())
+    }
+}
+
+impl PartialEq<&str> for RulePattern {
+    fn eq(&self, other: &&str) -> bool {
+        matches!(self, RulePattern::Single(pattern) if pattern == other)
+    }
+}
+
+impl From<String> for RulePattern {
+    fn from(pattern: String) -> Self {
+        RulePattern::Single(pattern)
+    }
+}
+
+impl From<&str> for RulePattern {
+    fn from(pattern: &str) -> Self {
+        RulePattern::Single(pattern.to_string())
+    }
+}
+
+impl From<Vec<String>> for RulePattern {
+    fn from(patterns: Vec<String>) -> Self {
+        RulePattern::AnyOf(patterns)
+    }
+}
+
+impl<'source> FromPyObject<'source> for RulePattern {
+    fn extract(value: &'source PyAny) -> PyResult<Self> {
+        if let Ok(pattern) = value.downcast::<PyString>() {
+            return Ok(RulePattern::Single(pattern.to_str()?.to_string()));
+        }
+        Ok(RulePattern::AnyOf(value.extract()?))
+    }
+}
+
+impl IntoPy<PyObject> for RulePattern {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            RulePattern::Single(pattern) => pattern.into_py(py),
+            RulePattern::AnyOf(patterns) => patterns.into_py(py),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::{PatternMatcher, SecurityPattern};
+    use crate::severity::Severity;
+
+    #[test]
+    fn test_pattern_list_reports_as_one_rule() {
+        let pattern: RulePattern =
+            serde_yaml::from_str("- 'eval\\s*\\('\n- '(?i)new Function\\s*\\('\n").unwrap();
+        assert_eq!(pattern.source(), r"(?:eval\s*\()|(?:(?i)new Function\s*\()");
+        assert_eq!(
+            serde_json::from_str::<RulePattern>(r#""eval""#).unwrap(),
+            RulePattern::from("eval")
+        );
+
+        let mut rule = SecurityPattern::new(
+            "dynamic_code".to_string(),
+            pattern,
+            Severity::High,
+            "injection".to_string(),
+            "Code built from strings at runtime".to_string(),
+        );
+        let mut matcher = PatternMatcher::new_empty();
+        matcher.add_pattern(rule.clone()).unwrap();
+        let names: Vec<String> = matcher
+            .match_content("eval(src)\nconst f = NEW FUNCTION ('a', body)\nevaluate(x)\n")
+            .into_iter()
+            .map(|m| format!("{}:{}", m.line_number, m.pattern_name))
+            .collect();
+        assert_eq!(names, vec!["1:dynamic_code", "2:dynamic_code"]);
+
+        rule.pattern = vec!["eval".to_string(), "exec(".to_string()].into();
+        assert!(PatternMatcher::new_empty().add_pattern(rule.clone()).is_err());
+        rule.pattern = RulePattern::AnyOf(Vec::new());
+        assert!(PatternMatcher::new_empty().add_pattern(rule).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+//! Rule set files
+//!
+//! `PatternMatcher.export_rules` writes the matcher's effective rules
+//! (built-ins, registered packs and custom rules, with severity overrides
+//! applied, plus the names of disabled rules) to a JSON or YAML file that
+//! `load_rules` reads back, so a tuned rule set can be versioned in git and
+//! shared between projects. Unlike a session file it carries no scanner
+//! configuration.
+
+use crate::composite::CompositeRule;
+use crate::config_rules::ConfigRule;
+use crate::constructs::ConstructRule;
+use crate::file_rules::FileRule;
+use crate::matcher::SecurityPattern;
+use crate::proximity::ProximityRule;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Bumped whenever the rule set layout changes incompatibly
+pub const RULESET_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuleFormat {
+    Json,
+    Yaml,
+}
+
+impl RuleFormat {
+    /// Parse `json`, `yaml` or `yml`, case-insensitively
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "json" => Some(RuleFormat::Json),
+            "yaml" | "yml" => Some(RuleFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Format implied by the extension of `path`; anything but `.json` is
+    /// read as YAML, which also accepts most JSON
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => RuleFormat::Json,
+            _ => RuleFormat::Yaml,
+        }
+    }
+}
+
+/// Every rule of a matcher, by family
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub format_version: u32,
+    #[serde(default)]
+    pub patterns: Vec<SecurityPattern>,
+    #[serde(default)]
+    pub file_rules: Vec<FileRule>,
+    #[serde(default)]
+    pub construct_rules: Vec<ConstructRule>,
+    #[serde(default)]
+    pub config_rules: Vec<ConfigRule>,
+    #[serde(default)]
+    pub composite_rules: Vec<CompositeRule>,
+    #[serde(default)]
+    pub proximity_rules: Vec<ProximityRule>,
+    /// Rules loaded but not reported, sorted
+    #[serde(default)]
+    pub disabled_rules: Vec<String>,
+    /// Categories whose rules are not reported, sorted
+    #[serde(default)]
+    pub disabled_categories: Vec<String>,
+}
+
+impl RuleSet {
+    /// Number of rules across all families
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+            + self.file_rules.len()
+            + self.construct_rules.len()
+            + self.config_rules.len()
+            + self.composite_rules.len()
+            + self.proximity_rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Write a rule set to `path`
+pub fn save(rules: &RuleSet, path: &Path, format: RuleFormat) -> io::Result<()> {
+    let text = match format {
+        RuleFormat::Json => serde_json::to_string_pretty(rules).map_err(io::Error::other)?,
+        RuleFormat::Yaml => serde_yaml::to_string(rules).map_err(io::Error::other)?,
+    };
+    fs::write(path, text)
+}
+
+/// Read a rule set back, in the format implied by the extension of `path`
+pub fn load(path: &Path) -> io::Result<RuleSet> {
+    let text = fs::read_to_string(path)?;
+    let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+    let rules: RuleSet = match RuleFormat::from_path(path) {
+        RuleFormat::Json => serde_json::from_str(&text).map_err(|e| invalid(e.to_string()))?,
+        RuleFormat::Yaml => serde_yaml::from_str(&text).map_err(|e| invalid(e.to_string()))?,
+    };
+
+    if rules.format_version != RULESET_FORMAT_VERSION {
+        return Err(invalid(format!(
+            "unsupported rule set format {} (expected {})",
+            rules.format_version, RULESET_FORMAT_VERSION
+        )));
+    }
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::matcher::{PatternMatcher, SecurityPattern};
+    use crate::severity::Severity;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rules_round_trip_through_yaml_and_json() {
+        let mut matcher = PatternMatcher::new();
+        matcher.set_severity("debug_mode", "critical").unwrap();
+        matcher.disable_rule("weak_crypto_md5");
+        matcher
+            .add_pattern(SecurityPattern::new(
+                "internal_token".to_string(),
+                r"itk_[0-9a-f]{12}".to_string(),
+                Severity::High,
+                "secrets".to_string(),
+                "Internal service token".to_string(),
+            ))
+            .unwrap();
+        let content = "hashlib.md5(x)\nDEBUG = True\ntoken = 'itk_0123456789ab'\n";
+        let findings = |matcher: &mut PatternMatcher| -> Vec<(String, Severity)> {
+            matcher
+                .match_content(content)
+                .into_iter()
+                .map(|m| (m.pattern_name, m.severity))
+                .collect()
+        };
+        let expected = findings(&mut matcher);
+        assert!(expected.contains(&("debug_mode".to_string(), Severity::Critical)));
+
+        let temp_dir = TempDir::new().unwrap();
+        for file in ["rules.yaml", "rules.json"] {
+            let path = temp_dir.path().join(file).to_string_lossy().into_owned();
+            let written = matcher.export_rules(path.clone(), None).unwrap();
+
+            let mut loaded = PatternMatcher::new_empty();
+            assert_eq!(loaded.load_rules(path).unwrap(), written);
+            assert_eq!(findings(&mut loaded), expected);
+            assert_eq!(loaded.rule_set().disabled_rules, vec!["weak_crypto_md5"]);
+        }
+
+        let path = temp_dir
+            .path()
+            .join("rules.toml")
+            .to_string_lossy()
+            .into_owned();
+        assert!(matcher
+            .export_rules(path, Some("toml".to_string()))
+            .is_err());
+    }
+}
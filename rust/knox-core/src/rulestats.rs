@@ -0,0 +1,187 @@
+//! Per-rule match statistics
+//!
+//! With statistics enabled, a matcher counts for each rule how often it was
+//! evaluated, how many findings it produced and how long it ran, to single
+//! out noisy and expensive rules across a large scan. Findings are counted
+//! as the rule reports them, before suppressions, the allowlist and
+//! deduplication.
+//!
+//! Clones of a matcher share its counters, so the per-file copies of a
+//! parallel scan add up to one total. Each copy batches its updates and
+//! hands them over when dropped or asked for statistics, so workers don't
+//! contend on every line.
+
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct RuleStatistics {
+    #[pyo3(get)]
+    pub rule: String,
+    /// Times the rule ran: per line (or string literal) for line patterns
+    /// the prefilter let through, per file for the other rule families
+    #[pyo3(get)]
+    pub evaluations: u64,
+    #[pyo3(get)]
+    pub matches: u64,
+    /// Cumulative time spent evaluating the rule
+    #[pyo3(get)]
+    pub total_time_ms: f64,
+}
+
+#[pymethods]
+impl RuleStatistics {
+    /// Average time per evaluation
+    #[getter]
+    pub fn mean_time_us(&self) -> f64 {
+        if self.evaluations == 0 {
+            return 0.0;
+        }
+        self.total_time_ms * 1000.0 / self.evaluations as f64
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RuleStatistics(rule={}, evaluations={}, matches={}, total_time_ms={:.3})",
+            self.rule, self.evaluations, self.matches, self.total_time_ms
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Counter {
+    evaluations: u64,
+    matches: u64,
+    elapsed: Duration,
+}
+
+impl Counter {
+    fn add(&mut self, other: &Counter) {
+        self.evaluations += other.evaluations;
+        self.matches += other.matches;
+        self.elapsed += other.elapsed;
+    }
+}
+
+/// Counters of one matcher, shared with its clones
+#[derive(Debug, Default)]
+pub struct RuleStats {
+    shared: Arc<Mutex<HashMap<String, Counter>>>,
+    pending: HashMap<String, Counter>,
+}
+
+impl RuleStats {
+    fn counter(&mut self, rule: &str) -> &mut Counter {
+        if !self.pending.contains_key(rule) {
+            self.pending.insert(rule.to_string(), Counter::default());
+        }
+        self.pending.get_mut(rule).expect("counter just inserted")
+    }
+
+    /// Count one run of `rule` that took `elapsed`
+    pub fn record_evaluation(&mut self, rule: &str, elapsed: Duration) {
+        let counter = self.counter(rule);
+        counter.evaluations += 1;
+        counter.elapsed += elapsed;
+    }
+
+    /// Count `count` findings of `rule`
+    pub fn record_matches(&mut self, rule: &str, count: usize) {
+        if count > 0 {
+            self.counter(rule).matches += count as u64;
+        }
+    }
+
+    /// Hand the batched updates over to the shared counters
+    pub fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let mut shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        for (rule, counter) in self.pending.drain() {
+            shared.entry(rule).or_default().add(&counter);
+        }
+    }
+
+    /// Totals so far, most expensive rule first
+    pub fn snapshot(&mut self) -> Vec<RuleStatistics> {
+        self.flush();
+        let shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        let mut statistics: Vec<RuleStatistics> = shared
+            .iter()
+            .map(|(rule, counter)| RuleStatistics {
+                rule: rule.clone(),
+                evaluations: counter.evaluations,
+                matches: counter.matches,
+                total_time_ms: counter.elapsed.as_secs_f64() * 1000.0,
+            })
+            .collect();
+        statistics.sort_by(|a, b| {
+            b.total_time_ms
+                .total_cmp(&a.total_time_ms)
+                .then_with(|| a.rule.cmp(&b.rule))
+        });
+        statistics
+    }
+
+    /// Zero the counters, for this matcher and its clones
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.shared
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+impl Clone for RuleStats {
+    fn clone(&self) -> Self {
+        RuleStats {
+            shared: Arc::clone(&self.shared),
+            pending: HashMap::new(),
+        }
+    }
+}
+
+impl Drop for RuleStats {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::matcher::PatternMatcher;
+
+    #[test]
+    fn test_statistics_count_across_clones() {
+        let content = "DEBUG = True\nx = 1\nDEBUG = True  # knox:ignore\n";
+        let mut matcher = PatternMatcher::new();
+        assert!(matcher.statistics().is_empty());
+        matcher.set_statistics(true);
+        matcher.match_content(content);
+        {
+            let mut worker = matcher.clone();
+            worker.match_content(content);
+        }
+
+        let statistics = matcher.statistics();
+        let debug = statistics.iter().find(|s| s.rule == "debug_mode").unwrap();
+        // Suppressed findings still count as the rule's hits
+        assert_eq!(debug.matches, 4);
+        assert!(debug.evaluations >= 4);
+        assert!(statistics
+            .windows(2)
+            .all(|w| w[0].total_time_ms >= w[1].total_time_ms));
+
+        matcher.reset_statistics();
+        assert!(matcher.statistics().is_empty());
+        matcher.set_statistics(false);
+        matcher.match_content(content);
+        assert!(matcher.statistics().is_empty());
+    }
+}
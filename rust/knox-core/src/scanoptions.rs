@@ -0,0 +1,122 @@
+//! Settings shared by the directory scan entry points
+//!
+//! `scan_directory`, `scan_to_sink` and `scan_directory_buffer` all take a
+//! `ScanOptions`, so a new setting is added in one place and reaches every
+//! entry point. From Python the options are keyword-only:
+//! `ScanOptions(profile="quick", parallel=False)`.
+
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct ScanOptions {
+    /// Deepest directory level walked below the root
+    #[pyo3(get, set)]
+    pub max_depth: Option<usize>,
+    /// Scan files on all cores
+    #[pyo3(get, set)]
+    pub parallel: bool,
+    /// Built-in profile name ("quick", "full" or "deep")
+    #[pyo3(get, set)]
+    pub profile: Option<String>,
+    /// Keep matches in an internal store so Python only materializes the
+    /// pages it reads
+    #[pyo3(get, set)]
+    pub lazy: bool,
+    /// Walk dependency and build directories too, keeping only the
+    /// profile's own excludes
+    #[pyo3(get, set)]
+    pub no_default_excludes: bool,
+    /// Tag findings with the kind of path they are in (test, vendored, ...)
+    #[pyo3(get, set)]
+    pub classify_paths: bool,
+    /// Directories, relative to the root, holding third-party code
+    #[pyo3(get, set)]
+    pub third_party_roots: Vec<String>,
+    /// Share of files that must be readable for the scan to succeed
+    #[pyo3(get, set)]
+    pub min_coverage: Option<f64>,
+    /// Most files read at once, for network filesystems
+    #[pyo3(get, set)]
+    pub max_concurrent_io: Option<usize>,
+    /// Attach line counts and function sizes to each result
+    #[pyo3(get, set)]
+    pub with_metrics: bool,
+    /// Record every file's content hash, for baselines
+    #[pyo3(get, set)]
+    pub hash_contents: bool,
+}
+
+#[pymethods]
+impl ScanOptions {
+    #[new]
+    #[pyo3(signature = (
+        *,
+        max_depth=None,
+        parallel=true,
+        profile=None,
+        lazy=false,
+        no_default_excludes=false,
+        classify_paths=false,
+        third_party_roots=Vec::new(),
+        min_coverage=None,
+        max_concurrent_io=None,
+        with_metrics=false,
+        hash_contents=false
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        max_depth: Option<usize>,
+        parallel: bool,
+        profile: Option<String>,
+        lazy: bool,
+        no_default_excludes: bool,
+        classify_paths: bool,
+        third_party_roots: Vec<String>,
+        min_coverage: Option<f64>,
+        max_concurrent_io: Option<usize>,
+        with_metrics: bool,
+        hash_contents: bool,
+    ) -> Self {
+        ScanOptions {
+            max_depth,
+            parallel,
+            profile,
+            lazy,
+            no_default_excludes,
+            classify_paths,
+            third_party_roots,
+            min_coverage,
+            max_concurrent_io,
+            with_metrics,
+            hash_contents,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ScanOptions(profile={}, parallel={})",
+            self.profile.as_deref().unwrap_or("default"),
+            self.parallel
+        )
+    }
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            max_depth: None,
+            parallel: true,
+            profile: None,
+            lazy: false,
+            no_default_excludes: false,
+            classify_paths: false,
+            third_party_roots: Vec::new(),
+            min_coverage: None,
+            max_concurrent_io: None,
+            with_metrics: false,
+            hash_contents: false,
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! Repository security posture scorecard
+//!
+//! Rolls scan results up into a handful of dimensions (secret hygiene,
+//! injection exposure, crypto hygiene, config hardening, dependency risk),
+//! each scored 0-100 with a letter grade and the findings that cost the
+//! most points, for reporting without post-processing on the Python side.
+
+use crate::matcher::Match;
+use crate::scanner::ScanResult;
+use crate::severity::Severity;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// (dimension, categories counted against it)
+const DIMENSIONS: &[(&str, &[&str])] = &[
+    ("secret_hygiene", &["secrets"]),
+    (
+        "injection_exposure",
+        &[
+            "injection",
+            "xss",
+            "deserialization",
+            "ssrf",
+            "path_traversal",
+        ],
+    ),
+    ("crypto_hygiene", &["crypto"]),
+    ("config_hardening", &["config", "network"]),
+    ("dependency_risk", &["dependencies", "supply_chain"]),
+];
+
+/// Findings listed per dimension
+const TOP_FINDINGS: usize = 5;
+
+/// Penalties are scaled to this many lines of code, so small and large
+/// repositories with the same finding density grade the same
+const PENALTY_LINES: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct ScoreDimension {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub score: u32,
+    #[pyo3(get)]
+    pub grade: String,
+    #[pyo3(get)]
+    pub finding_count: usize,
+    /// Highest-severity findings in this dimension
+    #[pyo3(get)]
+    pub top_findings: Vec<Match>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct Scorecard {
+    #[pyo3(get)]
+    pub score: u32,
+    #[pyo3(get)]
+    pub grade: String,
+    #[pyo3(get)]
+    pub dimensions: Vec<ScoreDimension>,
+    #[pyo3(get)]
+    pub files_scanned: usize,
+    #[pyo3(get)]
+    pub code_lines: Option<usize>,
+}
+
+#[pymethods]
+impl Scorecard {
+    pub fn dimension(&self, name: &str) -> Option<ScoreDimension> {
+        self.dimensions.iter().find(|d| d.name == name).cloned()
+    }
+
+    pub fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Scorecard(grade={}, score={})", self.grade, self.score)
+    }
+}
+
+fn severity_penalty(severity: Severity) -> f64 {
+    match severity {
+        Severity::Critical => 25.0,
+        Severity::High => 10.0,
+        Severity::Medium => 4.0,
+        Severity::Low => 1.0,
+        Severity::Info => 0.0,
+    }
+}
+
+pub fn letter_grade(score: u32) -> &'static str {
+    match score {
+        90.. => "A",
+        80..=89 => "B",
+        70..=79 => "C",
+        60..=69 => "D",
+        _ => "F",
+    }
+}
+
+/// Build a scorecard from directory scan results
+///
+/// `parse_summary` is the output of `CodeParser.analyze_complexity` summed
+/// over the repository; its `code_lines` scales penalties to the code size.
+#[pyfunction]
+pub fn build_scorecard(
+    results: Vec<ScanResult>,
+    parse_summary: Option<HashMap<String, usize>>,
+) -> Scorecard {
+    let code_lines = parse_summary.and_then(|s| s.get("code_lines").copied());
+    let scale = code_lines.map_or(1.0, |lines| {
+        PENALTY_LINES as f64 / lines.max(PENALTY_LINES) as f64
+    });
+
+    let matches: Vec<&Match> = results.iter().flat_map(|r| r.all_matches()).collect();
+
+    let dimensions: Vec<ScoreDimension> = DIMENSIONS
+        .iter()
+        .map(|(name, categories)| {
+            let mut found: Vec<&Match> = matches
+                .iter()
+                .copied()
+                .filter(|m| categories.contains(&m.category.as_str()))
+                .collect();
+            found.sort_by_key(|m| std::cmp::Reverse(m.severity));
+
+            let penalty: f64 = found.iter().map(|m| severity_penalty(m.severity)).sum();
+            let score = (100.0 - penalty * scale).clamp(0.0, 100.0).round() as u32;
+
+            ScoreDimension {
+                name: name.to_string(),
+                score,
+                grade: letter_grade(score).to_string(),
+                finding_count: found.len(),
+                top_findings: found.into_iter().take(TOP_FINDINGS).cloned().collect(),
+            }
+        })
+        .collect();
+
+    // The weakest dimension caps the overall grade
+    let average = dimensions.iter().map(|d| d.score).sum::<u32>() / dimensions.len() as u32;
+    let worst = dimensions.iter().map(|d| d.score).min().unwrap_or(100);
+    let score = average.min(worst + 20);
+
+    Scorecard {
+        score,
+        grade: letter_grade(score).to_string(),
+        dimensions,
+        files_scanned: results.len(),
+        code_lines,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::FastScanner;
+    use std::fs::write;
+    use tempfile::TempDir;
+
+    fn scan(content: &str) -> ScanResult {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("app.py");
+        write(&file_path, content).unwrap();
+        FastScanner::new(None)
+            .scan_file_sync(file_path.to_string_lossy().to_string())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_scorecard_grades_dimensions() {
+        let result = scan("password = \"hunter2hunter2\"\n");
+
+        let card = build_scorecard(vec![result], None);
+        let secrets = card.dimension("secret_hygiene").unwrap();
+        assert_eq!(secrets.finding_count, 1);
+        assert_eq!(secrets.score, 75);
+        assert_eq!(secrets.grade, "C");
+        assert_eq!(secrets.top_findings[0].pattern_name, "hardcoded_password");
+        assert_eq!(card.dimension("config_hardening").unwrap().grade, "A");
+        assert_eq!(card.files_scanned, 1);
+    }
+
+    #[test]
+    fn test_penalties_scale_with_code_size() {
+        let result = scan("password = \"hunter2hunter2\"\n");
+        let summary = HashMap::from([("code_lines".to_string(), 10_000)]);
+
+        let card = build_scorecard(vec![result], Some(summary));
+        assert_eq!(card.dimension("secret_hygiene").unwrap().score, 98);
+        assert_eq!(card.grade, "A");
+    }
+}
@@ -0,0 +1,201 @@
+//! Robustness harness for hostile input (`fuzzing` feature)
+//!
+//! `exercise` drives the matcher and parsers over arbitrary bytes the way a
+//! scan of an untrusted repository would. The cargo-fuzz targets under
+//! `fuzz/` call it directly; `self_test` runs it from Python over a corpus
+//! directory plus built-in adversarial inputs and deterministic mutations
+//! of each, reporting every input that panicked instead of crashing.
+
+use crate::config_rules;
+use crate::language;
+use crate::matcher::PatternMatcher;
+use crate::parser::CodeParser;
+use crate::sniff;
+use pyo3::prelude::*;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::time::Instant;
+
+/// Languages whose parsers are exercised on every input
+const LANGUAGES: &[&str] = &["python", "javascript", "typescript", "rust"];
+/// Lines matched one by one, on top of the whole-content pass
+const MAX_LINE_CALLS: usize = 64;
+/// Mutated variants generated per input
+const MUTATIONS_PER_INPUT: usize = 8;
+
+/// Run matcher and parsers over `data`; any panic is a bug
+pub fn exercise(data: &[u8]) {
+    let _ = sniff::sniff_kind(data);
+    let _ = language::detect_language(Path::new("input"), data);
+    let text = String::from_utf8_lossy(data);
+
+    let mut matcher = PatternMatcher::new();
+    matcher.match_content(&text);
+    matcher.match_file_rules(&text);
+    matcher.match_composite_rules(&text);
+    matcher.match_proximity_rules(&text);
+    for (i, line) in text.lines().take(MAX_LINE_CALLS).enumerate() {
+        matcher.match_line(line, i + 1);
+    }
+    for language in config_rules::LANGUAGES {
+        matcher.match_config(language, &text);
+    }
+
+    for language in LANGUAGES {
+        let parser = CodeParser::new(Some(language.to_string()));
+        let artifacts = parser.parse(&text);
+        matcher.match_constructs(&artifacts);
+        let _ = parser.analyze_complexity(&text);
+    }
+}
+
+/// Inputs known to stress regex engines, parsers and offset mapping
+fn adversarial_inputs() -> Vec<Vec<u8>> {
+    vec![
+        Vec::new(),
+        b"\0\0\0\xff\xfe\x00password = \"".to_vec(),
+        "a".repeat(1 << 16).into_bytes(),
+        format!("api_key = \"{}", "x".repeat(10_000)).into_bytes(),
+        "(".repeat(5_000).into_bytes(),
+        format!("def f({}):\n", "a,".repeat(5_000)).into_bytes(),
+        "\r\n\r\r\n\t\t\u{feff}DEBUG = True\r".as_bytes().to_vec(),
+        "ſ\u{130}\u{10FFFF}password=\"ǅǅǅǅǅǅǅǅ\"\n"
+            .repeat(100)
+            .into_bytes(),
+        "\"\\".repeat(10_000).into_bytes(),
+        "# knox:ignore[".repeat(1_000).into_bytes(),
+        "import ".repeat(2_000).into_bytes(),
+        "\n".repeat(100_000).into_bytes(),
+        vec![0xc3; 4096],
+    ]
+}
+
+/// Deterministic byte-level mutations of `input` (xorshift seeded by its length)
+fn mutations(input: &[u8]) -> Vec<Vec<u8>> {
+    if input.is_empty() {
+        return Vec::new();
+    }
+    let mut state = 0x9e37_79b9_7f4a_7c15_u64 ^ input.len() as u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    (0..MUTATIONS_PER_INPUT)
+        .map(|i| {
+            let mut mutated = input.to_vec();
+            let at = next() as usize % mutated.len();
+            match i % 4 {
+                0 => mutated.truncate(at),
+                1 => mutated[at] ^= 1 << (next() % 8),
+                2 => {
+                    let chunk = mutated[at..].to_vec();
+                    mutated.extend_from_slice(&chunk);
+                }
+                _ => mutated.insert(at, next() as u8),
+            }
+            mutated
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Default)]
+#[pyclass]
+pub struct SelfTestReport {
+    /// Inputs run, including mutations
+    #[pyo3(get)]
+    pub inputs: usize,
+    /// One entry per input that panicked: its origin and the panic message
+    #[pyo3(get)]
+    pub failures: Vec<String>,
+    #[pyo3(get)]
+    pub elapsed_ms: u64,
+}
+
+#[pymethods]
+impl SelfTestReport {
+    #[getter]
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "SelfTestReport(inputs={}, failures={}, elapsed={}ms)",
+            self.inputs,
+            self.failures.len(),
+            self.elapsed_ms
+        )
+    }
+}
+
+/// Exercise every input and its mutations, collecting panics
+pub fn run(corpus: Vec<(String, Vec<u8>)>) -> SelfTestReport {
+    let start = Instant::now();
+    let mut report = SelfTestReport::default();
+
+    let builtin = adversarial_inputs()
+        .into_iter()
+        .enumerate()
+        .map(|(i, input)| (format!("builtin#{}", i), input));
+    for (origin, input) in builtin.chain(corpus) {
+        let variants = std::iter::once(input.clone()).chain(mutations(&input));
+        for (i, variant) in variants.enumerate() {
+            report.inputs += 1;
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| exercise(&variant))) {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                report
+                    .failures
+                    .push(format!("{} (variant {}): {}", origin, i, message));
+            }
+        }
+    }
+
+    report.elapsed_ms = start.elapsed().as_millis() as u64;
+    report
+}
+
+/// Run the robustness harness over the files in `corpus` (a directory)
+/// plus built-in adversarial inputs
+#[pyfunction]
+pub fn self_test(py: Python<'_>, corpus: Option<String>) -> PyResult<SelfTestReport> {
+    let mut inputs = Vec::new();
+    if let Some(dir) = corpus {
+        let to_py_err =
+            |e: std::io::Error| pyo3::exceptions::PyIOError::new_err(format!("{}: {}", dir, e));
+        for entry in std::fs::read_dir(&dir).map_err(to_py_err)? {
+            let path = entry.map_err(to_py_err)?.path();
+            if path.is_file() {
+                let bytes = std::fs::read(&path).map_err(to_py_err)?;
+                inputs.push((path.to_string_lossy().to_string(), bytes));
+            }
+        }
+        inputs.sort();
+    }
+    Ok(py.allow_threads(|| run(inputs)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_survives_adversarial_inputs() {
+        let report = run(vec![(
+            "inline".to_string(),
+            b"password = \"hunter2hunter2\"\nos.system(cmd)\n".to_vec(),
+        )]);
+        // The empty built-in input has no mutations
+        assert_eq!(
+            report.inputs,
+            adversarial_inputs().len() * (MUTATIONS_PER_INPUT + 1) + 1
+        );
+        assert!(report.passed(), "{:?}", report.failures);
+    }
+}
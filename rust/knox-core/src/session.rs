@@ -0,0 +1,110 @@
+//! Saved scan sessions
+//!
+//! A session file captures a scanner's configuration together with its
+//! effective rule set (built-ins, registered packs, custom patterns and
+//! file rules) and rule diagnostics, so a later run can restore the exact
+//! scanning state. Compiled regexes are rebuilt lazily after loading.
+
+use crate::scanner::FastScanner;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bumped whenever the session layout changes incompatibly
+pub const SESSION_FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct SessionFile {
+    format_version: u32,
+    created_unix: u64,
+    scanner: FastScanner,
+}
+
+/// Write a scanner's state to `path` as JSON
+pub fn save(scanner: &FastScanner, path: &Path) -> io::Result<()> {
+    #[derive(Serialize)]
+    struct SessionRef<'a> {
+        format_version: u32,
+        created_unix: u64,
+        scanner: &'a FastScanner,
+    }
+
+    let created_unix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let json = serde_json::to_string_pretty(&SessionRef {
+        format_version: SESSION_FORMAT_VERSION,
+        created_unix,
+        scanner,
+    })
+    .map_err(io::Error::other)?;
+    fs::write(path, json)
+}
+
+/// Read a scanner back from a session file
+pub fn load(path: &Path) -> io::Result<FastScanner> {
+    let json = fs::read_to_string(path)?;
+    let session: SessionFile =
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    if session.format_version != SESSION_FORMAT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "unsupported session format {} (expected {})",
+                session.format_version, SESSION_FORMAT_VERSION
+            ),
+        ));
+    }
+
+    Ok(session.scanner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_session_round_trip_keeps_rules_and_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let session_path = temp_dir.path().join("session.json");
+        let source = temp_dir.path().join("settings.cfg");
+        write(&source, "DEBUG = True\n").unwrap();
+
+        let mut scanner = FastScanner::new(Some(1));
+        scanner.add_extension(".cfg".to_string());
+        scanner.add_excluded_dir("generated".to_string());
+        save(&scanner, &session_path).unwrap();
+
+        let mut restored = load(&session_path).unwrap();
+        assert!(restored.get_extensions().contains(&".cfg".to_string()));
+        assert!(restored
+            .get_excluded_dirs()
+            .contains(&"generated".to_string()));
+
+        let result = restored
+            .scan_file_sync(source.to_string_lossy().to_string())
+            .unwrap();
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].pattern_name, "debug_mode");
+    }
+
+    #[test]
+    fn test_unknown_session_version_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let session_path = temp_dir.path().join("session.json");
+        write(
+            &session_path,
+            r#"{"format_version": 99, "created_unix": 0, "scanner": {}}"#,
+        )
+        .unwrap();
+
+        let err = load(&session_path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
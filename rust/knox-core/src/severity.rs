@@ -0,0 +1,204 @@
+//! Finding severity
+//!
+//! Severities are parsed once, where a rule or filter is defined, so
+//! "Critical", "critical" and "CRIT" all name the same level and findings
+//! can be ordered and filtered by comparison. In Python, `Severity` values
+//! also compare equal to their names, so `finding.severity == "high"` keeps
+//! working, and order against each other: `Severity.HIGH < Severity.CRITICAL`.
+
+use pyo3::basic::CompareOp;
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Severity of a rule and its findings, least severe first so the derived
+/// order ranks them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+#[pyclass]
+pub enum Severity {
+    #[pyo3(name = "INFO")]
+    Info,
+    #[pyo3(name = "LOW")]
+    Low,
+    #[pyo3(name = "MEDIUM")]
+    Medium,
+    #[pyo3(name = "HIGH")]
+    High,
+    #[pyo3(name = "CRITICAL")]
+    Critical,
+}
+
+impl Severity {
+    /// Every severity, least severe first
+    pub const ALL: [Severity; 5] = [
+        Severity::Info,
+        Severity::Low,
+        Severity::Medium,
+        Severity::High,
+        Severity::Critical,
+    ];
+
+    /// Canonical lowercase name, as serialized
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+
+    /// Parse a severity name case-insensitively, accepting common
+    /// abbreviations and synonyms such as "crit", "med" or "informational"
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "critical" | "crit" => Some(Severity::Critical),
+            "high" | "hi" => Some(Severity::High),
+            "medium" | "med" | "moderate" => Some(Severity::Medium),
+            "low" => Some(Severity::Low),
+            "info" | "informational" | "note" => Some(Severity::Info),
+            _ => None,
+        }
+    }
+
+    /// This severity moved `steps` levels up (positive) or down
+    /// (negative), clamped to the ends of the scale
+    pub fn shifted(self, steps: i8) -> Self {
+        let index = (self as i8 + steps).clamp(0, Self::ALL.len() as i8 - 1);
+        Self::ALL[index as usize]
+    }
+
+    fn unknown(text: &str) -> String {
+        format!(
+            "Unknown severity: {} (expected one of {:?})",
+            text,
+            Self::ALL.map(Severity::as_str)
+        )
+    }
+}
+
+#[pymethods]
+impl Severity {
+    /// Parse a severity name, raising ValueError for unknown names
+    #[staticmethod]
+    #[pyo3(name = "parse")]
+    fn py_parse(text: &str) -> PyResult<Self> {
+        text.parse()
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// 0 for info up to 4 for critical
+    #[getter]
+    fn rank(&self) -> u8 {
+        *self as u8
+    }
+
+    fn __str__(&self) -> &'static str {
+        self.as_str()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Severity.{}", self.as_str().to_uppercase())
+    }
+
+    /// Hashes like its name, since it compares equal to it
+    fn __hash__(&self, py: Python<'_>) -> PyResult<isize> {
+        PyString::new(py, self.as_str()).hash()
+    }
+
+    fn __richcmp__(&self, other: &PyAny, op: CompareOp, py: Python<'_>) -> PyObject {
+        let other = match other.extract::<Severity>() {
+            Ok(other) => Some(other),
+            Err(_) => match other.extract::<&str>() {
+                Ok(text) => Severity::parse(text),
+                Err(_) => return py.NotImplemented(),
+            },
+        };
+        match (op, other) {
+            (CompareOp::Eq, other) => (Some(*self) == other).into_py(py),
+            (CompareOp::Ne, other) => (Some(*self) != other).into_py(py),
+            (_, None) => py.NotImplemented(),
+            (op, Some(other)) => op.matches(self.cmp(&other)).into_py(py),
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Severity::parse(text).ok_or_else(|| Severity::unknown(text))
+    }
+}
+
+impl TryFrom<String> for Severity {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<Severity> for String {
+    fn from(severity: Severity) -> Self {
+        severity.as_str().to_string()
+    }
+}
+
+impl PartialEq<&str> for Severity {
+    fn eq(&self, other: &&str) -> bool {
+        Severity::parse(other) == Some(*self)
+    }
+}
+
+/// Extract a severity given from Python as a `Severity` or its name
+pub fn from_py(value: &PyAny) -> PyResult<Severity> {
+    if let Ok(severity) = value.extract::<Severity>() {
+        return Ok(severity);
+    }
+    value
+        .extract::<&str>()?
+        .parse()
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// `from_py` for optional arguments, `None` standing for no severity
+pub fn from_py_optional(value: &PyAny) -> PyResult<Option<Severity>> {
+    if value.is_none() {
+        return Ok(None);
+    }
+    from_py(value).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_order() {
+        assert_eq!(Severity::parse(" CRIT "), Some(Severity::Critical));
+        assert_eq!(Severity::parse("Critical"), Some(Severity::Critical));
+        assert_eq!(Severity::parse("urgent"), None);
+        assert!(Severity::Low < Severity::Medium && Severity::High < Severity::Critical);
+        assert_eq!(Severity::Low.shifted(-3), Severity::Info);
+        assert_eq!(Severity::High.shifted(2), Severity::Critical);
+
+        let parsed: Vec<Severity> = serde_json::from_str(r#"["HIGH", "med"]"#).unwrap();
+        assert_eq!(parsed, vec![Severity::High, Severity::Medium]);
+        assert_eq!(
+            serde_json::to_string(&parsed).unwrap(),
+            r#"["high","medium"]"#
+        );
+        assert!(serde_json::from_str::<Severity>(r#""urgent""#).is_err());
+    }
+}
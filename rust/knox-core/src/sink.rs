@@ -0,0 +1,258 @@
+//! Incremental delivery of scan results
+//!
+//! `scan_directory` returns every result at once, which on large trees
+//! means holding all findings in memory until the walk ends. A
+//! `ResultSink` instead receives each file's result as soon as it is
+//! scanned: collected into a list, streamed to a JSON Lines file, split by
+//! category across outputs (see `routing`), or handed to a Python callable
+//! (which can insert into SQLite, a queue, ...).
+//! Worker threads deliver results one at a time, so sinks need no locking
+//! of their own.
+//!
+//! Finer still, a `MatchCallback` set with `set_on_match` on a matcher or
+//! scanner receives each finding as soon as the content it was found in
+//! has been matched, for live feedback while a large tree is scanned.
+
+use crate::matcher::Match;
+use crate::routing::{RoutingSink, ScanConfig};
+use crate::scanner::ScanResult;
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+/// Destination for scan results, fed one file at a time
+pub trait ResultSink: Send {
+    fn accept(&mut self, result: ScanResult) -> io::Result<()>;
+
+    /// Called once after the last result
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl ResultSink for Vec<ScanResult> {
+    fn accept(&mut self, result: ScanResult) -> io::Result<()> {
+        self.push(result);
+        Ok(())
+    }
+}
+
+/// Writes one JSON-encoded result per line
+pub struct JsonlSink<W: Write + Send> {
+    writer: W,
+}
+
+impl<W: Write + Send> JsonlSink<W> {
+    pub fn new(writer: W) -> Self {
+        JsonlSink { writer }
+    }
+}
+
+impl<W: Write + Send> ResultSink for JsonlSink<W> {
+    fn accept(&mut self, result: ScanResult) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, &result)?;
+        self.writer.write_all(b"\n")
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Calls a Python callable with each result, taking the GIL per call
+pub struct CallbackSink {
+    callback: PyObject,
+}
+
+impl ResultSink for CallbackSink {
+    fn accept(&mut self, result: ScanResult) -> io::Result<()> {
+        Python::with_gil(|py| {
+            self.callback
+                .call1(py, (result,))
+                .map(|_| ())
+                .map_err(|e| io::Error::other(format!("result callback failed: {}", e)))
+        })
+    }
+}
+
+/// Python callable handed each finding as it is produced
+#[derive(Clone)]
+pub struct MatchCallback {
+    callback: PyObject,
+}
+
+impl MatchCallback {
+    /// Wrap `callback`, raising TypeError if it isn't callable
+    pub fn new(py: Python<'_>, callback: PyObject) -> PyResult<Self> {
+        if !callback.as_ref(py).is_callable() {
+            return Err(pyo3::exceptions::PyTypeError::new_err(
+                "on_match callback must be callable",
+            ));
+        }
+        Ok(MatchCallback { callback })
+    }
+
+    /// Call the callback with each of `matches`, taking the GIL once
+    ///
+    /// Matching can't be unwound from the callback, so an exception it
+    /// raises is reported through `sys.unraisablehook` and delivery goes
+    /// on with the next finding.
+    pub fn deliver(&self, matches: &[Match]) {
+        if matches.is_empty() {
+            return;
+        }
+        Python::with_gil(|py| {
+            for m in matches {
+                if let Err(e) = self.callback.call1(py, (m.clone(),)) {
+                    e.write_unraisable(py, Some(self.callback.as_ref(py)));
+                }
+            }
+        });
+    }
+}
+
+enum SinkKind {
+    Collect(Vec<ScanResult>),
+    Jsonl(JsonlSink<BufWriter<File>>),
+    Routed(RoutingSink),
+    Callback(CallbackSink),
+}
+
+/// Result sink chosen from Python, for `scan_to_sink`
+#[pyclass]
+pub struct ScanSink {
+    kind: SinkKind,
+    result_count: usize,
+    match_count: usize,
+}
+
+impl ScanSink {
+    fn from_kind(kind: SinkKind) -> Self {
+        ScanSink {
+            kind,
+            result_count: 0,
+            match_count: 0,
+        }
+    }
+}
+
+#[pymethods]
+impl ScanSink {
+    /// Keep results in memory, to be retrieved with `take_results`
+    #[staticmethod]
+    pub fn collect() -> Self {
+        Self::from_kind(SinkKind::Collect(Vec::new()))
+    }
+
+    /// Stream results to `path` as JSON Lines
+    #[staticmethod]
+    pub fn jsonl(path: String) -> PyResult<Self> {
+        let file = File::create(&path)
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("{}: {}", path, e)))?;
+        Ok(Self::from_kind(SinkKind::Jsonl(JsonlSink::new(
+            BufWriter::new(file),
+        ))))
+    }
+
+    /// Send each finding to the outputs `config` routes its category to
+    #[staticmethod]
+    pub fn routed(config: ScanConfig) -> PyResult<Self> {
+        let sink = RoutingSink::new(config)
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
+        Ok(Self::from_kind(SinkKind::Routed(sink)))
+    }
+
+    /// Call `callback(result)` for each file as it is scanned
+    #[staticmethod]
+    pub fn callback(callback: PyObject) -> Self {
+        Self::from_kind(SinkKind::Callback(CallbackSink { callback }))
+    }
+
+    /// Results accepted so far
+    #[getter]
+    pub fn result_count(&self) -> usize {
+        self.result_count
+    }
+
+    /// Matches across the results accepted so far
+    #[getter]
+    pub fn match_count(&self) -> usize {
+        self.match_count
+    }
+
+    /// Findings written per output path by a `routed` sink
+    #[getter]
+    pub fn route_counts(&self) -> BTreeMap<String, usize> {
+        match &self.kind {
+            SinkKind::Routed(sink) => sink.counts().clone(),
+            _ => BTreeMap::new(),
+        }
+    }
+
+    /// Results held by a `collect` sink, emptying it
+    pub fn take_results(&mut self) -> Vec<ScanResult> {
+        match &mut self.kind {
+            SinkKind::Collect(results) => std::mem::take(results),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl ResultSink for ScanSink {
+    fn accept(&mut self, result: ScanResult) -> io::Result<()> {
+        self.result_count += 1;
+        self.match_count += result.match_count();
+        match &mut self.kind {
+            SinkKind::Collect(results) => results.accept(result),
+            SinkKind::Jsonl(sink) => sink.accept(result),
+            SinkKind::Routed(sink) => sink.accept(result),
+            SinkKind::Callback(sink) => sink.accept(result),
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match &mut self.kind {
+            SinkKind::Collect(results) => results.finish(),
+            SinkKind::Jsonl(sink) => sink.finish(),
+            SinkKind::Routed(sink) => sink.finish(),
+            SinkKind::Callback(sink) => sink.finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::MemoryProvider;
+    use crate::scanner::FastScanner;
+
+    #[test]
+    fn test_sinks_receive_each_result() {
+        let provider = MemoryProvider::new(Some(BTreeMap::from([
+            ("a.py".to_string(), "DEBUG = True\n".to_string()),
+            ("b.py".to_string(), "x = 1\n".to_string()),
+        ])));
+        let results = FastScanner::new(None).scan_with_provider(&provider);
+
+        let mut jsonl = JsonlSink::new(Vec::new());
+        let mut collect = ScanSink::collect();
+        for result in results {
+            jsonl.accept(result.clone()).unwrap();
+            collect.accept(result).unwrap();
+        }
+        jsonl.finish().unwrap();
+        collect.finish().unwrap();
+
+        let lines: Vec<ScanResult> = String::from_utf8(jsonl.writer)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].matches[0].pattern_name, "debug_mode");
+        assert_eq!((collect.result_count(), collect.match_count()), (2, 1));
+        assert_eq!(collect.take_results().len(), 2);
+        assert!(collect.take_results().is_empty());
+    }
+}
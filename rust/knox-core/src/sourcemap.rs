@@ -0,0 +1,178 @@
+//! Source mapping for content derived from a file
+//!
+//! Notebook cells, HTML script blocks and similar extracted regions are
+//! concatenated into one synthetic buffer for matching; the segments recorded
+//! here map each synthetic line back to its original location
+
+use crate::matcher::Match;
+use pyo3::prelude::*;
+use regex::Regex;
+
+#[derive(Debug, Clone)]
+struct Segment {
+    synthetic_start: usize,
+    line_count: usize,
+    original_line: usize,
+    column_offset: usize,
+    origin: String,
+}
+
+/// Synthetic buffer built from extracted regions of a file
+#[pyclass]
+#[derive(Debug, Clone, Default)]
+pub struct ExtractedContent {
+    content: String,
+    segments: Vec<Segment>,
+    total_lines: usize,
+}
+
+#[pymethods]
+impl ExtractedContent {
+    #[new]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a region that starts at `original_line`/`column_offset` in the source
+    pub fn push_segment(
+        &mut self,
+        text: &str,
+        original_line: usize,
+        column_offset: usize,
+        origin: String,
+    ) {
+        let line_count = text.lines().count().max(1);
+        self.segments.push(Segment {
+            synthetic_start: self.total_lines + 1,
+            line_count,
+            original_line,
+            column_offset,
+            origin,
+        });
+        self.content.push_str(text);
+        if !text.ends_with('\n') {
+            self.content.push('\n');
+        }
+        self.total_lines += line_count;
+    }
+
+    /// Extract code cells from a Jupyter notebook, one segment per cell
+    #[staticmethod]
+    pub fn from_notebook(json: &str) -> PyResult<Self> {
+        let notebook: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        let mut extracted = Self::new();
+
+        let cells = notebook
+            .get("cells")
+            .and_then(|c| c.as_array())
+            .cloned()
+            .unwrap_or_default();
+        for (index, cell) in cells.iter().enumerate() {
+            if cell.get("cell_type").and_then(|t| t.as_str()) != Some("code") {
+                continue;
+            }
+            let source = match cell.get("source") {
+                Some(serde_json::Value::Array(lines)) => {
+                    lines.iter().filter_map(|l| l.as_str()).collect::<String>()
+                }
+                Some(serde_json::Value::String(text)) => text.clone(),
+                _ => continue,
+            };
+            extracted.push_segment(&source, 1, 0, format!("cell {}", index + 1));
+        }
+
+        Ok(extracted)
+    }
+
+    /// Extract inline `<script>` blocks from an HTML document
+    #[staticmethod]
+    pub fn from_html_scripts(html: &str) -> Self {
+        let script_regex = Regex::new(r"(?is)<script\b[^>]*>(.*?)</script>").unwrap();
+        let mut extracted = Self::new();
+
+        for (index, capture) in script_regex.captures_iter(html).enumerate() {
+            if let Some(body) = capture.get(1) {
+                let before = &html[..body.start()];
+                let original_line = before.matches('\n').count() + 1;
+                let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+                let column_offset = before[line_start..].chars().count();
+                extracted.push_segment(
+                    body.as_str(),
+                    original_line,
+                    column_offset,
+                    format!("script block {}", index + 1),
+                );
+            }
+        }
+
+        extracted
+    }
+
+    #[getter]
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.segments.len()
+    }
+}
+
+impl ExtractedContent {
+    /// Rewrite a match found in the synthetic buffer to its original location
+    pub fn remap(&self, m: &mut Match) {
+        let segment = self.segments.iter().find(|s| {
+            m.line_number >= s.synthetic_start && m.line_number < s.synthetic_start + s.line_count
+        });
+
+        if let Some(segment) = segment {
+            let relative = m.line_number - segment.synthetic_start;
+            if relative == 0 {
+                m.column += segment.column_offset;
+            }
+            m.line_number = segment.original_line + relative;
+            m.origin = Some(segment.origin.clone());
+            if relative == 0 && !m.matched_text.contains('\n') {
+                m.end_column += segment.column_offset;
+            }
+            // Offsets into the extracted text don't address the original
+            m.start_byte = None;
+            m.end_byte = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::PatternMatcher;
+
+    #[test]
+    fn test_html_script_matches_map_to_document_lines() {
+        let html = "<html>\n<body>\n<script>let x = 1;\ndocument.write = y;\n</script>\n</body>";
+        let extracted = ExtractedContent::from_html_scripts(html);
+        let mut matcher = PatternMatcher::new();
+        let matches = matcher.match_extracted(&extracted, Some("index.html".to_string()));
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 4);
+        assert_eq!(matches[0].origin.as_deref(), Some("script block 1"));
+        assert_eq!(matches[0].file_path.as_deref(), Some("index.html"));
+    }
+
+    #[test]
+    fn test_notebook_cells_map_to_cell_lines() {
+        let notebook = r##"{"cells": [
+            {"cell_type": "markdown", "source": ["# API_KEY = 'sk-123456789012345678901234'"]},
+            {"cell_type": "code", "source": ["import os\n", "password = 'hunter22222'\n"]}
+        ]}"##;
+        let extracted = ExtractedContent::from_notebook(notebook).unwrap();
+        let mut matcher = PatternMatcher::new();
+        let matches = matcher.match_extracted(&extracted, None);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 2);
+        assert_eq!(matches[0].origin.as_deref(), Some("cell 2"));
+    }
+}
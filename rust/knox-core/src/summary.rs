@@ -0,0 +1,158 @@
+//! Totals over a set of scan results
+//!
+//! Language, line and size information is gathered per file during the
+//! scan itself, so summarizing is a pass over results with no further IO.
+
+use crate::inventory::TAG_THIRD_PARTY;
+use crate::scanner::ScanResult;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Language name used for files no language could be detected for
+pub const UNKNOWN_LANGUAGE: &str = "unknown";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[pyclass]
+pub struct LanguageStats {
+    #[pyo3(get)]
+    pub files: usize,
+    #[pyo3(get)]
+    pub lines: usize,
+    #[pyo3(get)]
+    pub bytes: u64,
+}
+
+#[pymethods]
+impl LanguageStats {
+    fn __repr__(&self) -> String {
+        format!(
+            "LanguageStats(files={}, lines={}, bytes={})",
+            self.files, self.lines, self.bytes
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[pyclass]
+pub struct ScanSummary {
+    #[pyo3(get)]
+    pub files_scanned: usize,
+    /// Paths reported with an `error` instead of being scanned
+    #[pyo3(get)]
+    #[serde(default)]
+    pub unreadable_files: usize,
+    #[pyo3(get)]
+    pub total_matches: usize,
+    /// Matches that count toward policy gates: everything except findings
+    /// in third-party code
+    #[pyo3(get)]
+    pub gating_matches: usize,
+    #[pyo3(get)]
+    pub third_party_files: usize,
+    #[pyo3(get)]
+    pub total_lines: usize,
+    #[pyo3(get)]
+    pub total_bytes: u64,
+    /// Time spent waiting for IO slots, summed over files (throttled scans)
+    #[pyo3(get)]
+    #[serde(default)]
+    pub io_wait_us: u64,
+    #[pyo3(get)]
+    #[serde(default)]
+    pub io_read_us: u64,
+    #[pyo3(get)]
+    pub language_breakdown: BTreeMap<String, LanguageStats>,
+}
+
+#[pymethods]
+impl ScanSummary {
+    pub fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ScanSummary(files={}, matches={}, languages={})",
+            self.files_scanned,
+            self.total_matches,
+            self.language_breakdown.len()
+        )
+    }
+}
+
+/// Summarize results from `scan_directory`
+#[pyfunction]
+pub fn summarize_results(results: Vec<ScanResult>) -> ScanSummary {
+    let mut summary = ScanSummary::default();
+
+    for result in &results {
+        if result.error.is_some() {
+            summary.unreadable_files += 1;
+            continue;
+        }
+        summary.files_scanned += 1;
+        summary.total_matches += result.match_count();
+        summary.gating_matches += result
+            .all_matches()
+            .iter()
+            .filter(|m| !m.tags.iter().any(|t| t == TAG_THIRD_PARTY))
+            .count();
+        summary.third_party_files += usize::from(result.third_party);
+        summary.total_lines += result.line_count;
+        summary.total_bytes += result.file_size;
+        summary.io_wait_us += result.io_wait_us;
+        summary.io_read_us += result.io_read_us;
+
+        let language = result.language.as_deref().unwrap_or(UNKNOWN_LANGUAGE);
+        let stats = summary
+            .language_breakdown
+            .entry(language.to_string())
+            .or_default();
+        stats.files += 1;
+        stats.lines += result.line_count;
+        stats.bytes += result.file_size;
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::scan_directory;
+    use crate::scanoptions::ScanOptions;
+    use std::fs::write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_language_breakdown() {
+        let temp_dir = TempDir::new().unwrap();
+        write(temp_dir.path().join("a.py"), "import os\nDEBUG = True\n").unwrap();
+        write(temp_dir.path().join("b.py"), "x = 1").unwrap();
+        write(temp_dir.path().join("c.js"), "let a = 1;\n").unwrap();
+
+        let results = scan_directory(
+            temp_dir.path().to_string_lossy().to_string(),
+            Some(ScanOptions {
+                parallel: false,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let summary = summarize_results(results);
+
+        assert_eq!(summary.files_scanned, 3);
+        assert_eq!(summary.total_matches, 1);
+        assert_eq!(
+            summary.language_breakdown["python"],
+            LanguageStats {
+                files: 2,
+                lines: 3,
+                bytes: 28,
+            }
+        );
+        assert_eq!(summary.language_breakdown["javascript"].files, 1);
+    }
+}
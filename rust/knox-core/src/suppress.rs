@@ -0,0 +1,323 @@
+//! Inline suppression comments
+//!
+//! `# knox:ignore[rule_a,rule_b] reason="test fixture" until=2025-12-31`
+//! on the offending line (or alone on the line above it) suppresses the
+//! listed rules, or every rule when no list is given. Once `until` has
+//! passed the suppression no longer hides the finding and the match is
+//! tagged `suppression_expired` instead. An `until` that isn't a real
+//! `YYYY-MM-DD` date counts as already passed, so a typo can't make a
+//! suppression permanent.
+//!
+//! When enabled, suppressions written for other tools are honored too:
+//! `# nosec`, `// NOSONAR`, `// eslint-disable-line` and
+//! `// eslint-disable-next-line`, and Rust `#[allow(knox::rule)]`. A
+//! marker without a rule list covers every rule. A list covers the entries
+//! prefixed `knox/` or `knox::` and the bandit and eslint-plugin-security
+//! ids of checks Knox also makes (`B105`, `security/detect-child-process`);
+//! a list naming none of those is about other checks and suppresses nothing.
+
+use crate::language;
+use regex::Regex;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Tag added to matches whose suppression comment has expired
+pub const TAG_SUPPRESSION_EXPIRED: &str = "suppression_expired";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suppression {
+    /// Rules covered by the comment; empty means every rule
+    pub rules: Vec<String>,
+    pub reason: Option<String>,
+    /// Expiry date as (year, month, day)
+    pub until: Option<(i32, u32, u32)>,
+    /// `until` value that isn't a valid date; the suppression is expired
+    pub malformed_until: Option<String>,
+}
+
+fn suppression_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r#"knox:ignore(?:\[([^\]]*)\])?(?:\s+reason="([^"]*)")?(?:\s+until=(\S+))?"#)
+            .unwrap()
+    })
+}
+
+fn foreign_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r"#\s*nosec\b([^#]*)|//\s*NOSONAR\b()|(?:/[/*])\s*eslint-disable-(next-)?line\b([^*]*)|#\[allow\(([^)]*)\)\]",
+        )
+        .unwrap()
+    })
+}
+
+/// Knox rules covering another tool's checks, by that tool's rule id
+const FOREIGN_RULES: &[(&str, &str)] = &[
+    // bandit
+    ("B105", "hardcoded_password"),
+    ("B106", "hardcoded_password"),
+    ("B107", "hardcoded_password"),
+    ("B201", "debug_mode"),
+    ("B301", "insecure_deserialization"),
+    ("B303", "weak_crypto_md5"),
+    ("B303", "weak_crypto_sha1"),
+    ("B324", "weak_crypto_md5"),
+    ("B324", "weak_crypto_sha1"),
+    ("B501", "ssl_verification_disabled"),
+    ("B506", "insecure_deserialization"),
+    ("B602", "command_injection"),
+    ("B605", "command_injection"),
+    ("B608", "sql_injection"),
+    // eslint-plugin-security
+    ("security/detect-child-process", "command_injection"),
+    (
+        "security/detect-disable-mustache-escape",
+        "xss_vulnerability",
+    ),
+];
+
+/// Knox rule names in another tool's rule list, e.g. `knox/debug_mode` or
+/// an id in `FOREIGN_RULES`
+fn knox_rules(list: &str) -> Vec<String> {
+    let mut rules: Vec<String> = Vec::new();
+    for entry in list.split([',', ' ']).map(str::trim) {
+        if let Some(rule) = entry
+            .strip_prefix("knox/")
+            .or_else(|| entry.strip_prefix("knox::"))
+        {
+            rules.push(rule.to_string());
+        }
+        for (_, rule) in FOREIGN_RULES.iter().filter(|(id, _)| *id == entry) {
+            if !rules.iter().any(|r| r == rule) {
+                rules.push(rule.to_string());
+            }
+        }
+    }
+    rules.retain(|r| !r.is_empty());
+    rules
+}
+
+impl Suppression {
+    /// Parse a `knox:ignore` comment out of a line, if present
+    pub fn parse(line: &str) -> Option<Self> {
+        if !line.contains("knox:ignore") {
+            return None;
+        }
+        let captures = suppression_regex().captures(line)?;
+
+        let rules = captures
+            .get(1)
+            .map(|m| {
+                m.as_str()
+                    .split(',')
+                    .map(|r| r.trim().to_string())
+                    .filter(|r| !r.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let reason = captures.get(2).map(|m| m.as_str().to_string());
+        let raw_until = captures.get(3).map(|m| m.as_str());
+        let until = raw_until.and_then(parse_date);
+
+        Some(Suppression {
+            rules,
+            reason,
+            until,
+            malformed_until: raw_until.filter(|_| until.is_none()).map(str::to_string),
+        })
+    }
+
+    /// Parse a suppression marker written for another tool
+    ///
+    /// The flag is true when the marker covers the following line rather
+    /// than its own (`eslint-disable-next-line`, `#[allow(...)]`).
+    pub fn parse_foreign(line: &str) -> Option<(Self, bool)> {
+        if !line.contains("nosec")
+            && !line.contains("NOSONAR")
+            && !line.contains("eslint-disable")
+            && !line.contains("#[allow(")
+        {
+            return None;
+        }
+        let captures = foreign_regex().captures(line)?;
+
+        let (list, next_line) = if let Some(list) = captures.get(1).or(captures.get(2)) {
+            (list.as_str(), false)
+        } else if let Some(list) = captures.get(4) {
+            (list.as_str(), captures.get(3).is_some())
+        } else {
+            // `#[allow(...)]` is only a Knox suppression when it names Knox rules
+            let rules = knox_rules(captures.get(5)?.as_str());
+            if rules.is_empty() {
+                return None;
+            }
+            return Some((
+                Suppression {
+                    rules,
+                    reason: None,
+                    until: None,
+                    malformed_until: None,
+                },
+                true,
+            ));
+        };
+
+        // A list naming only other tools' checks doesn't cover Knox rules
+        let rules = knox_rules(list);
+        if rules.is_empty() && !list.trim().is_empty() {
+            return None;
+        }
+        Some((
+            Suppression {
+                rules,
+                reason: None,
+                until: None,
+                malformed_until: None,
+            },
+            next_line,
+        ))
+    }
+
+    pub fn applies_to(&self, rule: &str) -> bool {
+        self.rules.is_empty() || self.rules.iter().any(|r| r == rule)
+    }
+
+    pub fn is_expired(&self, today: (i32, u32, u32)) -> bool {
+        self.malformed_until.is_some() || self.until.is_some_and(|until| today > until)
+    }
+}
+
+/// Whether a line holds nothing but a comment, in a built-in comment
+/// style or one of a registered language
+pub fn is_comment_only(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with('#')
+        || trimmed.starts_with("//")
+        || trimmed.starts_with("--")
+        || language::registered_comment_markers()
+            .iter()
+            .any(|marker| trimmed.starts_with(marker.as_str()))
+}
+
+/// Current UTC date as (year, month, day)
+pub fn today() -> (i32, u32, u32) {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    civil_from_days((secs / 86_400) as i64)
+}
+
+/// Parse a `YYYY-MM-DD` date, rejecting days the month doesn't have
+pub fn parse_date(text: &str) -> Option<(i32, u32, u32)> {
+    let mut parts = text.trim().splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    let date = (year, month, day);
+    ((1..=12).contains(&month)
+        && (1..=31).contains(&day)
+        && civil_from_days(days_from_civil(date)) == date)
+        .then_some(date)
+}
+
+/// Convert a proleptic Gregorian date to days since 1970-01-01
+pub fn days_from_civil((year, month, day): (i32, u32, u32)) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = i64::from(if month > 2 { month - 3 } else { month + 9 });
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Convert days since 1970-01-01 to a proleptic Gregorian date
+fn civil_from_days(days: i64) -> (i32, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = (yoe + era * 400 + i64::from(month <= 2)) as i32;
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_suppression_with_reason_and_expiry() {
+        let line = r#"KEY = "abc"  # knox:ignore[hardcoded_api_key, hardcoded_password] reason="test fixture" until=2025-12-31"#;
+        let suppression = Suppression::parse(line).unwrap();
+
+        assert_eq!(
+            suppression.rules,
+            vec!["hardcoded_api_key", "hardcoded_password"]
+        );
+        assert_eq!(suppression.reason.as_deref(), Some("test fixture"));
+        assert!(suppression.is_expired((2026, 1, 1)));
+        assert!(!suppression.is_expired((2025, 12, 31)));
+        assert!(suppression.applies_to("hardcoded_password"));
+        assert!(!suppression.applies_to("debug_mode"));
+    }
+
+    #[test]
+    fn test_malformed_until_counts_as_expired() {
+        let parse = |until: &str| Suppression::parse(&format!("# knox:ignore until={}", until));
+        assert_eq!(parse("2025-1-1").unwrap().until, Some((2025, 1, 1)));
+        for until in ["2025/12/31", "2025-13-45", "2025-02-30", "someday"] {
+            let suppression = parse(until).unwrap();
+            assert_eq!(suppression.until, None);
+            assert_eq!(suppression.malformed_until.as_deref(), Some(until));
+            assert!(suppression.is_expired((2000, 1, 1)));
+        }
+        assert!(!parse("").unwrap().is_expired((2000, 1, 1)));
+    }
+
+    #[test]
+    fn test_parse_foreign_suppressions() {
+        let (nosec, next_line) = Suppression::parse_foreign("eval(x)  # nosec").unwrap();
+        assert!(nosec.rules.is_empty());
+        assert!(!next_line);
+        let (nosec, _) = Suppression::parse_foreign("pwd = 'hunter22'  # nosec B105").unwrap();
+        assert_eq!(nosec.rules, vec!["hardcoded_password"]);
+        assert!(Suppression::parse_foreign("eval(x)  # nosec B307").is_none());
+
+        let (eslint, next_line) =
+            Suppression::parse_foreign("// eslint-disable-next-line knox/debug_mode, no-eval")
+                .unwrap();
+        assert_eq!(eslint.rules, vec!["debug_mode"]);
+        assert!(next_line);
+        let (eslint, _) =
+            Suppression::parse_foreign("// eslint-disable-next-line security/detect-child-process")
+                .unwrap();
+        assert_eq!(eslint.rules, vec!["command_injection"]);
+        assert!(Suppression::parse_foreign("// eslint-disable-next-line no-console").is_none());
+        assert!(Suppression::parse_foreign(
+            "/* eslint-disable-line security/detect-object-injection */"
+        )
+        .is_none());
+
+        let (allow, _) = Suppression::parse_foreign("#[allow(knox::weak_crypto_md5)]").unwrap();
+        assert_eq!(allow.rules, vec!["weak_crypto_md5"]);
+        assert!(Suppression::parse_foreign("#[allow(dead_code)]").is_none());
+    }
+
+    #[test]
+    fn test_date_conversions() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(20_454), (2026, 1, 1));
+        assert_eq!(days_from_civil((2026, 1, 1)), 20_454);
+        assert_eq!(days_from_civil(civil_from_days(-1)), -1);
+        assert_eq!(parse_date("2024-02-29"), Some((2024, 2, 29)));
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("2023-02-29"), None);
+    }
+}
@@ -0,0 +1,181 @@
+//! Taint sources for `CodeParser`
+//!
+//! Injection findings start where request data enters the code. In PHP
+//! that is a read of a superglobal (`$_GET['id']`, `$_POST`), wherever it
+//! appears: in an expression, an `include`, or interpolated into a
+//! double-quoted string. Each read outside comments and single-quoted
+//! strings is reported with its subscripts, so rules and, later, dataflow
+//! over `extract_assignments` can tell user input from other variables.
+
+use crate::calls::literal_at;
+use crate::context::LineIndex;
+use crate::parser::{ParsedComment, ParsedSource, StringLiteral};
+
+/// Variables holding request data, by language
+const SOURCES: &[(&str, &[&str])] = &[(
+    "php",
+    &[
+        "$_GET",
+        "$_POST",
+        "$_REQUEST",
+        "$_COOKIE",
+        "$_FILES",
+        "$_SERVER",
+        "$_ENV",
+    ],
+)];
+
+fn is_word(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+/// Offset just past the `[...]` subscripts starting at `offset`, if any
+fn subscripts_end(bytes: &[u8], mut offset: usize) -> usize {
+    while bytes.get(offset) == Some(&b'[') {
+        let mut depth = 0usize;
+        let mut end = None;
+        for (i, &byte) in bytes[offset..].iter().enumerate() {
+            match byte {
+                b'[' => depth += 1,
+                b']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(offset + i + 1);
+                        break;
+                    }
+                }
+                b'\n' => break,
+                _ => {}
+            }
+        }
+        let Some(end) = end else {
+            break;
+        };
+        offset = end;
+    }
+    offset
+}
+
+/// Every read of a taint source in `content`, given its string literals
+/// and comments (with byte spans)
+pub fn scan_sources(
+    content: &str,
+    language: &str,
+    literals: &[StringLiteral],
+    comments: &[ParsedComment],
+) -> Vec<ParsedSource> {
+    let Some((_, names)) = SOURCES.iter().find(|(name, _)| *name == language) else {
+        return Vec::new();
+    };
+    let index = LineIndex::new(content);
+    let bytes = content.as_bytes();
+    let mut sources = Vec::new();
+    for name in names.iter() {
+        for (start, _) in content.match_indices(name) {
+            let end = start + name.len();
+            if bytes.get(end).copied().is_some_and(is_word) {
+                continue;
+            }
+            if literal_at(literals, start).is_some_and(|l| l.quote_type == "'")
+                || comments
+                    .iter()
+                    .any(|c| c.start_byte <= start && start < c.end_byte)
+            {
+                continue;
+            }
+            let (line_number, column) = index.line_col(start);
+            sources.push(ParsedSource {
+                name: name.to_string(),
+                expression: content[start..subscripts_end(bytes, end)].to_string(),
+                line_number,
+                column,
+            });
+        }
+    }
+    sources.sort_by_key(|s| (s.line_number, s.column));
+    sources
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constructs::ConstructRule;
+    use crate::matcher::PatternMatcher;
+    use crate::parser::CodeParser;
+    use crate::severity::Severity;
+
+    #[test]
+    fn test_php_superglobals_and_includes() {
+        let parser = CodeParser::new(Some("php".to_string()));
+        let code = concat!(
+            "<?php\n",
+            "use App\\Models\\{User, Post as P};\n",
+            "use Monolog\\Logger;\n",
+            "require_once 'lib/db.php';\n",
+            "$config = require __DIR__ . '/config.php';\n",
+            "include($_GET['page'] . \".php\");\n",
+            "// $_POST['old'] is gone\n",
+            "public static function find(int $id, ?string &$name = null, ...$rest) {\n",
+            "    $sql = \"SELECT * FROM t WHERE id = $_REQUEST[id]\";\n",
+            "    echo '$_GET is fine here', $_SERVER['HTTP_X_USER'][0], $_GETTER;\n",
+            "}\n",
+        );
+
+        let imports: Vec<(String, Vec<String>)> = parser
+            .extract_imports(code)
+            .into_iter()
+            .map(|i| (i.module, i.items))
+            .collect();
+        assert_eq!(
+            imports,
+            vec![
+                (
+                    "App\\Models".to_string(),
+                    vec!["User".to_string(), "Post as P".to_string()]
+                ),
+                ("Monolog".to_string(), vec!["Logger".to_string()]),
+                ("lib/db.php".to_string(), Vec::new()),
+                ("__DIR__ . '/config.php'".to_string(), Vec::new()),
+                ("$_GET['page'] . \".php\"".to_string(), Vec::new()),
+            ]
+        );
+
+        let functions = parser.extract_functions(code);
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].modifiers, vec!["public", "static"]);
+        assert_eq!(functions[0].parameters, vec!["$id", "$name", "$rest"]);
+
+        let sources: Vec<(String, usize, usize)> = parser
+            .extract_sources(code)
+            .into_iter()
+            .map(|s| (s.expression, s.line_number, s.column))
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                ("$_GET['page']".to_string(), 6, 8),
+                ("$_REQUEST[id]".to_string(), 9, 39),
+                ("$_SERVER['HTTP_X_USER'][0]".to_string(), 10, 31),
+            ]
+        );
+
+        let mut matcher = PatternMatcher::new();
+        matcher.add_construct_rule(
+            ConstructRule::new(
+                "request_input".to_string(),
+                Severity::Medium,
+                "injection".to_string(),
+                "Request data read".to_string(),
+                "taint_source".to_string(),
+                r"^\$_(GET|POST|REQUEST)\b".to_string(),
+                None,
+            )
+            .unwrap(),
+        );
+        let lines: Vec<usize> = matcher
+            .match_constructs(&parser.parse(code))
+            .iter()
+            .map(|m| m.line_number)
+            .collect();
+        assert_eq!(lines, vec![6, 9]);
+    }
+}
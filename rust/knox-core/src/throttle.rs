@@ -0,0 +1,122 @@
+//! IO throttling for network filesystems
+//!
+//! Parallel scans open as many files at once as there are worker threads,
+//! which can overwhelm an NFS or SMB server. An `IoThrottle` caps the
+//! number of concurrent reads independently of CPU parallelism: workers
+//! read a whole file while holding a slot, then match it without one. Time
+//! spent waiting for a slot is recorded on each result, so the summary
+//! shows whether the limit is the bottleneck.
+
+use crate::provider::{Content, FileProvider};
+use std::path::Path;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+pub struct IoThrottle {
+    available: Mutex<usize>,
+    released: Condvar,
+}
+
+/// Content read under the throttle, with how long it took
+pub struct ThrottledRead<'a> {
+    pub bytes: Content<'a>,
+    /// Time spent waiting for a free slot
+    pub wait: Duration,
+    /// Time spent reading once a slot was free
+    pub read: Duration,
+}
+
+impl IoThrottle {
+    /// Allow at most `max_concurrent` reads at once (at least one)
+    pub fn new(max_concurrent: usize) -> Self {
+        IoThrottle {
+            available: Mutex::new(max_concurrent.max(1)),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Read a whole file from `provider` while holding a slot
+    pub fn read<'a>(
+        &self,
+        provider: &'a dyn FileProvider,
+        path: &Path,
+    ) -> std::io::Result<ThrottledRead<'a>> {
+        let waiting = Instant::now();
+        {
+            let mut available = self.available.lock().unwrap_or_else(|e| e.into_inner());
+            while *available == 0 {
+                available = self
+                    .released
+                    .wait(available)
+                    .unwrap_or_else(|e| e.into_inner());
+            }
+            *available -= 1;
+        }
+        let wait = waiting.elapsed();
+
+        let reading = Instant::now();
+        let bytes = provider.read(path);
+        let read = reading.elapsed();
+
+        *self.available.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+        self.released.notify_one();
+
+        Ok(ThrottledRead {
+            bytes: bytes?,
+            wait,
+            read,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::FsProvider;
+    use crate::scanner::scan_directory;
+    use crate::scanoptions::ScanOptions;
+    use std::fs::write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_throttled_scan_matches_unthrottled() {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..8 {
+            write(
+                temp_dir.path().join(format!("f{}.py", i)),
+                "DEBUG = True\nhashlib.md5(x)\n",
+            )
+            .unwrap();
+        }
+        let root = temp_dir.path().to_string_lossy().to_string();
+        let scan = |max_concurrent_io| {
+            let mut results = scan_directory(
+                root.clone(),
+                Some(ScanOptions {
+                    max_concurrent_io,
+                    ..Default::default()
+                }),
+            )
+            .unwrap();
+            results.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+            results
+        };
+
+        let throttled = scan(Some(2));
+        let unthrottled = scan(None);
+        assert_eq!(throttled.len(), 8);
+        for (a, b) in throttled.iter().zip(&unthrottled) {
+            assert_eq!(a.file_path, b.file_path);
+            assert_eq!(a.matches.len(), b.matches.len());
+            assert_eq!(a.line_count, b.line_count);
+        }
+        assert!(unthrottled.iter().all(|r| r.io_wait_us == 0));
+
+        // A zero limit still admits one reader at a time
+        let provider = FsProvider::new(temp_dir.path());
+        let read = IoThrottle::new(0)
+            .read(&provider, &temp_dir.path().join("f0.py"))
+            .unwrap();
+        assert_eq!(read.bytes.len(), 28);
+    }
+}
@@ -0,0 +1,165 @@
+//! Timing-unsafe secret comparisons
+//!
+//! `==` on strings returns at the first differing byte, so comparing a
+//! token or signature an attacker controls one side of leaks, through the
+//! response time, how much of it was right. These rules flag a
+//! secret-named operand compared with `==`/`!=` and point to the
+//! language's constant-time comparison.
+//!
+//! A name alone is weak evidence, so findings are checked against the
+//! parsed content before they are reported: comparisons written inside a
+//! string literal, and those against a literal value (`None`, `""`, a
+//! number, ...), which reveal nothing about the secret, are dropped.
+
+use crate::matcher::{Match, SecurityPattern};
+use crate::parser::CodeParser;
+use crate::severity::Severity;
+
+pub const CATEGORY_TIMING: &str = "timing";
+
+/// Suffixes of identifiers taken to hold a secret
+const SECRET_NAME: &str =
+    r"(?:[A-Za-z_][\w.]*)?(?:token|secret|signature|hmac|digest|api_?key|password_hash|nonce)";
+
+/// Operand values that make a comparison safe to do in variable time
+const LITERAL_VALUES: &[&str] = &["none", "null", "nil", "undefined", "true", "false"];
+
+/// A secret-named operand on either side of `==`/`!=` (and `===`/`!==`),
+/// capturing both operands
+fn comparison_pattern() -> String {
+    format!(
+        r#"(?i)\b({secret})\s*(?:===?|!==?)\s*([^\s;)&|,:]+)|([\w.\[\]"'-]+(?:\([^)]*\))?)\s*(?:===?|!==?)\s*({secret})\b"#,
+        secret = SECRET_NAME
+    )
+}
+
+fn timing_rule(
+    name: &str,
+    languages: &[&str],
+    description: &str,
+    positive: &str,
+    negative: &str,
+) -> SecurityPattern {
+    let mut rule = SecurityPattern::new(
+        name.to_string(),
+        comparison_pattern(),
+        Severity::Medium,
+        CATEGORY_TIMING.to_string(),
+        description.to_string(),
+    );
+    rule.languages = languages.iter().map(|l| l.to_string()).collect();
+    rule.positive_examples = vec![positive.to_string()];
+    rule.negative_examples = vec![negative.to_string()];
+    rule.with_cwe("CWE-208", "A02:2021")
+}
+
+/// Built-in timing rules, appended to the default rules
+pub fn timing_patterns() -> Vec<SecurityPattern> {
+    vec![
+        timing_rule(
+            "timing_unsafe_compare",
+            &["python"],
+            "Secret compared with ==; use hmac.compare_digest",
+            r#"if token == request.headers["X-Token"]:"#,
+            "if token is None or token_count == 0:",
+        ),
+        timing_rule(
+            "timing_unsafe_compare_js",
+            &["javascript", "typescript"],
+            "Secret compared with ===; use crypto.timingSafeEqual",
+            "if (req.get('x-signature') !== expectedSignature) {",
+            "if (apiKey === undefined) {",
+        ),
+        timing_rule(
+            "timing_unsafe_compare_go",
+            &["go"],
+            "Secret compared with ==; use subtle.ConstantTimeCompare",
+            r#"if r.Header.Get("X-Api-Key") != apiKey {"#,
+            r#"if apiKey == "" {"#,
+        ),
+    ]
+}
+
+/// Whether a compared operand is a literal, so the comparison leaks
+/// nothing about the secret on the other side
+fn is_literal(operand: &str) -> bool {
+    let operand = operand.trim();
+    operand.is_empty()
+        || operand.starts_with(['"', '\'', '`'])
+        || operand.starts_with(|c: char| c.is_ascii_digit())
+        || LITERAL_VALUES.contains(&operand.to_lowercase().as_str())
+}
+
+/// Drop timing findings the parsed `content` shows to be harmless:
+/// inside a string literal, or comparing against a literal value
+///
+/// Byte offsets of `matches` are relative to `content`.
+pub fn drop_safe_comparisons(matches: &mut Vec<Match>, content: &str, language: Option<String>) {
+    if !matches.iter().any(|m| m.category == CATEGORY_TIMING) {
+        return;
+    }
+    let literals = CodeParser::new(language).extract_strings(content);
+    matches.retain(|m| {
+        if m.category != CATEGORY_TIMING {
+            return true;
+        }
+        let in_literal = m.start_byte.is_some_and(|start| {
+            literals
+                .iter()
+                .any(|l| l.start_byte <= start && start < l.end_byte)
+        });
+        let captures = &m.explanation.captures;
+        let against_literal = captures
+            .chunks(2)
+            .filter(|pair| pair.iter().any(|c| !c.is_empty()))
+            .any(|pair| pair.iter().any(|c| is_literal(c)));
+        !in_literal && !against_literal
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::PatternMatcher;
+
+    #[test]
+    fn test_timing_rules_skip_literal_comparisons() {
+        let content = concat!(
+            "def check(request, token):\n",
+            "    if token is None or token == \"\":\n",
+            "        return False\n",
+            "    log(\"compare token == header\")\n",
+            "    if settings.API_TOKEN != request.headers.get(\"X-Token\"):\n",
+            "        raise Forbidden()\n",
+            "    return request.headers[\"X-Signature\"] == expected_signature\n",
+        );
+        let mut matcher = PatternMatcher::new();
+        matcher.set_language(Some("python".to_string()));
+        let flagged: Vec<(usize, String)> = matcher
+            .match_content(content)
+            .into_iter()
+            .filter(|m| m.category == CATEGORY_TIMING)
+            .map(|m| (m.line_number, m.matched_text))
+            .collect();
+        assert_eq!(
+            flagged,
+            vec![
+                (
+                    5,
+                    "settings.API_TOKEN != request.headers.get(\"X-Token\"".to_string()
+                ),
+                (
+                    7,
+                    "request.headers[\"X-Signature\"] == expected_signature".to_string()
+                ),
+            ]
+        );
+
+        matcher.set_language(Some("go".to_string()));
+        let go = "if subtle.ConstantTimeCompare([]byte(got), []byte(apiKey)) != 1 {\n";
+        assert!(matcher
+            .match_content(go)
+            .iter()
+            .all(|m| m.category != CATEGORY_TIMING));
+    }
+}
@@ -0,0 +1,859 @@
+//! Syntax-tree backend for `CodeParser`
+//!
+//! The regex backend reads one line at a time, so it misses functions whose
+//! signature spans lines, imports wrapped in parentheses or braces, and
+//! string-looking text inside comments. Built with the `tree-sitter`
+//! feature, `CodeParser` parses Python, JavaScript, TypeScript and Rust
+//! into a syntax tree instead and walks it for the same `ParsedFunction`,
+//! `ParsedImport` and `StringLiteral` values, covering nested and decorated
+//! definitions, methods and arrow functions bound to a name, and
+//! `ParsedCall`s, `ParsedClass`es, `ParsedComment`s and
+//! `ParsedAssignment`s. Other languages keep the regex backend.
+
+use crate::comments::{comment_text, docstring_text};
+use crate::context::char_column;
+use crate::parser::{
+    ParsedAssignment, ParsedCall, ParsedClass, ParsedComment, ParsedFunction, ParsedImport,
+    StringLiteral,
+};
+use tree_sitter::{Language, Node, Parser, Tree};
+
+/// Grammar of a language name, None for languages left to the regex
+/// backend
+pub fn grammar(language: &str) -> Option<Language> {
+    let grammar = match language {
+        "python" => tree_sitter_python::LANGUAGE,
+        "javascript" => tree_sitter_javascript::LANGUAGE,
+        // The TSX grammar also parses plain TypeScript, and `.tsx` files
+        // are detected as typescript
+        "typescript" => tree_sitter_typescript::LANGUAGE_TSX,
+        "rust" => tree_sitter_rust::LANGUAGE,
+        _ => return None,
+    };
+    Some(grammar.into())
+}
+
+pub fn supports(language: &str) -> bool {
+    grammar(language).is_some()
+}
+
+fn parse(language: &str, content: &str) -> Option<Tree> {
+    let mut parser = Parser::new();
+    parser.set_language(&grammar(language)?).ok()?;
+    parser.parse(content, None)
+}
+
+/// Every node of the tree in document order, not descending into nodes
+/// `stop` accepts
+fn walk<'tree>(tree: &'tree Tree, stop: impl Fn(&Node) -> bool) -> Vec<Node<'tree>> {
+    let mut nodes = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        nodes.push(node);
+        if stop(&node) {
+            continue;
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    nodes
+}
+
+fn text<'a>(node: Node, content: &'a str) -> &'a str {
+    &content[node.byte_range()]
+}
+
+fn line(node: Node) -> usize {
+    node.start_position().row + 1
+}
+
+/// Name of a parameter as the regex backend reports it: the text before
+/// any type annotation or default value
+fn parameter_name(text: &str) -> String {
+    let name = text.split(':').next().unwrap_or_default();
+    let name = name.split('=').next().unwrap_or_default();
+    name.trim().trim_end_matches('?').trim().to_string()
+}
+
+fn is_async(node: Node) -> bool {
+    let mut cursor = node.walk();
+    let found = node.children(&mut cursor).any(|child| match child.kind() {
+        "async" => true,
+        "function_modifiers" => {
+            let mut cursor = child.walk();
+            let found = child.children(&mut cursor).any(|m| m.kind() == "async");
+            found
+        }
+        _ => false,
+    });
+    found
+}
+
+fn function(node: Node, name: Node, function: Node, content: &str) -> ParsedFunction {
+    let parameters = match function.child_by_field_name("parameters") {
+        Some(list) => {
+            let mut cursor = list.walk();
+            let names: Vec<String> = list
+                .named_children(&mut cursor)
+                .filter(|p| !p.kind().contains("comment") && p.kind() != "attribute_item")
+                .map(|p| parameter_name(text(p, content)))
+                .filter(|p| !p.is_empty())
+                .collect();
+            names
+        }
+        // An arrow function's lone unparenthesized parameter
+        None => function
+            .child_by_field_name("parameter")
+            .map(|p| vec![text(p, content).to_string()])
+            .unwrap_or_default(),
+    };
+    ParsedFunction {
+        name: text(name, content).to_string(),
+        // Decorators are part of a JavaScript method node
+        line_number: line(name),
+        parameters,
+        is_async: is_async(function),
+        decorators: decorators(node, content),
+        modifiers: Vec::new(),
+    }
+}
+
+/// Function definitions, including nested functions, methods and, in
+/// JavaScript and TypeScript, function values bound to a variable
+pub fn extract_functions(language: &str, content: &str) -> Option<Vec<ParsedFunction>> {
+    let tree = parse(language, content)?;
+    let mut functions = Vec::new();
+    for node in walk(&tree, |_| false) {
+        match node.kind() {
+            "function_definition"
+            | "function_declaration"
+            | "generator_function_declaration"
+            | "method_definition"
+            | "function_signature"
+            | "method_signature"
+            | "abstract_method_signature"
+            | "function_item"
+            | "function_signature_item" => {
+                if let Some(name) = node.child_by_field_name("name") {
+                    functions.push(function(node, name, node, content));
+                }
+            }
+            "variable_declarator" => {
+                let (Some(name), Some(value)) = (
+                    node.child_by_field_name("name"),
+                    node.child_by_field_name("value"),
+                ) else {
+                    continue;
+                };
+                if matches!(
+                    value.kind(),
+                    "arrow_function" | "function_expression" | "generator_function"
+                ) {
+                    functions.push(function(node, name, value, content));
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(functions)
+}
+
+/// Import statements, one per statement however many lines it spans
+pub fn extract_imports(language: &str, content: &str) -> Option<Vec<ParsedImport>> {
+    let tree = parse(language, content)?;
+    let mut imports = Vec::new();
+    for node in walk(&tree, |_| false) {
+        let import = match (language, node.kind()) {
+            ("python", "import_statement") => python_import(node, None, content),
+            ("python", "import_from_statement") => {
+                python_import(node, node.child_by_field_name("module_name"), content)
+            }
+            ("javascript" | "typescript", "import_statement") => js_import(node, content),
+            ("rust", "use_declaration") => rust_use(node, content),
+            _ => None,
+        };
+        imports.extend(import);
+    }
+    Some(imports)
+}
+
+fn python_import(node: Node, module: Option<Node>, content: &str) -> Option<ParsedImport> {
+    let mut cursor = node.walk();
+    let mut items: Vec<String> = node
+        .children_by_field_name("name", &mut cursor)
+        .map(|item| text(item, content).to_string())
+        .collect();
+    let mut cursor = node.walk();
+    let is_wildcard = node
+        .named_children(&mut cursor)
+        .any(|child| child.kind() == "wildcard_import");
+    if is_wildcard {
+        items = vec!["*".to_string()];
+    }
+    Some(ParsedImport {
+        module: module
+            .map(|m| text(m, content).to_string())
+            .unwrap_or_default(),
+        items,
+        line_number: line(node),
+        is_wildcard,
+    })
+}
+
+fn js_import(node: Node, content: &str) -> Option<ParsedImport> {
+    let source = node.child_by_field_name("source")?;
+    let module = text(source, content)
+        .trim_matches(|c| c == '"' || c == '\'')
+        .to_string();
+    let mut items = Vec::new();
+    let mut is_wildcard = false;
+    let mut cursor = node.walk();
+    for clause in node
+        .named_children(&mut cursor)
+        .filter(|c| c.kind() == "import_clause")
+    {
+        let mut cursor = clause.walk();
+        for part in clause.named_children(&mut cursor) {
+            match part.kind() {
+                "namespace_import" => {
+                    is_wildcard = true;
+                    items.push("*".to_string());
+                }
+                "named_imports" => {
+                    let mut cursor = part.walk();
+                    items.extend(
+                        part.named_children(&mut cursor)
+                            .filter(|s| s.kind() == "import_specifier")
+                            .map(|s| text(s, content).to_string()),
+                    );
+                }
+                _ => items.push(text(part, content).to_string()),
+            }
+        }
+    }
+    Some(ParsedImport {
+        module,
+        items,
+        line_number: line(node),
+        is_wildcard,
+    })
+}
+
+fn rust_use(node: Node, content: &str) -> Option<ParsedImport> {
+    let argument = node.child_by_field_name("argument")?;
+    let (module, items) = match argument.kind() {
+        "scoped_use_list" => {
+            let module = argument
+                .child_by_field_name("path")
+                .map(|p| text(p, content).to_string())
+                .unwrap_or_default();
+            let items = match argument.child_by_field_name("list") {
+                Some(list) => {
+                    let mut cursor = list.walk();
+                    let items: Vec<String> = list
+                        .named_children(&mut cursor)
+                        .map(|item| text(item, content).to_string())
+                        .collect();
+                    items
+                }
+                None => Vec::new(),
+            };
+            (module, items)
+        }
+        "use_wildcard" => {
+            let path = text(argument, content);
+            let module = path.trim_end_matches('*').trim_end_matches("::");
+            (module.to_string(), vec!["*".to_string()])
+        }
+        _ => (text(argument, content).to_string(), Vec::new()),
+    };
+    Some(ParsedImport {
+        module,
+        is_wildcard: items.iter().any(|item| item == "*"),
+        items,
+        line_number: line(node),
+    })
+}
+
+fn is_string(node: &Node) -> bool {
+    matches!(
+        node.kind(),
+        "string" | "template_string" | "string_literal" | "raw_string_literal"
+    )
+}
+
+/// String literals, not those inside comments; interpolations are part of
+/// the enclosing literal's value
+pub fn extract_strings(language: &str, content: &str) -> Option<Vec<StringLiteral>> {
+    let tree = parse(language, content)?;
+    let mut strings = Vec::new();
+    for node in walk(&tree, is_string).into_iter().filter(is_string) {
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        let (start, end) = match (children.first(), children.last()) {
+            // Delimited by its first and last tokens
+            (Some(open), Some(close)) if children.len() >= 2 && open.kind() != "string_content" => {
+                (open.end_byte(), close.start_byte())
+            }
+            // Raw strings expose only their content
+            (Some(first), Some(last)) => (first.start_byte(), last.end_byte()),
+            _ => continue,
+        };
+        let opening = &content[node.start_byte()..start];
+        let quote = opening.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+        let prefix = &opening[..opening.len() - quote.len()];
+        let quote_type = if node.kind() == "raw_string_literal" {
+            opening
+        } else {
+            quote
+        };
+        // Python's `{expr!r:>5}` and JavaScript's `${expr}`
+        let interpolations = children
+            .iter()
+            .filter_map(|child| match child.kind() {
+                "interpolation" => child.child_by_field_name("expression"),
+                "template_substitution" => child.named_child(0),
+                _ => None,
+            })
+            .map(|expression| text(expression, content).trim().to_string())
+            .collect();
+        let value = &content[start..end];
+        strings.push(StringLiteral {
+            value: value.to_string(),
+            line_number: line(node),
+            is_multiline: value.contains('\n'),
+            end_line: node.end_position().row + 1,
+            quote_type: quote_type.to_string(),
+            start_byte: start,
+            end_byte: end,
+            prefix: prefix.to_string(),
+            interpolations,
+        });
+    }
+    Some(strings)
+}
+
+/// Callee and argument list of a call node, None for other nodes and
+/// for calls without a parenthesized list (tagged templates)
+fn call_parts<'tree>(node: Node<'tree>) -> Option<(Node<'tree>, Node<'tree>, &'static str)> {
+    let (callee, arguments, suffix) = match node.kind() {
+        "call" | "call_expression" => (
+            node.child_by_field_name("function")?,
+            node.child_by_field_name("arguments")?,
+            "",
+        ),
+        "new_expression" => (
+            node.child_by_field_name("constructor")?,
+            node.child_by_field_name("arguments")?,
+            "",
+        ),
+        "macro_invocation" => {
+            let mut cursor = node.walk();
+            let tokens = node
+                .children(&mut cursor)
+                .find(|c| c.kind() == "token_tree")?;
+            (node.child_by_field_name("macro")?, tokens, "!")
+        }
+        _ => return None,
+    };
+    (arguments.kind() != "template_string").then_some((callee, arguments, suffix))
+}
+
+/// Calls, method calls, constructor calls (`new Foo()`) and Rust macro
+/// invocations, nested ones included; the grammar leaves macro arguments
+/// unparsed, so calls inside them aren't seen
+pub fn extract_calls(language: &str, content: &str) -> Option<Vec<ParsedCall>> {
+    let tree = parse(language, content)?;
+    let mut calls = Vec::new();
+    for node in walk(&tree, |_| false) {
+        let Some((callee, arguments, suffix)) = call_parts(node) else {
+            continue;
+        };
+        // A method called on a call's result is named from the last `)`
+        // on, as the regex backend sees it: `run(cmd).stdout.decode` is
+        // reported as `stdout.decode`
+        let callee = text(callee, content);
+        let callee = callee.rsplit(')').next().unwrap_or_default();
+        let mut name: String = callee
+            .trim_start_matches(|c: char| c.is_whitespace() || c == '.')
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        name.push_str(suffix);
+        // Without the delimiters, which are single characters
+        let list = text(arguments, content);
+        let inner = list
+            .get(1..list.len().saturating_sub(1))
+            .unwrap_or_default();
+        let start = node.start_position();
+        let line_start = node.start_byte() - start.column;
+        calls.push(ParsedCall {
+            callee: name,
+            arguments: inner.trim().to_string(),
+            line_number: start.row + 1,
+            column: char_column(&content[line_start..], start.column),
+        });
+    }
+    Some(calls)
+}
+
+/// Target and value of an assignment or declaration node, None for other
+/// nodes and for declarations without a value (`let x;`)
+fn assignment_parts<'tree>(node: Node<'tree>) -> Option<(Node<'tree>, Node<'tree>)> {
+    let (target, value) = match node.kind() {
+        // Python, JavaScript and Rust; augmented assignments are other kinds
+        "assignment" | "assignment_expression" => ("left", "right"),
+        "variable_declarator" | "const_item" | "static_item" => ("name", "value"),
+        "let_declaration" => ("pattern", "value"),
+        "field_definition" => ("property", "value"),
+        "public_field_definition" => ("name", "value"),
+        _ => return None,
+    };
+    let mut value = node.child_by_field_name(value)?;
+    // `a = b = 1` assigns 1 to both; the inner assignment is its own node
+    while value.kind() == "assignment" {
+        value = value.child_by_field_name("right")?;
+    }
+    Some((node.child_by_field_name(target)?, value))
+}
+
+pub fn extract_assignments(language: &str, content: &str) -> Option<Vec<ParsedAssignment>> {
+    let tree = parse(language, content)?;
+    let assignments = walk(&tree, |_| false)
+        .into_iter()
+        .filter_map(|node| {
+            let (target, value) = assignment_parts(node)?;
+            Some(ParsedAssignment {
+                name: text(target, content).trim().to_string(),
+                value: text(value, content).trim().to_string(),
+                line_number: line(target),
+            })
+        })
+        .collect();
+    Some(assignments)
+}
+
+/// Decorator expressions on a class or function, without their `@`: the
+/// siblings before the definition (inside Python's `decorated_definition`,
+/// a JavaScript export or a TypeScript class body) or its own children
+fn decorators(node: Node, content: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut sibling = node.prev_named_sibling();
+    while let Some(decorator) = sibling.filter(|s| s.kind() == "decorator") {
+        found.push(decorator);
+        sibling = decorator.prev_named_sibling();
+    }
+    found.reverse();
+    let mut cursor = node.walk();
+    found.extend(
+        node.children(&mut cursor)
+            .filter(|c| c.kind() == "decorator"),
+    );
+    found
+        .into_iter()
+        .map(|d| text(d, content).trim_start_matches('@').trim().to_string())
+        .collect()
+}
+
+fn named_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+    children
+}
+
+/// Base classes, then implemented interfaces, as written
+fn bases(node: Node, content: &str) -> Vec<String> {
+    let mut bases = Vec::new();
+    if let Some(list) = node.child_by_field_name("superclasses") {
+        // Python's `metaclass=` and other keyword arguments aren't bases
+        bases.extend(
+            named_children(list)
+                .into_iter()
+                .filter(|b| b.kind() != "keyword_argument" && !b.kind().contains("comment"))
+                .map(|b| text(b, content).to_string()),
+        );
+    }
+    for heritage in named_children(node)
+        .into_iter()
+        .filter(|c| c.kind() == "class_heritage")
+    {
+        for clause in named_children(heritage) {
+            match clause.kind() {
+                // TypeScript's base and its type arguments are siblings
+                "extends_clause" => bases.push(
+                    text(clause, content)
+                        .trim_start_matches("extends")
+                        .trim()
+                        .to_string(),
+                ),
+                "implements_clause" => bases.extend(
+                    named_children(clause)
+                        .into_iter()
+                        .map(|b| text(b, content).to_string()),
+                ),
+                _ => bases.push(text(clause, content).to_string()),
+            }
+        }
+    }
+    bases
+}
+
+/// Class definitions, nested ones and named class expressions included
+pub fn extract_classes(language: &str, content: &str) -> Option<Vec<ParsedClass>> {
+    let tree = parse(language, content)?;
+    let mut classes = Vec::new();
+    for node in walk(&tree, |_| false) {
+        if !matches!(
+            node.kind(),
+            "class_definition" | "class_declaration" | "abstract_class_declaration" | "class"
+        ) {
+            continue;
+        }
+        let Some(name) = node.child_by_field_name("name") else {
+            continue;
+        };
+        classes.push(ParsedClass {
+            name: text(name, content).to_string(),
+            bases: bases(node, content),
+            decorators: decorators(node, content),
+            line_number: line(name),
+            end_line: node.end_position().row + 1,
+        });
+    }
+    Some(classes)
+}
+
+/// Whether `node` is a Python docstring: a string standing alone as the
+/// first statement of a module, class or function body
+fn is_docstring(node: Node) -> bool {
+    let Some(statement) = node.parent().filter(|p| p.kind() == "expression_statement") else {
+        return false;
+    };
+    if statement.named_child_count() != 1 {
+        return false;
+    }
+    let Some(body) = statement.parent() else {
+        return false;
+    };
+    let first = named_children(body)
+        .into_iter()
+        .find(|c| c.kind() != "comment");
+    let in_definition = body.kind() == "module"
+        || body
+            .parent()
+            .is_some_and(|d| matches!(d.kind(), "class_definition" | "function_definition"));
+    first == Some(statement) && in_definition
+}
+
+/// Comments, and in Python docstrings
+pub fn extract_comments(language: &str, content: &str) -> Option<Vec<ParsedComment>> {
+    let tree = parse(language, content)?;
+    let mut comments = Vec::new();
+    for node in walk(&tree, |_| false) {
+        let (value, is_docstring) = match node.kind() {
+            "comment" | "line_comment" | "block_comment" => {
+                (comment_text(text(node, content)), false)
+            }
+            "string" if language == "python" && is_docstring(node) => {
+                (docstring_text(text(node, content)), true)
+            }
+            _ => continue,
+        };
+        // Rust line comments end with their newline
+        let raw = text(node, content);
+        let end = node.start_byte() + raw.trim_end().len();
+        comments.push(ParsedComment {
+            text: value.to_string(),
+            line_number: line(node),
+            end_line: line(node) + raw.trim_end().matches('\n').count(),
+            start_byte: node.start_byte(),
+            end_byte: end,
+            is_docstring,
+        });
+    }
+    Some(comments)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{CodeParser, BACKEND_REGEX, BACKEND_TREE_SITTER};
+
+    #[test]
+    fn test_syntax_tree_sees_multiline_and_nested_definitions() {
+        let code = concat!(
+            "from .models import (\n",
+            "    User,\n",
+            "    Token as T,\n",
+            ")\n",
+            "# api_key = \"not a literal\"\n",
+            "@app.route(\"/login\")\n",
+            "async def login(\n",
+            "    request,\n",
+            "    remember: bool = False,\n",
+            "):\n",
+            "    def check(token):\n",
+            "        return f\"{token}\" == \"\"\"x\"\"\"\n",
+        );
+        let mut parser = CodeParser::new(Some("python".to_string()));
+        assert_eq!(parser.backend(), BACKEND_TREE_SITTER);
+
+        let functions = parser.extract_functions(code);
+        let names: Vec<(&str, usize, bool)> = functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.line_number, f.is_async))
+            .collect();
+        assert_eq!(names, vec![("login", 7, true), ("check", 11, false)]);
+        assert_eq!(functions[0].parameters, vec!["request", "remember"]);
+
+        let imports = parser.extract_imports(code);
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].module, ".models");
+        assert_eq!(imports[0].items, vec!["User", "Token as T"]);
+
+        let strings = parser.extract_strings(code);
+        let values: Vec<(&str, &str)> = strings
+            .iter()
+            .map(|s| (s.value.as_str(), s.quote_type.as_str()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![("/login", "\""), ("{token}", "\""), ("x", "\"\"\"")]
+        );
+        assert_eq!(&code[strings[0].start_byte..strings[0].end_byte], "/login");
+
+        parser.set_backend(BACKEND_REGEX.to_string()).unwrap();
+        // Line by line, the multi-line signature goes unseen
+        assert_eq!(parser.extract_functions(code)[0].name, "check");
+        assert!(parser.set_backend("ast".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_syntax_tree_javascript_and_rust() {
+        let js = CodeParser::new(Some("typescript".to_string()));
+        let code = concat!(
+            "import * as fs from 'fs';\n",
+            "import express, { Router } from \"express\";\n",
+            "const handler = async (req: Request, res?: Response) => res.send(`ok`);\n",
+            "class Api { get(id: string) { return id; } }\n",
+        );
+        let functions: Vec<(String, Vec<String>, bool)> = js
+            .extract_functions(code)
+            .into_iter()
+            .map(|f| (f.name, f.parameters, f.is_async))
+            .collect();
+        assert_eq!(
+            functions,
+            vec![
+                (
+                    "handler".to_string(),
+                    vec!["req".to_string(), "res".to_string()],
+                    true
+                ),
+                ("get".to_string(), vec!["id".to_string()], false),
+            ]
+        );
+        let imports = js.extract_imports(code);
+        assert!(imports[0].is_wildcard && imports[0].module == "fs");
+        assert_eq!(imports[1].items, vec!["express", "Router"]);
+        assert_eq!(js.extract_strings(code)[2].quote_type, "`");
+
+        let rust = CodeParser::new(Some("rust".to_string()));
+        let code = "use std::io::{self, Read};\npub async fn load(\n    path: &str,\n) -> String { r#\"raw \"q\"\"#.into() }\n";
+        let imports = rust.extract_imports(code);
+        assert_eq!(imports[0].module, "std::io");
+        assert_eq!(imports[0].items, vec!["self", "Read"]);
+        let functions = rust.extract_functions(code);
+        assert_eq!(functions[0].parameters, vec!["path"]);
+        assert!(functions[0].is_async);
+        let strings = rust.extract_strings(code);
+        assert_eq!(strings[0].value, "raw \"q\"");
+    }
+
+    #[test]
+    fn test_syntax_tree_calls() {
+        let python = CodeParser::new(Some("python".to_string()));
+        let code = "# eval(x)\nresult = subprocess.run(\n    cmd, shell=True\n).stdout.decode(\"utf-8\")\n";
+        let calls: Vec<(String, String, usize, usize)> = python
+            .extract_calls(code)
+            .into_iter()
+            .map(|c| (c.callee, c.arguments, c.line_number, c.column))
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("stdout.decode".to_string(), "\"utf-8\"".to_string(), 2, 9),
+                (
+                    "subprocess.run".to_string(),
+                    "cmd, shell=True".to_string(),
+                    2,
+                    9
+                ),
+            ]
+        );
+
+        let js = CodeParser::new(Some("javascript".to_string()));
+        let calls = js.extract_calls("const é = new Function(\"return 1\"); html`<b>${x}</b>`;\n");
+        assert_eq!(calls.len(), 1);
+        assert_eq!(
+            (calls[0].callee.as_str(), calls[0].column),
+            ("Function", 10)
+        );
+
+        let rust = CodeParser::new(Some("rust".to_string()));
+        let names: Vec<String> = rust
+            .extract_calls("fn main() { Command::new(\"sh\").arg(x); println!(\"{}\", f(x)); }\n")
+            .into_iter()
+            .map(|c| c.callee)
+            .collect();
+        assert_eq!(names, vec!["arg", "Command::new", "println!"]);
+    }
+
+    #[test]
+    fn test_syntax_tree_classes() {
+        let python = CodeParser::new(Some("python".to_string()));
+        let code = concat!(
+            "@register\n",
+            "@dataclass(\n",
+            "    frozen=True,\n",
+            ")\n",
+            "class Loader(\n",
+            "    pickle.Unpickler,\n",
+            "    metaclass=ABCMeta,\n",
+            "):\n",
+            "    class Meta:\n",
+            "        permissions = []\n",
+        );
+        let classes = python.extract_classes(code);
+        let spans: Vec<(&str, usize, usize)> = classes
+            .iter()
+            .map(|c| (c.name.as_str(), c.line_number, c.end_line))
+            .collect();
+        assert_eq!(spans, vec![("Loader", 5, 10), ("Meta", 9, 10)]);
+        assert_eq!(classes[0].bases, vec!["pickle.Unpickler"]);
+        assert_eq!(
+            classes[0].decorators,
+            vec!["register", "dataclass(\n    frozen=True,\n)"]
+        );
+        assert!(classes[1].decorators.is_empty());
+
+        let ts = CodeParser::new(Some("typescript".to_string()));
+        let code = "@Component({ selector: 'app' })\nexport class Store<T> extends Base<T> implements Repo {\n}\nconst C = class Named extends React.Component {};\n";
+        let classes = ts.extract_classes(code);
+        assert_eq!(classes.len(), 2);
+        assert_eq!(classes[0].bases, vec!["Base<T>", "Repo"]);
+        assert_eq!(
+            classes[0].decorators,
+            vec!["Component({ selector: 'app' })"]
+        );
+        assert_eq!((classes[0].line_number, classes[0].end_line), (2, 3));
+        assert_eq!(classes[1].bases, vec!["React.Component"]);
+    }
+
+    #[test]
+    fn test_syntax_tree_function_decorators() {
+        let python = CodeParser::new(Some("python".to_string()));
+        let code = "class View:\n    @method_decorator(\n        csrf_exempt,\n    )\n    def post(self):\n        pass\n";
+        let functions = python.extract_functions(code);
+        assert_eq!(
+            functions[0].decorators,
+            vec!["method_decorator(\n        csrf_exempt,\n    )"]
+        );
+        assert_eq!(functions[0].line_number, 5);
+
+        let ts = CodeParser::new(Some("typescript".to_string()));
+        let code = "class Api {\n  @UseGuards(AuthGuard)\n  @Post()\n  create(body: Dto) {}\n  list() {}\n}\n";
+        let functions: Vec<(String, usize, Vec<String>)> = ts
+            .extract_functions(code)
+            .into_iter()
+            .map(|f| (f.name, f.line_number, f.decorators))
+            .collect();
+        assert_eq!(
+            functions,
+            vec![
+                (
+                    "create".to_string(),
+                    4,
+                    vec!["UseGuards(AuthGuard)".to_string(), "Post()".to_string()]
+                ),
+                ("list".to_string(), 5, Vec::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_syntax_tree_comments() {
+        let python = CodeParser::new(Some("python".to_string()));
+        let code = "# header\n\"\"\"Module.\"\"\"\nclass A:\n    \"Doc.\"\n    x = \"\"\"not a docstring\"\"\"\n";
+        let comments: Vec<(String, bool)> = python
+            .extract_comments(code)
+            .into_iter()
+            .map(|c| (c.text, c.is_docstring))
+            .collect();
+        assert_eq!(
+            comments,
+            vec![
+                ("header".to_string(), false),
+                ("Module.".to_string(), true),
+                ("Doc.".to_string(), true),
+            ]
+        );
+
+        let rust = CodeParser::new(Some("rust".to_string()));
+        let code = "/// Docs\nfn f() -> char { '\"' /* a\n b */ }\n";
+        let comments = rust.extract_comments(code);
+        assert_eq!(comments.len(), 2);
+        assert_eq!(
+            &code[comments[0].start_byte..comments[0].end_byte],
+            "/// Docs"
+        );
+        assert_eq!(
+            (comments[1].text.as_str(), comments[1].end_line),
+            ("a\n b", 3)
+        );
+    }
+
+    #[test]
+    fn test_syntax_tree_assignments() {
+        let pairs = |language: &str, code: &str| -> Vec<(String, String)> {
+            CodeParser::new(Some(language.to_string()))
+                .extract_assignments(code)
+                .into_iter()
+                .map(|a| (a.name, a.value))
+                .collect()
+        };
+        let pair = |name: &str, value: &str| (name.to_string(), value.to_string());
+
+        let code = "a = b = \"x\"\ntimeout: int\ncount += 1\nf(key=1)\nclass C:\n    token: str = os.environ[\"T\"]\n";
+        assert_eq!(
+            pairs("python", code),
+            vec![
+                pair("a", "\"x\""),
+                pair("b", "\"x\""),
+                pair("token", "os.environ[\"T\"]")
+            ]
+        );
+
+        let code = "let key; const { user } = req, n = 1; class A { secret = 's'; }\nkey = n;\n";
+        assert_eq!(
+            pairs("javascript", code),
+            vec![
+                pair("{ user }", "req"),
+                pair("n", "1"),
+                pair("secret", "'s'"),
+                pair("key", "n")
+            ]
+        );
+
+        let code = "const KEY: &str = \"k\";\nfn f() { let mut x: u8 = 1; x += 1; x = 2; }\n";
+        let assignments = CodeParser::new(Some("rust".to_string())).extract_assignments(code);
+        let lines: Vec<(&str, &str, usize)> = assignments
+            .iter()
+            .map(|a| (a.name.as_str(), a.value.as_str(), a.line_number))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![("KEY", "\"k\"", 1), ("x", "1", 2), ("x", "2", 2)]
+        );
+    }
+}
@@ -0,0 +1,281 @@
+//! Cross-checking findings against a secret manager's inventory
+//!
+//! A `SecretInventory` holds the names of secrets already managed in Vault,
+//! SSM Parameter Store and the like. Hardcoded-secret findings and
+//! environment variable reads are classified against it by the name they
+//! are stored under, to order remediation:
+//!
+//! - `should_vault`: a hardcoded secret the inventory doesn't manage;
+//! - `vaulted_elsewhere`: a secret the inventory manages, hardcoded here
+//!   anyway or read from the environment;
+//! - `unknown`: no name could be inferred, or an environment variable the
+//!   inventory doesn't know.
+//!
+//! Names compare by their last path segment, case-insensitively, with `-`
+//! and `.` treated as `_`, so `/prod/app/db-password` manages `DB_PASSWORD`.
+
+use crate::matcher::Match;
+use crate::scanner::ScanResult;
+use pyo3::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::sync::OnceLock;
+
+pub const STATUS_SHOULD_VAULT: &str = "should_vault";
+pub const STATUS_VAULTED_ELSEWHERE: &str = "vaulted_elsewhere";
+pub const STATUS_UNKNOWN: &str = "unknown";
+
+/// Origin of a cross-check: a hardcoded-secret finding
+pub const SOURCE_LITERAL: &str = "literal";
+/// Origin of a cross-check: an environment variable read
+pub const SOURCE_ENV: &str = "env";
+
+const SECRETS_CATEGORY: &str = "secrets";
+
+/// Key a finding's literal is assigned to, e.g. `db_password` in
+/// `db_password = "..."` or `db.password: ...` from a config rule
+fn assignment_key() -> &'static Regex {
+    static KEY: OnceLock<Regex> = OnceLock::new();
+    KEY.get_or_init(|| {
+        Regex::new(r#"^\s*["']?([A-Za-z_][\w.\-]*)["']?\s*[:=]"#).expect("valid key regex")
+    })
+}
+
+/// Reads of a named environment variable across common languages
+fn env_read() -> &'static Regex {
+    static ENV: OnceLock<Regex> = OnceLock::new();
+    ENV.get_or_init(|| {
+        Regex::new(
+            r#"(?x)
+            os\.environ(?:\.get)?\s*[\[(]\s*["']([A-Za-z_]\w*)["']
+            | (?i:\bgetenv)\s*\(\s*["']([A-Za-z_]\w*)["']
+            | process\.env\.([A-Za-z_]\w*)
+            | process\.env\[\s*["']([A-Za-z_]\w*)["']
+            | env::var(?:_os)?\s*\(\s*"([A-Za-z_]\w*)"
+            | \bENV(?:\.fetch\s*\(|\[)\s*["']([A-Za-z_]\w*)["']
+            "#,
+        )
+        .expect("valid environment regex")
+    })
+}
+
+/// Canonical form names are compared in
+pub fn normalize_name(name: &str) -> String {
+    let leaf = name
+        .trim()
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or("");
+    leaf.to_uppercase().replace(['-', '.'], "_")
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct SecretCrossCheck {
+    #[pyo3(get)]
+    pub file_path: Option<String>,
+    #[pyo3(get)]
+    pub line_number: usize,
+    /// Name the secret is stored under, when one could be inferred
+    #[pyo3(get)]
+    pub name: Option<String>,
+    /// `literal` or `env`
+    #[pyo3(get)]
+    pub source: String,
+    /// `should_vault`, `vaulted_elsewhere` or `unknown`
+    #[pyo3(get)]
+    pub status: String,
+    /// Rule of the finding, for literals
+    #[pyo3(get)]
+    pub pattern_name: Option<String>,
+}
+
+#[pymethods]
+impl SecretCrossCheck {
+    fn __repr__(&self) -> String {
+        format!(
+            "SecretCrossCheck(name={:?}, source={}, status={}, line={})",
+            self.name, self.source, self.status, self.line_number
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[pyclass]
+pub struct SecretInventory {
+    names: HashSet<String>,
+}
+
+#[pymethods]
+impl SecretInventory {
+    #[new]
+    pub fn new(names: Vec<String>) -> Self {
+        SecretInventory {
+            names: names
+                .iter()
+                .map(|n| normalize_name(n))
+                .filter(|n| !n.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Load secret names from an export: a JSON array of names, a JSON
+    /// object keyed by name, an SSM `get-parameters-by-path` response, or
+    /// plain text with one name per line
+    #[staticmethod]
+    pub fn from_file(path: String) -> PyResult<Self> {
+        let text = fs::read_to_string(&path)
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(format!("{}: {}", path, e)))?;
+        let names = match serde_json::from_str::<serde_json::Value>(&text) {
+            Ok(serde_json::Value::Array(items)) => items
+                .iter()
+                .filter_map(|item| item.as_str().map(String::from))
+                .collect(),
+            Ok(serde_json::Value::Object(object)) => match object.get("Parameters") {
+                Some(serde_json::Value::Array(parameters)) => parameters
+                    .iter()
+                    .filter_map(|p| p.get("Name")?.as_str().map(String::from))
+                    .collect(),
+                _ => object.keys().cloned().collect(),
+            },
+            Ok(_) => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "{}: expected a JSON array or object of secret names",
+                    path
+                )))
+            }
+            Err(_) => text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(String::from)
+                .collect(),
+        };
+        Ok(Self::new(names))
+    }
+
+    /// Whether the inventory manages a secret stored under `name`
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(&normalize_name(name))
+    }
+
+    fn __len__(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Classify the hardcoded-secret findings of `results`
+    pub fn cross_check(&self, results: Vec<ScanResult>) -> Vec<SecretCrossCheck> {
+        results
+            .iter()
+            .flat_map(|result| {
+                result
+                    .all_matches()
+                    .iter()
+                    .filter(|m| m.category == SECRETS_CATEGORY)
+                    .map(|m| self.check_literal(m, &result.file_path))
+            })
+            .collect()
+    }
+
+    /// Classify the environment variable reads in `content`
+    pub fn cross_check_env(
+        &self,
+        content: &str,
+        file_path: Option<String>,
+    ) -> Vec<SecretCrossCheck> {
+        let mut checks = Vec::new();
+        for (i, line) in content.lines().enumerate() {
+            for captures in env_read().captures_iter(line) {
+                let Some(name) = captures.iter().skip(1).flatten().next() else {
+                    continue;
+                };
+                let status = if self.contains(name.as_str()) {
+                    STATUS_VAULTED_ELSEWHERE
+                } else {
+                    STATUS_UNKNOWN
+                };
+                checks.push(SecretCrossCheck {
+                    file_path: file_path.clone(),
+                    line_number: i + 1,
+                    name: Some(name.as_str().to_string()),
+                    source: SOURCE_ENV.to_string(),
+                    status: status.to_string(),
+                    pattern_name: None,
+                });
+            }
+        }
+        checks
+    }
+}
+
+impl SecretInventory {
+    fn check_literal(&self, m: &Match, file_path: &str) -> SecretCrossCheck {
+        let name = assignment_key()
+            .captures(&m.matched_text)
+            .map(|c| c[1].rsplit('.').next().unwrap_or(&c[1]).to_string());
+        let status = match &name {
+            Some(name) if self.contains(name) => STATUS_VAULTED_ELSEWHERE,
+            Some(_) => STATUS_SHOULD_VAULT,
+            None => STATUS_UNKNOWN,
+        };
+        SecretCrossCheck {
+            file_path: Some(m.file_path.clone().unwrap_or_else(|| file_path.to_string())),
+            line_number: m.line_number,
+            name,
+            source: SOURCE_LITERAL.to_string(),
+            status: status.to_string(),
+            pattern_name: Some(m.pattern_name.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::MemoryProvider;
+    use crate::scanner::FastScanner;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_findings_classified_against_inventory() {
+        let inventory = SecretInventory::new(vec![
+            "/prod/app/password".to_string(),
+            "secret/data/stripe-key".to_string(),
+        ]);
+        assert!(inventory.contains("STRIPE_KEY"));
+
+        let code = "password = \"hunter2hunter2\"\napi_key = \"abcdefghij0123456789xyz\"\n";
+        let provider = MemoryProvider::new(Some(BTreeMap::from([(
+            "app.py".to_string(),
+            code.to_string(),
+        )])));
+        let results = FastScanner::new(None).scan_with_provider(&provider);
+        let checks: Vec<(Option<String>, String)> = inventory
+            .cross_check(results)
+            .into_iter()
+            .map(|c| (c.name, c.status))
+            .collect();
+        assert_eq!(
+            checks,
+            vec![
+                (
+                    Some("password".to_string()),
+                    STATUS_VAULTED_ELSEWHERE.to_string()
+                ),
+                (Some("api_key".to_string()), STATUS_SHOULD_VAULT.to_string()),
+            ]
+        );
+
+        let env = "key = os.environ[\"STRIPE_KEY\"]\ntoken = process.env.GH_TOKEN\n";
+        let checks = inventory.cross_check_env(env, None);
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].status, STATUS_VAULTED_ELSEWHERE);
+        assert_eq!(
+            (checks[1].name.as_deref(), checks[1].status.as_str()),
+            (Some("GH_TOKEN"), STATUS_UNKNOWN)
+        );
+    }
+}
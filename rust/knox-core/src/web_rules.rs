@@ -0,0 +1,234 @@
+//! Built-in rules for request-handling flaws
+//!
+//! Server-side request forgery, path traversal, open redirects and XML
+//! external entities share a shape: a value from the incoming request
+//! (`request.args`, `req.query`, ...) reaches an API that fetches, opens or
+//! redirects to it, or a parser is configured to resolve entities. The
+//! rules look for that shape on one line, so a value laundered through a
+//! variable first is not caught; they trade that recall for few false
+//! positives in the default pack.
+
+use crate::matcher::SecurityPattern;
+use crate::severity::Severity;
+
+pub const CATEGORY_SSRF: &str = "ssrf";
+pub const CATEGORY_PATH_TRAVERSAL: &str = "path_traversal";
+pub const CATEGORY_OPEN_REDIRECT: &str = "open_redirect";
+pub const CATEGORY_XXE: &str = "xxe";
+
+struct WebRule {
+    name: &'static str,
+    pattern: &'static str,
+    severity: Severity,
+    category: &'static str,
+    description: &'static str,
+    confidence: &'static str,
+    languages: &'static [&'static str],
+    positive: &'static [&'static str],
+    negative: &'static [&'static str],
+}
+
+const JS: &[&str] = &["javascript", "typescript"];
+
+const RULES: &[WebRule] = &[
+    WebRule {
+        name: "ssrf_request_url",
+        pattern: r#"\b(?:(?:requests|httpx)\.(?:get|post|put|patch|delete|head|request)|urlopen)\s*\(\s*(?:url\s*=\s*)?(?:f["'][^"']*\{\s*)?request\.(?:args|form|values|json|data|GET|POST|query_params)\b"#,
+        severity: Severity::High,
+        category: CATEGORY_SSRF,
+        description: "Outbound request to a URL taken from the incoming request",
+        confidence: "medium",
+        languages: &["python"],
+        positive: &[r#"resp = requests.get(request.args.get("url"))"#],
+        negative: &[r#"resp = requests.get(settings.API_URL, params=request.args)"#],
+    },
+    WebRule {
+        name: "ssrf_request_url_js",
+        pattern: r"\b(?:fetch|axios(?:\.(?:get|post|put|patch|delete|request))?|got|https?\.(?:get|request))\s*\(\s*req\.(?:query|body|params)\b",
+        severity: Severity::High,
+        category: CATEGORY_SSRF,
+        description: "Outbound request to a URL taken from the incoming request",
+        confidence: "medium",
+        languages: JS,
+        positive: &["const resp = await fetch(req.query.url);"],
+        negative: &["const resp = await fetch(`${API_BASE}/users`, { body: req.body });"],
+    },
+    WebRule {
+        name: "path_traversal_request_path",
+        pattern: r"\b(?:open|send_file|send_from_directory|FileResponse|os\.path\.join)\s*\([^)]*\brequest\.(?:args|form|values|files|GET|POST|query_params)\b",
+        severity: Severity::High,
+        category: CATEGORY_PATH_TRAVERSAL,
+        description: "File path taken from the incoming request",
+        confidence: "medium",
+        languages: &["python"],
+        positive: &[r#"with open(request.args["name"]) as f:"#],
+        negative: &["with open(safe_join(UPLOAD_DIR, name)) as f:"],
+    },
+    WebRule {
+        name: "path_traversal_request_path_js",
+        pattern: r"\b(?:fs\.(?:readFile|readFileSync|createReadStream|writeFile|writeFileSync|unlink)|res\.(?:sendFile|download)|path\.(?:join|resolve))\s*\([^)]*\breq\.(?:query|body|params)\b",
+        severity: Severity::High,
+        category: CATEGORY_PATH_TRAVERSAL,
+        description: "File path taken from the incoming request",
+        confidence: "medium",
+        languages: JS,
+        positive: &["res.sendFile(path.join(__dirname, req.params.file));"],
+        negative: &["res.sendFile(path.join(__dirname, 'index.html'));"],
+    },
+    WebRule {
+        name: "path_traversal_parent_join",
+        pattern: r#"\b(?:os\.path\.join|path\.(?:join|resolve)|Paths?\.get|filepath\.Join)\s*\([^)]*["']\.\.[/\\]"#,
+        severity: Severity::Medium,
+        category: CATEGORY_PATH_TRAVERSAL,
+        description: "Path joined with a parent-directory segment, escaping its base",
+        confidence: "low",
+        languages: &[],
+        positive: &[r#"config = os.path.join(BASE_DIR, "../secrets", name)"#],
+        negative: &[r#"config = os.path.join(BASE_DIR, "config", name)"#],
+    },
+    WebRule {
+        name: "open_redirect",
+        pattern: r"\b(?:redirect|HttpResponseRedirect|RedirectResponse)\s*\(\s*request\.(?:args|form|values|GET|POST|query_params)\b",
+        severity: Severity::Medium,
+        category: CATEGORY_OPEN_REDIRECT,
+        description: "Redirect to a location taken from the incoming request",
+        confidence: "medium",
+        languages: &["python"],
+        positive: &[r#"return redirect(request.args.get("next"))"#],
+        negative: &[r#"return redirect(url_for("index"))"#],
+    },
+    WebRule {
+        name: "open_redirect_js",
+        pattern: r"\bres\.redirect\s*\(\s*(?:\d{3}\s*,\s*)?req\.(?:query|body|params)\b|\blocation(?:\.href)?\s*=\s*[^;]*\b(?:location\.(?:search|hash)|document\.referrer)\b",
+        severity: Severity::Medium,
+        category: CATEGORY_OPEN_REDIRECT,
+        description: "Redirect to a location taken from the incoming request",
+        confidence: "medium",
+        languages: JS,
+        positive: &["res.redirect(302, req.query.returnTo);"],
+        negative: &["res.redirect('/login');"],
+    },
+    WebRule {
+        name: "xxe_entity_resolution",
+        pattern: r"\bXMLParser\s*\([^)]*\b(?:resolve_entities\s*=\s*True|no_network\s*=\s*False)|setFeature\s*\(\s*(?:xml\.sax\.handler\.)?feature_external_(?:ges|pes)\s*,\s*(?:True|1)\s*\)",
+        severity: Severity::High,
+        category: CATEGORY_XXE,
+        description: "XML parser configured to resolve external entities",
+        confidence: "high",
+        languages: &["python"],
+        positive: &["parser = etree.XMLParser(resolve_entities=True)"],
+        negative: &["parser = etree.XMLParser(resolve_entities=False)"],
+    },
+    WebRule {
+        name: "xxe_entity_resolution_java",
+        pattern: r#"setFeature\s*\(\s*"http://(?:xml\.org/sax/features/external-(?:general|parameter)-entities"\s*,\s*true|apache\.org/xml/features/disallow-doctype-decl"\s*,\s*false)\s*\)|\bsetExpandEntityReferences\s*\(\s*true\s*\)"#,
+        severity: Severity::High,
+        category: CATEGORY_XXE,
+        description: "XML parser configured to resolve external entities",
+        confidence: "high",
+        languages: &["java", "kotlin"],
+        positive: &[
+            r#"dbf.setFeature("http://apache.org/xml/features/disallow-doctype-decl", false);"#,
+        ],
+        negative: &[
+            r#"dbf.setFeature("http://apache.org/xml/features/disallow-doctype-decl", true);"#,
+        ],
+    },
+    WebRule {
+        name: "xxe_entity_resolution_php",
+        pattern: r"\blibxml_disable_entity_loader\s*\(\s*false\s*\)|\bLIBXML_NOENT\b",
+        severity: Severity::High,
+        category: CATEGORY_XXE,
+        description: "XML parser configured to resolve external entities",
+        confidence: "high",
+        languages: &["php"],
+        positive: &["$doc->loadXML($xml, LIBXML_NOENT | LIBXML_DTDLOAD);"],
+        negative: &["$doc->loadXML($xml, LIBXML_NONET);"],
+    },
+    WebRule {
+        name: "xxe_entity_resolution_dotnet",
+        pattern: r"\bDtdProcessing\s*=\s*DtdProcessing\.Parse\b|\bXmlResolver\s*=\s*new\s+XmlUrlResolver\b",
+        severity: Severity::High,
+        category: CATEGORY_XXE,
+        description: "XML parser configured to resolve external entities",
+        confidence: "high",
+        languages: &["csharp"],
+        positive: &["settings.DtdProcessing = DtdProcessing.Parse;"],
+        negative: &["settings.DtdProcessing = DtdProcessing.Prohibit;"],
+    },
+];
+
+/// CWE and OWASP Top 10 category of a rule category
+fn weakness(category: &str) -> (&'static str, &'static str) {
+    match category {
+        CATEGORY_SSRF => ("CWE-918", "A10:2021"),
+        CATEGORY_PATH_TRAVERSAL => ("CWE-22", "A01:2021"),
+        CATEGORY_OPEN_REDIRECT => ("CWE-601", "A01:2021"),
+        _ => ("CWE-611", "A05:2021"),
+    }
+}
+
+/// Built-in request-handling rules, appended to the default rules
+pub fn web_patterns() -> Vec<SecurityPattern> {
+    RULES
+        .iter()
+        .map(|spec| {
+            let mut rule = SecurityPattern::new(
+                spec.name.to_string(),
+                spec.pattern.to_string(),
+                spec.severity,
+                spec.category.to_string(),
+                spec.description.to_string(),
+            );
+            let (cwe, owasp) = weakness(spec.category);
+            rule.confidence = spec.confidence.to_string();
+            rule.languages = spec.languages.iter().map(|l| l.to_string()).collect();
+            rule.positive_examples = spec.positive.iter().map(|e| e.to_string()).collect();
+            rule.negative_examples = spec.negative.iter().map(|e| e.to_string()).collect();
+            rule.with_cwe(cwe, owasp)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::PatternMatcher;
+
+    #[test]
+    fn test_web_rules_flag_their_examples() {
+        let mut matcher = PatternMatcher::new_empty();
+        for rule in web_patterns() {
+            matcher.add_pattern(rule).unwrap();
+        }
+        assert!(matcher.diagnostics().is_empty());
+        assert!(matcher.validate_patterns().is_empty());
+    }
+
+    #[test]
+    fn test_web_rules_follow_language() {
+        let mut matcher = PatternMatcher::new();
+        let flagged = |matcher: &mut PatternMatcher, language: &str, code: &str| -> Vec<String> {
+            matcher.set_language(Some(language.to_string()));
+            matcher
+                .match_content(code)
+                .into_iter()
+                .map(|m| format!("{}:{}", m.pattern_name, m.cwe.unwrap_or_default()))
+                .collect()
+        };
+
+        let view = "def fetch():\n    target = request.args[\"url\"]\n    return requests.get(request.args[\"url\"], timeout=5)\n";
+        assert_eq!(
+            flagged(&mut matcher, "python", view),
+            vec!["ssrf_request_url:CWE-918"]
+        );
+        assert!(flagged(&mut matcher, "javascript", view).is_empty());
+
+        let handler =
+            "app.get('/f', (req, res) => res.sendFile(path.resolve('public', req.query.f)));\n";
+        assert_eq!(
+            flagged(&mut matcher, "typescript", handler),
+            vec!["path_traversal_request_path_js:CWE-22"]
+        );
+    }
+}
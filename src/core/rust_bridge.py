@@ -54,7 +54,8 @@ class RustScanner:
     ) -> List[Dict[str, Any]]:
         """Scan directory recursively"""
         if self.use_rust:
-            results = knox_core.scan_directory(path, max_depth, parallel)
+            options = knox_core.ScanOptions(max_depth=max_depth, parallel=parallel)
+            results = knox_core.scan_directory(path, options)
             return [
                 {
                     "file_path": r.file_path,
@@ -95,7 +96,7 @@ class RustScanner:
             "line": rust_match.line_number,
             "column": rust_match.column,
             "type": rust_match.pattern_name,
-            "severity": rust_match.severity,
+            "severity": str(rust_match.severity),
             "code": rust_match.matched_text,
             "category": rust_match.category,
         }
@@ -123,8 +124,11 @@ class RustPatternMatcher:
                 {
                     "line_number": m.line_number,
                     "column": m.column,
+                    "end_column": m.end_column,
+                    "start_byte": m.start_byte,
+                    "end_byte": m.end_byte,
                     "pattern_name": m.pattern_name,
-                    "severity": m.severity,
+                    "severity": str(m.severity),
                     "matched_text": m.matched_text,
                     "category": m.category,
                 }
@@ -169,6 +173,8 @@ class RustCodeParser:
                     "line_number": f.line_number,
                     "parameters": f.parameters,
                     "is_async": f.is_async,
+                    "decorators": f.decorators,
+                    "modifiers": f.modifiers,
                 }
                 for f in functions
             ]
@@ -199,14 +205,99 @@ class RustCodeParser:
                 {
                     "value": s.value,
                     "line_number": s.line_number,
+                    "end_line": s.end_line,
                     "is_multiline": s.is_multiline,
                     "quote_type": s.quote_type,
+                    "prefix": s.prefix,
+                    "interpolations": s.interpolations,
                 }
                 for s in strings
             ]
         else:
             return []
 
+    def extract_classes(self, content: str) -> List[Dict[str, Any]]:
+        """Extract class definitions from code"""
+        if self._parser:
+            classes = self._parser.extract_classes(content)
+            return [
+                {
+                    "name": c.name,
+                    "bases": c.bases,
+                    "decorators": c.decorators,
+                    "line_number": c.line_number,
+                    "end_line": c.end_line,
+                }
+                for c in classes
+            ]
+        else:
+            return []
+
+    def extract_comments(self, content: str) -> List[Dict[str, Any]]:
+        """Extract comments and docstrings from code"""
+        if self._parser:
+            comments = self._parser.extract_comments(content)
+            return [
+                {
+                    "text": c.text,
+                    "line_number": c.line_number,
+                    "end_line": c.end_line,
+                    "start_byte": c.start_byte,
+                    "end_byte": c.end_byte,
+                    "is_docstring": c.is_docstring,
+                }
+                for c in comments
+            ]
+        else:
+            return []
+
+    def extract_calls(self, content: str) -> List[Dict[str, Any]]:
+        """Extract call expressions from code"""
+        if self._parser:
+            calls = self._parser.extract_calls(content)
+            return [
+                {
+                    "callee": c.callee,
+                    "arguments": c.arguments,
+                    "line_number": c.line_number,
+                    "column": c.column,
+                }
+                for c in calls
+            ]
+        else:
+            return []
+
+    def extract_assignments(self, content: str) -> List[Dict[str, Any]]:
+        """Extract variable assignments from code"""
+        if self._parser:
+            assignments = self._parser.extract_assignments(content)
+            return [
+                {
+                    "name": a.name,
+                    "value": a.value,
+                    "line_number": a.line_number,
+                }
+                for a in assignments
+            ]
+        else:
+            return []
+
+    def extract_sources(self, content: str) -> List[Dict[str, Any]]:
+        """Extract reads of request data (taint sources) from code"""
+        if self._parser:
+            sources = self._parser.extract_sources(content)
+            return [
+                {
+                    "name": s.name,
+                    "expression": s.expression,
+                    "line_number": s.line_number,
+                    "column": s.column,
+                }
+                for s in sources
+            ]
+        else:
+            return []
+
     def analyze_complexity(self, content: str) -> Dict[str, int]:
         """Analyze code complexity metrics"""
         if self._parser:
@@ -217,8 +308,28 @@ class RustCodeParser:
                 "code_lines": 0,
                 "functions": 0,
                 "imports": 0,
+                "max_complexity": 0,
+                "max_nesting_depth": 0,
             }
 
+    def analyze_functions(self, content: str) -> List[Dict[str, Any]]:
+        """Per-function cyclomatic complexity and nesting depth"""
+        if self._parser:
+            functions = self._parser.analyze_functions(content)
+            return [
+                {
+                    "name": f.name,
+                    "line_number": f.line_number,
+                    "end_line": f.end_line,
+                    "cyclomatic": f.cyclomatic,
+                    "nesting_depth": f.nesting_depth,
+                    "deepest_line": f.deepest_line,
+                }
+                for f in functions
+            ]
+        else:
+            return []
+
 
 def is_rust_available() -> bool:
     """Check if Rust performance modules are available"""